@@ -152,6 +152,7 @@ graph-success-description = Ihr {$type}-Diagramm wurde generiert und ist bereit
 graph-error-title = Diagramm-Generierung Fehlgeschlagen
 graph-error-description = Es gab einen Fehler beim Generieren Ihres {$type}-Diagramms: {$error}
 graph-processing = Verarbeite Ihren {$command}-Befehl...
+auto-graphs-header = Wiedergabestatistik der letzten {$days} Tage
 
 # Permission messages
 permission-error-title = Berechtigung Verweigert
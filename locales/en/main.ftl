@@ -152,6 +152,7 @@ graph-success-description = Your {$type} graph has been generated and is ready f
 graph-error-title = Graph Generation Failed
 graph-error-description = There was an error generating your {$type} graph: {$error}
 graph-processing = Processing your {$command} command...
+auto-graphs-header = Viewing statistics for the last {$days} days
 
 # Permission messages
 permission-error-title = Permission Denied
@@ -152,6 +152,7 @@ graph-success-description = Tu gráfico {$type} ha sido generado y está listo p
 graph-error-title = Falló la Generación del Gráfico
 graph-error-description = Hubo un error generando tu gráfico {$type}: {$error}
 graph-processing = Procesando tu comando {$command}...
+auto-graphs-header = Estadísticas de reproducción de los últimos {$days} días
 
 # Permission messages
 permission-error-title = Permiso Denegado
@@ -152,6 +152,7 @@ graph-success-description = Votre graphique {$type} a été généré et est pr
 graph-error-title = Échec de la Génération du Graphique
 graph-error-description = Il y a eu une erreur lors de la génération de votre graphique {$type} : {$error}
 graph-processing = Traitement de votre commande {$command}...
+auto-graphs-header = Statistiques de visionnage des {$days} derniers jours
 
 # Permission messages
 permission-error-title = Permission Refusée
@@ -0,0 +1,143 @@
+//! Background sampling of Tautulli activity
+//!
+//! Tautulli history only records individual plays, so concurrency has to be
+//! measured by polling `get_activity`. Each poll stores one sample per server
+//! through the [`PersistenceManager`] for the concurrency graphs.
+
+use anyhow::Result;
+use chrono::{DateTime, SubsecRound, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tgraph_common::{CancellationContext, TautulliServers};
+use tgraph_graphs::ActivitySample;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{debug, error, info, warn};
+
+use crate::persistence::PersistenceManager;
+
+/// Polls every Tautulli server and stores activity samples
+pub struct ActivitySampler {
+    servers: Arc<TautulliServers>,
+    persistence_manager: Arc<PersistenceManager>,
+}
+
+impl ActivitySampler {
+    /// Create a sampler for the given servers
+    pub fn new(servers: Arc<TautulliServers>, persistence_manager: Arc<PersistenceManager>) -> Self {
+        Self {
+            servers,
+            persistence_manager,
+        }
+    }
+
+    /// Poll every server once and persist the samples
+    ///
+    /// All samples of one poll share a timestamp so they can be summed across
+    /// servers. Servers that fail to respond are skipped.
+    pub async fn sample_once(&self, sampled_at: DateTime<Utc>) -> Result<Vec<ActivitySample>> {
+        let sampled_at = sampled_at.trunc_subsecs(0);
+        let mut samples = Vec::with_capacity(self.servers.len());
+
+        for name in &self.servers.names() {
+            let Some(client) = self.servers.get(name) else {
+                continue;
+            };
+            match client.get_activity().await {
+                Ok(activity) => samples.push(ActivitySample::from_activity(name, &activity, sampled_at)),
+                Err(e) => warn!("Skipping activity sample for {}: {}", name, e),
+            }
+        }
+
+        if !samples.is_empty() {
+            self.persistence_manager.save_activity_samples(&samples).await?;
+        }
+        debug!("Sampled activity on {} servers", samples.len());
+        Ok(samples)
+    }
+
+    /// Sample on a fixed interval until `shutdown` is cancelled
+    pub fn spawn(self, every: Duration, shutdown: CancellationContext) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = interval(every);
+            // A slow poll should not cause a burst of catch-up samples
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = shutdown.token().cancelled() => break,
+                    _ = interval.tick() => {}
+                }
+
+                tokio::select! {
+                    _ = shutdown.token().cancelled() => break,
+                    result = self.sample_once(Utc::now()) => {
+                        if let Err(e) = result {
+                            error!("Failed to store activity samples: {}", e);
+                        }
+                    }
+                }
+            }
+            info!("Activity sampling stopped");
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tgraph_graphs::{aggregate_activity_samples, SampleInterval};
+
+    #[tokio::test]
+    async fn test_activity_samples_round_trip() {
+        let persistence = PersistenceManager::new(":memory:").await.unwrap();
+        let sampled_at = DateTime::from_timestamp(1641067200, 0).unwrap();
+        let samples = vec![
+            ActivitySample {
+                server: "Main".to_string(),
+                sampled_at,
+                stream_count: 3,
+                transcode_count: 1,
+                total_bandwidth: 12000,
+            },
+            ActivitySample {
+                server: "Cabin".to_string(),
+                sampled_at,
+                stream_count: 1,
+                transcode_count: 0,
+                total_bandwidth: 4000,
+            },
+        ];
+        persistence.save_activity_samples(&samples).await.unwrap();
+
+        let start = sampled_at - chrono::Duration::hours(1);
+        let end = sampled_at + chrono::Duration::hours(1);
+        let loaded = persistence.load_activity_samples(None, start, end).await.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].sampled_at, sampled_at);
+
+        let main_only = persistence.load_activity_samples(Some("Main"), start, end).await.unwrap();
+        assert_eq!(main_only, samples[..1]);
+
+        let points = aggregate_activity_samples(&loaded, SampleInterval::Hourly);
+        assert_eq!(points[0].peak_streams, 4);
+    }
+
+    #[tokio::test]
+    async fn test_sample_without_servers() {
+        let persistence = Arc::new(PersistenceManager::new(":memory:").await.unwrap());
+        let sampler = ActivitySampler::new(Arc::new(TautulliServers::new()), persistence);
+        assert!(sampler.sample_once(Utc::now()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_spawned_sampler_stops_on_shutdown() {
+        let persistence = Arc::new(PersistenceManager::new(":memory:").await.unwrap());
+        let sampler = ActivitySampler::new(Arc::new(TautulliServers::new()), persistence);
+        let shutdown = CancellationContext::new();
+        let handle = sampler.spawn(Duration::from_secs(3600), shutdown.clone());
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+    }
+}
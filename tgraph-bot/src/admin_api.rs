@@ -6,7 +6,7 @@
 use anyhow::Result;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, StatusCode},
     response::Json,
     routing::{delete, get, post, put},
     Router,
@@ -16,12 +16,18 @@ use prometheus::{Encoder, TextEncoder};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use tgraph_common::{CircuitState, ServerMetrics, ServerSelection, TautulliServers};
+use tgraph_graphs::{
+    aggregate_activity_samples, render_png, server_title, ConcurrencyDataPoint, ConcurrencyGraph,
+    SampleInterval,
+};
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::alerting::{Alert, AlertManager, AlertRule, AlertRuleId, AlertSeverity};
+use crate::config_reload::GraphDefaultsReloader;
 use crate::metrics::{AggregatedMetrics, MetricsCollector, TaskExecutionMetric};
 use crate::persistence::PersistenceManager;
 use crate::schedule_config::ScheduleConfig;
@@ -41,6 +47,10 @@ pub struct AdminApiState {
     pub scheduler_service: Arc<SchedulerService>,
     /// Task queue for priority-based execution
     pub task_queue: Arc<TaskQueue>,
+    /// Tautulli clients, if configured
+    pub tautulli: Option<Arc<TautulliServers>>,
+    /// Defaults of the `[graph]` section for rendered graphs
+    pub graph_defaults: Arc<GraphDefaultsReloader>,
 }
 
 /// Query parameters for metrics endpoints
@@ -67,6 +77,19 @@ pub struct AlertsQuery {
     pub limit: Option<u32>,
 }
 
+/// Query parameters for the activity concurrency endpoints
+#[derive(Debug, Deserialize)]
+pub struct ConcurrencyQuery {
+    /// Only include samples from this server
+    pub server: Option<String>,
+    /// Group samples per hour or per day (default: hourly)
+    pub interval: Option<SampleInterval>,
+    /// Start time for samples (ISO 8601)
+    pub start_time: Option<DateTime<Utc>>,
+    /// End time for samples (ISO 8601)
+    pub end_time: Option<DateTime<Utc>>,
+}
+
 /// Request body for acknowledging alerts
 #[derive(Debug, Deserialize)]
 pub struct AcknowledgeAlertRequest {
@@ -96,6 +119,8 @@ pub struct SystemHealthResponse {
     pub active_alerts: usize,
     /// Database statistics
     pub database_stats: HashMap<String, i64>,
+    /// Tautulli client metrics, including circuit breaker state
+    pub tautulli: Vec<ServerMetrics>,
     /// System uptime information
     pub uptime_seconds: u64,
 }
@@ -131,6 +156,10 @@ pub fn create_admin_api_router(state: AdminApiState) -> Router {
         
         // Task queue endpoints
         .route("/queue/stats", get(get_queue_stats))
+
+        // Tautulli activity endpoints
+        .route("/activity/concurrency", get(get_concurrency))
+        .route("/activity/concurrency/graph", get(get_concurrency_graph))
         
         // Scheduler endpoints
         // .route("/scheduler/jobs", get(get_scheduler_jobs)) // Temporarily disabled due to type issues
@@ -157,10 +186,19 @@ async fn get_system_health(
     let database_stats = state.persistence_manager.get_database_stats().await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    let tautulli = state
+        .tautulli
+        .as_ref()
+        .map(|servers| servers.client_metrics())
+        .unwrap_or_default();
+    let tautulli_available = tautulli
+        .iter()
+        .all(|server| server.metrics.circuit_breaker.state == CircuitState::Closed);
+
     // Simple uptime calculation (would be better to track actual start time)
     let uptime_seconds = 0; // TODO: Implement proper uptime tracking
 
-    let status = if scheduler_running && active_alerts == 0 {
+    let status = if scheduler_running && active_alerts == 0 && tautulli_available {
         "healthy"
     } else if scheduler_running {
         "warning"
@@ -175,6 +213,7 @@ async fn get_system_health(
         queue_stats,
         active_alerts,
         database_stats,
+        tautulli,
         uptime_seconds,
     };
 
@@ -439,6 +478,63 @@ async fn get_scheduler_jobs(
     Json(jobs)
 }
 
+/// Load sampled activity and group it into peaks
+async fn load_concurrency(
+    state: &AdminApiState,
+    query: &ConcurrencyQuery,
+) -> Result<(SampleInterval, Vec<ConcurrencyDataPoint>), StatusCode> {
+    let interval = query.interval.unwrap_or(SampleInterval::Hourly);
+    let end_time = query.end_time.unwrap_or_else(Utc::now);
+    let start_time = query.start_time.unwrap_or_else(|| match interval {
+        SampleInterval::Hourly => end_time - chrono::Duration::days(2),
+        SampleInterval::Daily => end_time - chrono::Duration::days(30),
+    });
+
+    let samples = state.persistence_manager
+        .load_activity_samples(query.server.as_deref(), start_time, end_time)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((interval, aggregate_activity_samples(&samples, interval)))
+}
+
+/// Get peak concurrent streams and bandwidth per hour or day
+async fn get_concurrency(
+    Query(query): Query<ConcurrencyQuery>,
+    State(state): State<AdminApiState>,
+) -> Result<Json<Vec<ConcurrencyDataPoint>>, StatusCode> {
+    let (_, points) = load_concurrency(&state, &query).await?;
+    Ok(Json(points))
+}
+
+/// Render peak concurrent streams and bandwidth as a PNG graph
+async fn get_concurrency_graph(
+    Query(query): Query<ConcurrencyQuery>,
+    State(state): State<AdminApiState>,
+) -> Result<([(header::HeaderName, &'static str); 1], Vec<u8>), StatusCode> {
+    let (interval, points) = load_concurrency(&state, &query).await?;
+    if points.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let server_count = state.tautulli.as_ref().map_or(0, |servers| servers.len());
+    let selection = match &query.server {
+        Some(name) => Some(ServerSelection::Server(name.clone())),
+        None if server_count > 1 => Some(ServerSelection::All),
+        None => None,
+    };
+    let title = server_title("Peak Concurrent Streams", selection.as_ref());
+    let (mut graph, mut config) = ConcurrencyGraph::with_config(&title, interval);
+    state.graph_defaults.get().apply(&mut config);
+    graph.set_data(points);
+
+    let image = render_png(&graph, &config, "concurrency.png").await.map_err(|e| {
+        warn!("Failed to render concurrency graph: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], image.data))
+}
+
 /// Start the admin API server
 pub async fn start_admin_api_server(
     state: AdminApiState,
@@ -10,6 +10,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
+use tgraph_graphs::{Anomaly, AnomalyKind};
 use uuid::Uuid;
 
 use crate::metrics::{AggregatedMetrics, MetricsCollector, TaskExecutionMetric};
@@ -20,6 +21,9 @@ pub type AlertRuleId = Uuid;
 /// Unique identifier for an alert instance
 pub type AlertId = Uuid;
 
+/// Last time each rule fired for each Tautulli server
+type ServerCooldowns = HashMap<(AlertRuleId, String), DateTime<Utc>>;
+
 /// Severity levels for alerts
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -83,8 +87,19 @@ pub enum AlertCondition {
         /// Minimum number of executions required
         min_executions: u32,
     },
+    /// Alert when a server's daily play count is an anomaly
+    ///
+    /// Raised by the play count monitor rather than from task metrics. The
+    /// rule's task name filter can limit it to one Tautulli server.
+    PlayCountAnomaly {
+        /// Also alert on unusually busy days, not just drops
+        include_spikes: bool,
+    },
 }
 
+/// Task type of alerts raised for play count anomalies
+pub const PLAY_COUNT_TASK_TYPE: &str = "play_count";
+
 /// Configuration for an alert rule
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertRule {
@@ -303,6 +318,8 @@ pub struct AlertManager {
     metrics_collector: Arc<MetricsCollector>,
     /// Cooldown tracking for rules
     rule_cooldowns: Arc<RwLock<HashMap<AlertRuleId, DateTime<Utc>>>>,
+    /// Cooldown tracking for rules raised per Tautulli server
+    server_cooldowns: Arc<RwLock<ServerCooldowns>>,
 }
 
 impl AlertManager {
@@ -316,6 +333,7 @@ impl AlertManager {
             channels: Arc::new(RwLock::new(HashMap::new())),
             metrics_collector,
             rule_cooldowns: Arc::new(RwLock::new(HashMap::new())),
+            server_cooldowns: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -418,6 +436,16 @@ impl AlertManager {
         false
     }
 
+    /// Check if a rule is currently in cooldown for one Tautulli server
+    async fn is_server_in_cooldown(&self, rule: &AlertRule, server: &str) -> bool {
+        let cooldowns = self.server_cooldowns.read().await;
+        cooldowns
+            .get(&(rule.id, server.to_string()))
+            .is_some_and(|last_alert_time| {
+                Utc::now() - *last_alert_time < ChronoDuration::minutes(rule.cooldown_minutes as i64)
+            })
+    }
+
     /// Evaluate a specific rule condition
     async fn evaluate_rule_condition(&self, rule: &AlertRule) -> Result<Option<Alert>> {
         match &rule.condition {
@@ -436,9 +464,81 @@ impl AlertManager {
             AlertCondition::HighRetryRate { threshold_ratio, window_minutes, min_executions } => {
                 self.evaluate_high_retry_rate_condition(rule, *threshold_ratio, *window_minutes, *min_executions).await
             }
+            // Raised through raise_play_count_anomalies
+            AlertCondition::PlayCountAnomaly { .. } => Ok(None),
         }
     }
 
+    /// Raise alerts for play count anomalies found on a Tautulli server
+    ///
+    /// Each matching play count rule raises at most one alert, describing the
+    /// most recent anomaly it covers, and then enters its cooldown for this
+    /// server only, so other servers can still raise alerts.
+    pub async fn raise_play_count_anomalies(&self, server: &str, anomalies: &[Anomaly]) -> Result<Vec<Alert>> {
+        let rules = self.rules.read().await;
+        let mut new_alerts = Vec::new();
+
+        for rule in rules.values() {
+            let AlertCondition::PlayCountAnomaly { include_spikes } = rule.condition else {
+                continue;
+            };
+            if !rule.enabled || !rule.matches_task(PLAY_COUNT_TASK_TYPE, server) {
+                continue;
+            }
+            let Some(anomaly) = anomalies
+                .iter()
+                .rev()
+                .find(|anomaly| include_spikes || anomaly.kind != AnomalyKind::Spike)
+            else {
+                continue;
+            };
+            if self.is_server_in_cooldown(rule, server).await {
+                continue;
+            }
+
+            let message = match anomaly.kind {
+                AnomalyKind::ZeroPlays => format!(
+                    "No plays on {} on {} (usually about {:.0}), the server may be down",
+                    server, anomaly.date, anomaly.expected
+                ),
+                AnomalyKind::Drop => format!(
+                    "Play count on {} dropped to {:.0} on {} (usually about {:.0})",
+                    server, anomaly.value, anomaly.date, anomaly.expected
+                ),
+                AnomalyKind::Spike => format!(
+                    "Play count on {} spiked to {:.0} on {} (usually about {:.0})",
+                    server, anomaly.value, anomaly.date, anomaly.expected
+                ),
+            };
+
+            let mut context = HashMap::new();
+            context.insert("date".to_string(), serde_json::json!(anomaly.date.to_string()));
+            context.insert("play_count".to_string(), serde_json::json!(anomaly.value));
+            context.insert("expected".to_string(), serde_json::json!(anomaly.expected));
+            context.insert("score".to_string(), serde_json::json!(anomaly.score));
+            context.insert("kind".to_string(), serde_json::json!(anomaly.kind));
+
+            warn!("Play count anomaly alert: {}", message);
+            new_alerts.push(Alert::new(
+                rule,
+                PLAY_COUNT_TASK_TYPE.to_string(),
+                Some(server.to_string()),
+                message,
+                context,
+            ));
+            self.server_cooldowns.write().await.insert((rule.id, server.to_string()), Utc::now());
+        }
+
+        if !new_alerts.is_empty() {
+            let mut alerts = self.alerts.write().await;
+            for alert in &new_alerts {
+                alerts.insert(alert.id, alert.clone());
+            }
+        }
+
+        Ok(new_alerts)
+    }
+
     /// Evaluate failure rate condition
     async fn evaluate_failure_rate_condition(
         &self,
@@ -0,0 +1,358 @@
+//! Bodies of the jobs scheduled from the configuration
+//!
+//! The automatic graph job posts graphs of recent plays to the configured
+//! Discord channels, guilds with their own schedule get the same graphs in
+//! their own channels and theme instead, and the cleanup job removes
+//! rendered graphs that a failed run left behind.
+
+use anyhow::{Context, Result};
+use chrono::Duration as ChronoDuration;
+use poise::serenity_prelude::{ChannelId, CreateAttachment, CreateMessage, GuildId, Http};
+use std::path::Path;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tgraph_commands::context::configured_privacy_policy;
+use tgraph_commands::graph::apply_guild_theme;
+use tgraph_commands::CommandContext;
+use tgraph_i18n::{fluent_args, FluentValue};
+use tgraph_common::{CancellationContext, ServerSelection, TautulliServers};
+use tgraph_config::{Config, ConfigManager};
+use tgraph_graphs::{
+    entries_in_range, render_scheduled_graphs, AggregationConfig, GraphConfig, GraphDefaults, PipelineConfig,
+    TimeRangeContext,
+};
+use tracing::{debug, error, info, warn};
+
+use crate::scheduler::{SchedulerService, AUTO_GRAPH_JOB, CLEANUP_JOB};
+
+/// Days of history in automatically posted graphs
+const AUTO_GRAPH_DAYS: u32 = 30;
+
+/// Time allowed for loading one server's history
+const HISTORY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Rendered graphs older than this were left behind by a failed run
+const STALE_GRAPH_AGE: Duration = Duration::from_secs(3600);
+
+/// Renders the scheduled graphs and posts them to Discord channels
+pub struct AutoGraphPoster {
+    http: Arc<Http>,
+    tautulli: Arc<TautulliServers>,
+}
+
+impl AutoGraphPoster {
+    pub fn new(http: Arc<Http>, tautulli: Arc<TautulliServers>) -> Self {
+        Self { http, tautulli }
+    }
+
+    /// Post graphs of every server to the channels of `config`, under `header`
+    ///
+    /// The graphs cover the last days up to today in `dates`, counted and
+    /// bucketed in its timezone. Graph types for which `graph_enabled`
+    /// returns false are left out and `style` can adjust each graph before it
+    /// is drawn. Returns the number of channels posted to; channels that fail
+    /// are logged and skipped.
+    pub async fn post<E, S>(
+        &self,
+        config: &Config,
+        dates: &TimeRangeContext,
+        header: &str,
+        graph_enabled: E,
+        style: S,
+    ) -> Result<usize>
+    where
+        E: Fn(&str) -> bool,
+        S: Fn(&mut GraphConfig),
+    {
+        let channels: Vec<ChannelId> = config
+            .discord
+            .channels
+            .iter()
+            .filter_map(|channel| match channel.parse::<u64>() {
+                Ok(id) if id > 0 => Some(ChannelId::new(id)),
+                _ => {
+                    warn!("Skipping invalid channel ID for automatic graphs: {}", channel);
+                    None
+                }
+            })
+            .collect();
+        if channels.is_empty() {
+            info!("No channels configured, skipping automatic graphs");
+            return Ok(0);
+        }
+
+        let servers = self.tautulli.select(&ServerSelection::All)?;
+        let window = dates.last_days(AUTO_GRAPH_DAYS);
+        // Tautulli reads the dates in its own timezone, so fetch a day more on
+        // either side and keep the plays on the window's local days
+        let after = window.start - ChronoDuration::days(1);
+        let before = window.end + ChronoDuration::days(1);
+        let cancellation = CancellationContext::with_timeout(HISTORY_TIMEOUT);
+        let mut entries = Vec::new();
+        for (name, client) in &servers {
+            let history = client
+                .fetch_history_between(&cancellation, after, before, 1000)
+                .await
+                .with_context(|| format!("Failed to fetch history from server '{}'", name))?;
+            entries.extend(history);
+        }
+        let entries = entries_in_range(&entries, &window, dates.timezone);
+
+        // Name the servers in the titles once there is more than one
+        let selection = (servers.len() > 1).then_some(ServerSelection::All);
+        let aggregation = AggregationConfig {
+            privacy: configured_privacy_policy(&config.privacy),
+            timezone: dates.timezone,
+            ..Default::default()
+        };
+        let defaults = GraphDefaults::from(&config.graph);
+        let style = |graph: &mut GraphConfig| {
+            defaults.apply(graph);
+            style(graph);
+        };
+        let graphs = render_scheduled_graphs(entries, selection.as_ref(), &aggregation, graph_enabled, style).await?;
+        if graphs.is_empty() {
+            info!("No plays in the last {} days, skipping automatic graphs", AUTO_GRAPH_DAYS);
+            return Ok(0);
+        }
+
+        let content = format!("📊 **{}**", header);
+        let mut posted = 0;
+        for channel in channels {
+            let files = graphs
+                .iter()
+                .map(|graph| CreateAttachment::bytes(graph.data.clone(), graph.file_name.clone()));
+            match channel
+                .send_files(&self.http, files, CreateMessage::new().content(&content))
+                .await
+            {
+                Ok(_) => posted += 1,
+                Err(e) => warn!("Failed to post automatic graphs to channel {}: {}", channel, e),
+            }
+        }
+        info!("Posted {} automatic graphs to {} channels", graphs.len(), posted);
+        Ok(posted)
+    }
+
+    /// IDs of the channels in a guild
+    pub async fn guild_channels(&self, guild_id: u64) -> Result<Vec<u64>> {
+        let channels = GuildId::new(guild_id)
+            .channels(&self.http)
+            .await
+            .with_context(|| format!("Failed to list the channels of guild {}", guild_id))?;
+        Ok(channels.into_keys().map(|id| id.get()).collect())
+    }
+}
+
+/// Split channel IDs into those among `guild_channels` and the rest
+fn split_channels(channels: &[String], guild_channels: &[u64]) -> (Vec<String>, Vec<String>) {
+    channels
+        .iter()
+        .cloned()
+        .partition(|channel| channel.parse().is_ok_and(|id: u64| guild_channels.contains(&id)))
+}
+
+/// Remove rendered graphs in `dir` that are older than `max_age`
+///
+/// Only files named like the renderer's temporary files are touched.
+/// Returns the number of files removed.
+pub async fn cleanup_stale_graphs(dir: &Path, max_age: Duration) -> Result<usize> {
+    let mut files = match tokio::fs::read_dir(dir).await {
+        Ok(files) => files,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+
+    let mut removed = 0;
+    while let Some(file) = files.next_entry().await? {
+        if !file.file_name().to_string_lossy().starts_with("tgraph_") {
+            continue;
+        }
+        let age = file
+            .metadata()
+            .await?
+            .modified()?
+            .elapsed()
+            .unwrap_or_default();
+        if age >= max_age {
+            tokio::fs::remove_file(file.path()).await?;
+            debug!("Removed stale graph file: {}", file.path().display());
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Register the bodies of the configured jobs with the scheduler
+///
+/// Must run before the scheduling configuration is first applied. The
+/// automatic graph job reads the configuration when it runs, so reloaded
+/// channels and privacy settings apply without rescheduling. Channels of
+/// guilds with their own schedule are skipped, as those guilds get their
+/// graphs from their own job.
+pub async fn register_config_jobs(
+    scheduler: &Arc<SchedulerService>,
+    poster: Arc<AutoGraphPoster>,
+    config_manager: Arc<ConfigManager>,
+) {
+    // The scheduler owns the job, so a strong reference would never be dropped
+    let guild_jobs: Weak<SchedulerService> = Arc::downgrade(scheduler);
+    scheduler
+        .register_config_job(AUTO_GRAPH_JOB, move || {
+            let poster = poster.clone();
+            let config_manager = config_manager.clone();
+            let guild_jobs = guild_jobs.clone();
+            async move {
+                let mut config = match config_manager.get_config() {
+                    Ok(config) => config,
+                    Err(e) => {
+                        error!("Failed to read the configuration for automatic graphs: {}", e);
+                        return;
+                    }
+                };
+                let scheduled_guilds = match guild_jobs.upgrade() {
+                    Some(scheduler) => scheduler.scheduled_guilds().await,
+                    None => Vec::new(),
+                };
+                let mut excluded = Vec::new();
+                for guild_id in scheduled_guilds {
+                    match poster.guild_channels(guild_id).await {
+                        Ok(channels) => excluded.extend(channels),
+                        Err(e) => warn!("{:#}, its channels may get automatic graphs twice", e),
+                    }
+                }
+                config.discord.channels = split_channels(&config.discord.channels, &excluded).1;
+
+                let timezone = config.scheduling.timezone.as_deref().unwrap_or("UTC");
+                let dates = TimeRangeContext::for_timezone_name(timezone).unwrap_or_else(|e| {
+                    warn!("{}, using UTC for automatic graphs", e);
+                    TimeRangeContext::default()
+                });
+                if let Err(e) = poster.post(&config, &dates, &default_header(), |_| true, |_| {}).await {
+                    error!("Failed to post automatic graphs: {:#}", e);
+                }
+            }
+        })
+        .await;
+
+    scheduler
+        .register_config_job(CLEANUP_JOB, || async {
+            let temp_dir = PipelineConfig::default().temp_dir;
+            match cleanup_stale_graphs(&temp_dir, STALE_GRAPH_AGE).await {
+                Ok(removed) => info!("Cleanup removed {} stale graph files", removed),
+                Err(e) => error!("Failed to clean up stale graph files: {:#}", e),
+            }
+        })
+        .await;
+}
+
+/// Register the body of the automatic graph jobs of guilds with their own schedule
+///
+/// Must run before the guild schedules are first applied. Each run posts to
+/// the guild's channels with its enabled graph types, theme and locale, and
+/// never to channels outside the guild.
+pub async fn register_guild_job(scheduler: &SchedulerService, poster: Arc<AutoGraphPoster>, data: CommandContext) {
+    let data = Arc::new(data);
+    scheduler
+        .register_guild_job(move |guild_id| {
+            let poster = poster.clone();
+            let data = data.clone();
+            async move {
+                let guild = Some(GuildId::new(guild_id));
+                let overrides = data.guild_settings.get(guild_id);
+                if !overrides.has_own_schedule() {
+                    warn!("Guild {} has no schedule and channels of its own, skipping automatic graphs", guild_id);
+                    return;
+                }
+                let guild_channels = match poster.guild_channels(guild_id).await {
+                    Ok(channels) => channels,
+                    Err(e) => {
+                        error!("Failed to post automatic graphs for guild {}: {:#}", guild_id, e);
+                        return;
+                    }
+                };
+                let mut config = data.guild_config(guild);
+                config.discord.channels = split_channels(&config.discord.channels, &guild_channels).0;
+                let args = fluent_args(&[("days", FluentValue::from(AUTO_GRAPH_DAYS))]);
+                let header = data.translate(guild, None, "auto-graphs-header", Some(&args), &default_header());
+                let posted = poster
+                    .post(
+                        &config,
+                        &data.time_range_context(guild, None),
+                        &header,
+                        |graph_type| overrides.graph_enabled(graph_type),
+                        |graph| apply_guild_theme(&mut graph.style, &overrides, &config),
+                    )
+                    .await;
+                if let Err(e) = posted {
+                    error!("Failed to post automatic graphs for guild {}: {:#}", guild_id, e);
+                }
+            }
+        })
+        .await;
+}
+
+/// Header of automatically posted graphs when there is no translation
+fn default_header() -> String {
+    format!("Viewing statistics for the last {} days", AUTO_GRAPH_DAYS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tgraph_config::settings::SchedulingConfig;
+    use tgraph_config::GuildOverrides;
+
+    #[tokio::test]
+    async fn test_cleanup_stale_graphs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("tgraph_1234_daily_plays.png"), b"png").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"keep").unwrap();
+
+        assert_eq!(cleanup_stale_graphs(dir.path(), STALE_GRAPH_AGE).await.unwrap(), 0);
+        assert_eq!(cleanup_stale_graphs(dir.path(), Duration::ZERO).await.unwrap(), 1);
+        assert!(dir.path().join("notes.txt").exists());
+        assert_eq!(cleanup_stale_graphs(&dir.path().join("missing"), Duration::ZERO).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_guild_with_only_a_schedule_posts_nowhere_else() {
+        // Channel 1 is in another guild, channel 3 in the scheduled one
+        let mut global = Config::default();
+        global.discord.channels = vec!["1".to_string(), "3".to_string()];
+        let guild_channels = [3, 4];
+
+        let mut overrides = GuildOverrides::default();
+        overrides.set("auto_graph_cron", "0 0 18 * * *", &[]).unwrap();
+        assert!(!overrides.has_own_schedule());
+        let config = overrides.apply(&global);
+        assert_eq!(split_channels(&config.discord.channels, &guild_channels).0, vec!["3"]);
+
+        overrides.set("channels", "4", &guild_channels).unwrap();
+        let config = overrides.apply(&global);
+        assert_eq!(split_channels(&config.discord.channels, &guild_channels).0, vec!["4"]);
+        assert_eq!(split_channels(&global.discord.channels, &guild_channels).1, vec!["1"]);
+    }
+
+    #[tokio::test]
+    async fn test_config_jobs_are_scheduled() {
+        let scheduler = Arc::new(SchedulerService::new().await.unwrap());
+        let poster = Arc::new(AutoGraphPoster::new(
+            Arc::new(Http::new("")),
+            Arc::new(TautulliServers::new()),
+        ));
+        let config_manager = Arc::new(ConfigManager::new(Config::default()));
+        register_config_jobs(&scheduler, poster, config_manager).await;
+
+        let config = SchedulingConfig {
+            auto_graph_cron: Some("0 0 * * *".to_string()),
+            cleanup_cron: Some("0 2 * * 0".to_string()),
+            timezone: None,
+            enabled: true,
+        };
+        scheduler.apply_scheduling_config(&config).await.unwrap();
+        let mut names: Vec<String> = scheduler.list_jobs().await.into_iter().map(|job| job.name).collect();
+        names.sort();
+        assert_eq!(names, vec![AUTO_GRAPH_JOB, CLEANUP_JOB]);
+    }
+}
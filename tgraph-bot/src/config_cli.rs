@@ -0,0 +1,271 @@
+//! `tgraph-bot config` subcommands
+//!
+//! `validate` checks a configuration file the way the bot would load it and
+//! prints every problem with its field path; `schema` prints the JSON Schema
+//! for editor autocompletion; `migrate` upgrades configuration, schedule and
+//! saved graph files written by older releases.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use poise::serenity_prelude as serenity;
+use serde_json::Value;
+use tgraph_common::TautulliClient;
+use tgraph_config::migrate::{dropped_settings, strip_nulls};
+use tgraph_config::validation::field_errors;
+use tgraph_config::{
+    config_schema_json, migrate_as, Config, ConfigError, ConfigFormat, ConfigLoader, ConfigSource,
+    DocumentKind, LayeredConfig,
+};
+use tgraph_graphs::CompleteGraphConfig;
+
+use crate::schedule_config::ScheduleConfigCollection;
+
+/// Actions of the `config` subcommand
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Check a configuration file and print every validation error
+    Validate {
+        /// Configuration file to check
+        file: PathBuf,
+
+        /// Also connect to every Tautulli server and check the Discord token
+        #[arg(long)]
+        check_connectivity: bool,
+    },
+    /// Print the JSON Schema for configuration files
+    Schema,
+    /// Upgrade a file written by an older release, keeping a backup of the original
+    Migrate {
+        /// Configuration, schedule or saved graph file
+        file: PathBuf,
+
+        /// Kind of file: config, schedules or graph; detected from the content when omitted
+        #[arg(long, value_parser = parse_kind)]
+        kind: Option<DocumentKind>,
+
+        /// Print the changes without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+fn parse_kind(name: &str) -> Result<DocumentKind, String> {
+    DocumentKind::from_name(name).ok_or_else(|| format!("expected config, schedules or graph, got '{}'", name))
+}
+
+/// Run a `config` subcommand
+pub async fn run(command: ConfigCommand, overrides: &[String]) -> Result<()> {
+    match command {
+        ConfigCommand::Validate { file, check_connectivity } => {
+            let config = validate(&file, overrides)?;
+            println!("{} is valid", file.display());
+            if check_connectivity {
+                check_connectivity_of(&config).await?;
+            }
+            Ok(())
+        }
+        ConfigCommand::Schema => {
+            println!("{}", config_schema_json());
+            Ok(())
+        }
+        ConfigCommand::Migrate { file, kind, dry_run } => migrate(&file, kind, dry_run).map(|_| ()),
+    }
+}
+
+/// Load `file` with its fragments, environment and overrides, printing every validation error
+pub fn validate(file: &Path, overrides: &[String]) -> Result<Config> {
+    let layered = ConfigLoader::load_layered(Some(file), overrides)?;
+    report_validation(file, &layered)
+}
+
+/// Load `file` on top of the defaults only, printing every validation error
+///
+/// Unlike [`validate`], fragments and `TGRAPH_*` variables are ignored, so
+/// the result says whether the file is complete on its own.
+pub fn validate_file(file: &Path) -> Result<Config> {
+    let mut layered = LayeredConfig::defaults();
+    layered.merge_file(file)?;
+    report_validation(file, &layered)
+}
+
+fn report_validation(file: &Path, layered: &LayeredConfig) -> Result<Config> {
+    match layered.build() {
+        Ok(config) => Ok(config),
+        Err(ConfigError::ValidationError(errors)) => {
+            let errors = field_errors(&errors);
+            for error in &errors {
+                println!("  {}", error);
+            }
+            bail!("{} has {} validation error(s)", file.display(), errors.len());
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Upgrade `file` in place in its own format, returning the backup of the original
+///
+/// Nothing is written when the file is current, on a dry run, or when a
+/// setting in the upgraded file would not be read by this release. The file
+/// is rewritten from its parsed values, so comments are lost.
+pub fn migrate(file: &Path, kind: Option<DocumentKind>, dry_run: bool) -> Result<Option<PathBuf>> {
+    let content = fs::read_to_string(file)?;
+    let format = ConfigFormat::detect(file, &content);
+    let value: Value = format.parse(file, &content)?;
+    let kind = kind.unwrap_or_else(|| DocumentKind::detect(&value));
+    let report = migrate_as(kind, value)?;
+
+    if report.is_noop() {
+        println!("{} is already {} version {}", file.display(), kind, report.to);
+        return Ok(None);
+    }
+
+    println!("{}: {} version {} -> {}", file.display(), kind, report.from, report.to);
+    for step in &report.applied {
+        println!("  * {}", step);
+    }
+    for line in report.changes() {
+        println!("  {}", line);
+    }
+    for note in &report.notes {
+        println!("  note: {}", note);
+    }
+
+    let dropped = dropped_keys(kind, &report.migrated)?;
+    if !dropped.is_empty() {
+        for key in &dropped {
+            println!("  unknown setting: {}", key);
+        }
+        bail!("{} setting(s) in {} would be dropped; remove or rename them first", dropped.len(), file.display());
+    }
+    if format != ConfigFormat::Json {
+        // The file is written back from the parsed values
+        println!("  note: comments and formatting in {} are not kept, the backup keeps the original", file.display());
+    }
+    if dry_run {
+        println!("Dry run, {} was not changed", file.display());
+        return Ok(None);
+    }
+
+    let backup = backup_path(file, &report.from);
+    fs::copy(file, &backup)?;
+    let mut migrated = report.migrated;
+    if format == ConfigFormat::Toml {
+        strip_nulls(&mut migrated);
+    }
+    fs::write(file, format.serialize(&migrated)?)?;
+    println!("Migrated {}, the original is saved as {}", file.display(), backup.display());
+    Ok(Some(backup))
+}
+
+/// Settings of a migrated document that the current release would not read
+fn dropped_keys(kind: DocumentKind, value: &Value) -> Result<Vec<String>, ConfigError> {
+    match kind {
+        DocumentKind::Config => {
+            let mut layered = LayeredConfig::defaults();
+            layered.merge_value(value.clone(), ConfigSource::Cli);
+            Ok(layered.unknown_keys())
+        }
+        DocumentKind::Schedules => dropped_settings::<ScheduleConfigCollection>(value),
+        DocumentKind::GraphConfig => dropped_settings::<CompleteGraphConfig>(value),
+    }
+}
+
+/// `<file>.v<version>.bak`, numbered when that backup already exists
+fn backup_path(file: &Path, version: &str) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let candidate = |suffix: String| file.with_file_name(format!("{}.v{}{}.bak", name, version, suffix));
+    let mut path = candidate(String::new());
+    let mut n = 1;
+    while path.exists() {
+        path = candidate(format!(".{}", n));
+        n += 1;
+    }
+    path
+}
+
+/// Contact every Tautulli server and Discord with the configured credentials
+async fn check_connectivity_of(config: &Config) -> Result<()> {
+    let mut failures = 0;
+
+    for (name, client_config) in config.tautulli.client_configs() {
+        let reachable = TautulliClient::new(client_config)?.test_connection().await;
+        println!("Tautulli server {}: {}", name, if reachable { "reachable" } else { "unreachable" });
+        if !reachable {
+            failures += 1;
+        }
+    }
+
+    let http = serenity::Http::new(&config.discord.token);
+    match http.get_current_user().await {
+        Ok(user) => println!("Discord token: valid (logged in as {})", user.name),
+        Err(e) => {
+            println!("Discord token: rejected ({})", e);
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        bail!("{} connectivity check(s) failed", failures);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_validate_reports_every_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[discord]\ntoken = \"abc.def.ghi\"\n\n[graph]\nwidth = 5\nheight = 5\n").unwrap();
+
+        let err = validate(&path, &[]).unwrap_err();
+        assert!(err.to_string().ends_with("has 2 validation error(s)"), "{}", err);
+
+        let config = validate(&path, &["graph.width=1280".to_string(), "graph.height=720".to_string()]).unwrap();
+        assert_eq!(config.graph.width, 1280);
+    }
+
+    #[test]
+    fn test_migrate_config_with_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let original = "[discord]\ntoken = \"abc.def.ghi\"\nchannels = [\"1\"]\n\n[graph]\nwidth = 1280\n";
+        fs::write(&path, original).unwrap();
+
+        assert_eq!(migrate(&path, None, true).unwrap(), None);
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+
+        let backup = migrate(&path, None, false).unwrap().unwrap();
+        assert_eq!(backup, dir.path().join("config.toml.v1.bak"));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+        let config = validate(&path, &[]).unwrap();
+        assert_eq!(config.version, tgraph_config::migrate::CONFIG_VERSION);
+        assert_eq!(config.graph.width, 1280);
+        assert_eq!(config.discord.channels, vec!["1"]);
+        assert_eq!(migrate(&path, None, false).unwrap(), None);
+
+        fs::write(&path, original.replace("width", "dpi")).unwrap();
+        assert!(migrate(&path, None, false).is_err());
+        assert!(!dir.path().join("config.toml.v1.1.bak").exists());
+    }
+
+    #[test]
+    fn test_migrate_saved_graph_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("weekly.json");
+        let mut saved = CompleteGraphConfig::default();
+        saved.metadata.version = "1.0.0".to_string();
+        saved.filters.time_range_preset = Some(tgraph_graphs::TimeRangePreset::LastWeek);
+        fs::write(&path, serde_json::to_string_pretty(&saved).unwrap()).unwrap();
+
+        migrate(&path, Some(DocumentKind::GraphConfig), false).unwrap();
+        let migrated: CompleteGraphConfig = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(migrated.metadata.version, tgraph_config::migrate::GRAPH_CONFIG_VERSION);
+        assert!(matches!(migrated.filters.time_range_preset, Some(tgraph_graphs::TimeRangePreset::LastSevenDays)));
+    }
+}
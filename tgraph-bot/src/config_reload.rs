@@ -0,0 +1,109 @@
+//! Live configuration reload for the bot's subsystems
+//!
+//! The scheduler and permissions subscribe to configuration changes
+//! themselves; the adapters here do the same for the shared Tautulli clients
+//! and the graph defaults.
+
+use std::sync::{Arc, PoisonError, RwLock};
+use async_trait::async_trait;
+use tgraph_common::TautulliServers;
+use tgraph_config::{Config, ConfigSection, ConfigSubscriber};
+use tgraph_graphs::GraphDefaults;
+
+/// Reconnects the Tautulli clients when servers are added, removed or changed
+pub struct TautulliReloader {
+    servers: Arc<TautulliServers>,
+}
+
+impl TautulliReloader {
+    /// Reloader for the shared Tautulli clients
+    pub fn new(servers: Arc<TautulliServers>) -> Self {
+        Self { servers }
+    }
+}
+
+#[async_trait]
+impl ConfigSubscriber for TautulliReloader {
+    fn name(&self) -> &str {
+        "tautulli"
+    }
+
+    fn sections(&self) -> &[ConfigSection] {
+        &[ConfigSection::Tautulli]
+    }
+
+    async fn apply(&self, config: &Config) -> Result<(), String> {
+        self.servers
+            .reconfigure(config.tautulli.client_configs())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Keeps the graph defaults of the `[graph]` section for graphs rendered from now on
+#[derive(Debug, Default)]
+pub struct GraphDefaultsReloader {
+    current: RwLock<GraphDefaults>,
+}
+
+impl GraphDefaultsReloader {
+    /// Reloader starting from the defaults of `config`
+    pub fn new(config: &Config) -> Self {
+        Self {
+            current: RwLock::new(GraphDefaults::from(&config.graph)),
+        }
+    }
+
+    /// The graph defaults as of the last reload
+    pub fn get(&self) -> GraphDefaults {
+        self.current.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+#[async_trait]
+impl ConfigSubscriber for GraphDefaultsReloader {
+    fn name(&self) -> &str {
+        "graph defaults"
+    }
+
+    fn sections(&self) -> &[ConfigSection] {
+        &[ConfigSection::Graph]
+    }
+
+    async fn apply(&self, config: &Config) -> Result<(), String> {
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = GraphDefaults::from(&config.graph);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tautulli_reloader_keeps_servers_on_error() {
+        let mut config = Config::default();
+        let servers = Arc::new(TautulliServers::from_configs(config.tautulli.client_configs()).unwrap());
+        let reloader = TautulliReloader::new(servers.clone());
+
+        let mut cabin = config.tautulli.all_servers().remove(0);
+        cabin.name = "Cabin".to_string();
+        config.tautulli.servers.push(cabin.clone());
+        reloader.apply(&config).await.unwrap();
+        assert_eq!(servers.len(), 2);
+
+        config.tautulli.servers.push(cabin);
+        assert!(reloader.apply(&config).await.is_err());
+        assert_eq!(servers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_graph_defaults_follow_reloads() {
+        let mut config = Config::default();
+        let reloader = GraphDefaultsReloader::new(&config);
+        assert_eq!(reloader.get().width, config.graph.width);
+
+        config.graph.width = 1280;
+        reloader.apply(&config).await.unwrap();
+        assert_eq!(reloader.get().width, 1280);
+    }
+}
@@ -1536,6 +1536,7 @@ mod tests {
     fn create_test_config(token: &str) -> DiscordConfig {
         DiscordConfig {
             token: token.to_string(),
+            token_file: None,
             channels: vec![],
             max_concurrent_requests: 10,
             request_timeout_seconds: 30,
@@ -0,0 +1,437 @@
+//! `tgraph-bot init` first-run setup
+//!
+//! Asks for the Tautulli and Discord credentials, checks each one live, lets
+//! the user pick from the channels the bot can actually post graphs in, and
+//! writes a configuration file that passes validation.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use anyhow::{bail, Context, Result};
+use chrono_tz::Tz;
+use poise::serenity_prelude::{self as serenity, ChannelType};
+use tgraph_common::TautulliClient;
+use tgraph_config::migrate::strip_nulls;
+use tgraph_config::validation::{field_errors, validate_timezone};
+use tgraph_config::{Config, ConfigFormat};
+
+use crate::config_cli;
+use crate::discord::DiscordClient;
+
+/// Everything the wizard asks for
+#[derive(Debug, Clone)]
+pub struct InitAnswers {
+    pub tautulli_url: String,
+    pub tautulli_api_key: String,
+    pub discord_token: String,
+    pub channels: Vec<u64>,
+    pub owner_id: Option<u64>,
+    pub timezone: String,
+}
+
+/// A channel the bot can post graphs in
+#[derive(Debug, Clone)]
+struct PostableChannel {
+    id: u64,
+    label: String,
+}
+
+/// Line-based prompts on any input and output
+pub struct Prompter<R, W> {
+    input: R,
+    output: W,
+    hide_secrets: bool,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            hide_secrets: false,
+        }
+    }
+
+    /// Turn off terminal echo on stdin while answering [`ask_secret`](Self::ask_secret)
+    pub fn hide_secrets(mut self) -> Self {
+        self.hide_secrets = true;
+        self
+    }
+
+    /// Ask for a required secret, without echoing it when secrets are hidden
+    pub fn ask_secret(&mut self, question: &str) -> Result<String> {
+        let _echo = if self.hide_secrets { EchoOff::stdin()? } else { None };
+        self.ask(question, None)
+    }
+
+    /// Ask a question; an empty answer selects `default` when there is one
+    ///
+    /// An empty `default` makes the question optional.
+    pub fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String> {
+        loop {
+            match default.filter(|default| !default.is_empty()) {
+                Some(default) => write!(self.output, "{} [{}]: ", question, default)?,
+                None => write!(self.output, "{}: ", question)?,
+            }
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                bail!("input ended before setup was complete");
+            }
+            let answer = line.trim();
+            match (answer.is_empty(), default) {
+                (false, _) => return Ok(answer.to_string()),
+                (true, Some(default)) => return Ok(default.to_string()),
+                (true, None) => writeln!(self.output, "  A value is required.")?,
+            }
+        }
+    }
+
+    /// Ask a yes/no question
+    pub fn confirm(&mut self, question: &str, default: bool) -> Result<bool> {
+        let answer = self.ask(question, Some(if default { "Y/n" } else { "y/N" }))?;
+        Ok(match answer.to_lowercase().as_str() {
+            "y" | "yes" => true,
+            "n" | "no" => false,
+            _ => default,
+        })
+    }
+
+    /// Print a line
+    pub fn say(&mut self, line: impl AsRef<str>) -> Result<()> {
+        writeln!(self.output, "{}", line.as_ref())?;
+        Ok(())
+    }
+}
+
+/// Run the wizard on the terminal and write the configuration to `output`
+pub async fn run(output: &Path, force: bool) -> Result<()> {
+    if output.exists() && !force {
+        bail!("{} already exists; pass --force to overwrite it", output.display());
+    }
+
+    let stdin = io::stdin();
+    let mut prompter = Prompter::new(stdin.lock(), io::stdout()).hide_secrets();
+    prompter.say("TGraph Bot setup. Press Enter to accept the value in brackets.")?;
+
+    let (tautulli_url, tautulli_api_key) = ask_tautulli(&mut prompter).await?;
+    let (discord_token, http) = ask_discord_token(&mut prompter).await?;
+    let channels = ask_channels(&mut prompter, &discord_token, &http).await?;
+
+    let app_owner = http
+        .get_current_application_info()
+        .await
+        .ok()
+        .and_then(|info| info.owner)
+        .map(|owner| owner.id.get().to_string())
+        .unwrap_or_default();
+    let owner_id = prompter.ask("Your Discord user ID, for owner commands (optional)", Some(&app_owner))?;
+    let owner_id = match owner_id.as_str() {
+        "" => None,
+        id => Some(id.parse().with_context(|| format!("'{}' is not a Discord user ID", id))?),
+    };
+    let timezone = ask_timezone(&mut prompter)?;
+
+    let answers = InitAnswers {
+        tautulli_url,
+        tautulli_api_key,
+        discord_token,
+        channels,
+        owner_id,
+        timezone,
+    };
+    let config = build_config(&answers)?;
+    write_config(output, &config)?;
+    config_cli::validate_file(output)?;
+    prompter.say(format!("Wrote {}. Start the bot with `tgraph-bot --config {}`.", output.display(), output.display()))?;
+    Ok(())
+}
+
+/// Ask for the Tautulli URL and API key until the server answers
+async fn ask_tautulli<R: BufRead, W: Write>(prompter: &mut Prompter<R, W>) -> Result<(String, String)> {
+    loop {
+        let url = prompter.ask("Tautulli URL", Some("http://localhost:8181"))?;
+        let api_key = prompter.ask_secret("Tautulli API key (Settings > Web Interface > API)")?;
+
+        let mut config = Config::default();
+        config.tautulli.url = url.clone();
+        config.tautulli.api_key = api_key.clone();
+        let (_, client_config) = config.tautulli.client_configs().remove(0);
+        let client = TautulliClient::new(client_config)?;
+
+        if client.test_connection().await {
+            if let Ok(info) = client.get_server_info().await {
+                prompter.say(format!(
+                    "  Connected to Tautulli {} on {}",
+                    info.version.as_deref().unwrap_or("(unknown version)"),
+                    info.platform.as_deref().unwrap_or("an unknown platform")
+                ))?;
+            }
+            return Ok((url, api_key));
+        }
+
+        prompter.say("  Could not reach Tautulli with that URL and API key.")?;
+        if !prompter.confirm("Try again?", true)? {
+            bail!("setup cancelled: Tautulli is not reachable");
+        }
+    }
+}
+
+/// Ask for the bot token until Discord accepts it
+async fn ask_discord_token<R: BufRead, W: Write>(prompter: &mut Prompter<R, W>) -> Result<(String, serenity::Http)> {
+    loop {
+        let token = prompter.ask_secret("Discord bot token (Developer Portal > Bot > Reset Token)")?;
+        let http = serenity::Http::new(&token);
+        match http.get_current_user().await {
+            Ok(user) => {
+                prompter.say(format!("  Logged in as {}", user.name))?;
+                return Ok((token, http));
+            }
+            Err(e) => {
+                prompter.say(format!("  Discord rejected the token: {}", e))?;
+                if !prompter.confirm("Try again?", true)? {
+                    bail!("setup cancelled: the Discord token was rejected");
+                }
+            }
+        }
+    }
+}
+
+/// List the text channels the bot can post graphs in and let the user pick some
+async fn ask_channels<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    token: &str,
+    http: &serenity::Http,
+) -> Result<Vec<u64>> {
+    let mut discord_config = Config::default().discord;
+    discord_config.token = token.to_string();
+    let discord = DiscordClient::new(discord_config);
+
+    let mut postable = Vec::new();
+    for guild in http.get_guilds(None, None).await? {
+        let mut channels: Vec<_> = guild
+            .id
+            .channels(http)
+            .await?
+            .into_values()
+            .filter(|channel| matches!(channel.kind, ChannelType::Text | ChannelType::News))
+            .collect();
+        channels.sort_by_key(|channel| channel.position);
+
+        for channel in channels {
+            if discord.check_channel_permissions(http, channel.id).await.can_post_graphs() {
+                postable.push(PostableChannel {
+                    id: channel.id.get(),
+                    label: format!("{} / #{}", guild.name, channel.name),
+                });
+            }
+        }
+    }
+
+    if postable.is_empty() {
+        prompter.say("  The bot cannot post in any channel yet. Invite it with the Send Messages and Attach Files permissions, then add channel IDs to discord.channels.")?;
+        return Ok(Vec::new());
+    }
+
+    prompter.say("Channels the bot can post graphs in:")?;
+    for (i, channel) in postable.iter().enumerate() {
+        prompter.say(format!("  {}. {}", i + 1, channel.label))?;
+    }
+    loop {
+        let answer = prompter.ask("Channels to use, as numbers separated by commas", Some("1"))?;
+        match select_channels(&answer, &postable) {
+            Ok(ids) => return Ok(ids),
+            Err(e) => prompter.say(format!("  {}", e))?,
+        }
+    }
+}
+
+/// Channel IDs for a selection like `1, 3`
+fn select_channels(answer: &str, channels: &[PostableChannel]) -> Result<Vec<u64>> {
+    let mut ids = Vec::new();
+    for part in answer.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let index: usize = part.parse().with_context(|| format!("'{}' is not a number", part))?;
+        let channel = index
+            .checked_sub(1)
+            .and_then(|i| channels.get(i))
+            .with_context(|| format!("there is no channel {}", index))?;
+        if !ids.contains(&channel.id) {
+            ids.push(channel.id);
+        }
+    }
+    Ok(ids)
+}
+
+/// Ask for the timezone until it names a known IANA timezone
+fn ask_timezone<R: BufRead, W: Write>(prompter: &mut Prompter<R, W>) -> Result<String> {
+    loop {
+        let answer = prompter.ask("Timezone for schedules and graphs", Some("UTC"))?;
+        match check_timezone(&answer) {
+            Ok(()) => return Ok(answer),
+            Err(e) => prompter.say(format!("  {}", e))?,
+        }
+    }
+}
+
+/// Accept the timezones the configuration validation accepts, if chrono knows them
+fn check_timezone(name: &str) -> Result<()> {
+    if name.parse::<Tz>().is_err() || validate_timezone(name).is_err() {
+        bail!("'{}' is not a timezone such as UTC or Europe/Berlin", name);
+    }
+    Ok(())
+}
+
+/// Default configuration completed with the answers, validated
+pub fn build_config(answers: &InitAnswers) -> Result<Config> {
+    let mut config = Config::default();
+    config.tautulli.url = answers.tautulli_url.clone();
+    config.tautulli.api_key = answers.tautulli_api_key.clone();
+    config.discord.token = answers.discord_token.clone();
+    config.discord.channels = answers.channels.iter().map(u64::to_string).collect();
+    config.discord.owner_ids = answers.owner_id.into_iter().collect();
+    config.scheduling.timezone = Some(answers.timezone.clone());
+
+    if let Err(errors) = config.validate_all() {
+        let errors = field_errors(&errors);
+        let details: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        bail!("the configuration is not valid: {}", details.join("; "));
+    }
+    Ok(config)
+}
+
+/// Write `config` in the format implied by the file extension, readable only by the owner
+pub fn write_config(path: &Path, config: &Config) -> Result<()> {
+    let format = ConfigFormat::from_extension(path).unwrap_or(ConfigFormat::Toml);
+    let mut value = serde_json::to_value(config)?;
+    if format == ConfigFormat::Toml {
+        strip_nulls(&mut value);
+    }
+    let content = format.serialize(&value)?;
+
+    // Created owner-only so the secrets are never readable by others, even briefly
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    // An existing file keeps its mode, so narrow it before writing
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+/// Terminal echo on stdin turned off until dropped
+struct EchoOff {
+    #[cfg(unix)]
+    original: rustix::termios::Termios,
+}
+
+impl EchoOff {
+    /// Turn off echo, or do nothing when stdin is not a terminal
+    #[cfg(unix)]
+    fn stdin() -> Result<Option<Self>> {
+        use rustix::termios::{isatty, tcgetattr, tcsetattr, LocalModes, OptionalActions};
+
+        let stdin = io::stdin();
+        if !isatty(&stdin) {
+            return Ok(None);
+        }
+        let original = tcgetattr(&stdin)?;
+        let mut hidden = original.clone();
+        // Still echo the Enter so the next prompt starts on a new line
+        hidden.local_modes.remove(LocalModes::ECHO);
+        hidden.local_modes.insert(LocalModes::ECHONL);
+        tcsetattr(&stdin, OptionalActions::Now, &hidden)?;
+        Ok(Some(Self { original }))
+    }
+
+    #[cfg(not(unix))]
+    fn stdin() -> Result<Option<Self>> {
+        Ok(None)
+    }
+}
+
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        {
+            let _ = rustix::termios::tcsetattr(io::stdin(), rustix::termios::OptionalActions::Now, &self.original);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompter_defaults_and_selection() {
+        let mut output = Vec::new();
+        let mut prompter = Prompter::new("\n\nhttp://tautulli:8181\nn\n".as_bytes(), &mut output);
+        assert_eq!(prompter.ask("Tautulli URL", None).unwrap(), "http://tautulli:8181");
+        assert!(!prompter.confirm("Try again?", true).unwrap());
+        assert!(prompter.ask_secret("Token").is_err());
+        assert!(String::from_utf8(output).unwrap().contains("A value is required."));
+
+        let channels: Vec<PostableChannel> = (1..=3)
+            .map(|id| PostableChannel { id: id * 100, label: format!("Home / #c{}", id) })
+            .collect();
+        assert_eq!(select_channels("3, 1, 3", &channels).unwrap(), vec![300, 100]);
+        assert!(select_channels("4", &channels).is_err());
+        assert!(select_channels("two", &channels).is_err());
+    }
+
+    #[test]
+    fn test_timezone_is_asked_again_until_known() {
+        let mut output = Vec::new();
+        let mut prompter = Prompter::new("Mars/Olympus
+EST
+Europe/Oslo
+".as_bytes(), &mut output);
+        assert_eq!(ask_timezone(&mut prompter).unwrap(), "Europe/Oslo");
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("'Mars/Olympus' is not a timezone"));
+        assert!(output.contains("'EST' is not a timezone"));
+        assert!(check_timezone("UTC").is_ok());
+    }
+
+    #[test]
+    fn test_written_config_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let answers = InitAnswers {
+            tautulli_url: "http://tautulli:8181".to_string(),
+            tautulli_api_key: "0123456789abcdef".to_string(),
+            discord_token: Config::default().discord.token,
+            channels: vec![123456789012345678],
+            owner_id: Some(42),
+            timezone: "Europe/Oslo".to_string(),
+        };
+
+        // A stale file is replaced and narrowed to the owner
+        fs::write(&path, "old").unwrap();
+        write_config(&path, &build_config(&answers).unwrap()).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let config = config_cli::validate_file(&path).unwrap();
+        assert_eq!(config.discord.channels, vec!["123456789012345678"]);
+        assert_eq!(config.discord.owner_ids, vec![42]);
+        assert_eq!(config.scheduling.timezone.as_deref(), Some("Europe/Oslo"));
+
+        let invalid = InitAnswers { tautulli_url: "not a url".to_string(), ..answers };
+        assert!(build_config(&invalid).is_err());
+    }
+}
@@ -10,10 +10,16 @@ pub mod task_queue;
 pub mod metrics;
 pub mod alerting;
 pub mod persistence;
+pub mod activity_sampler;
+pub mod play_count_monitor;
 pub mod admin_api;
 pub mod timezone_support;
 pub mod monitoring_system;
 pub mod discord;
+pub mod auto_graph;
+pub mod config_reload;
+pub mod config_cli;
+pub mod init_wizard;
 
 // Re-export commonly used types
 pub use scheduler::{SchedulerService, JobMetadata};
@@ -23,6 +29,9 @@ pub use task_queue::{TaskQueue, QueuedTask, TaskResult, RetryStrategy, QueueStat
 pub use metrics::{MetricsCollector, TaskExecutionMetric, AggregatedMetrics};
 pub use alerting::{AlertManager, Alert, AlertRule, AlertSeverity};
 pub use persistence::PersistenceManager;
+pub use activity_sampler::ActivitySampler;
+pub use play_count_monitor::PlayCountMonitor;
 pub use admin_api::{AdminApiState, create_admin_api_router, start_admin_api_server};
 pub use timezone_support::{TimezoneManager, TimezoneConfig, TimezoneInfo};
 pub use monitoring_system::{MonitoringSystem, MonitoringConfig, MonitoringHealthStatus};
+pub use config_reload::{TautulliReloader, GraphDefaultsReloader};
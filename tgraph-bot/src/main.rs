@@ -1,17 +1,26 @@
 //! TGraph Telegram Bot - Main Entry Point
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use poise::serenity_prelude::{self as serenity, GatewayIntents};
-use tracing::{info, error};
+use tracing::{info, warn, error};
 use tracing_subscriber::{self, EnvFilter};
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tgraph_config::ConfigLoader;
+use tokio::sync::broadcast::error::RecvError;
+use tgraph_common::{TGraphError, TautulliServers};
+use tgraph_config::{Config, ConfigLoader, ConfigManager};
 use tgraph_commands::{CommandRegistry, CommandContext, create_command_context};
+use tgraph_commands::context::configured_privacy_policy;
 
+use auto_graph::AutoGraphPoster;
+use config_cli::ConfigCommand;
+use config_reload::{GraphDefaultsReloader, TautulliReloader};
+use scheduler::SchedulerService;
 use scheduling_integration::SchedulingSystem;
 
+mod auto_graph;
 mod discord;
 mod scheduler;
 mod task_manager;
@@ -20,10 +29,15 @@ mod task_queue;
 mod metrics;
 mod alerting;
 mod persistence;
+mod activity_sampler;
+mod play_count_monitor;
 mod admin_api;
 mod timezone_support;
 mod monitoring_system;
 mod scheduling_integration;
+mod config_reload;
+mod config_cli;
+mod init_wizard;
 
 // Use the command context from tgraph_commands
 type Data = CommandContext;
@@ -43,6 +57,37 @@ struct Args {
     /// Log level
     #[arg(short, long, default_value = "info")]
     log_level: String,
+
+    /// Override a configuration value, e.g. `--set graph.width=1280` (repeatable)
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    set: Vec<String>,
+
+    /// Print the effective configuration and where each value came from, then exit
+    #[arg(long)]
+    print_effective_config: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Subcommands; without one the bot starts
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Validate configuration files or export their schema
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Create a configuration file interactively, checking Tautulli and Discord as you go
+    Init {
+        /// File to write; the extension selects TOML, YAML or JSON
+        #[arg(default_value = "config.toml")]
+        output: PathBuf,
+
+        /// Overwrite the file if it exists
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 /// Setup function for Poise framework - initializes shared data and handles bot ready event
@@ -50,6 +95,10 @@ async fn setup(
     ctx: &serenity::Context,
     ready: &serenity::Ready,
     framework: &poise::Framework<Data, Error>,
+    config: Config,
+    tautulli: Arc<TautulliServers>,
+    config_manager: Arc<ConfigManager>,
+    scheduler: Arc<SchedulerService>,
 ) -> Result<Data, Error> {
     info!("Bot connected as: {}", ready.user.name);
     info!("Bot ID: {}", ready.user.id);
@@ -59,11 +108,30 @@ async fn setup(
     poise::builtins::register_globally(ctx, &framework.options().commands).await?;
     info!("Slash commands registered globally");
     
-    // Load configuration
-    let config = ConfigLoader::load()?;
-    
     // Create command context with all required components
-    let data = create_command_context(config).await?;
+    let scheduling_enabled = config.scheduling.enabled;
+    let data = create_command_context(config, tautulli.clone()).await?;
+    config_manager.spawn_subscriber(data.permissions.clone());
+    config_manager.spawn_subscriber(data.config.clone());
+
+    // Keep the automatic graph jobs of guilds with their own schedule up to date
+    let poster = Arc::new(AutoGraphPoster::new(ctx.http.clone(), tautulli));
+    auto_graph::register_guild_job(&scheduler, poster, data.clone()).await;
+    let guild_settings = data.guild_settings.clone();
+    let shared_config = data.config.clone();
+    let guild_schedules = move || {
+        if scheduling_enabled { guild_settings.schedules(&shared_config.get()) } else { Vec::new() }
+    };
+    scheduler.apply_guild_schedules(&guild_schedules()).await?;
+    let mut changes = data.guild_settings.subscribe();
+    tokio::spawn(async move {
+        // A lagging receiver only missed guild IDs; the schedules are re-read whole
+        while !matches!(changes.recv().await, Err(RecvError::Closed)) {
+            if let Err(e) = scheduler.apply_guild_schedules(&guild_schedules()).await {
+                warn!("Failed to update guild schedules: {:#}", e);
+            }
+        }
+    });
     
     info!("Command context initialized successfully");
     Ok(data)
@@ -88,6 +156,23 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
         }
         poise::FrameworkError::Command { error, ctx, .. } => {
             error!("Error in command '{}': {:?}", ctx.command().name, error);
+
+            // Tell the user plainly when Tautulli is down instead of failing silently
+            if let Some(unavailable) = error
+                .downcast_ref::<TGraphError>()
+                .filter(|e| e.is_service_unavailable())
+            {
+                let message = match unavailable.retry_after() {
+                    Some(retry_after) => format!(
+                        "📡 Tautulli is currently unavailable. Please try again in about {} seconds.",
+                        retry_after.as_secs().max(1)
+                    ),
+                    None => "📡 Tautulli is currently unavailable. Please try again shortly.".to_string(),
+                };
+                if let Err(e) = ctx.say(message).await {
+                    error!("Failed to send unavailable notice: {:?}", e);
+                }
+            }
         }
         poise::FrameworkError::EventHandler { error, event, .. } => {
             error!("Error in event handler for {:?}: {:?}", event.snake_case_name(), error);
@@ -102,6 +187,19 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    match args.command {
+        Some(Command::Config { action }) => return config_cli::run(action, &args.set).await,
+        Some(Command::Init { output, force }) => return init_wizard::run(&output, force).await,
+        None => {}
+    }
+
+    // Defaults, file, conf.d fragments, environment, then --set overrides
+    let layered = ConfigLoader::load_layered(args.config.as_deref().map(Path::new), &args.set)?;
+    if args.print_effective_config {
+        print!("{}", layered.describe());
+        return Ok(());
+    }
+
     // Initialize logging
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::new(&args.log_level))
@@ -109,20 +207,54 @@ async fn main() -> Result<()> {
 
     info!("Starting TGraph Discord Bot");
 
-    // Load configuration
-    let config = match args.config {
-        Some(path) => ConfigLoader::load_from_file(&path)?,
-        None => ConfigLoader::load()?,
-    };
+    let config = layered.build()?;
 
     info!("Configuration loaded successfully");
 
+    // Reload the main file and its conf.d fragments when they change
+    let config_manager = Arc::new(ConfigManager::new(config.clone()).with_overrides(&args.set));
+    if let Some(path) = layered.main_file() {
+        let watched = std::fs::canonicalize(path)?;
+        if let Err(e) = config_manager.start_watching(&watched) {
+            warn!("Configuration changes will not be applied until restart: {}", e);
+        }
+    }
+
     // Initialize scheduling system
     info!("Initializing scheduling system...");
     let scheduling_system = Arc::new(SchedulingSystem::new().await?);
+
+    // Tautulli clients are shared so their metrics and breaker state show up in the admin API
+    let tautulli = Arc::new(TautulliServers::from_configs(config.tautulli.client_configs())?);
+    scheduling_system.monitoring().set_tautulli(tautulli.clone()).await?;
+    scheduling_system
+        .monitoring()
+        .set_privacy_policy(configured_privacy_policy(&config.privacy))
+        .await;
+    let timezone = config.scheduling.timezone.as_deref().unwrap_or("UTC");
+    let timezone = timezone.parse().unwrap_or_else(|_| {
+        warn!("Unknown timezone {}, checking play counts by day in UTC", timezone);
+        chrono_tz::Tz::UTC
+    });
+    scheduling_system.monitoring().set_timezone(timezone).await;
+    let graph_defaults = Arc::new(GraphDefaultsReloader::new(&config));
+    scheduling_system.monitoring().set_graph_defaults(graph_defaults.clone()).await;
+
     scheduling_system.start().await?;
     info!("Scheduling system started successfully");
 
+    // Job bodies have to be registered before the configuration schedules them
+    let scheduler = scheduling_system.scheduler();
+    let poster = Arc::new(AutoGraphPoster::new(
+        Arc::new(serenity::Http::new(&config.discord.token)),
+        tautulli.clone(),
+    ));
+    auto_graph::register_config_jobs(&scheduler, poster, config_manager.clone()).await;
+    scheduler.apply_scheduling_config(&config.scheduling).await?;
+    config_manager.spawn_subscriber(scheduler.clone());
+    config_manager.spawn_subscriber(Arc::new(TautulliReloader::new(tautulli.clone())));
+    config_manager.spawn_subscriber(graph_defaults);
+
     // Validate Discord token
     if config.discord.token.is_empty() {
         anyhow::bail!("Discord token is required but not provided in configuration");
@@ -139,6 +271,9 @@ async fn main() -> Result<()> {
     let commands = registry.take_commands();
 
     // Set up Poise framework
+    let setup_config = config.clone();
+    let setup_config_manager = config_manager.clone();
+    let setup_scheduler = scheduler.clone();
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
             commands,
@@ -154,7 +289,7 @@ async fn main() -> Result<()> {
             ..Default::default()
         })
         .setup(move |ctx, ready, framework| {
-            Box::pin(setup(ctx, ready, framework))
+            Box::pin(setup(ctx, ready, framework, setup_config, tautulli, setup_config_manager, setup_scheduler))
         })
         .build();
 
@@ -12,6 +12,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::{self, MetricFamily, MetricType};
+use tgraph_common::{CircuitState, TautulliServers};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
@@ -246,6 +249,152 @@ impl PrometheusMetrics {
     }
 }
 
+/// Prometheus collector exporting Tautulli client metrics at scrape time
+///
+/// The clients keep their own per-command counters and latency histograms;
+/// this collector converts them into metric families labelled by server and
+/// `cmd` whenever the registry is gathered.
+pub struct TautulliMetricsCollector {
+    servers: Arc<TautulliServers>,
+    descs: Vec<Desc>,
+}
+
+impl TautulliMetricsCollector {
+    /// Name, help text and label names of every exported metric family
+    const FAMILIES: [(&'static str, &'static str, &'static [&'static str]); 6] = [
+        ("tautulli_requests_total", "Total Tautulli API requests", &["server", "cmd"]),
+        ("tautulli_request_errors_total", "Failed Tautulli API requests by error class", &["server", "cmd", "class"]),
+        ("tautulli_request_retries_total", "Tautulli API retry attempts", &["server", "cmd"]),
+        ("tautulli_response_parse_failures_total", "Tautulli API responses that could not be parsed", &["server", "cmd"]),
+        ("tautulli_request_duration_seconds", "Tautulli API request latency in seconds, including retries", &["server", "cmd"]),
+        ("tautulli_circuit_breaker_state", "Tautulli circuit breaker state (0 closed, 1 half-open, 2 open)", &["server"]),
+    ];
+
+    /// Create a collector for the given Tautulli clients
+    pub fn new(servers: Arc<TautulliServers>) -> Result<Self> {
+        let descs = Self::FAMILIES
+            .iter()
+            .map(|(name, help, labels)| {
+                Desc::new(
+                    name.to_string(),
+                    help.to_string(),
+                    labels.iter().map(|label| label.to_string()).collect(),
+                    HashMap::new(),
+                )
+            })
+            .collect::<prometheus::Result<Vec<_>>>()?;
+
+        Ok(Self { servers, descs })
+    }
+
+    fn family(index: usize, field_type: MetricType, metrics: Vec<proto::Metric>) -> MetricFamily {
+        let (name, help, _) = Self::FAMILIES[index];
+        let mut family = MetricFamily::default();
+        family.set_name(name.to_string());
+        family.set_help(help.to_string());
+        family.set_field_type(field_type);
+        family.set_metric(metrics.into());
+        family
+    }
+
+    fn labelled(labels: &[(&str, &str)]) -> proto::Metric {
+        let pairs: Vec<proto::LabelPair> = labels
+            .iter()
+            .map(|(name, value)| {
+                let mut pair = proto::LabelPair::default();
+                pair.set_name(name.to_string());
+                pair.set_value(value.to_string());
+                pair
+            })
+            .collect();
+
+        let mut metric = proto::Metric::default();
+        metric.set_label(pairs.into());
+        metric
+    }
+
+    fn counter(labels: &[(&str, &str)], value: u64) -> proto::Metric {
+        let mut counter = proto::Counter::default();
+        counter.set_value(value as f64);
+        let mut metric = Self::labelled(labels);
+        metric.set_counter(counter);
+        metric
+    }
+}
+
+impl Collector for TautulliMetricsCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let mut requests = Vec::new();
+        let mut errors = Vec::new();
+        let mut retries = Vec::new();
+        let mut parse_failures = Vec::new();
+        let mut durations = Vec::new();
+        let mut breaker_states = Vec::new();
+
+        for server in self.servers.client_metrics() {
+            let name = server.server.as_str();
+
+            let state = match server.metrics.circuit_breaker.state {
+                CircuitState::Closed => 0.0,
+                CircuitState::HalfOpen => 1.0,
+                CircuitState::Open => 2.0,
+            };
+            let mut gauge = proto::Gauge::default();
+            gauge.set_value(state);
+            let mut metric = Self::labelled(&[("server", name)]);
+            metric.set_gauge(gauge);
+            breaker_states.push(metric);
+
+            for endpoint in &server.metrics.endpoints {
+                let labels = [("server", name), ("cmd", endpoint.cmd.as_str())];
+                requests.push(Self::counter(&labels, endpoint.requests));
+                retries.push(Self::counter(&labels, endpoint.retries));
+                parse_failures.push(Self::counter(&labels, endpoint.parse_failures));
+
+                for (class, count) in &endpoint.errors {
+                    errors.push(Self::counter(
+                        &[("server", name), ("cmd", endpoint.cmd.as_str()), ("class", class.as_str())],
+                        *count,
+                    ));
+                }
+
+                let buckets: Vec<proto::Bucket> = endpoint
+                    .latency
+                    .bounds
+                    .iter()
+                    .zip(&endpoint.latency.cumulative_counts)
+                    .map(|(bound, count)| {
+                        let mut bucket = proto::Bucket::default();
+                        bucket.set_upper_bound(*bound);
+                        bucket.set_cumulative_count(*count);
+                        bucket
+                    })
+                    .collect();
+                let mut histogram = proto::Histogram::default();
+                histogram.set_sample_count(endpoint.latency.count);
+                histogram.set_sample_sum(endpoint.latency.sum_seconds);
+                histogram.set_bucket(buckets.into());
+                let mut metric = Self::labelled(&labels);
+                metric.set_histogram(histogram);
+                durations.push(metric);
+            }
+        }
+
+        vec![
+            Self::family(0, MetricType::COUNTER, requests),
+            Self::family(1, MetricType::COUNTER, errors),
+            Self::family(2, MetricType::COUNTER, retries),
+            Self::family(3, MetricType::COUNTER, parse_failures),
+            Self::family(4, MetricType::HISTOGRAM, durations),
+            Self::family(5, MetricType::GAUGE, breaker_states),
+        ]
+    }
+}
+
 /// Main metrics collector that aggregates and stores task execution metrics
 pub struct MetricsCollector {
     /// In-memory storage for recent metrics
@@ -475,6 +624,16 @@ impl MetricsCollector {
         self.prometheus.registry()
     }
 
+    /// Export Tautulli client metrics through the Prometheus registry
+    pub fn register_tautulli(&self, servers: Arc<TautulliServers>) -> Result<()> {
+        let collector = TautulliMetricsCollector::new(servers)
+            .context("Failed to create Tautulli metrics collector")?;
+        self.prometheus_registry()
+            .register(Box::new(collector))
+            .context("Failed to register Tautulli metrics")?;
+        Ok(())
+    }
+
     /// Clean up old metrics to prevent memory bloat
     async fn cleanup_old_metrics(&self, metrics: &mut HashMap<MetricId, TaskExecutionMetric>) {
         let target_size = self.max_metrics_in_memory * 3 / 4; // Remove 25% when cleanup is triggered
@@ -499,3 +658,38 @@ impl MetricsCollector {
         info!("Cleaned up old metrics, kept {} out of {} metrics", to_keep.len(), metrics.len() + to_keep.len());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{Encoder, TextEncoder};
+    use tgraph_common::TautulliConfig;
+
+    #[tokio::test]
+    async fn test_tautulli_metrics_exported() {
+        let servers = Arc::new(
+            TautulliServers::from_configs(vec![(
+                "Home".to_string(),
+                TautulliConfig::new("http://127.0.0.1:1", "key").with_max_retries(0),
+            )])
+            .unwrap(),
+        );
+        let collector = MetricsCollector::with_defaults().unwrap();
+        collector.register_tautulli(servers.clone()).unwrap();
+
+        // Nothing listens on port 1, so the request fails with a network error
+        let client = servers.get("Home").unwrap();
+        assert!(client.get_activity().await.is_err());
+
+        let mut output = Vec::new();
+        TextEncoder::new()
+            .encode(&collector.prometheus_registry().gather(), &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(r#"tautulli_requests_total{server="Home",cmd="get_activity"} 1"#));
+        assert!(output.contains(r#"tautulli_request_errors_total{server="Home",cmd="get_activity",class="network"} 1"#));
+        assert!(output.contains("tautulli_request_duration_seconds_bucket"));
+        assert!(output.contains(r#"tautulli_circuit_breaker_state{server="Home"} 0"#));
+    }
+}
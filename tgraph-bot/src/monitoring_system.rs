@@ -5,15 +5,23 @@
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use std::sync::Arc;
 use std::time::Duration;
+use tgraph_common::{CancellationContext, TautulliServers};
+use tgraph_graphs::PrivacyPolicy;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tokio::time::interval;
 use tracing::{error, info};
 
+use crate::activity_sampler::ActivitySampler;
 use crate::admin_api::{AdminApiState, start_admin_api_server};
 use crate::alerting::{AlertManager, AlertRule, AlertCondition, AlertSeverity};
+use crate::config_reload::GraphDefaultsReloader;
 use crate::metrics::MetricsCollector;
 use crate::persistence::PersistenceManager;
+use crate::play_count_monitor::PlayCountMonitor;
 use crate::scheduler::SchedulerService;
 use crate::task_manager::TaskManager;
 use crate::task_queue::TaskQueue;
@@ -36,6 +44,10 @@ pub struct MonitoringConfig {
     pub data_retention_days: u32,
     /// How often to run cleanup (in hours)
     pub cleanup_interval_hours: u64,
+    /// How often to sample Tautulli activity (in seconds), 0 to disable
+    pub activity_sample_interval: u64,
+    /// How often to check daily play counts for anomalies (in seconds), 0 to disable
+    pub play_count_check_interval: u64,
 }
 
 impl Default for MonitoringConfig {
@@ -48,6 +60,8 @@ impl Default for MonitoringConfig {
             metrics_persistence_interval: 300, // 5 minutes
             data_retention_days: 30,
             cleanup_interval_hours: 24, // Daily cleanup
+            activity_sample_interval: 60, // 1 minute
+            play_count_check_interval: 6 * 3600, // 6 hours
         }
     }
 }
@@ -70,6 +84,18 @@ pub struct MonitoringSystem {
     task_manager: Arc<TaskManager>,
     /// Task queue reference
     task_queue: Arc<TaskQueue>,
+    /// Tautulli clients whose circuit breakers are reported in health checks
+    tautulli: RwLock<Option<Arc<TautulliServers>>>,
+    /// Username privacy policy for aggregating Tautulli history
+    privacy: RwLock<PrivacyPolicy>,
+    /// Timezone that decides where the play count monitor's days begin
+    timezone: RwLock<Tz>,
+    /// Graph defaults for graphs rendered by the admin API
+    graph_defaults: RwLock<Arc<GraphDefaultsReloader>>,
+    /// Cancelled on shutdown to stop the background tasks that watch it
+    shutdown: CancellationContext,
+    /// Background tasks awaited on shutdown
+    background_tasks: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl MonitoringSystem {
@@ -121,13 +147,55 @@ impl MonitoringSystem {
             scheduler_service,
             task_manager,
             task_queue,
+            tautulli: RwLock::new(None),
+            privacy: RwLock::new(PrivacyPolicy::default()),
+            timezone: RwLock::new(Tz::UTC),
+            graph_defaults: RwLock::new(Arc::new(GraphDefaultsReloader::default())),
+            shutdown: CancellationContext::new(),
+            background_tasks: Mutex::new(Vec::new()),
         })
     }
 
+    /// Register the Tautulli clients to report in health checks and metrics
+    ///
+    /// Must be called before [`start`](Self::start) for the admin API and the
+    /// activity sampler to see them.
+    pub async fn set_tautulli(&self, servers: Arc<TautulliServers>) -> Result<()> {
+        self.metrics_collector.register_tautulli(servers.clone())?;
+        *self.tautulli.write().await = Some(servers);
+        Ok(())
+    }
+
+    /// Set the username privacy policy used when aggregating history
+    ///
+    /// Must be called before [`start`](Self::start) to reach the play count
+    /// monitor.
+    pub async fn set_privacy_policy(&self, policy: PrivacyPolicy) {
+        *self.privacy.write().await = policy;
+    }
+
+    /// Set the timezone the play count monitor counts days in
+    ///
+    /// Must be called before [`start`](Self::start) to reach the play count
+    /// monitor.
+    pub async fn set_timezone(&self, timezone: Tz) {
+        *self.timezone.write().await = timezone;
+    }
+
+    /// Set the graph defaults used for graphs rendered by the admin API
+    ///
+    /// Must be called before [`start`](Self::start) to reach the admin API.
+    pub async fn set_graph_defaults(&self, graph_defaults: Arc<GraphDefaultsReloader>) {
+        *self.graph_defaults.write().await = graph_defaults;
+    }
+
     /// Start the monitoring system with all background tasks
     pub async fn start(&self) -> Result<()> {
         info!("Starting monitoring system");
 
+        // Set up default alert rules before the monitors that look for them
+        self.setup_default_alert_rules().await?;
+
         // Start alert evaluation loop
         self.start_alert_evaluation_loop().await;
 
@@ -137,16 +205,30 @@ impl MonitoringSystem {
         // Start cleanup loop
         self.start_cleanup_loop().await;
 
+        // Start Tautulli activity sampling
+        self.start_activity_sampling().await;
+
+        // Start play count anomaly checks
+        self.start_play_count_monitoring().await;
+
         // Start admin API server
         self.start_admin_api().await?;
 
-        // Set up default alert rules if none exist
-        self.setup_default_alert_rules().await?;
-
         info!("Monitoring system started successfully");
         Ok(())
     }
 
+    /// Stop the background tasks that watch for shutdown and wait for them
+    pub async fn shutdown(&self) {
+        self.shutdown.cancel();
+        for handle in self.background_tasks.lock().await.drain(..) {
+            if let Err(e) = handle.await {
+                error!("Monitoring task failed during shutdown: {}", e);
+            }
+        }
+        info!("Monitoring system stopped");
+    }
+
     /// Start the alert evaluation background task
     async fn start_alert_evaluation_loop(&self) {
         let alert_manager = self.alert_manager.clone();
@@ -225,6 +307,49 @@ impl MonitoringSystem {
         });
     }
 
+    /// Start sampling Tautulli activity for the concurrency graphs
+    async fn start_activity_sampling(&self) {
+        if self.config.activity_sample_interval == 0 {
+            info!("Activity sampling is disabled");
+            return;
+        }
+        let Some(servers) = self.tautulli.read().await.clone() else {
+            info!("No Tautulli servers registered, activity sampling is disabled");
+            return;
+        };
+
+        let sampler = ActivitySampler::new(servers, self.persistence_manager.clone());
+        let handle = sampler.spawn(Duration::from_secs(self.config.activity_sample_interval), self.shutdown.clone());
+        self.background_tasks.lock().await.push(handle);
+        info!("Sampling Tautulli activity every {} seconds", self.config.activity_sample_interval);
+    }
+
+    /// Start checking daily play counts for anomalies
+    async fn start_play_count_monitoring(&self) {
+        if self.config.play_count_check_interval == 0 {
+            info!("Play count anomaly checks are disabled");
+            return;
+        }
+        let Some(servers) = self.tautulli.read().await.clone() else {
+            info!("No Tautulli servers registered, play count anomaly checks are disabled");
+            return;
+        };
+
+        // Without an enabled rule the anomalies found could never raise an alert
+        let rules = self.alert_manager.get_rules().await;
+        if !rules.iter().any(|rule| rule.enabled && is_play_count_rule(rule)) {
+            info!("No play count anomaly alert rule is enabled, play count anomaly checks are disabled");
+            return;
+        }
+
+        let monitor = PlayCountMonitor::new(servers, self.alert_manager.clone(), self.persistence_manager.clone())
+            .with_privacy_policy(self.privacy.read().await.clone())
+            .with_timezone(*self.timezone.read().await);
+        let handle = monitor.spawn(Duration::from_secs(self.config.play_count_check_interval), self.shutdown.clone());
+        self.background_tasks.lock().await.push(handle);
+        info!("Checking play counts for anomalies every {} seconds", self.config.play_count_check_interval);
+    }
+
     /// Start the admin API server
     async fn start_admin_api(&self) -> Result<()> {
         let state = AdminApiState {
@@ -233,6 +358,8 @@ impl MonitoringSystem {
             persistence_manager: self.persistence_manager.clone(),
             scheduler_service: self.scheduler_service.clone(),
             task_queue: self.task_queue.clone(),
+            tautulli: self.tautulli.read().await.clone(),
+            graph_defaults: self.graph_defaults.read().await.clone(),
         };
 
         let bind_address = self.config.admin_api_address.clone();
@@ -308,12 +435,18 @@ impl MonitoringSystem {
             );
 
             // Add all default rules
-            for rule in [failure_rate_rule, consecutive_failures_rule, long_duration_rule, high_retry_rule] {
+            for rule in [failure_rate_rule, consecutive_failures_rule, long_duration_rule, high_retry_rule, play_count_anomaly_rule()] {
                 self.alert_manager.add_rule(rule.clone()).await?;
                 self.persistence_manager.save_alert_rule(&rule).await?;
             }
 
             info!("Default alert rules created successfully");
+        } else if !existing_rules.iter().any(is_play_count_rule) {
+            // Rules stored before play count checks existed lack this one
+            info!("Adding the default play count anomaly alert rule");
+            let rule = play_count_anomaly_rule();
+            self.alert_manager.add_rule(rule.clone()).await?;
+            self.persistence_manager.save_alert_rule(&rule).await?;
         }
 
         Ok(())
@@ -345,8 +478,12 @@ impl MonitoringSystem {
         let database_stats = self.persistence_manager.get_database_stats().await?;
         let scheduler_running = self.scheduler_service.is_running().await;
         let queue_stats = self.task_queue.get_stats().await;
+        let tautulli_unavailable: Vec<String> = match self.tautulli.read().await.as_ref() {
+            Some(servers) => servers.unavailable_servers(),
+            None => Vec::new(),
+        };
 
-        let status = if scheduler_running && active_alerts == 0 {
+        let status = if scheduler_running && active_alerts == 0 && tautulli_unavailable.is_empty() {
             "healthy"
         } else if scheduler_running {
             "warning"
@@ -361,6 +498,7 @@ impl MonitoringSystem {
             scheduler_running,
             queue_pending_tasks: queue_stats.pending_tasks,
             queue_running_tasks: queue_stats.running_tasks,
+            tautulli_unavailable,
             last_check: Utc::now(),
         })
     }
@@ -381,10 +519,33 @@ pub struct MonitoringHealthStatus {
     pub queue_pending_tasks: usize,
     /// Number of running tasks in queue
     pub queue_running_tasks: usize,
+    /// Tautulli servers whose circuit breaker is open or half-open
+    pub tautulli_unavailable: Vec<String>,
     /// When this status was last checked
     pub last_check: DateTime<Utc>,
 }
 
+/// Alert on play count drops, e.g. no plays because Plex is down
+fn play_count_anomaly_rule() -> AlertRule {
+    let mut rule = AlertRule::new(
+        "Play Count Anomaly".to_string(),
+        "".to_string(),
+        "".to_string(), // All Tautulli servers
+        AlertCondition::PlayCountAnomaly {
+            include_spikes: false,
+        },
+        AlertSeverity::High,
+        vec!["log".to_string()],
+    );
+    // Checks run several times a day for the same last complete day
+    rule.cooldown_minutes = 24 * 60;
+    rule
+}
+
+fn is_play_count_rule(rule: &AlertRule) -> bool {
+    matches!(rule.condition, AlertCondition::PlayCountAnomaly { .. })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,6 +561,8 @@ mod tests {
             metrics_persistence_interval: 1,
             data_retention_days: 1,
             cleanup_interval_hours: 1,
+            activity_sample_interval: 0,
+            play_count_check_interval: 0,
         };
 
         let scheduler = Arc::new(SchedulerService::new().await?);
@@ -517,6 +680,29 @@ mod tests {
         assert_eq!(loaded_schedules[0].name, "Test Schedule");
     }
 
+    #[tokio::test]
+    async fn test_play_count_rule_added_to_existing_rules() {
+        let monitoring_system = create_test_monitoring_system().await.unwrap();
+        let existing = AlertRule::new(
+            "Consecutive Failures".to_string(),
+            "".to_string(),
+            "".to_string(),
+            AlertCondition::ConsecutiveFailures { threshold: 3 },
+            AlertSeverity::Critical,
+            vec!["log".to_string()],
+        );
+        monitoring_system.alert_manager.add_rule(existing).await.unwrap();
+
+        monitoring_system.setup_default_alert_rules().await.unwrap();
+        monitoring_system.setup_default_alert_rules().await.unwrap();
+
+        let rules = monitoring_system.alert_manager.get_rules().await;
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules.iter().filter(|rule| is_play_count_rule(rule)).count(), 1);
+        let saved = monitoring_system.persistence_manager.load_alert_rules().await.unwrap();
+        assert!(saved.iter().any(is_play_count_rule));
+    }
+
     #[tokio::test]
     async fn test_timezone_support() {
         let monitoring_system = create_test_monitoring_system().await.unwrap();
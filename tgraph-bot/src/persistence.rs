@@ -1,13 +1,15 @@
 //! Persistent storage for schedules, metrics, and alerts
 //!
 //! This module provides SQLite-based persistence for schedule definitions,
-//! task execution metrics, and alert configurations with recovery capabilities.
+//! task execution metrics, alert configurations and sampled Tautulli activity
+//! with recovery capabilities.
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde_json;
 use sqlx::{Row, SqlitePool};
 use std::collections::HashMap;
+use tgraph_graphs::ActivitySample;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
@@ -16,7 +18,7 @@ use crate::metrics::{AggregatedMetrics, TaskExecutionMetric};
 use crate::schedule_config::{ScheduleConfig, ScheduleConfigCollection};
 
 /// Database schema version for migrations
-const SCHEMA_VERSION: i32 = 1;
+const SCHEMA_VERSION: i32 = 2;
 
 /// Persistent storage manager for the scheduling system
 pub struct PersistenceManager {
@@ -217,6 +219,22 @@ impl PersistenceManager {
         .execute(&self.pool)
         .await?;
 
+        // Create activity samples table for concurrency graphs
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS activity_samples (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                server TEXT NOT NULL,
+                sampled_at DATETIME NOT NULL,
+                stream_count INTEGER NOT NULL,
+                transcode_count INTEGER NOT NULL,
+                total_bandwidth INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         // Create indexes for better query performance
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_task_metrics_task_type ON task_metrics(task_type)")
             .execute(&self.pool)
@@ -242,6 +260,10 @@ impl PersistenceManager {
             .execute(&self.pool)
             .await?;
 
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_activity_samples_sampled_at ON activity_samples(sampled_at)")
+            .execute(&self.pool)
+            .await?;
+
         // Update schema version
         sqlx::query("INSERT OR REPLACE INTO schema_version (version) VALUES (?)")
             .bind(SCHEMA_VERSION)
@@ -466,8 +488,8 @@ impl PersistenceManager {
             INSERT OR REPLACE INTO alert_rules (
                 id, name, description, task_type_filter, task_name_filter,
                 condition_type, condition_config, severity, enabled,
-                cooldown_minutes, notification_channels, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+                cooldown_minutes, notification_channels, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(rule.id.to_string())
@@ -481,6 +503,8 @@ impl PersistenceManager {
         .bind(rule.enabled)
         .bind(rule.cooldown_minutes as i64)
         .bind(notification_channels)
+        .bind(rule.created_at)
+        .bind(rule.updated_at)
         .execute(&self.pool)
         .await?;
 
@@ -517,10 +541,10 @@ impl PersistenceManager {
             let notification_channels = serde_json::from_str(&notification_channels_str)?;
 
             let created_at_str: String = row.get("created_at");
-            let created_at = DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&Utc);
+            let created_at = parse_rule_timestamp(&created_at_str)?;
 
             let updated_at_str: String = row.get("updated_at");
-            let updated_at = DateTime::parse_from_rfc3339(&updated_at_str)?.with_timezone(&Utc);
+            let updated_at = parse_rule_timestamp(&updated_at_str)?;
 
             let rule = AlertRule {
                 id,
@@ -703,6 +727,68 @@ impl PersistenceManager {
         Ok(())
     }
 
+    /// Save one poll of Tautulli activity
+    pub async fn save_activity_samples(&self, samples: &[ActivitySample]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for sample in samples {
+            sqlx::query(
+                r#"
+                INSERT INTO activity_samples (
+                    server, sampled_at, stream_count, transcode_count, total_bandwidth
+                ) VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&sample.server)
+            .bind(sample.sampled_at)
+            .bind(sample.stream_count as i64)
+            .bind(sample.transcode_count as i64)
+            .bind(sample.total_bandwidth as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        debug!("Saved {} activity samples", samples.len());
+        Ok(())
+    }
+
+    /// Load activity samples taken within a time range, oldest first
+    pub async fn load_activity_samples(
+        &self,
+        server: Option<&str>,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<ActivitySample>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT server, sampled_at, stream_count, transcode_count, total_bandwidth
+            FROM activity_samples
+            WHERE sampled_at >= ? AND sampled_at <= ? AND (? IS NULL OR server = ?)
+            ORDER BY sampled_at
+            "#,
+        )
+        .bind(start_time)
+        .bind(end_time)
+        .bind(server)
+        .bind(server)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let samples: Vec<ActivitySample> = rows
+            .into_iter()
+            .map(|row| ActivitySample {
+                server: row.get("server"),
+                sampled_at: row.get("sampled_at"),
+                stream_count: row.get::<i64, _>("stream_count") as u32,
+                transcode_count: row.get::<i64, _>("transcode_count") as u32,
+                total_bandwidth: row.get::<i64, _>("total_bandwidth") as u32,
+            })
+            .collect();
+
+        debug!("Loaded {} activity samples from database", samples.len());
+        Ok(samples)
+    }
+
     /// Clean up old data to prevent database bloat
     pub async fn cleanup_old_data(&self, retention_days: u32) -> Result<()> {
         let cutoff_date = Utc::now() - chrono::Duration::days(retention_days as i64);
@@ -723,9 +809,16 @@ impl PersistenceManager {
         .await?
         .rows_affected();
 
+        // Clean up old activity samples
+        let samples_deleted = sqlx::query("DELETE FROM activity_samples WHERE sampled_at < ?")
+            .bind(cutoff_date)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
         info!(
-            "Cleaned up old data: {} task metrics, {} resolved alerts (retention: {} days)",
-            metrics_deleted, alerts_deleted, retention_days
+            "Cleaned up old data: {} task metrics, {} resolved alerts, {} activity samples (retention: {} days)",
+            metrics_deleted, alerts_deleted, samples_deleted, retention_days
         );
 
         Ok(())
@@ -767,6 +860,25 @@ impl PersistenceManager {
         .await?;
         stats.insert("active_alerts".to_string(), active_alerts_count);
 
+        // Count activity samples
+        let samples_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM activity_samples")
+            .fetch_one(&self.pool)
+            .await?;
+        stats.insert("activity_samples".to_string(), samples_count);
+
         Ok(stats)
     }
 }
+
+/// Parse an alert rule timestamp
+///
+/// Rules used to be stored with SQLite's `CURRENT_TIMESTAMP`, which is UTC
+/// without an offset, so that format is accepted as well as RFC 3339.
+fn parse_rule_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    let timestamp = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .with_context(|| format!("Invalid alert rule timestamp: {}", value))?;
+    Ok(timestamp.and_utc())
+}
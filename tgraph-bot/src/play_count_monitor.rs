@@ -0,0 +1,227 @@
+//! Background anomaly detection on daily play counts
+//!
+//! Once per interval the monitor loads recent history from every Tautulli
+//! server, runs [`TrendAnalyzer`] anomaly detection over the daily play
+//! counts and raises alerts for anomalies on the last complete day.
+
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use std::sync::Arc;
+use std::time::Duration;
+use tgraph_common::{CancellationContext, TautulliServers};
+use tgraph_graphs::{
+    fill_missing_days, AggregationConfig, AnomalyConfig, DailyPlayCountAggregator, DataAggregator, PrivacyPolicy,
+    TrendAnalyzer, TrendConfig,
+};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{debug, error, info, warn};
+
+use crate::alerting::{Alert, AlertManager};
+use crate::persistence::PersistenceManager;
+
+/// Days of history loaded as the baseline for the last complete day
+const LOOKBACK_DAYS: i64 = 56;
+
+/// Time allowed for loading one server's history
+const HISTORY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Checks daily play counts for anomalies and raises alerts
+pub struct PlayCountMonitor {
+    servers: Arc<TautulliServers>,
+    alert_manager: Arc<AlertManager>,
+    persistence_manager: Arc<PersistenceManager>,
+    analyzer: TrendAnalyzer,
+    privacy: PrivacyPolicy,
+    timezone: Tz,
+}
+
+impl PlayCountMonitor {
+    /// Create a monitor with the default anomaly settings
+    pub fn new(
+        servers: Arc<TautulliServers>,
+        alert_manager: Arc<AlertManager>,
+        persistence_manager: Arc<PersistenceManager>,
+    ) -> Self {
+        let analyzer = TrendAnalyzer::new(TrendConfig {
+            enabled: true,
+            anomaly_detection: Some(AnomalyConfig::default()),
+            ..Default::default()
+        });
+        Self {
+            servers,
+            alert_manager,
+            persistence_manager,
+            analyzer,
+            privacy: PrivacyPolicy::default(),
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Aggregate history under the configured username privacy policy
+    pub fn with_privacy_policy(mut self, privacy: PrivacyPolicy) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    /// Count plays by day in `timezone` instead of UTC
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Check every server once for anomalies on the day before `today`
+    ///
+    /// `today` is a date in the monitor's timezone. Today is skipped because its count is still incomplete. Servers that
+    /// fail to respond are skipped.
+    pub async fn check_once(&self, today: NaiveDate) -> Result<Vec<Alert>> {
+        let last_day = today - ChronoDuration::days(1);
+        let first_day = today - ChronoDuration::days(LOOKBACK_DAYS);
+        let mut raised = Vec::new();
+
+        for name in &self.servers.names() {
+            let Some(client) = self.servers.get(name) else {
+                continue;
+            };
+            let cancellation = CancellationContext::with_timeout(HISTORY_TIMEOUT);
+            let entries = match client.fetch_history_between(&cancellation, first_day, last_day, 1000).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("Skipping play count check for {}: {}", name, e);
+                    continue;
+                }
+            };
+
+            let config = AggregationConfig {
+                privacy: self.privacy.clone(),
+                timezone: self.timezone,
+                ..Default::default()
+            };
+            let daily = DailyPlayCountAggregator::new().aggregate(entries, &config)?;
+            let daily = fill_missing_days(&daily, first_day, last_day);
+            let analysis = self.analyzer.analyze_play_counts(&daily)?;
+            let latest: Vec<_> = analysis
+                .anomalies
+                .into_iter()
+                .filter(|anomaly| anomaly.date == last_day)
+                .collect();
+            debug!("Play count check for {} found {} anomalies on {}", name, latest.len(), last_day);
+
+            let alerts = self.alert_manager.raise_play_count_anomalies(name, &latest).await?;
+            for alert in &alerts {
+                self.persistence_manager.save_alert(alert).await?;
+            }
+            raised.extend(alerts);
+        }
+
+        Ok(raised)
+    }
+
+    /// Check on a fixed interval until `shutdown` is cancelled
+    pub fn spawn(self, every: Duration, shutdown: CancellationContext) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = interval(every);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = shutdown.token().cancelled() => break,
+                    _ = interval.tick() => {}
+                }
+
+                tokio::select! {
+                    _ = shutdown.token().cancelled() => break,
+                    result = self.check_once(Utc::now().with_timezone(&self.timezone).date_naive()) => {
+                        if let Err(e) = result {
+                            error!("Failed to check play counts for anomalies: {}", e);
+                        }
+                    }
+                }
+            }
+            info!("Play count anomaly checks stopped");
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::{AlertCondition, AlertRule, AlertSeverity};
+    use crate::metrics::MetricsCollector;
+    use tgraph_graphs::{Anomaly, AnomalyKind};
+
+    fn anomaly(kind: AnomalyKind) -> Anomaly {
+        Anomaly {
+            date: NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(),
+            value: 0.0,
+            expected: 12.0,
+            score: -8.1,
+            kind,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_play_count_anomaly_alerts() {
+        let alert_manager = AlertManager::new(Arc::new(MetricsCollector::new(100).unwrap()));
+        let rule = AlertRule::new(
+            "Play Count Anomaly".to_string(),
+            String::new(),
+            "Main".to_string(),
+            AlertCondition::PlayCountAnomaly { include_spikes: false },
+            AlertSeverity::High,
+            vec!["log".to_string()],
+        );
+        alert_manager.add_rule(rule).await.unwrap();
+
+        // Spikes are ignored and other servers are filtered out
+        let spike = alert_manager.raise_play_count_anomalies("Main", &[anomaly(AnomalyKind::Spike)]).await.unwrap();
+        assert!(spike.is_empty());
+        let other = alert_manager.raise_play_count_anomalies("Cabin", &[anomaly(AnomalyKind::ZeroPlays)]).await.unwrap();
+        assert!(other.is_empty());
+
+        let alerts = alert_manager.raise_play_count_anomalies("Main", &[anomaly(AnomalyKind::ZeroPlays)]).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, AlertSeverity::High);
+        assert!(alerts[0].message.starts_with("No plays on Main on 2024-01-08"));
+        assert_eq!(alert_manager.get_active_alerts().await.len(), 1);
+
+        // The rule is now in cooldown
+        let repeat = alert_manager.raise_play_count_anomalies("Main", &[anomaly(AnomalyKind::Drop)]).await.unwrap();
+        assert!(repeat.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_is_per_server() {
+        let alert_manager = AlertManager::new(Arc::new(MetricsCollector::new(100).unwrap()));
+        let mut rule = AlertRule::new(
+            "Play Count Anomaly".to_string(),
+            String::new(),
+            String::new(), // All Tautulli servers
+            AlertCondition::PlayCountAnomaly { include_spikes: false },
+            AlertSeverity::High,
+            vec!["log".to_string()],
+        );
+        rule.cooldown_minutes = 24 * 60;
+        alert_manager.add_rule(rule).await.unwrap();
+
+        let main = alert_manager.raise_play_count_anomalies("Main", &[anomaly(AnomalyKind::Drop)]).await.unwrap();
+        assert_eq!(main.len(), 1);
+
+        // An outage on another server is still reported
+        let cabin = alert_manager.raise_play_count_anomalies("Cabin", &[anomaly(AnomalyKind::ZeroPlays)]).await.unwrap();
+        assert_eq!(cabin.len(), 1);
+        assert!(cabin[0].message.starts_with("No plays on Cabin"));
+
+        let repeat = alert_manager.raise_play_count_anomalies("Main", &[anomaly(AnomalyKind::ZeroPlays)]).await.unwrap();
+        assert!(repeat.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_without_servers() {
+        let persistence = Arc::new(PersistenceManager::new(":memory:").await.unwrap());
+        let alert_manager = Arc::new(AlertManager::new(Arc::new(MetricsCollector::new(100).unwrap())));
+        let monitor = PlayCountMonitor::new(Arc::new(TautulliServers::new()), alert_manager, persistence);
+        assert!(monitor.check_once(Utc::now().date_naive()).await.unwrap().is_empty());
+    }
+}
@@ -8,7 +8,8 @@ use std::path::Path;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use chrono_tz::Tz;
-use tracing::{info, debug};
+use tracing::{info, debug, warn};
+use tgraph_config::migrate::{dropped_settings, migrate_as, DocumentKind};
 use uuid::Uuid;
 
 use crate::scheduler::{JobId, JobMetadata};
@@ -173,6 +174,9 @@ pub enum ScheduleConfigError {
     
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Unsupported schedule configuration: {0}")]
+    Migration(#[from] tgraph_config::ConfigError),
 }
 
 /// Schedule configuration parser and validator
@@ -237,7 +241,7 @@ impl ScheduleConfigParser {
     pub async fn parse_yaml_string(&self, yaml_content: &str) -> Result<ScheduleConfigCollection, ScheduleConfigError> {
         debug!("Parsing YAML content ({} bytes)", yaml_content.len());
 
-        let mut config: ScheduleConfigCollection = serde_yaml::from_str(yaml_content)?;
+        let mut config = self.upgrade(serde_yaml::from_str(yaml_content)?)?;
         self.apply_defaults_and_validate(&mut config).await?;
 
         info!("Successfully parsed {} schedules from YAML", config.schedules.len());
@@ -273,13 +277,27 @@ impl ScheduleConfigParser {
     pub async fn parse_json_string(&self, json_content: &str) -> Result<ScheduleConfigCollection, ScheduleConfigError> {
         debug!("Parsing JSON content ({} bytes)", json_content.len());
 
-        let mut config: ScheduleConfigCollection = serde_json::from_str(json_content)?;
+        let mut config = self.upgrade(serde_json::from_str(json_content)?)?;
         self.apply_defaults_and_validate(&mut config).await?;
 
         info!("Successfully parsed {} schedules from JSON", config.schedules.len());
         Ok(config)
     }
 
+    /// Migrate a parsed document to the current format and deserialize it
+    fn upgrade(&self, value: serde_json::Value) -> Result<ScheduleConfigCollection, ScheduleConfigError> {
+        let report = migrate_as(DocumentKind::Schedules, value)?;
+        for note in &report.notes {
+            warn!("Schedule configuration: {}", note);
+        }
+        if let Ok(dropped) = dropped_settings::<ScheduleConfigCollection>(&report.migrated) {
+            for key in dropped {
+                warn!("Ignoring unknown schedule configuration key '{}'", key);
+            }
+        }
+        Ok(serde_json::from_value(report.migrated)?)
+    }
+
     /// Apply default values and validate the configuration
     async fn apply_defaults_and_validate(&self, config: &mut ScheduleConfigCollection) -> Result<(), ScheduleConfigError> {
         debug!("Applying defaults and validating configuration");
@@ -459,6 +477,7 @@ impl ScheduleConfigParser {
             id,
             name: schedule.name.clone(),
             cron_expression: schedule.cron_expression.clone(),
+            timezone: schedule.timezone.parse().unwrap_or(Tz::UTC),
             description: schedule.description.clone(),
             enabled: schedule.enabled,
         }
@@ -668,6 +687,20 @@ schedules:
         assert_eq!(schedule.timeout_seconds, 600);
     }
 
+    #[tokio::test]
+    async fn test_schedule_format_versions() {
+        let parser = ScheduleConfigParser::new();
+        let unversioned = "schedules:\n  - id: nightly\n    name: Nightly\n    cron_expression: \"0 0 3 * * *\"\n    task_type: cleanup\n";
+        let config = parser.parse_yaml_string(unversioned).await.unwrap();
+        assert_eq!(config.version, "1.0");
+
+        let newer = format!("version: \"2.0\"\n{}", unversioned);
+        assert!(matches!(
+            parser.parse_yaml_string(&newer).await,
+            Err(ScheduleConfigError::Migration(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_json_parsing() {
         let json_content = r#"
@@ -3,8 +3,15 @@
 //! This module provides the foundation for automated graph generation and posting
 //! with configurable cron-based schedules.
 
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 use anyhow::{Result, Context};
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use tgraph_config::settings::SchedulingConfig;
+use tgraph_config::validation::expand_cron_expression;
+use tgraph_config::{Config, ConfigSection, ConfigSubscriber};
 use tokio::sync::{RwLock, Mutex};
 use tokio_cron_scheduler::{JobScheduler, Job};
 use tracing::{info, warn, debug};
@@ -14,6 +21,21 @@ use std::collections::HashMap;
 /// Type alias for job identifiers (same as tokio-cron-scheduler's JobId)
 pub type JobId = Uuid;
 
+/// Job body, kept so a job can be rescheduled with a new cron expression
+type JobHandler = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Name of the job run on `scheduling.auto_graph_cron`
+pub const AUTO_GRAPH_JOB: &str = "auto_graph";
+
+/// Prefix of the jobs run on a guild's own `auto_graph_cron`, followed by the guild ID
+pub const GUILD_AUTO_GRAPH_JOB_PREFIX: &str = "auto_graph:";
+
+/// Body of a guild's automatic graph job, called with the guild ID
+type GuildJobHandler = Arc<dyn Fn(u64) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Name of the job run on `scheduling.cleanup_cron`
+pub const CLEANUP_JOB: &str = "cleanup";
+
 /// Core scheduler service that manages cron-based job scheduling
 pub struct SchedulerService {
     /// The underlying tokio-cron-scheduler instance
@@ -22,6 +44,12 @@ pub struct SchedulerService {
     jobs: Arc<RwLock<HashMap<JobId, JobMetadata>>>,
     /// Whether the scheduler is currently running
     is_running: Arc<RwLock<bool>>,
+    /// Bodies of the scheduled jobs
+    handlers: Arc<RwLock<HashMap<JobId, JobHandler>>>,
+    /// Bodies of the jobs scheduled from the configuration, by job name
+    config_jobs: Arc<RwLock<HashMap<String, JobHandler>>>,
+    /// Body of the automatic graph jobs scheduled for single guilds
+    guild_job: Arc<RwLock<Option<GuildJobHandler>>>,
 }
 
 /// Metadata for tracking scheduled jobs
@@ -33,6 +61,8 @@ pub struct JobMetadata {
     pub name: String,
     /// Cron expression used for scheduling
     pub cron_expression: String,
+    /// Timezone the cron expression is read in
+    pub timezone: Tz,
     /// Optional description of what the job does
     pub description: Option<String>,
     /// Whether the job is currently enabled
@@ -58,6 +88,9 @@ impl SchedulerService {
             scheduler: Arc::new(Mutex::new(scheduler)),
             jobs: Arc::new(RwLock::new(HashMap::new())),
             is_running: Arc::new(RwLock::new(false)),
+            handlers: Arc::new(RwLock::new(HashMap::new())),
+            config_jobs: Arc::new(RwLock::new(HashMap::new())),
+            guild_job: Arc::new(RwLock::new(None)),
         })
     }
     
@@ -149,16 +182,24 @@ impl SchedulerService {
         F: Fn() -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = ()> + Send + 'static,
     {
-        info!("Adding new job: {} with cron: {}", name, cron_expression);
+        self.schedule(name, cron_expression, Tz::UTC, description, into_handler(job_fn)).await
+    }
+
+    /// Schedule a job body under a name and cron expression read in `timezone`
+    async fn schedule(
+        &self,
+        name: String,
+        cron_expression: String,
+        timezone: Tz,
+        description: Option<String>,
+        handler: JobHandler,
+    ) -> Result<JobId> {
+        info!("Adding new job: {} with cron: {} ({})", name, cron_expression, timezone);
         
         // Validate cron expression by creating a job
-        let job = Job::new_async(cron_expression.as_str(), move |_uuid, _scheduler| {
-            let job_fn = job_fn();
-            Box::pin(async move {
-                job_fn.await;
-            })
-        })
-        .context("Invalid cron expression or job creation failed")?;
+        let job_handler = handler.clone();
+        let job = Job::new_async_tz(cron_expression.as_str(), timezone, move |_uuid, _scheduler| job_handler())
+            .context("Invalid cron expression or job creation failed")?;
         
         let scheduler = self.scheduler.lock().await;
         let job_id = scheduler.add(job)
@@ -170,12 +211,14 @@ impl SchedulerService {
             id: job_id,
             name: name.clone(),
             cron_expression,
+            timezone,
             description,
             enabled: true,
         };
         
         let mut jobs = self.jobs.write().await;
         jobs.insert(job_id, metadata);
+        self.handlers.write().await.insert(job_id, handler);
         
         info!("Successfully added job: {} with ID: {:?}", name, job_id);
         
@@ -210,12 +253,140 @@ impl SchedulerService {
         // Remove from our metadata tracking
         let mut jobs = self.jobs.write().await;
         jobs.remove(&job_id);
+        self.handlers.write().await.remove(&job_id);
         
         info!("Successfully removed job: {}", job_name);
         
         Ok(())
     }
     
+    /// Change the cron expression or timezone of a scheduled job
+    ///
+    /// The job is re-added under a new ID, which is returned. If the new
+    /// expression is invalid the job keeps its current schedule.
+    pub async fn reschedule_job(&self, job_id: JobId, cron_expression: String, timezone: Tz) -> Result<JobId> {
+        let metadata = self.get_job(job_id).await
+            .with_context(|| format!("Unknown job {:?}", job_id))?;
+        let handler = self.handlers.read().await.get(&job_id).cloned()
+            .with_context(|| format!("Unknown job {:?}", job_id))?;
+
+        info!(
+            "Rescheduling job {} from '{}' ({}) to '{}' ({})",
+            metadata.name, metadata.cron_expression, metadata.timezone, cron_expression, timezone
+        );
+        let new_id = self.schedule(metadata.name, cron_expression, timezone, metadata.description, handler).await?;
+        self.remove_job(job_id).await?;
+        Ok(new_id)
+    }
+
+    /// Register the body of a job scheduled from the configuration
+    ///
+    /// `name` is one of [`AUTO_GRAPH_JOB`] or [`CLEANUP_JOB`]. The job runs
+    /// once [`apply_scheduling_config`](Self::apply_scheduling_config) finds
+    /// a cron expression for it.
+    pub async fn register_config_job<F, Fut>(&self, name: &str, job_fn: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        self.config_jobs.write().await.insert(name.to_string(), into_handler(job_fn));
+    }
+
+    /// Bring the configured jobs in line with the scheduling configuration
+    ///
+    /// Jobs are added, rescheduled or removed as their cron expressions are
+    /// set, changed or cleared, and all of them are removed when scheduling
+    /// is disabled. Cron expressions are read in `scheduling.timezone`.
+    pub async fn apply_scheduling_config(&self, config: &SchedulingConfig) -> Result<()> {
+        let timezone = schedule_timezone(config.timezone.as_deref());
+        let configured = [
+            (AUTO_GRAPH_JOB, &config.auto_graph_cron),
+            (CLEANUP_JOB, &config.cleanup_cron),
+        ];
+
+        for (name, cron_expression) in configured {
+            let wanted = cron_expression.as_deref()
+                .filter(|_| config.enabled)
+                .map(expand_cron_expression);
+            let existing = self.list_jobs().await.into_iter().find(|job| job.name == name);
+
+            match (existing, wanted) {
+                (Some(job), Some(cron_expression))
+                    if job.cron_expression == cron_expression && job.timezone == timezone => {}
+                (Some(job), Some(cron_expression)) => {
+                    self.reschedule_job(job.id, cron_expression, timezone).await?;
+                }
+                (Some(job), None) => self.remove_job(job.id).await?,
+                (None, Some(cron_expression)) => {
+                    let Some(handler) = self.config_jobs.read().await.get(name).cloned() else {
+                        debug!("No handler registered for configured job {}", name);
+                        continue;
+                    };
+                    self.schedule(name.to_string(), cron_expression, timezone, None, handler).await?;
+                }
+                (None, None) => {}
+            }
+        }
+        Ok(())
+    }
+    
+    /// Register the body of the automatic graph job of guilds with their own schedule
+    pub async fn register_guild_job<F, Fut>(&self, job_fn: F)
+    where
+        F: Fn(u64) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        *self.guild_job.write().await = Some(Arc::new(move |guild_id| Box::pin(job_fn(guild_id))));
+    }
+
+    /// Bring the guild jobs in line with the guilds' own cron expressions
+    ///
+    /// `schedules` lists every guild that overrides `auto_graph_cron`, with
+    /// the timezone its expression is read in; jobs of guilds missing from it
+    /// are removed.
+    pub async fn apply_guild_schedules(&self, schedules: &[(u64, String, Option<String>)]) -> Result<()> {
+        let existing: Vec<JobMetadata> = self.list_jobs().await.into_iter()
+            .filter(|job| job.name.starts_with(GUILD_AUTO_GRAPH_JOB_PREFIX))
+            .collect();
+
+        for job in &existing {
+            let guild_id = &job.name[GUILD_AUTO_GRAPH_JOB_PREFIX.len()..];
+            if !schedules.iter().any(|(id, _, _)| id.to_string() == guild_id) {
+                self.remove_job(job.id).await?;
+            }
+        }
+
+        for (guild_id, cron_expression, timezone) in schedules {
+            let name = format!("{}{}", GUILD_AUTO_GRAPH_JOB_PREFIX, guild_id);
+            let cron_expression = expand_cron_expression(cron_expression);
+            let timezone = schedule_timezone(timezone.as_deref());
+            match existing.iter().find(|job| job.name == name) {
+                Some(job) if job.cron_expression == cron_expression && job.timezone == timezone => {}
+                Some(job) => {
+                    self.reschedule_job(job.id, cron_expression, timezone).await?;
+                }
+                None => {
+                    let Some(guild_job) = self.guild_job.read().await.clone() else {
+                        debug!("No handler registered for the automatic graphs of guild {}", guild_id);
+                        continue;
+                    };
+                    let guild_id = *guild_id;
+                    let handler: JobHandler = Arc::new(move || guild_job(guild_id));
+                    let description = Some(format!("Automatic graphs for guild {}", guild_id));
+                    self.schedule(name, cron_expression, timezone, description, handler).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Guilds that have an automatic graph job of their own
+    pub async fn scheduled_guilds(&self) -> Vec<u64> {
+        self.list_jobs().await.iter()
+            .filter_map(|job| job.name.strip_prefix(GUILD_AUTO_GRAPH_JOB_PREFIX)?.parse().ok())
+            .collect()
+    }
+
     /// List all currently scheduled jobs
     /// 
     /// # Returns
@@ -247,6 +418,42 @@ impl SchedulerService {
     }
 }
 
+#[async_trait]
+impl ConfigSubscriber for SchedulerService {
+    fn name(&self) -> &str {
+        "scheduler"
+    }
+
+    fn sections(&self) -> &[ConfigSection] {
+        &[ConfigSection::Scheduling]
+    }
+
+    async fn apply(&self, config: &Config) -> Result<(), String> {
+        self.apply_scheduling_config(&config.scheduling)
+            .await
+            .map_err(|e| format!("{:#}", e))
+    }
+}
+
+/// Timezone named in the configuration, UTC when unset or unknown
+fn schedule_timezone(name: Option<&str>) -> Tz {
+    let Some(name) = name else {
+        return Tz::UTC;
+    };
+    name.parse().unwrap_or_else(|_| {
+        warn!("Unknown timezone {}, reading cron expressions in UTC", name);
+        Tz::UTC
+    })
+}
+
+fn into_handler<F, Fut>(job_fn: F) -> JobHandler
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+    Arc::new(move || Box::pin(job_fn()))
+}
+
 /// Helper function to validate a cron expression without creating a job
 /// 
 /// # Arguments
@@ -357,6 +564,136 @@ mod tests {
         assert!(job_names.contains(&&"job2".to_string()));
     }
     
+    #[tokio::test]
+    async fn test_apply_scheduling_config() {
+        let scheduler = SchedulerService::new().await.unwrap();
+        scheduler.register_config_job(AUTO_GRAPH_JOB, || async {}).await;
+
+        let mut config = SchedulingConfig {
+            auto_graph_cron: Some("0 0 * * *".to_string()),
+            cleanup_cron: Some("0 2 * * 0".to_string()),
+            timezone: None,
+            enabled: true,
+        };
+        scheduler.apply_scheduling_config(&config).await.unwrap();
+        // The cleanup job has no handler registered, so only one job runs
+        let jobs = scheduler.list_jobs().await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].name, AUTO_GRAPH_JOB);
+        assert_eq!(jobs[0].cron_expression, "0 0 0 * * *");
+
+        config.auto_graph_cron = Some("0 6 * * *".to_string());
+        scheduler.apply_scheduling_config(&config).await.unwrap();
+        let jobs = scheduler.list_jobs().await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].cron_expression, "0 0 6 * * *");
+
+        // An invalid expression leaves the current schedule in place
+        assert!(scheduler.reschedule_job(jobs[0].id, "invalid".to_string(), Tz::UTC).await.is_err());
+        assert_eq!(scheduler.list_jobs().await[0].cron_expression, "0 0 6 * * *");
+
+        config.timezone = Some("America/New_York".to_string());
+        scheduler.apply_scheduling_config(&config).await.unwrap();
+        let jobs = scheduler.list_jobs().await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].timezone, Tz::America__New_York);
+
+        config.enabled = false;
+        scheduler.apply_scheduling_config(&config).await.unwrap();
+        assert_eq!(scheduler.job_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rescheduled_config_job_keeps_running() {
+        let scheduler = SchedulerService::new().await.unwrap();
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = runs.clone();
+        scheduler.register_config_job(AUTO_GRAPH_JOB, move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+        }).await;
+        scheduler.start().await.unwrap();
+
+        let mut config = SchedulingConfig {
+            auto_graph_cron: Some("* * * * * *".to_string()),
+            cleanup_cron: None,
+            timezone: None,
+            enabled: true,
+        };
+        scheduler.apply_scheduling_config(&config).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+        assert!(runs.load(std::sync::atomic::Ordering::SeqCst) > 0);
+
+        // The body survives a changed expression from a configuration reload
+        config.auto_graph_cron = Some("*/1 * * * * *".to_string());
+        scheduler.apply_scheduling_config(&config).await.unwrap();
+        assert_eq!(scheduler.list_jobs().await[0].cron_expression, "*/1 * * * * *");
+        runs.store(0, std::sync::atomic::Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+        assert!(runs.load(std::sync::atomic::Ordering::SeqCst) > 0);
+        assert_eq!(scheduler.job_count().await, 1);
+
+        scheduler.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_apply_guild_schedules() {
+        let scheduler = SchedulerService::new().await.unwrap();
+        scheduler.apply_guild_schedules(&[(1, "0 6 * * *".to_string(), None)]).await.unwrap();
+        assert_eq!(scheduler.job_count().await, 0);
+
+        scheduler.register_guild_job(|_guild_id| async {}).await;
+        let schedules = [(1, "0 6 * * *".to_string(), None), (2, "0 18 * * *".to_string(), None)];
+        scheduler.apply_guild_schedules(&schedules).await.unwrap();
+        assert_eq!(scheduler.job_count().await, 2);
+
+        scheduler.apply_guild_schedules(&[(2, "0 20 * * *".to_string(), None)]).await.unwrap();
+        let jobs = scheduler.list_jobs().await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].name, "auto_graph:2");
+        assert_eq!(jobs[0].cron_expression, "0 0 20 * * *");
+        assert_eq!(jobs[0].timezone, Tz::UTC);
+        assert_eq!(scheduler.scheduled_guilds().await, vec![2]);
+
+        // A changed timezone alone moves the job
+        let schedules = [(2, "0 20 * * *".to_string(), Some("Europe/Berlin".to_string()))];
+        scheduler.apply_guild_schedules(&schedules).await.unwrap();
+        let jobs = scheduler.list_jobs().await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].timezone, Tz::Europe__Berlin);
+    }
+
+    #[tokio::test]
+    async fn test_guild_job_fires_with_its_guild() {
+        let scheduler = SchedulerService::new().await.unwrap();
+        let guilds = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let fired = guilds.clone();
+        scheduler.register_guild_job(move |guild_id| {
+            let fired = fired.clone();
+            async move {
+                fired.lock().unwrap().push(guild_id);
+            }
+        }).await;
+        scheduler.start().await.unwrap();
+
+        scheduler.apply_guild_schedules(&[(7, "* * * * * *".to_string(), None)]).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+        let fired = guilds.lock().unwrap().clone();
+        assert!(!fired.is_empty());
+        assert!(fired.iter().all(|&guild_id| guild_id == 7));
+
+        scheduler.stop().await.unwrap();
+    }
+
+    #[test]
+    fn test_scheduler_accepts_expanded_cron() {
+        for expression in ["0 2 * * 0", "30 8 * * 1-5,7", "0 8 * * */2"] {
+            assert!(validate_cron_expression(&expand_cron_expression(expression)), "{}", expression);
+        }
+    }
+
     #[test]
     fn test_validate_cron_expression() {
         // Valid expressions (6-field format: second minute hour day month weekday)
@@ -77,6 +77,8 @@ impl SchedulingSystem {
         info!("Shutting down scheduling system");
 
         // Shutdown in reverse order of startup
+        self.monitoring_system.shutdown().await;
+
         if let Err(e) = self.task_queue.stop().await {
             error!("Error shutting down task queue: {}", e);
         }
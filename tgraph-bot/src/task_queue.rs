@@ -8,8 +8,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BinaryHeap, HashMap};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
+use tgraph_common::CancellationContext;
 use tokio::sync::{mpsc, RwLock};
-use tokio::time::timeout;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
@@ -225,6 +225,24 @@ impl QueuedTask {
         }
     }
 
+    /// Mark this task as timed out and schedule retry if possible
+    pub fn mark_timed_out(&mut self) -> bool {
+        let will_retry = self.mark_failed("Task execution timed out".to_string());
+        self.last_result = Some(TaskResult::TimedOut);
+        will_retry
+    }
+
+    /// Create the cancellation context for one execution attempt
+    ///
+    /// The deadline is derived from the task timeout and is passed on to the
+    /// executor so history fetching, aggregation and rendering can honour it.
+    pub fn cancellation_context(&self) -> CancellationContext {
+        match self.timeout {
+            Some(timeout) => CancellationContext::with_timeout(timeout),
+            None => CancellationContext::new(),
+        }
+    }
+
     /// Mark this task as completed successfully
     pub fn mark_completed(&mut self) {
         self.status = TaskStatus::Completed;
@@ -304,7 +322,20 @@ impl PriorityQueueItem {
 }
 
 /// Task execution function type
-pub type TaskExecutor = Arc<dyn Fn(serde_json::Value) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send>> + Send + Sync>;
+///
+/// Executors receive the task parameters and a cancellation context carrying
+/// the task deadline, which is cancelled when the task is cancelled.
+pub type TaskExecutor = Arc<dyn Fn(serde_json::Value, CancellationContext) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send>> + Send + Sync>;
+
+/// Reason a single execution attempt did not succeed
+enum ExecutionFailure {
+    /// The executor returned an error
+    Failed(anyhow::Error),
+    /// The task was cancelled while running
+    Cancelled,
+    /// The task ran past its timeout
+    TimedOut,
+}
 
 /// Configuration for the task queue
 #[derive(Debug, Clone)]
@@ -348,6 +379,8 @@ pub struct TaskQueue {
     is_running: Arc<RwLock<bool>>,
     /// Registered task executors by name
     executors: Arc<RwLock<HashMap<String, TaskExecutor>>>,
+    /// Cancellation contexts of tasks that are currently executing
+    running: Arc<RwLock<HashMap<QueuedTaskId, CancellationContext>>>,
 }
 
 /// Commands that can be sent to the queue processor
@@ -383,6 +416,7 @@ impl TaskQueue {
             command_tx,
             is_running: Arc::new(RwLock::new(false)),
             executors: Arc::new(RwLock::new(HashMap::new())),
+            running: Arc::new(RwLock::new(HashMap::new())),
         };
 
         // Start the queue processor
@@ -399,13 +433,13 @@ impl TaskQueue {
     /// Register a task executor for a specific task type
     pub async fn register_executor<F, Fut>(&self, task_type: String, executor: F) -> Result<()>
     where
-        F: Fn(serde_json::Value) -> Fut + Send + Sync + 'static,
+        F: Fn(serde_json::Value, CancellationContext) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = Result<()>> + Send + 'static,
     {
         info!("Registering executor for task type: {}", task_type);
 
-        let boxed_executor: TaskExecutor = Arc::new(move |params| {
-            Box::pin(executor(params))
+        let boxed_executor: TaskExecutor = Arc::new(move |params, ctx| {
+            Box::pin(executor(params, ctx))
         });
 
         let mut executors = self.executors.write().await;
@@ -608,6 +642,7 @@ impl TaskQueue {
         let tasks = self.tasks.clone();
         let task_manager = self.task_manager.clone();
         let executors = self.executors.clone();
+        let running = self.running.clone();
         let db = self.db.clone();
         let config = self.config.clone();
 
@@ -627,7 +662,7 @@ impl TaskQueue {
                                 Self::handle_add_task(&pending_queue, &tasks, task).await;
                             }
                             Some(QueueCommand::CancelTask(task_id)) => {
-                                Self::handle_cancel_task(&tasks, &db, task_id).await;
+                                Self::handle_cancel_task(&tasks, &running, &db, task_id).await;
                             }
                             Some(QueueCommand::UpdateTaskStatus(task_id, status)) => {
                                 Self::handle_update_task_status(&tasks, &db, task_id, status).await;
@@ -649,7 +684,7 @@ impl TaskQueue {
                         if shutdown_requested {
                             break;
                         }
-                        Self::process_ready_tasks(&pending_queue, &tasks, &task_manager, &executors, &db, &config).await;
+                        Self::process_ready_tasks(&pending_queue, &tasks, &task_manager, &executors, &running, &db, &config).await;
                     }
                 }
             }
@@ -688,11 +723,17 @@ impl TaskQueue {
     /// Handle cancelling a task
     async fn handle_cancel_task(
         tasks: &Arc<RwLock<HashMap<QueuedTaskId, QueuedTask>>>,
+        running: &Arc<RwLock<HashMap<QueuedTaskId, CancellationContext>>>,
         db: &Arc<sled::Db>,
         task_id: QueuedTaskId,
     ) {
         debug!("Cancelling task: {:?}", task_id);
 
+        // Stop a running execution at its next cancellation point
+        if let Some(ctx) = running.read().await.get(&task_id) {
+            ctx.cancel();
+        }
+
         let mut tasks_map = tasks.write().await;
         if let Some(task) = tasks_map.get_mut(&task_id) {
             task.mark_cancelled();
@@ -735,6 +776,7 @@ impl TaskQueue {
         tasks: &Arc<RwLock<HashMap<QueuedTaskId, QueuedTask>>>,
         task_manager: &Arc<TaskManager>,
         executors: &Arc<RwLock<HashMap<String, TaskExecutor>>>,
+        running: &Arc<RwLock<HashMap<QueuedTaskId, CancellationContext>>>,
         db: &Arc<sled::Db>,
         config: &TaskQueueConfig,
     ) {
@@ -750,18 +792,31 @@ impl TaskQueue {
             let current_workers = task_manager.task_count().await;
             let available_workers = config.max_workers.saturating_sub(current_workers);
 
-            for _ in 0..available_workers {
-                if let Some(item) = queue.pop() {
+            let tasks_map = tasks.read().await;
+            while tasks_to_execute.len() < available_workers {
+                let Some(item) = queue.pop() else {
+                    break;
+                };
+
+                // Skip tasks that were cancelled after being queued, and
+                // duplicate entries for a task already picked in this pass
+                let still_pending = tasks_map
+                    .get(&item.task.id)
+                    .is_some_and(|task| task.status == TaskStatus::Pending);
+                let already_picked = tasks_to_execute
+                    .iter()
+                    .any(|task: &QueuedTask| task.id == item.task.id);
+                if still_pending && !already_picked {
                     tasks_to_execute.push(item.task);
                 } else {
-                    break;
+                    debug!("Skipping task {:?} that is no longer pending", item.task.id);
                 }
             }
         }
 
         // Execute the tasks
         for task in tasks_to_execute {
-            Self::execute_task(task, tasks, task_manager, executors, db).await;
+            Self::execute_task(task, tasks, task_manager, executors, running, db).await;
         }
     }
 
@@ -798,6 +853,7 @@ impl TaskQueue {
         tasks: &Arc<RwLock<HashMap<QueuedTaskId, QueuedTask>>>,
         task_manager: &Arc<TaskManager>,
         executors: &Arc<RwLock<HashMap<String, TaskExecutor>>>,
+        running: &Arc<RwLock<HashMap<QueuedTaskId, CancellationContext>>>,
         db: &Arc<sled::Db>,
     ) {
         let task_id = task.id;
@@ -828,9 +884,14 @@ impl TaskQueue {
         };
 
         let tasks_clone = tasks.clone();
+        let running_clone = running.clone();
         let db_clone = db.clone();
         let task_clone = task.clone();
 
+        // Register the execution so cancel_task can reach it
+        let ctx = task.cancellation_context();
+        running.write().await.insert(task_id, ctx.clone());
+
         // Spawn the task execution
         let execution_result = task_manager.spawn_task(
             format!("queue_task_{}", task_name),
@@ -839,37 +900,64 @@ impl TaskQueue {
             move |_shutdown_rx| {
                 let task = task_clone;
                 let tasks = tasks_clone;
+                let running = running_clone;
                 let db = db_clone;
                 let executor = executor;
+                let ctx = ctx;
 
                 async move {
                     let task_id = task.id;
                     let mut final_task = task;
 
                     let execution_result = if let Some(executor) = executor {
-                        // Execute with timeout if specified
-                        let execution_future = executor(final_task.parameters.clone());
-
-                        if let Some(timeout_duration) = final_task.timeout {
-                            match timeout(timeout_duration, execution_future).await {
-                                Ok(Ok(())) => Ok(()),
-                                Ok(Err(e)) => Err(e),
-                                Err(_) => Err(anyhow::anyhow!("Task execution timed out")),
+                        let execution_future = executor(final_task.parameters.clone(), ctx.clone());
+                        let deadline = async {
+                            match ctx.remaining() {
+                                Some(remaining) => tokio::time::sleep(remaining).await,
+                                None => std::future::pending::<()>().await,
                             }
-                        } else {
-                            execution_future.await
+                        };
+
+                        // The deadline is also enforced here so executors that
+                        // ignore the context cannot overrun their timeout
+                        tokio::select! {
+                            biased;
+                            _ = ctx.token().cancelled() => Err(ExecutionFailure::Cancelled),
+                            _ = deadline => {
+                                ctx.cancel();
+                                Err(ExecutionFailure::TimedOut)
+                            }
+                            result = execution_future => result.map_err(ExecutionFailure::Failed),
                         }
                     } else {
-                        Err(anyhow::anyhow!("No executor found for task"))
+                        Err(ExecutionFailure::Failed(anyhow::anyhow!("No executor found for task")))
                     };
 
+                    running.write().await.remove(&task_id);
+
                     // Update task based on execution result
                     match execution_result {
                         Ok(()) => {
                             info!("Task {} completed successfully", final_task.name);
                             final_task.mark_completed();
                         }
-                        Err(e) => {
+                        Err(ExecutionFailure::Cancelled) => {
+                            info!("Task {} was cancelled while running", final_task.name);
+                            final_task.mark_cancelled();
+                        }
+                        Err(ExecutionFailure::TimedOut) => {
+                            warn!("Task {} timed out", final_task.name);
+
+                            if final_task.mark_timed_out() {
+                                info!("Task {} will be retried after timing out (attempt {} of max {})",
+                                     final_task.name, final_task.attempts,
+                                     Self::get_max_attempts(&final_task.retry_strategy));
+                            } else {
+                                error!("Task {} timed out permanently after {} attempts",
+                                      final_task.name, final_task.attempts);
+                            }
+                        }
+                        Err(ExecutionFailure::Failed(e)) => {
                             let error_msg = e.to_string();
                             warn!("Task {} failed: {}", final_task.name, error_msg);
 
@@ -899,6 +987,7 @@ impl TaskQueue {
 
         if let Err(e) = execution_result {
             error!("Failed to spawn task execution for {}: {}", task_name, e);
+            running.write().await.remove(&task_id);
 
             // Mark task as failed
             let mut failed_task = task;
@@ -1111,6 +1200,81 @@ mod tests {
         queue.stop().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_running_task_cancellation_reaches_executor() {
+        let (queue, _temp_dir) = create_test_queue().await;
+        let (started_tx, mut started_rx) = mpsc::unbounded_channel();
+
+        queue.register_executor("default".to_string(), move |_params, ctx: CancellationContext| {
+            let started_tx = started_tx.clone();
+            async move {
+                let _ = started_tx.send(ctx.clone());
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            }
+        }).await.unwrap();
+        queue.start().await.unwrap();
+
+        let task = QueuedTask::new(
+            "long_running_task".to_string(),
+            TaskPriority::Normal,
+            None,
+            serde_json::json!({}),
+        );
+        let task_id = task.id;
+        queue.enqueue_task(task).await.unwrap();
+
+        // Wait until the task is running, then cancel it
+        let executor_ctx = tokio::time::timeout(Duration::from_secs(2), started_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(queue.get_task(task_id).await.unwrap().status, TaskStatus::Running);
+        queue.cancel_task(task_id).await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(2), executor_ctx.token().cancelled())
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(100)).await;
+        let cancelled_task = queue.get_task(task_id).await.unwrap();
+        assert_eq!(cancelled_task.status, TaskStatus::Cancelled);
+        assert!(matches!(cancelled_task.last_result, Some(TaskResult::Cancelled)));
+
+        queue.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_task_timeout_sets_deadline() {
+        let (queue, _temp_dir) = create_test_queue().await;
+
+        queue.register_executor("default".to_string(), |_params, ctx: CancellationContext| async move {
+            assert!(ctx.deadline().is_some());
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        }).await.unwrap();
+        queue.start().await.unwrap();
+
+        let task = QueuedTask::new(
+            "slow_task".to_string(),
+            TaskPriority::Normal,
+            None,
+            serde_json::json!({}),
+        )
+        .with_timeout(Duration::from_millis(100))
+        .with_retry_strategy(RetryStrategy::None);
+        let task_id = task.id;
+        queue.enqueue_task(task).await.unwrap();
+
+        sleep(Duration::from_millis(600)).await;
+
+        let timed_out_task = queue.get_task(task_id).await.unwrap();
+        assert_eq!(timed_out_task.status, TaskStatus::FailedPermanently);
+        assert!(matches!(timed_out_task.last_result, Some(TaskResult::TimedOut)));
+
+        queue.stop().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_retry_strategy() {
         let mut task = QueuedTask::new(
@@ -2,13 +2,18 @@
 
 use crate::context::{Context, CommandError, record_command_execution};
 use crate::cooldown::CooldownConfig;
+use crate::database::LinkStatus;
+use crate::linking::{import_links, parse_link_import, resolve_link};
+use crate::permissions::require_administrator;
+use poise::serenity_prelude::{Attachment, User};
 use std::time::{Duration, Instant};
-use tracing::info;
+use tracing::{info, warn};
 
 /// Update graphs command - triggers graph regeneration (admin only)
 #[poise::command(
     slash_command,
-    default_member_permissions = "MANAGE_GUILD"
+    default_member_permissions = "MANAGE_GUILD",
+    check = "require_administrator"
 )]
 pub async fn update_graphs(ctx: Context<'_>) -> Result<(), CommandError> {
     let start_time = Instant::now();
@@ -60,7 +65,8 @@ pub async fn update_graphs(ctx: Context<'_>) -> Result<(), CommandError> {
 /// Metrics command - displays bot usage statistics (admin only)
 #[poise::command(
     slash_command,
-    default_member_permissions = "MANAGE_GUILD"
+    default_member_permissions = "MANAGE_GUILD",
+    check = "require_administrator"
 )]
 pub async fn metrics(ctx: Context<'_>) -> Result<(), CommandError> {
     let start_time = Instant::now();
@@ -158,7 +164,8 @@ pub async fn metrics(ctx: Context<'_>) -> Result<(), CommandError> {
 /// Scheduler status command - displays scheduling system status (admin only)
 #[poise::command(
     slash_command,
-    default_member_permissions = "MANAGE_GUILD"
+    default_member_permissions = "MANAGE_GUILD",
+    check = "require_administrator"
 )]
 pub async fn scheduler_status(ctx: Context<'_>) -> Result<(), CommandError> {
     let start_time = Instant::now();
@@ -208,4 +215,190 @@ pub async fn scheduler_status(ctx: Context<'_>) -> Result<(), CommandError> {
     record_command_execution(&ctx, "scheduler_status", start_time, &result);
 
     result
-}
\ No newline at end of file
+}
+
+/// Link admin command - manages Discord to Tautulli account links (admin only)
+#[poise::command(
+    slash_command,
+    rename = "link_admin",
+    subcommands("link_approve", "link_set", "link_import", "link_pending"),
+    subcommand_required,
+    default_member_permissions = "MANAGE_GUILD",
+    check = "require_administrator"
+)]
+pub async fn link_admin(_ctx: Context<'_>) -> Result<(), CommandError> {
+    Ok(())
+}
+
+/// Approve a user's pending account link
+#[poise::command(slash_command, rename = "approve")]
+pub async fn link_approve(
+    ctx: Context<'_>,
+    #[description = "User whose link request to approve"]
+    user: User,
+) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        let admin_id = ctx.author().id.get();
+
+        match ctx.data().user_db.approve_link(user.id.get(), admin_id).await {
+            Ok(Some(link)) => {
+                ctx.say(format!(
+                    "✅ <@{}> is now linked to **{}** on **{}**.",
+                    user.id, link.tautulli_username, link.server
+                )).await?;
+                ctx.data().audit_logger.log_preferences_modification(user.id.get(), Some(admin_id), "tautulli_link_approved").await;
+            }
+            Ok(None) => {
+                ctx.say(format!("ℹ️ <@{}> has not requested an account link.", user.id)).await?;
+            }
+            Err(e) => {
+                ctx.say(format!("❌ Unable to approve link: {}", e)).await?;
+            }
+        }
+
+        info!("Link approve command executed by admin user {}", admin_id);
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "link_admin_approve", start_time, &result);
+
+    result
+}
+
+/// Link a user to a Plex account directly
+#[poise::command(slash_command, rename = "set")]
+pub async fn link_set(
+    ctx: Context<'_>,
+    #[description = "Discord user to link"]
+    user: User,
+    #[description = "Plex username or email"]
+    identifier: String,
+    #[description = "Tautulli server name (defaults to the primary server)"]
+    server: Option<String>,
+) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        let admin_id = ctx.author().id.get();
+
+        let link = match resolve_link(&ctx.data().tautulli, server.as_deref(), &identifier).await? {
+            Some(link) => link,
+            None => {
+                ctx.say(format!("❌ No Plex account matches `{}`.", identifier)).await?;
+                return Ok(());
+            }
+        };
+
+        match ctx.data().user_db.set_link(user.id.get(), link, admin_id).await {
+            Ok(link) => {
+                ctx.say(format!(
+                    "✅ <@{}> is now linked to **{}** on **{}**.",
+                    user.id, link.tautulli_username, link.server
+                )).await?;
+                ctx.data().audit_logger.log_preferences_modification(user.id.get(), Some(admin_id), "tautulli_link_set").await;
+            }
+            Err(e) => {
+                ctx.say(format!("❌ Unable to set link: {}", e)).await?;
+            }
+        }
+
+        info!("Link set command executed by admin user {}", admin_id);
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "link_admin_set", start_time, &result);
+
+    result
+}
+
+/// Bulk import account links from a CSV or JSON file
+#[poise::command(slash_command, rename = "import")]
+pub async fn link_import(
+    ctx: Context<'_>,
+    #[description = "CSV (discord_user_id,identifier[,server]) or JSON file"]
+    file: Attachment,
+) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        let admin_id = ctx.author().id.get();
+
+        // Import files are small; refuse anything that looks like a mistake
+        if file.size > 1024 * 1024 {
+            ctx.say("❌ Import files must be smaller than 1 MB.").await?;
+            return Ok(());
+        }
+
+        let content = String::from_utf8(file.download().await?)?;
+        let entries = match parse_link_import(&content) {
+            Ok(entries) => entries,
+            Err(e) => {
+                ctx.say(format!("❌ Unable to read import file: {}", e)).await?;
+                return Ok(());
+            }
+        };
+
+        ctx.defer().await?;
+        let report = import_links(&ctx.data().user_db, &ctx.data().tautulli, entries, admin_id).await?;
+
+        let mut response = format!("📥 **Link Import Complete**\n✅ Linked: {}\n", report.linked);
+        if !report.failures.is_empty() {
+            response.push_str(&format!("❌ Failed: {}\n", report.failures.len()));
+            for failure in report.failures.iter().take(10) {
+                response.push_str(&format!("• {}\n", failure));
+            }
+            if report.failures.len() > 10 {
+                response.push_str(&format!("…and {} more\n", report.failures.len() - 10));
+            }
+            warn!("Link import by admin {} had {} failures", admin_id, report.failures.len());
+        }
+
+        ctx.say(response).await?;
+        info!("Link import command executed by admin user {}", admin_id);
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "link_admin_import", start_time, &result);
+
+    result
+}
+
+/// List account links waiting for approval
+#[poise::command(slash_command, rename = "pending")]
+pub async fn link_pending(ctx: Context<'_>) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        let pending = ctx.data().user_db.list_links(Some(LinkStatus::Pending))?;
+
+        if pending.is_empty() {
+            ctx.say("✅ No account links are waiting for approval.").await?;
+        } else {
+            let mut response = format!("🔗 **Pending Account Links** ({})\n", pending.len());
+            for (user_id, link) in pending.iter().take(20) {
+                response.push_str(&format!(
+                    "• <@{}> → **{}** on {} (requested {})\n",
+                    user_id,
+                    link.tautulli_username,
+                    link.server,
+                    link.requested_at.format("%Y-%m-%d")
+                ));
+            }
+            response.push_str("\nUse `/link_admin approve` to approve a request.");
+            ctx.say(response).await?;
+        }
+
+        info!("Link pending command executed by admin user {}", ctx.author().id);
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "link_admin_pending", start_time, &result);
+
+    result
+}
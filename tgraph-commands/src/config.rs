@@ -0,0 +1,144 @@
+//! `/config` commands for per-guild configuration overrides
+
+use crate::context::{Context, CommandError, record_command_execution};
+use crate::permissions::require_administrator;
+use poise::serenity_prelude::{AutocompleteChoice, GuildId};
+use std::time::Instant;
+use tgraph_config::guild::GUILD_KEYS;
+use tgraph_config::{ConfigError, GuildOverrides};
+use tracing::info;
+
+/// Autocomplete for the keys a guild can override
+async fn autocomplete_key(_ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    let partial = partial.trim().to_lowercase();
+    GUILD_KEYS
+        .iter()
+        .filter(|key| key.contains(&partial))
+        .map(|&key| AutocompleteChoice::new(key, key))
+        .collect()
+}
+
+/// Config command - shows and changes this server's settings (admin only)
+#[poise::command(
+    slash_command,
+    subcommands("config_view", "config_edit", "config_reset"),
+    subcommand_required,
+    guild_only,
+    default_member_permissions = "MANAGE_GUILD",
+    check = "require_administrator"
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<(), CommandError> {
+    Ok(())
+}
+
+/// Show this server's settings and which ones it overrides
+#[poise::command(slash_command, rename = "view", guild_only)]
+pub async fn config_view(ctx: Context<'_>) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        let guild_id = ctx.guild_id().ok_or("This command can only be used in a server")?;
+        let overrides = ctx.data().guild_settings.get(guild_id.get());
+
+        let mut response = String::from("⚙️ **Server Configuration**\n");
+        for (key, value, overridden) in overrides.describe(&ctx.data().config.get()) {
+            let marker = if overridden { " *(server)*" } else { "" };
+            response.push_str(&format!("• `{}`: {}{}\n", key, value, marker));
+        }
+        response.push_str("\nSettings marked *(server)* override the global configuration.");
+
+        ctx.send(poise::CreateReply::default().content(response).ephemeral(true)).await?;
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "config_view", start_time, &result);
+
+    result
+}
+
+/// Override a setting for this server
+#[poise::command(slash_command, rename = "edit", guild_only)]
+pub async fn config_edit(
+    ctx: Context<'_>,
+    #[description = "Setting to change"]
+    #[autocomplete = "autocomplete_key"]
+    key: String,
+    #[description = "New value; lists are separated by commas"]
+    value: String,
+) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        let guild_id = ctx.guild_id().ok_or("This command can only be used in a server")?;
+        // Only channel overrides need to know the server's channels
+        let guild_channels: Vec<u64> = if key == "channels" {
+            guild_id.channels(ctx.http()).await?.into_keys().map(|id| id.get()).collect()
+        } else {
+            Vec::new()
+        };
+        let outcome = update(ctx, guild_id, |overrides| overrides.set(&key, &value, &guild_channels)).await;
+        match outcome {
+            Ok(()) => ctx.say(format!("✅ `{}` updated for this server.", key)).await?,
+            Err(e) => ctx.say(format!("❌ {}", e)).await?,
+        };
+
+        info!("Config edit command executed by user {} in guild {}", ctx.author().id, guild_id);
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "config_edit", start_time, &result);
+
+    result
+}
+
+/// Remove this server's override of a setting, or of every setting
+#[poise::command(slash_command, rename = "reset", guild_only)]
+pub async fn config_reset(
+    ctx: Context<'_>,
+    #[description = "Setting to reset (all settings when omitted)"]
+    #[autocomplete = "autocomplete_key"]
+    key: Option<String>,
+) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        let guild_id = ctx.guild_id().ok_or("This command can only be used in a server")?;
+        let outcome = update(ctx, guild_id, |overrides| match &key {
+            Some(key) => overrides.reset(key),
+            None => {
+                *overrides = GuildOverrides::default();
+                Ok(())
+            }
+        })
+        .await;
+        match (outcome, &key) {
+            (Ok(()), Some(key)) => ctx.say(format!("✅ `{}` now uses the global setting.", key)).await?,
+            (Ok(()), None) => ctx.say("✅ All settings now use the global configuration.").await?,
+            (Err(e), _) => ctx.say(format!("❌ {}", e)).await?,
+        };
+
+        info!("Config reset command executed by user {} in guild {}", ctx.author().id, guild_id);
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "config_reset", start_time, &result);
+
+    result
+}
+
+/// Apply an edit to the guild's overrides and to the permissions
+async fn update<F>(ctx: Context<'_>, guild_id: GuildId, edit: F) -> anyhow::Result<()>
+where
+    F: FnOnce(&mut GuildOverrides) -> Result<(), ConfigError>,
+{
+    let data = ctx.data();
+    let overrides = data
+        .guild_settings
+        .update(guild_id.get(), &data.config.get(), ctx.author().id.get(), edit)
+        .await?;
+    data.permissions.set_guild_roles(guild_id, &overrides);
+    Ok(())
+}
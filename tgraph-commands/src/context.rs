@@ -1,18 +1,25 @@
 //! Command context and framework integration
 
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock};
 use std::time::{Duration, Instant};
-use tgraph_config::Config;
-use tgraph_i18n::I18nManager;
-use crate::{Permissions, CooldownManager, MetricsManager, UserDatabase, UserStatisticsManager, DmThrottleManager, AuditLogger};
-use tracing::info;
+use async_trait::async_trait;
+use tgraph_common::TautulliServers;
+use tgraph_config::settings::PrivacySettings;
+use tgraph_config::{Config, ConfigSection, ConfigSubscriber};
+use tgraph_graphs::{PrivacyPolicy, TimeRangeContext, UsernameDisplay, WeekStart};
+use tgraph_i18n::{FluentArgs, I18nManager, Locale};
+use poise::serenity_prelude::GuildId;
+use crate::{Permissions, CooldownManager, MetricsManager, UserDatabase, UserStatisticsManager, DmThrottleManager, AuditLogger, GuildSettings};
+use tracing::{info, warn};
 use tokio::time::interval;
 
 /// Shared application state accessible across commands and event handlers
-#[derive(Debug)]
+///
+/// Cloning is cheap; the clones share every manager.
+#[derive(Debug, Clone)]
 pub struct CommandContext {
-    /// Application configuration
-    pub config: Arc<Config>,
+    /// Application configuration, kept current across reloads
+    pub config: Arc<SharedConfig>,
     /// HTTP client for external API calls
     pub http_client: reqwest::Client,
     /// Internationalization manager
@@ -31,6 +38,117 @@ pub struct CommandContext {
     pub dm_throttle: Arc<DmThrottleManager>,
     /// Audit logger for GDPR compliance and data protection tracking
     pub audit_logger: Arc<AuditLogger>,
+    /// Tautulli clients shared with the rest of the bot
+    pub tautulli: Arc<TautulliServers>,
+    /// Per-guild configuration overrides
+    pub guild_settings: Arc<GuildSettings>,
+}
+
+impl CommandContext {
+    /// Username censoring policy from the config plus users' opt-outs
+    ///
+    /// Every Plex username shown in a graph or channel message goes through
+    /// this policy. Users who turned off username visibility are hidden.
+    pub fn privacy_policy(&self) -> PrivacyPolicy {
+        let mut policy = configured_privacy_policy(&self.config.get().privacy);
+        // Unreadable opt-outs hide everyone
+        match self.user_db.privacy_opt_outs() {
+            Ok(usernames) => {
+                for username in usernames {
+                    policy.opt_out(&username, UsernameDisplay::Hidden);
+                }
+            }
+            Err(e) => {
+                warn!("Failed to load username opt-outs, hiding all usernames: {}", e);
+                return PrivacyPolicy::new(UsernameDisplay::Hidden);
+            }
+        }
+        policy
+    }
+
+    /// The configuration with a guild's overrides applied
+    pub fn guild_config(&self, guild_id: Option<GuildId>) -> Config {
+        match guild_id {
+            Some(guild_id) => self.guild_settings.get(guild_id.get()).apply(&self.config.get()),
+            None => (*self.config.get()).clone(),
+        }
+    }
+
+    /// Locale for messages and week starts in a guild
+    ///
+    /// A locale set for the guild takes precedence over the user's locale.
+    pub fn locale(&self, guild_id: Option<GuildId>, user_locale: Option<&str>) -> Option<String> {
+        guild_id
+            .and_then(|id| self.guild_settings.get(id.get()).locale)
+            .or_else(|| user_locale.map(str::to_string))
+    }
+
+    /// Message `key` in the guild's locale, `default` when it has no translation
+    pub fn translate(
+        &self,
+        guild_id: Option<GuildId>,
+        user_locale: Option<&str>,
+        key: &str,
+        args: Option<&FluentArgs>,
+        default: &str,
+    ) -> String {
+        let locale = self
+            .locale(guild_id, user_locale)
+            .and_then(|code| Locale::from_code(&code))
+            .unwrap_or_else(|| self.i18n.default_locale().clone());
+        self.i18n.get_message_or_default(key, &locale, args, default)
+    }
+
+    /// Date context in the guild's timezone, weeks starting per [`locale`](Self::locale)
+    pub fn time_range_context(&self, guild_id: Option<GuildId>, locale: Option<&str>) -> TimeRangeContext {
+        let config = self.guild_config(guild_id);
+        let timezone = config.scheduling.timezone.as_deref().unwrap_or("UTC");
+        let context = TimeRangeContext::for_timezone_name(timezone).unwrap_or_else(|e| {
+            warn!("{}, using UTC for date ranges", e);
+            TimeRangeContext::default()
+        });
+        match self.locale(guild_id, locale) {
+            Some(locale) => context.with_week_start(WeekStart::for_locale(&locale)),
+            None => context,
+        }
+    }
+}
+
+/// The latest configuration, swapped in whenever the configuration is reloaded
+///
+/// Register it with `ConfigManager::spawn_subscriber` so commands see reloads.
+#[derive(Debug)]
+pub struct SharedConfig {
+    current: RwLock<Arc<Config>>,
+}
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(config)),
+        }
+    }
+
+    /// The configuration as of the last reload
+    pub fn get(&self) -> Arc<Config> {
+        self.current.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+#[async_trait]
+impl ConfigSubscriber for SharedConfig {
+    fn name(&self) -> &str {
+        "commands"
+    }
+
+    fn sections(&self) -> &[ConfigSection] {
+        &ConfigSection::ALL
+    }
+
+    async fn apply(&self, config: &Config) -> Result<(), String> {
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(config.clone());
+        Ok(())
+    }
 }
 
 /// Error type for commands
@@ -39,6 +157,15 @@ pub type CommandError = Box<dyn std::error::Error + Send + Sync>;
 /// Poise context type alias
 pub type Context<'a> = poise::Context<'a, CommandContext, CommandError>;
 
+/// Privacy policy of the configuration alone, without per-user opt-outs
+pub fn configured_privacy_policy(settings: &PrivacySettings) -> PrivacyPolicy {
+    // Fail closed: unknown modes hide everyone
+    let display = settings.username_display.parse().unwrap_or(UsernameDisplay::Hidden);
+    PrivacyPolicy::new(display)
+        .with_masked_chars(settings.masked_visible_chars as usize)
+        .with_pseudonym_salt(settings.pseudonym_salt.clone().unwrap_or_default())
+}
+
 /// Helper function to record command execution
 pub fn record_command_execution(
     ctx: &Context<'_>,
@@ -121,14 +248,19 @@ pub fn start_dm_throttle_cleanup_task(dm_throttle: Arc<DmThrottleManager>) -> to
 }
 
 /// Create a new command context with all required components
-pub async fn create_command_context(config: Config) -> Result<CommandContext, CommandError> {
+pub async fn create_command_context(
+    config: Config,
+    tautulli: Arc<TautulliServers>,
+) -> Result<CommandContext, CommandError> {
     // Initialize HTTP client
     let http_client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(config.discord.request_timeout_seconds))
         .build()?;
 
     // Initialize i18n
-    let i18n = I18nManager::new(tgraph_i18n::Locale::default(), "locales")?;
+    // Guilds can pick any supported locale, so load them all up front
+    let mut i18n = I18nManager::new(Locale::default(), "locales")?;
+    i18n.load_all_locales()?;
 
     // Initialize permissions from config
     let permissions = Permissions::new(&config);
@@ -144,6 +276,12 @@ pub async fn create_command_context(config: Config) -> Result<CommandContext, Co
     std::fs::create_dir_all(db_path.parent().unwrap())?;
     let user_db = Arc::new(UserDatabase::new(db_path)?);
 
+    // Load per-guild overrides and apply their role permissions
+    let guild_settings = Arc::new(GuildSettings::load(user_db.clone())?);
+    for (guild_id, overrides) in guild_settings.all() {
+        permissions.set_guild_roles(GuildId::new(guild_id), &overrides);
+    }
+
     // Initialize user statistics manager
     let user_stats = Arc::new(UserStatisticsManager::new(user_db.clone()));
 
@@ -159,7 +297,7 @@ pub async fn create_command_context(config: Config) -> Result<CommandContext, Co
     let _dm_throttle_cleanup_handle = start_dm_throttle_cleanup_task(dm_throttle.clone());
 
     Ok(CommandContext {
-        config: Arc::new(config),
+        config: Arc::new(SharedConfig::new(config)),
         http_client,
         i18n: Arc::new(i18n),
         permissions: Arc::new(permissions),
@@ -169,5 +307,25 @@ pub async fn create_command_context(config: Config) -> Result<CommandContext, Co
         user_stats,
         dm_throttle,
         audit_logger,
+        tautulli,
+        guild_settings,
     })
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shared_config_follows_reloads() {
+        let shared = SharedConfig::new(Config::default());
+        let before = shared.get();
+
+        let mut reloaded = Config::default();
+        reloaded.privacy.username_display = "hidden".to_string();
+        shared.apply(&reloaded).await.unwrap();
+
+        assert_eq!(shared.get().privacy.username_display, "hidden");
+        assert_ne!(before.privacy.username_display, "hidden");
+    }
+}
@@ -9,6 +9,7 @@ use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use tracing::{info, warn, debug};
+use tgraph_config::GuildOverrides;
 
 /// User privacy preferences and settings
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -31,6 +32,9 @@ pub struct UserPreferences {
     pub created_at: DateTime<Utc>,
     /// Timestamp when preferences were last updated
     pub updated_at: DateTime<Utc>,
+    /// Link to the user's Tautulli account, if requested or set by an admin
+    #[serde(default)]
+    pub tautulli_link: Option<TautulliLink>,
 }
 
 impl UserPreferences {
@@ -47,6 +51,7 @@ impl UserPreferences {
             prefer_dm_delivery: true,    // Default to DM delivery for privacy
             created_at: now,
             updated_at: now,
+            tautulli_link: None,
         }
     }
 
@@ -66,6 +71,13 @@ impl UserPreferences {
             }
         }
     }
+
+    /// The linked Tautulli account, only once an admin has approved it
+    pub fn approved_link(&self) -> Option<&TautulliLink> {
+        self.tautulli_link
+            .as_ref()
+            .filter(|link| link.status == LinkStatus::Approved)
+    }
 }
 
 impl Default for UserPreferences {
@@ -74,6 +86,85 @@ impl Default for UserPreferences {
     }
 }
 
+/// Stored configuration overrides of a guild
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GuildOverridesRecord {
+    /// Discord guild ID
+    pub guild_id: u64,
+    /// The overridden settings
+    pub overrides: GuildOverrides,
+    /// Discord ID of the user who last changed them
+    pub updated_by: u64,
+    /// When they were last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Approval state of a Discord to Tautulli account link
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStatus {
+    /// Requested by the user, waiting for an admin
+    Pending,
+    /// Approved or set by an admin
+    Approved,
+}
+
+/// Link between a Discord user and a Tautulli user
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TautulliLink {
+    /// Name of the Tautulli server the user belongs to
+    pub server: String,
+    /// Tautulli user ID on that server
+    pub tautulli_user_id: i32,
+    /// Tautulli username at the time of linking
+    pub tautulli_username: String,
+    /// Plex username or email the link was requested with
+    pub requested_identifier: String,
+    /// Approval state
+    pub status: LinkStatus,
+    /// When the link was requested or set
+    pub requested_at: DateTime<Utc>,
+    /// Discord ID of the admin who approved or set the link
+    pub approved_by: Option<u64>,
+    /// When the link was approved
+    pub approved_at: Option<DateTime<Utc>>,
+}
+
+impl TautulliLink {
+    /// Create a pending link request
+    pub fn pending(
+        server: impl Into<String>,
+        tautulli_user_id: i32,
+        tautulli_username: impl Into<String>,
+        requested_identifier: impl Into<String>,
+    ) -> Self {
+        Self {
+            server: server.into(),
+            tautulli_user_id,
+            tautulli_username: tautulli_username.into(),
+            requested_identifier: requested_identifier.into(),
+            status: LinkStatus::Pending,
+            requested_at: Utc::now(),
+            approved_by: None,
+            approved_at: None,
+        }
+    }
+
+    /// Mark the link as approved by an admin
+    pub fn approve(&mut self, admin_id: u64) {
+        self.status = LinkStatus::Approved;
+        self.approved_by = Some(admin_id);
+        self.approved_at = Some(Utc::now());
+    }
+
+    /// Whether this link points at the given Tautulli user
+    ///
+    /// Server names are compared without regard to case, as they are looked up.
+    pub fn targets(&self, server: &str, tautulli_user_id: i32) -> bool {
+        self.server.to_lowercase() == server.to_lowercase() && self.tautulli_user_id == tautulli_user_id
+    }
+}
+
 /// Database manager for user preferences using sled embedded database
 #[derive(Debug, Clone)]
 pub struct UserDatabase {
@@ -81,6 +172,8 @@ pub struct UserDatabase {
     db: Arc<sled::Db>,
     /// Tree for storing user preferences
     preferences_tree: sled::Tree,
+    /// Tree for storing per-guild configuration overrides
+    guild_tree: sled::Tree,
 }
 
 impl UserDatabase {
@@ -107,9 +200,13 @@ impl UserDatabase {
         let preferences_tree = db.open_tree("user_preferences")
             .context("Failed to open user preferences tree")?;
 
+        let guild_tree = db.open_tree("guild_overrides")
+            .context("Failed to open guild overrides tree")?;
+
         let database = Self {
             db: Arc::new(db),
             preferences_tree,
+            guild_tree,
         };
 
         info!("User preferences database initialized successfully");
@@ -329,6 +426,172 @@ impl UserDatabase {
         Ok(cleanup_count)
     }
 
+    /// Request a link to a Tautulli account, pending admin approval
+    ///
+    /// Replaces any existing link for the user. Fails if another Discord user
+    /// already has an approved link to the same Tautulli account.
+    pub async fn request_link(&self, user_id: u64, link: TautulliLink) -> Result<TautulliLink> {
+        self.ensure_link_available(user_id, &link)?;
+        info!(
+            "User {} requested link to Tautulli user {} on {}",
+            user_id, link.tautulli_username, link.server
+        );
+
+        let preferences = self
+            .update_preferences(user_id, |prefs| prefs.tautulli_link = Some(link))
+            .await?;
+        preferences.tautulli_link.context("Link missing after update")
+    }
+
+    /// Approve a user's pending link
+    ///
+    /// # Returns
+    /// * `Result<Option<TautulliLink>>` - The approved link, or None if the user has no link
+    pub async fn approve_link(&self, user_id: u64, admin_id: u64) -> Result<Option<TautulliLink>> {
+        let mut link = match self.get_preferences(user_id)?.and_then(|prefs| prefs.tautulli_link) {
+            Some(link) => link,
+            None => return Ok(None),
+        };
+
+        self.ensure_link_available(user_id, &link)?;
+        link.approve(admin_id);
+        info!("Admin {} approved Tautulli link for user {}", admin_id, user_id);
+
+        let preferences = self
+            .update_preferences(user_id, |prefs| prefs.tautulli_link = Some(link))
+            .await?;
+        Ok(preferences.tautulli_link)
+    }
+
+    /// Set an approved link on behalf of a user (admin action)
+    pub async fn set_link(&self, user_id: u64, mut link: TautulliLink, admin_id: u64) -> Result<TautulliLink> {
+        self.ensure_link_available(user_id, &link)?;
+        link.approve(admin_id);
+        info!(
+            "Admin {} linked user {} to Tautulli user {} on {}",
+            admin_id, user_id, link.tautulli_username, link.server
+        );
+
+        let preferences = self
+            .update_preferences(user_id, |prefs| prefs.tautulli_link = Some(link))
+            .await?;
+        preferences.tautulli_link.context("Link missing after update")
+    }
+
+    /// Remove a user's link
+    ///
+    /// # Returns
+    /// * `Result<bool>` - True if a link was removed
+    pub async fn remove_link(&self, user_id: u64) -> Result<bool> {
+        let had_link = self
+            .get_preferences(user_id)?
+            .is_some_and(|prefs| prefs.tautulli_link.is_some());
+
+        if had_link {
+            self.update_preferences(user_id, |prefs| prefs.tautulli_link = None)
+                .await?;
+            info!("Removed Tautulli link for user {}", user_id);
+        }
+        Ok(had_link)
+    }
+
+    /// List links, optionally filtered by status, as (Discord user ID, link) pairs
+    pub fn list_links(&self, status: Option<LinkStatus>) -> Result<Vec<(u64, TautulliLink)>> {
+        let mut links = Vec::new();
+
+        for result in self.preferences_tree.iter() {
+            let (_, value) = result.context("Failed to iterate over preferences tree")?;
+            let preferences: UserPreferences = serde_json::from_slice(&value)
+                .context("Failed to deserialize preferences while listing links")?;
+
+            if let Some(link) = preferences.tautulli_link {
+                if status.is_none() || status == Some(link.status) {
+                    links.push((preferences.user_id, link));
+                }
+            }
+        }
+
+        links.sort_by_key(|(_, link)| link.requested_at);
+        Ok(links)
+    }
+
+    /// Tautulli usernames of linked users who turned off username visibility
+    pub fn privacy_opt_outs(&self) -> Result<Vec<String>> {
+        let mut usernames = Vec::new();
+
+        for result in self.preferences_tree.iter() {
+            let (_, value) = result.context("Failed to iterate over preferences tree")?;
+            let preferences: UserPreferences = serde_json::from_slice(&value)
+                .context("Failed to deserialize preferences while listing opt-outs")?;
+
+            if !preferences.username_visible {
+                if let Some(link) = preferences.approved_link() {
+                    usernames.push(link.tautulli_username.clone());
+                }
+            }
+        }
+
+        Ok(usernames)
+    }
+
+    /// Find the Discord user with an approved link to a Tautulli user
+    pub fn find_linked_user(&self, server: &str, tautulli_user_id: i32) -> Result<Option<u64>> {
+        Ok(self
+            .list_links(Some(LinkStatus::Approved))?
+            .into_iter()
+            .find(|(_, link)| link.targets(server, tautulli_user_id))
+            .map(|(user_id, _)| user_id))
+    }
+
+    fn ensure_link_available(&self, user_id: u64, link: &TautulliLink) -> Result<()> {
+        if let Some(owner) = self.find_linked_user(&link.server, link.tautulli_user_id)? {
+            if owner != user_id {
+                anyhow::bail!(
+                    "Tautulli user {} on {} is already linked to another Discord account",
+                    link.tautulli_username,
+                    link.server
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Store a guild's configuration overrides, removing them when empty
+    pub async fn store_guild_overrides(&self, guild_id: u64, overrides: &GuildOverrides, updated_by: u64) -> Result<()> {
+        let key = guild_id.to_be_bytes();
+        if overrides.is_empty() {
+            self.guild_tree.remove(key)
+                .context("Failed to remove guild overrides from database")?;
+        } else {
+            let record = GuildOverridesRecord {
+                guild_id,
+                overrides: overrides.clone(),
+                updated_by,
+                updated_at: Utc::now(),
+            };
+            let json_data = serde_json::to_vec(&record)
+                .context("Failed to serialize guild overrides")?;
+            self.guild_tree.insert(key, json_data)
+                .context("Failed to insert guild overrides into database")?;
+        }
+
+        self.guild_tree.flush_async().await
+            .context("Failed to flush guild overrides to disk")?;
+        debug!("Stored configuration overrides for guild {}", guild_id);
+        Ok(())
+    }
+
+    /// Configuration overrides of every guild that has any
+    pub fn list_guild_overrides(&self) -> Result<Vec<GuildOverridesRecord>> {
+        self.guild_tree
+            .iter()
+            .map(|result| {
+                let (_, value) = result.context("Failed to iterate over guild overrides tree")?;
+                serde_json::from_slice(&value).context("Failed to deserialize guild overrides")
+            })
+            .collect()
+    }
+
     /// Get database statistics
     ///
     /// # Returns
@@ -636,6 +899,64 @@ mod tests {
             .is_none());
     }
 
+    #[tokio::test]
+    async fn test_tautulli_link_lifecycle() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = UserDatabase::new(temp_dir.path().join("test_db"))
+            .expect("Failed to initialize database");
+
+        let link = TautulliLink::pending("Home", 7, "alice", "alice@example.com");
+        db.request_link(1, link.clone()).await.expect("Failed to request link");
+
+        let prefs = db.get_preferences(1).unwrap().unwrap();
+        assert_eq!(prefs.tautulli_link.as_ref().unwrap().status, LinkStatus::Pending);
+        assert!(prefs.approved_link().is_none());
+        assert_eq!(db.list_links(Some(LinkStatus::Pending)).unwrap().len(), 1);
+
+        let approved = db.approve_link(1, 99).await.unwrap().unwrap();
+        assert_eq!(approved.status, LinkStatus::Approved);
+        assert_eq!(approved.approved_by, Some(99));
+        assert_eq!(db.find_linked_user("Home", 7).unwrap(), Some(1));
+
+        // The same Tautulli account cannot be claimed by someone else
+        assert!(db.request_link(2, link.clone()).await.is_err());
+        assert!(db.set_link(2, link, 99).await.is_err());
+        // Not even by typing the server name in another case
+        let retyped = TautulliLink::pending("home", 7, "alice", "alice@example.com");
+        assert!(db.request_link(2, retyped).await.is_err());
+        assert_eq!(db.find_linked_user("HOME", 7).unwrap(), Some(1));
+
+        assert!(db.remove_link(1).await.unwrap());
+        assert!(!db.remove_link(1).await.unwrap());
+        assert_eq!(db.find_linked_user("Home", 7).unwrap(), None);
+        assert!(db.approve_link(3, 99).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_privacy_opt_outs() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = UserDatabase::new(temp_dir.path().join("test_db"))
+            .expect("Failed to initialize database");
+
+        db.set_link(1, TautulliLink::pending("Home", 7, "alice", "alice"), 99).await.unwrap();
+        db.set_link(2, TautulliLink::pending("Home", 8, "bob", "bob"), 99).await.unwrap();
+        db.request_link(3, TautulliLink::pending("Home", 9, "carol", "carol")).await.unwrap();
+        for user_id in [1, 3] {
+            db.update_preferences(user_id, |prefs| prefs.username_visible = false).await.unwrap();
+        }
+
+        // Only approved links can be matched to Tautulli usernames
+        assert_eq!(db.privacy_opt_outs().unwrap(), vec!["alice".to_string()]);
+    }
+
+    #[test]
+    fn test_preferences_without_link_deserialize() {
+        let mut value = serde_json::to_value(UserPreferences::new(5)).unwrap();
+        value.as_object_mut().unwrap().remove("tautulli_link");
+        let prefs: UserPreferences = serde_json::from_value(value).unwrap();
+        assert!(prefs.tautulli_link.is_none());
+    }
+
     #[test]
     fn test_database_stats_format_size() {
         let stats = DatabaseStats {
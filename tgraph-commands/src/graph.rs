@@ -1,5 +1,22 @@
 //! Graph-related commands for the bot
 
+use crate::context::{record_command_execution, CommandError, Context};
+use crate::cooldown::CooldownConfig;
+use poise::serenity_prelude::{AutocompleteChoice, CreateAttachment};
+use poise::CreateReply;
+use std::time::{Duration, Instant};
+use tgraph_common::{CancellationContext, ServerSelection, TGraphError};
+use tgraph_config::{Config, GuildOverrides};
+use tgraph_graphs::{
+    comparison_span, render_png, server_title, suggest_date_ranges, AggregationConfig, ColorScheme, ComparisonConfig,
+    ComparisonDisplayMode, ComparisonGraph, ComparisonManager, DateRange, GraphDefaults, StyleConfig, TimeRangeContext,
+    TimeRangeSelector,
+};
+use tracing::{info, warn};
+
+/// Time allowed for fetching the history covered by a comparison
+const COMPARISON_HISTORY_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Graph command implementations
 pub struct GraphCommands;
 
@@ -14,4 +31,270 @@ impl Default for GraphCommands {
     fn default() -> Self {
         Self::new()
     }
-} 
\ No newline at end of file
+}
+
+/// Comparison preset: primary range label and the comparison config
+///
+/// "week", "month" and "year" compare the last 7, 30 or 365 days with the
+/// period before. Anything else is parsed as a date range, e.g.
+/// "last 14 days" or "Q3 2025", and compared with the period just before it.
+fn comparison_preset(period: &str, dates: &TimeRangeContext) -> Result<(String, ComparisonConfig), String> {
+    let (days, label, create): (u32, _, fn(DateRange) -> ComparisonConfig) = match period {
+        "week" => (7, "Last 7 Days", ComparisonManager::create_week_over_week_comparison),
+        "month" => (30, "Last 30 Days", ComparisonManager::create_month_over_month_comparison),
+        "year" => (365, "Last 365 Days", ComparisonManager::create_year_over_year_comparison),
+        _ => {
+            let mut selector = TimeRangeSelector::new().with_context(dates.clone());
+            let selection = selector.select_phrase(period).map_err(|e| match e {
+                TGraphError::Validation { message, .. } => message,
+                other => other.to_string(),
+            })?;
+            let config = ComparisonManager::create_previous_period_comparison(selection.date_range);
+            return Ok((selection.label, config));
+        }
+    };
+    Ok((label.to_string(), create(dates.last_days(days))))
+}
+
+/// Autocomplete for comparison periods: the presets, then date ranges
+async fn autocomplete_period(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    let dates = ctx.data().time_range_context(ctx.guild_id(), ctx.locale());
+    let partial_lower = partial.trim().to_lowercase();
+    let presets = [("week", "Last 7 Days"), ("month", "Last 30 Days"), ("year", "Last 365 Days")]
+        .into_iter()
+        .filter(|(value, _)| value.starts_with(&partial_lower))
+        .map(|(value, label)| AutocompleteChoice::new(format!("{} vs the {} before", label, value), value));
+    let ranges = suggest_date_ranges(partial, &dates)
+        .into_iter()
+        .map(|(label, value)| AutocompleteChoice::new(label, value));
+
+    presets.chain(ranges).take(25).collect()
+}
+
+/// Use the colours and font a guild chose instead of the defaults
+///
+/// `config` is the guild's configuration, see [`CommandContext::guild_config`](crate::context::CommandContext::guild_config).
+pub fn apply_guild_theme(style: &mut StyleConfig, overrides: &GuildOverrides, config: &Config) {
+    let theme = &overrides.theme;
+    if theme.background_color.is_some() {
+        style.background_color = Some(config.graph.background_color.clone());
+    }
+    if theme.primary_color.is_some() || theme.secondary_color.is_some() {
+        style.color_scheme = ColorScheme::Custom(vec![
+            config.graph.primary_color.clone(),
+            config.graph.secondary_color.clone(),
+        ]);
+    }
+    if theme.font_family.is_some() {
+        for font in [&mut style.title_font, &mut style.axis_font, &mut style.label_font] {
+            font.family = config.graph.font_family.clone();
+        }
+    }
+}
+
+fn parse_display_mode(mode: &str) -> Option<ComparisonDisplayMode> {
+    match mode {
+        "overlay" => Some(ComparisonDisplayMode::Overlay),
+        "side-by-side" => Some(ComparisonDisplayMode::SideBySide),
+        "stacked" => Some(ComparisonDisplayMode::Stacked),
+        "difference" => Some(ComparisonDisplayMode::Difference),
+        _ => None,
+    }
+}
+
+/// Compare command - graphs this period against the previous one
+#[poise::command(slash_command)]
+pub async fn compare(
+    ctx: Context<'_>,
+    #[description = "Period to compare (week, month, year or a range such as \"last 14 days\")"]
+    #[autocomplete = "autocomplete_period"]
+    period: Option<String>,
+    #[description = "What to compare (daily, weekday)"]
+    graph: Option<String>,
+    #[description = "How to draw the periods (overlay, side-by-side, stacked, difference)"]
+    mode: Option<String>,
+    #[description = "Tautulli server name (defaults to the primary server)"]
+    server: Option<String>,
+) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        // Each request pages through Tautulli history for both periods
+        let cooldown_config = CooldownConfig {
+            user: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+
+        if let Err(cooldown_err) = ctx.data().cooldown.check_cooldown(
+            "compare",
+            ctx.author().id,
+            Some(ctx.channel_id()),
+            &cooldown_config,
+        ) {
+            ctx.say(format!("⏰ {}", cooldown_err)).await?;
+            return Ok(());
+        }
+
+        let servers = &ctx.data().tautulli;
+        let client = match server.as_deref() {
+            Some(name) => servers.select(&ServerSelection::Server(name.to_string())).ok().and_then(|mut found| found.pop()),
+            None => servers.primary(),
+        };
+        let Some((server_name, client)) = client else {
+            ctx.say("❌ Unknown Tautulli server.").await?;
+            return Ok(());
+        };
+
+        // "All Time" starts at the server's first play
+        let dates = ctx.data().time_range_context(ctx.guild_id(), ctx.locale());
+        let dates = match dates.clone().with_history_origin(&client).await {
+            Ok(dates) => dates,
+            Err(e) => {
+                warn!("Failed to find the first play on {}: {}", server_name, e);
+                dates
+            }
+        };
+        let (primary_label, mut config) = match comparison_preset(period.as_deref().unwrap_or("week"), &dates) {
+            Ok(preset) => preset,
+            Err(message) => {
+                ctx.say(format!("❌ {}", message)).await?;
+                return Ok(());
+            }
+        };
+        let Some(display_mode) = parse_display_mode(mode.as_deref().unwrap_or("overlay")) else {
+            ctx.say("❌ Invalid mode. Use: overlay, side-by-side, stacked, or difference").await?;
+            return Ok(());
+        };
+        let graph_kind = graph.as_deref().unwrap_or("daily");
+        let graph_type = match graph_kind {
+            "daily" => "daily_play_count",
+            "weekday" => "day_of_week",
+            _ => {
+                ctx.say("❌ Invalid graph. Use: daily or weekday").await?;
+                return Ok(());
+            }
+        };
+
+        let overrides = ctx.guild_id().map(|id| ctx.data().guild_settings.get(id.get())).unwrap_or_default();
+        let guild_config = ctx.data().guild_config(ctx.guild_id());
+        let channels = &guild_config.discord.channels;
+        if !channels.is_empty() && !channels.contains(&ctx.channel_id().to_string()) {
+            ctx.say("❌ Graphs can't be posted in this channel.").await?;
+            return Ok(());
+        }
+        if !overrides.graph_enabled(graph_type) {
+            ctx.say("❌ This graph is turned off in this server.").await?;
+            return Ok(());
+        }
+        config.display_mode = display_mode.clone();
+
+        ctx.defer().await?;
+
+        let (after, before) = comparison_span(&config);
+        let cancellation = CancellationContext::with_timeout(COMPARISON_HISTORY_TIMEOUT);
+        let entries = match client.fetch_history_between(&cancellation, after, before, 1000).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Failed to fetch comparison history from {}: {}", server_name, e);
+                ctx.say(if e.is_service_unavailable() {
+                    "📡 Tautulli is currently unavailable, so the comparison could not be made."
+                } else {
+                    "⚠️ The viewing history could not be loaded right now. Please try again later."
+                })
+                .await?;
+                return Ok(());
+            }
+        };
+
+        let manager = ComparisonManager::new(config.clone()).with_aggregation_config(AggregationConfig {
+            privacy: ctx.data().privacy_policy(),
+            timezone: dates.timezone,
+            ..Default::default()
+        });
+        // Name the server in the title once there is more than one to pick from
+        let selection = (servers.len() > 1).then(|| ServerSelection::Server(server_name.clone()));
+        let title = format!("{} vs {}", primary_label, config.comparison_ranges[0].label);
+        let (mut comparison_graph, mut graph_config) =
+            ComparisonGraph::with_config(&server_title(&title, selection.as_ref()), display_mode);
+        GraphDefaults::from(&guild_config.graph).apply(&mut graph_config);
+        apply_guild_theme(&mut graph_config.style, &overrides, &guild_config);
+        if graph_kind == "daily" {
+            let mut comparison = manager.compare_daily_play_counts(entries.clone(), vec![entries]).await?;
+            comparison.primary.label = primary_label.clone();
+            comparison_graph.set_daily_data(&comparison);
+        } else {
+            let mut comparison = manager.compare_day_of_week(entries.clone(), vec![entries]).await?;
+            comparison.primary.label = primary_label.clone();
+            comparison_graph.set_day_of_week_data(&comparison);
+        }
+
+        let rendered = render_png(&comparison_graph, &graph_config, "comparison.png").await?;
+        let content = format!(
+            "📊 **{}**\n{}",
+            graph_config.title,
+            comparison_graph.summary_lines().join("\n")
+        );
+        ctx.send(
+            CreateReply::default()
+                .content(content)
+                .attachment(CreateAttachment::bytes(rendered.data, rendered.file_name)),
+        )
+        .await?;
+
+        ctx.data().cooldown.apply_cooldown(
+            "compare",
+            ctx.author().id,
+            Some(ctx.channel_id()),
+            &cooldown_config,
+        );
+
+        info!("Compare command executed by user {}", ctx.author().id);
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "compare", start_time, &result);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comparison_presets() {
+        let dates = TimeRangeContext::default();
+        let (label, config) = comparison_preset("week", &dates).unwrap();
+        assert_eq!(label, "Last 7 Days");
+        assert_eq!((config.primary_range.end - config.primary_range.start).num_days(), 6);
+        assert_eq!(config.primary_range.end, dates.today);
+        assert_eq!(config.comparison_ranges[0].label, "Previous Week");
+        assert!(comparison_preset("decade", &dates).unwrap_err().contains("Couldn't understand \"decade\""));
+
+        let (label, config) = comparison_preset("last 14 days", &dates).unwrap();
+        assert!(label.ends_with(&dates.today.to_string()));
+        assert_eq!(config.comparison_ranges[0].label, "Previous Period");
+        assert_eq!(config.comparison_ranges[0].date_range.end, config.primary_range.start - chrono::Duration::days(1));
+
+        let first_play = dates.today - chrono::Duration::days(100);
+        let (_, config) = comparison_preset("all time", &dates.clone().with_earliest_date(first_play)).unwrap();
+        assert_eq!(config.primary_range.start, first_play);
+
+        assert!(matches!(parse_display_mode("side-by-side"), Some(ComparisonDisplayMode::SideBySide)));
+        assert!(parse_display_mode("sideways").is_none());
+    }
+
+    #[test]
+    fn test_guild_theme_replaces_only_overridden_style() {
+        let mut overrides = GuildOverrides::default();
+        overrides.set("theme.primary_color", "#112233", &[]).unwrap();
+        let config = overrides.apply(&Config::default());
+
+        let mut style = StyleConfig::default();
+        let font = style.title_font.family.clone();
+        apply_guild_theme(&mut style, &overrides, &config);
+        assert!(matches!(&style.color_scheme, ColorScheme::Custom(colors) if colors[0] == "#112233"));
+        assert_eq!(style.title_font.family, font);
+    }
+}
@@ -0,0 +1,189 @@
+//! Per-guild configuration overrides stored in the database
+//!
+//! Overrides are cached in memory and written through to the database.
+//! Every change is broadcast with the guild ID so that the scheduler and
+//! permissions can follow it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use anyhow::{bail, Result};
+use tgraph_config::validation::field_errors;
+use tgraph_config::{Config, ConfigError, GuildOverrides};
+use tgraph_graphs::TimeRangeContext;
+use tgraph_i18n::Locale;
+use tokio::sync::{broadcast, Mutex};
+use tracing::info;
+use crate::UserDatabase;
+
+/// Configuration overrides of every guild, backed by the database
+#[derive(Debug)]
+pub struct GuildSettings {
+    db: Arc<UserDatabase>,
+    overrides: RwLock<HashMap<u64, GuildOverrides>>,
+    /// Held from reading overrides until the edited ones are cached
+    updates: Mutex<()>,
+    changes: broadcast::Sender<u64>,
+}
+
+impl GuildSettings {
+    /// Load the stored overrides
+    pub fn load(db: Arc<UserDatabase>) -> Result<Self> {
+        let overrides: HashMap<u64, GuildOverrides> = db
+            .list_guild_overrides()?
+            .into_iter()
+            .map(|record| (record.guild_id, record.overrides))
+            .collect();
+        info!("Loaded configuration overrides for {} guilds", overrides.len());
+
+        let (changes, _) = broadcast::channel(16);
+        Ok(Self {
+            db,
+            overrides: RwLock::new(overrides),
+            updates: Mutex::new(()),
+            changes,
+        })
+    }
+
+    /// Overrides of a guild, empty when it has none
+    pub fn get(&self, guild_id: u64) -> GuildOverrides {
+        self.read().get(&guild_id).cloned().unwrap_or_default()
+    }
+
+    /// Every guild with overrides
+    pub fn all(&self) -> Vec<(u64, GuildOverrides)> {
+        let mut all: Vec<_> = self.read().iter().map(|(id, overrides)| (*id, overrides.clone())).collect();
+        all.sort_by_key(|(id, _)| *id);
+        all
+    }
+
+    /// Guilds with their own automatic graph schedule, as (guild ID, cron expression, timezone)
+    ///
+    /// The timezone is the guild's own, else `scheduling.timezone` of `global`.
+    /// Guilds with a schedule but no channels of their own are left out.
+    pub fn schedules(&self, global: &Config) -> Vec<(u64, String, Option<String>)> {
+        self.all()
+            .into_iter()
+            .filter(|(_, overrides)| overrides.has_own_schedule())
+            .filter_map(|(id, overrides)| {
+                let timezone = overrides.timezone.or_else(|| global.scheduling.timezone.clone());
+                overrides.auto_graph_cron.map(|cron| (id, cron, timezone))
+            })
+            .collect()
+    }
+
+    /// Change a guild's overrides and store them if the result is valid
+    ///
+    /// The overrides are checked against `global` as a whole, so the guild
+    /// cannot end up with a configuration the bot would refuse to load.
+    /// Updates run one at a time, so concurrent edits never undo each other.
+    pub async fn update<F>(&self, guild_id: u64, global: &Config, updated_by: u64, edit: F) -> Result<GuildOverrides>
+    where
+        F: FnOnce(&mut GuildOverrides) -> Result<(), ConfigError>,
+    {
+        let _update = self.updates.lock().await;
+        let mut overrides = self.get(guild_id);
+        edit(&mut overrides)?;
+
+        if let Some(locale) = overrides.locale.as_deref().filter(|locale| Locale::from_code(locale).is_none()) {
+            let supported: Vec<&str> = Locale::all().iter().map(Locale::code).collect();
+            bail!("locale: '{}' is not supported, expected one of {}", locale, supported.join(", "));
+        }
+        if let Some(timezone) = overrides.timezone.as_deref() {
+            if TimeRangeContext::for_timezone_name(timezone).is_err() {
+                bail!("timezone: '{}' is not a known timezone, e.g. Europe/Oslo", timezone);
+            }
+        }
+        if let Err(errors) = overrides.validate(global) {
+            let errors: Vec<String> = field_errors(&errors).iter().map(ToString::to_string).collect();
+            bail!("{}", errors.join("; "));
+        }
+
+        self.db.store_guild_overrides(guild_id, &overrides, updated_by).await?;
+        {
+            let mut all = self.overrides.write().unwrap_or_else(PoisonError::into_inner);
+            if overrides.is_empty() {
+                all.remove(&guild_id);
+            } else {
+                all.insert(guild_id, overrides.clone());
+            }
+        }
+        info!("User {} changed the configuration overrides of guild {}", updated_by, guild_id);
+        let _ = self.changes.send(guild_id);
+        Ok(overrides)
+    }
+
+    /// Receive the ID of every guild whose overrides change
+    pub fn subscribe(&self) -> broadcast::Receiver<u64> {
+        self.changes.subscribe()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<u64, GuildOverrides>> {
+        self.overrides.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_update_persists_valid_overrides() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = Arc::new(UserDatabase::new(temp_dir.path().join("test_db")).unwrap());
+        let settings = GuildSettings::load(db.clone()).unwrap();
+        let global = Config::default();
+        let mut changes = settings.subscribe();
+
+        settings
+            .update(7, &global, 1, |overrides| {
+                overrides.set("locale", "de-DE", &[])?;
+                overrides.set("channels", "5", &[5])?;
+                overrides.set("auto_graph_cron", "0 0 18 * * *", &[])
+            })
+            .await
+            .unwrap();
+        assert_eq!(changes.recv().await.unwrap(), 7);
+        let schedule = (7, "0 0 18 * * *".to_string(), global.scheduling.timezone.clone());
+        assert_eq!(settings.schedules(&global), vec![schedule]);
+        settings.update(7, &global, 1, |o| o.set("timezone", "Europe/Berlin", &[])).await.unwrap();
+        assert_eq!(settings.schedules(&global)[0].2.as_deref(), Some("Europe/Berlin"));
+
+        assert!(settings.update(7, &global, 1, |o| o.set("locale", "tlh", &[])).await.is_err());
+        assert!(settings.update(7, &global, 1, |o| o.set("timezone", "Mars/Olympus", &[])).await.is_err());
+        assert_eq!(settings.get(7).locale.as_deref(), Some("de-DE"));
+        assert_eq!(GuildSettings::load(db.clone()).unwrap().get(7), settings.get(7));
+
+        settings
+            .update(7, &global, 1, |overrides| {
+                overrides.reset("locale")?;
+                overrides.reset("channels")?;
+                overrides.reset("timezone")?;
+                overrides.reset("auto_graph_cron")
+            })
+            .await
+            .unwrap();
+        assert!(settings.all().is_empty());
+        assert!(db.list_guild_overrides().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_updates_keep_every_change() {
+        let temp_dir = tempdir().expect("Failed to create temp directory");
+        let db = Arc::new(UserDatabase::new(temp_dir.path().join("test_db")).unwrap());
+        let settings = GuildSettings::load(db.clone()).unwrap();
+        let global = Config::default();
+
+        let (locale, theme) = tokio::join!(
+            settings.update(7, &global, 1, |o| o.set("locale", "de-DE", &[])),
+            settings.update(7, &global, 2, |o| o.set("theme.primary_color", "#112233", &[])),
+        );
+        locale.unwrap();
+        theme.unwrap();
+
+        for overrides in [settings.get(7), GuildSettings::load(db).unwrap().get(7)] {
+            assert_eq!(overrides.locale.as_deref(), Some("de-DE"));
+            assert_eq!(overrides.theme.primary_color.as_deref(), Some("#112233"));
+        }
+    }
+}
@@ -12,13 +12,17 @@ pub mod metrics;
 pub mod database;
 pub mod statistics;
 pub mod dm_throttle;
+pub mod linking;
+pub mod guild_settings;
+pub mod config;
 
 pub use registry::CommandRegistry;
 pub use permissions::{Permission, Permissions};
 pub use cooldown::{CooldownManager, CooldownError};
 pub use context::{CommandContext, create_command_context};
 pub use metrics::{MetricsManager, CommandMetrics, CommandExecution, MetricsReport};
-pub use database::{UserDatabase, UserPreferences};
+pub use database::{GuildOverridesRecord, LinkStatus, TautulliLink, UserDatabase, UserPreferences};
+pub use guild_settings::GuildSettings;
 pub use statistics::{UserStatisticsManager, UserActivity, TimePeriod};
 pub use dm_throttle::DmThrottleManager;
 pub use audit::{AuditLogger, AuditEventType, AuditLogEntry}; 
\ No newline at end of file
@@ -0,0 +1,228 @@
+//! Discord to Tautulli account linking
+//!
+//! Resolves Plex usernames or emails to Tautulli users and handles bulk
+//! imports of account links. Links themselves are stored with the user's
+//! preferences in [`UserDatabase`](crate::database::UserDatabase).
+
+use crate::database::{TautulliLink, UserDatabase};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tgraph_common::{TautulliServers, User};
+use tracing::{info, warn};
+
+/// Find the Tautulli user matching a Plex username or email
+///
+/// Username and email matches are preferred over friendly names, and all
+/// comparisons ignore case.
+pub fn resolve_tautulli_user<'a>(identifier: &str, users: &'a [User]) -> Option<&'a User> {
+    let identifier = identifier.trim();
+    if identifier.is_empty() {
+        return None;
+    }
+
+    let matches = |value: Option<&str>| value.is_some_and(|value| value.eq_ignore_ascii_case(identifier));
+
+    users
+        .iter()
+        .find(|user| matches(Some(&user.username)) || matches(user.email.as_deref()))
+        .or_else(|| users.iter().find(|user| matches(user.friendly_name.as_deref())))
+}
+
+/// Resolve an identifier on a named server into a pending link
+pub async fn resolve_link(
+    servers: &TautulliServers,
+    server: Option<&str>,
+    identifier: &str,
+) -> Result<Option<TautulliLink>> {
+    // Links keep the configured name, whatever case it was typed in
+    let (server, client) = match server {
+        Some(name) => servers
+            .find(name)
+            .with_context(|| format!("Unknown Tautulli server: {}", name))?,
+        None => servers.primary().context("No Tautulli servers are configured")?,
+    };
+
+    let users = client.get_users().await?;
+    Ok(resolve_tautulli_user(identifier, &users).map(|user| {
+        TautulliLink::pending(server, user.user_id, user.username.clone(), identifier.trim())
+    }))
+}
+
+/// One row of a bulk link import
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkImportEntry {
+    /// Discord user ID
+    pub discord_user_id: u64,
+    /// Plex username or email
+    pub identifier: String,
+    /// Tautulli server name, the primary server if omitted
+    #[serde(default)]
+    pub server: Option<String>,
+}
+
+/// Outcome of a bulk link import
+#[derive(Debug, Clone, Default)]
+pub struct LinkImportReport {
+    /// Number of links that were set
+    pub linked: usize,
+    /// Rows that could not be imported, with the reason
+    pub failures: Vec<String>,
+}
+
+/// Parse a bulk link import file
+///
+/// Accepts either a JSON array of [`LinkImportEntry`] objects or CSV lines of
+/// `discord_user_id,identifier[,server]`. Blank lines, `#` comments and a
+/// header row starting with `discord` are skipped.
+pub fn parse_link_import(content: &str) -> Result<Vec<LinkImportEntry>> {
+    if content.trim_start().starts_with('[') {
+        return serde_json::from_str(content).context("Invalid JSON link import");
+    }
+
+    let mut entries = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.to_lowercase().starts_with("discord") {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if !(2..=3).contains(&fields.len()) || fields[1].is_empty() {
+            anyhow::bail!("Line {}: expected discord_user_id,identifier[,server]", index + 1);
+        }
+
+        let discord_user_id = fields[0]
+            .parse()
+            .with_context(|| format!("Line {}: invalid Discord user ID '{}'", index + 1, fields[0]))?;
+
+        entries.push(LinkImportEntry {
+            discord_user_id,
+            identifier: fields[1].to_string(),
+            server: fields.get(2).filter(|s| !s.is_empty()).map(|s| s.to_string()),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Import links as approved by the given admin
+///
+/// Each server's user list is fetched once. Rows that cannot be resolved or
+/// conflict with an existing link are reported rather than aborting the import.
+pub async fn import_links(
+    db: &UserDatabase,
+    servers: &TautulliServers,
+    entries: Vec<LinkImportEntry>,
+    admin_id: u64,
+) -> Result<LinkImportReport> {
+    let primary = servers
+        .primary()
+        .map(|(name, _)| name.to_string())
+        .context("No Tautulli servers are configured")?;
+
+    let mut users_by_server: HashMap<String, Vec<User>> = HashMap::new();
+    let mut report = LinkImportReport::default();
+
+    for entry in entries {
+        let requested = entry.server.clone().unwrap_or_else(|| primary.clone());
+        let Some((server, client)) = servers.find(&requested) else {
+            report.failures.push(format!("{}: unknown server '{}'", entry.discord_user_id, requested));
+            continue;
+        };
+
+        if !users_by_server.contains_key(&server) {
+            users_by_server.insert(server.clone(), client.get_users().await?);
+        }
+
+        let Some(user) = resolve_tautulli_user(&entry.identifier, &users_by_server[&server]) else {
+            report.failures.push(format!(
+                "{}: no Tautulli user matches '{}'",
+                entry.discord_user_id, entry.identifier
+            ));
+            continue;
+        };
+
+        let link = TautulliLink::pending(&server, user.user_id, user.username.clone(), &entry.identifier);
+        match db.set_link(entry.discord_user_id, link, admin_id).await {
+            Ok(_) => report.linked += 1,
+            Err(e) => {
+                warn!("Skipping link import for {}: {}", entry.discord_user_id, e);
+                report.failures.push(format!("{}: {}", entry.discord_user_id, e));
+            }
+        }
+    }
+
+    info!(
+        "Link import by admin {}: {} linked, {} failed",
+        admin_id,
+        report.linked,
+        report.failures.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(user_id: i32, username: &str, email: Option<&str>, friendly_name: Option<&str>) -> User {
+        User {
+            user_id,
+            username: username.to_string(),
+            friendly_name: friendly_name.map(str::to_string),
+            email: email.map(str::to_string),
+            thumb: None,
+            plays: None,
+            duration: None,
+            last_seen: None,
+            is_active: None,
+            is_admin: None,
+            is_home_user: None,
+            is_allow_sync: None,
+            is_restricted: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_tautulli_user() {
+        let users = vec![
+            user(1, "alice", Some("alice@example.com"), Some("Bob")),
+            user(2, "bob", None, Some("Bobby")),
+        ];
+
+        assert_eq!(resolve_tautulli_user("ALICE@example.com", &users).unwrap().user_id, 1);
+        // Usernames win over another user's friendly name
+        assert_eq!(resolve_tautulli_user("bob", &users).unwrap().user_id, 2);
+        assert_eq!(resolve_tautulli_user(" bobby ", &users).unwrap().user_id, 2);
+        assert!(resolve_tautulli_user("carol", &users).is_none());
+        assert!(resolve_tautulli_user("", &users).is_none());
+    }
+
+    #[test]
+    fn test_parse_csv_import() {
+        let content = "discord_user_id,identifier,server\n\
+            # comment\n\
+            123,alice@example.com\n\
+            \n\
+            456, bob , Cabin\n";
+
+        let entries = parse_link_import(content).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].discord_user_id, 123);
+        assert_eq!(entries[0].server, None);
+        assert_eq!(entries[1].identifier, "bob");
+        assert_eq!(entries[1].server.as_deref(), Some("Cabin"));
+
+        assert!(parse_link_import("abc,alice").is_err());
+        assert!(parse_link_import("123").is_err());
+    }
+
+    #[test]
+    fn test_parse_json_import() {
+        let content = r#"[{"discord_user_id": 123, "identifier": "alice"}]"#;
+        let entries = parse_link_import(content).unwrap();
+        assert_eq!(entries[0].identifier, "alice");
+        assert_eq!(entries[0].server, None);
+    }
+}
@@ -1,9 +1,13 @@
 //! Permission system for Discord bot commands
 
+use crate::context::{CommandError, Context};
 use poise::serenity_prelude::{self as serenity, UserId, RoleId, GuildId};
-use std::collections::HashSet;
-use tgraph_config::Config;
-use tracing::{debug, warn};
+use poise::CreateReply;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tgraph_config::{Config, ConfigSection, ConfigSubscriber, GuildOverrides};
+use tracing::{debug, info, warn};
 
 /// Permission levels for bot commands
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -31,8 +35,25 @@ impl Permission {
 }
 
 /// Permission manager for checking user permissions
+///
+/// The configured IDs can be replaced while the bot runs, see [`Permissions::update`].
+/// Guilds may replace the admin and moderator roles, see [`Permissions::set_guild_roles`].
 #[derive(Debug)]
 pub struct Permissions {
+    sets: RwLock<PermissionSets>,
+    guild_roles: RwLock<HashMap<GuildId, GuildRoles>>,
+}
+
+/// Roles a guild uses instead of the configured ones; `None` keeps the global roles
+#[derive(Debug, Default)]
+struct GuildRoles {
+    admin_roles: Option<HashSet<RoleId>>,
+    moderator_roles: Option<HashSet<RoleId>>,
+}
+
+/// The configured user and role IDs
+#[derive(Debug, Default)]
+struct PermissionSets {
     /// Bot owner user IDs
     owners: HashSet<UserId>,
     /// Administrator user IDs
@@ -43,9 +64,8 @@ pub struct Permissions {
     moderator_roles: HashSet<RoleId>,
 }
 
-impl Permissions {
-    /// Create a new permissions manager from configuration
-    pub fn new(config: &Config) -> Self {
+impl PermissionSets {
+    fn from_config(config: &Config) -> Self {
         let owners = config.discord.owner_ids.iter()
             .map(|&id| UserId::new(id))
             .collect();
@@ -69,6 +89,57 @@ impl Permissions {
             moderator_roles,
         }
     }
+}
+
+impl Permissions {
+    /// Create a new permissions manager from configuration
+    pub fn new(config: &Config) -> Self {
+        Self {
+            sets: RwLock::new(PermissionSets::from_config(config)),
+            guild_roles: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Use the admin and moderator roles overridden by a guild
+    pub fn set_guild_roles(&self, guild_id: GuildId, overrides: &GuildOverrides) {
+        let roles = |ids: &Option<Vec<u64>>| {
+            ids.as_ref().map(|ids| ids.iter().map(|&id| RoleId::new(id)).collect())
+        };
+        let guild_roles = GuildRoles {
+            admin_roles: roles(&overrides.admin_role_ids),
+            moderator_roles: roles(&overrides.moderator_role_ids),
+        };
+
+        let mut all = self.guild_roles.write().unwrap_or_else(PoisonError::into_inner);
+        if guild_roles.admin_roles.is_none() && guild_roles.moderator_roles.is_none() {
+            all.remove(&guild_id);
+        } else {
+            all.insert(guild_id, guild_roles);
+        }
+    }
+
+    /// Permission level granted by a member's roles in a guild
+    pub fn role_permission(&self, guild_id: GuildId, roles: &[RoleId]) -> Permission {
+        let sets = self.read();
+        let guild_roles = self.guild_roles.read().unwrap_or_else(PoisonError::into_inner);
+        let guild_roles = guild_roles.get(&guild_id);
+        let admin_roles = guild_roles.and_then(|g| g.admin_roles.as_ref()).unwrap_or(&sets.admin_roles);
+        let moderator_roles = guild_roles.and_then(|g| g.moderator_roles.as_ref()).unwrap_or(&sets.moderator_roles);
+
+        if roles.iter().any(|role_id| admin_roles.contains(role_id)) {
+            Permission::Administrator
+        } else if roles.iter().any(|role_id| moderator_roles.contains(role_id)) {
+            Permission::Moderator
+        } else {
+            Permission::User
+        }
+    }
+
+    /// Replace the owners, administrators and roles with those in `config`
+    pub fn update(&self, config: &Config) {
+        *self.write() = PermissionSets::from_config(config);
+        info!("Permissions updated from configuration");
+    }
 
     /// Check if a user has the required permission level
     pub async fn check_permission(
@@ -81,7 +152,7 @@ impl Permissions {
         debug!("Checking permission for user {} (required: {})", user_id, required.as_str());
 
         // Owner check - highest priority
-        if self.owners.contains(&user_id) {
+        if self.is_owner(user_id) {
             debug!("User {} is bot owner", user_id);
             return true;
         }
@@ -93,7 +164,7 @@ impl Permissions {
         }
 
         // Administrator check
-        if self.administrators.contains(&user_id) {
+        if self.is_administrator(user_id) {
             debug!("User {} is configured as administrator", user_id);
             return required <= Permission::Administrator;
         }
@@ -101,16 +172,10 @@ impl Permissions {
         // Guild-based role checks
         if let Some(guild_id) = guild_id {
             if let Ok(member) = ctx.http.get_member(guild_id, user_id).await {
-                // Check admin roles
-                for role_id in &member.roles {
-                    if self.admin_roles.contains(role_id) {
-                        debug!("User {} has admin role {}", user_id, role_id);
-                        return required <= Permission::Administrator;
-                    }
-                    if self.moderator_roles.contains(role_id) {
-                        debug!("User {} has moderator role {}", user_id, role_id);
-                        return required <= Permission::Moderator;
-                    }
+                let level = self.role_permission(guild_id, &member.roles);
+                if level > Permission::User {
+                    debug!("User {} has {} role in guild {}", user_id, level.as_str(), guild_id);
+                    return required <= level;
                 }
             } else {
                 warn!("Could not fetch member info for user {} in guild {}", user_id, guild_id);
@@ -123,31 +188,122 @@ impl Permissions {
 
     /// Check if a user is a bot owner
     pub fn is_owner(&self, user_id: UserId) -> bool {
-        self.owners.contains(&user_id)
+        self.read().owners.contains(&user_id)
     }
 
     /// Check if a user is an administrator
     pub fn is_administrator(&self, user_id: UserId) -> bool {
-        self.administrators.contains(&user_id)
+        self.read().administrators.contains(&user_id)
     }
 
     /// Add a new owner (runtime modification)
-    pub fn add_owner(&mut self, user_id: UserId) {
-        self.owners.insert(user_id);
+    pub fn add_owner(&self, user_id: UserId) {
+        self.write().owners.insert(user_id);
     }
 
     /// Remove an owner (runtime modification)
-    pub fn remove_owner(&mut self, user_id: UserId) {
-        self.owners.remove(&user_id);
+    pub fn remove_owner(&self, user_id: UserId) {
+        self.write().owners.remove(&user_id);
     }
 
     /// Add a new administrator (runtime modification)
-    pub fn add_administrator(&mut self, user_id: UserId) {
-        self.administrators.insert(user_id);
+    pub fn add_administrator(&self, user_id: UserId) {
+        self.write().administrators.insert(user_id);
     }
 
     /// Remove an administrator (runtime modification)
-    pub fn remove_administrator(&mut self, user_id: UserId) {
-        self.administrators.remove(&user_id);
+    pub fn remove_administrator(&self, user_id: UserId) {
+        self.write().administrators.remove(&user_id);
     }
-} 
\ No newline at end of file
+
+    fn read(&self) -> RwLockReadGuard<'_, PermissionSets> {
+        self.sets.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, PermissionSets> {
+        self.sets.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Command check letting only bot administrators through
+///
+/// `MANAGE_GUILD` alone only hides a command in Discord; this check applies
+/// the configured owners, administrators and the guild's admin roles.
+pub async fn require_administrator(ctx: Context<'_>) -> Result<bool, CommandError> {
+    let allowed = ctx
+        .data()
+        .permissions
+        .check_permission(ctx.serenity_context(), ctx.author().id, ctx.guild_id(), Permission::Administrator)
+        .await;
+    if !allowed {
+        let message = ctx.data().translate(
+            ctx.guild_id(),
+            ctx.locale(),
+            "error-permission-denied",
+            None,
+            "You don't have permission to use this command.",
+        );
+        ctx.send(CreateReply::default().content(format!("❌ {}", message)).ephemeral(true)).await?;
+    }
+    Ok(allowed)
+}
+
+#[async_trait]
+impl ConfigSubscriber for Permissions {
+    fn name(&self) -> &str {
+        "permissions"
+    }
+
+    fn sections(&self) -> &[ConfigSection] {
+        &[ConfigSection::Discord]
+    }
+
+    async fn apply(&self, config: &Config) -> Result<(), String> {
+        self.update(config);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_replaces_ids() {
+        let mut config = Config::default();
+        config.discord.owner_ids = vec![1];
+        config.discord.admin_ids = vec![2];
+        let permissions = Permissions::new(&config);
+        assert!(permissions.is_owner(UserId::new(1)));
+        assert!(permissions.is_administrator(UserId::new(2)));
+
+        config.discord.owner_ids = vec![3];
+        config.discord.admin_ids = Vec::new();
+        permissions.update(&config);
+        assert!(!permissions.is_owner(UserId::new(1)));
+        assert!(permissions.is_owner(UserId::new(3)));
+        assert!(!permissions.is_administrator(UserId::new(2)));
+    }
+
+    #[test]
+    fn test_guild_roles_replace_global_roles() {
+        let mut config = Config::default();
+        config.discord.admin_role_ids = vec![10];
+        config.discord.moderator_role_ids = vec![20];
+        let permissions = Permissions::new(&config);
+        let (guild, other) = (GuildId::new(1), GuildId::new(2));
+
+        let overrides = GuildOverrides {
+            admin_role_ids: Some(vec![30]),
+            ..Default::default()
+        };
+        permissions.set_guild_roles(guild, &overrides);
+        assert_eq!(permissions.role_permission(guild, &[RoleId::new(10)]), Permission::User);
+        assert_eq!(permissions.role_permission(guild, &[RoleId::new(30)]), Permission::Administrator);
+        assert_eq!(permissions.role_permission(guild, &[RoleId::new(20)]), Permission::Moderator);
+        assert_eq!(permissions.role_permission(other, &[RoleId::new(10)]), Permission::Administrator);
+
+        permissions.set_guild_roles(guild, &GuildOverrides::default());
+        assert_eq!(permissions.role_permission(guild, &[RoleId::new(10)]), Permission::Administrator);
+    }
+}
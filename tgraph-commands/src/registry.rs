@@ -24,6 +24,13 @@ impl CommandRegistry {
         self.commands.push(crate::user::about());
         self.commands.push(crate::user::uptime());
         self.commands.push(crate::user::my_stats());
+
+        // Register graph commands
+        self.commands.push(crate::graph::compare());
+
+        // Register account linking commands
+        self.commands.push(crate::user::link());
+        self.commands.push(crate::user::unlink());
         
         // Register GDPR compliance commands
         self.commands.push(crate::user::export_my_data());
@@ -33,6 +40,8 @@ impl CommandRegistry {
         self.commands.push(crate::admin::update_graphs());
         self.commands.push(crate::admin::metrics());
         self.commands.push(crate::admin::scheduler_status());
+        self.commands.push(crate::admin::link_admin());
+        self.commands.push(crate::config::config());
 
         Ok(())
     }
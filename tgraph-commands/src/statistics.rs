@@ -10,6 +10,7 @@ use anyhow::Result;
 use chrono::{DateTime, Utc, Datelike, Timelike};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
+use tgraph_graphs::{PrivacyPolicy, UsernameDisplay};
 use tracing::{debug, info};
 
 use crate::database::{UserDatabase, UserPreferences};
@@ -115,20 +116,22 @@ impl UserActivity {
         }
     }
 
-    /// Get the most active hour (0-23)
+    /// Get the most active hour (0-23), `None` without activity
     pub fn most_active_hour(&self) -> Option<usize> {
         self.hourly_activity
             .iter()
             .enumerate()
+            .filter(|(_, &count)| count > 0)
             .max_by_key(|(_, &count)| count)
             .map(|(hour, _)| hour)
     }
 
-    /// Get the most active day (0=Sunday, 6=Saturday)
+    /// Get the most active day (0=Sunday, 6=Saturday), `None` without activity
     pub fn most_active_day(&self) -> Option<usize> {
         self.daily_activity
             .iter()
             .enumerate()
+            .filter(|(_, &count)| count > 0)
             .max_by_key(|(_, &count)| count)
             .map(|(day, _)| day)
     }
@@ -425,14 +428,31 @@ impl UserStatisticsManager {
         })
     }
 
-    /// Apply privacy filters to user activity based on preferences
-    pub fn apply_privacy_filters(&self, activity: &mut UserActivity, _preferences: &UserPreferences) {
-        // If username should not be visible, we don't modify the activity here
-        // as it doesn't contain username information. This is handled at display time.
-        
-        // If public stats are disabled, return None (handled in get_user_statistics)
-        
-        // Additional privacy filters can be added here as needed
+    /// Apply privacy filters to user activity before it is shown in a channel
+    ///
+    /// Unless the user's name would be shown as-is, the details that reveal
+    /// when and where they are active are removed and only totals remain.
+    pub fn apply_privacy_filters(
+        &self,
+        activity: &mut UserActivity,
+        preferences: &UserPreferences,
+        policy: &PrivacyPolicy,
+    ) {
+        let username = preferences
+            .tautulli_link
+            .as_ref()
+            .map(|link| link.tautulli_username.as_str())
+            .unwrap_or_default();
+        if preferences.username_visible && policy.display_for(username) == UsernameDisplay::Visible {
+            return;
+        }
+
+        activity.hourly_activity = [0; 24];
+        activity.daily_activity = [0; 7];
+        activity.first_command = None;
+        activity.last_command = None;
+        activity.unique_channels = 0;
+        activity.unique_guilds = 0;
         debug!("Applied privacy filters for user {}", activity.user_id);
     }
 
@@ -535,6 +555,33 @@ mod tests {
         assert_eq!(stats.unique_guilds, 1);
     }
 
+    #[tokio::test]
+    async fn test_privacy_filters() {
+        let (manager, _temp_dir) = create_test_manager().await;
+        let executions = create_test_executions();
+        let mut preferences = crate::database::UserPreferences::new(123);
+        preferences.allow_public_stats = true;
+        manager.user_db.store_preferences(preferences.clone()).await.unwrap();
+        let stats = manager.get_user_statistics(123, TimePeriod::Daily, &executions).await.unwrap().unwrap();
+
+        let mut visible = stats.clone();
+        manager.apply_privacy_filters(&mut visible, &preferences, &PrivacyPolicy::new(UsernameDisplay::Visible));
+        assert_eq!(visible.unique_channels, 2);
+        assert!(visible.most_active_hour().is_some());
+
+        let mut masked = stats.clone();
+        manager.apply_privacy_filters(&mut masked, &preferences, &PrivacyPolicy::new(UsernameDisplay::Masked));
+        assert_eq!(masked.total_commands, 3);
+        assert_eq!(masked.unique_channels, 0);
+        assert_eq!(masked.most_active_hour(), None);
+        assert_eq!(masked.last_command, None);
+
+        preferences.username_visible = false;
+        let mut opted_out = stats;
+        manager.apply_privacy_filters(&mut opted_out, &preferences, &PrivacyPolicy::new(UsernameDisplay::Visible));
+        assert_eq!(opted_out.most_active_day(), None);
+    }
+
     #[tokio::test]
     async fn test_cache_functionality() {
         let (manager, _temp_dir) = create_test_manager().await;
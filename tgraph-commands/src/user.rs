@@ -5,8 +5,15 @@ use crate::cooldown::CooldownConfig;
 use crate::statistics::TimePeriod;
 use std::time::{Duration, Instant};
 use tracing::{info, warn, error, debug};
-use poise::serenity_prelude::{UserId, CreateMessage, CreateEmbed, Colour};
+use crate::database::UserPreferences;
+use poise::serenity_prelude::{UserId, CreateMessage, CreateEmbed, CreateAttachment, Colour, PrivateChannel};
+use poise::CreateReply;
 use chrono::Utc;
+use tgraph_common::CancellationContext;
+use tgraph_graphs::{history_since, render_personal_graphs, PersonalViewingStats, RenderedGraph};
+
+/// Time allowed for fetching a user's viewing history
+const VIEWING_HISTORY_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// About command - shows bot information
 #[poise::command(slash_command)]
@@ -155,6 +162,16 @@ async fn send_direct_message(
         CreateMessage::new().content(content)
     };
 
+    deliver_direct_message(ctx, user_id, &dm_channel, message).await
+}
+
+/// Send a prepared message to a DM channel with retry logic
+async fn deliver_direct_message(
+    ctx: &Context<'_>,
+    user_id: UserId,
+    dm_channel: &PrivateChannel,
+    message: CreateMessage,
+) -> Result<bool, CommandError> {
     // Send the message with retry logic
     let mut retry_count = 0;
     const MAX_RETRIES: u32 = 3;
@@ -183,6 +200,166 @@ async fn send_direct_message(
     Ok(false)
 }
 
+/// Personal Plex viewing statistics ready for delivery
+struct ViewingReport {
+    content: String,
+    graphs: Vec<RenderedGraph>,
+}
+
+/// Build the viewing report for the user's linked Tautulli account
+///
+/// Users without an approved link get a hint instead, and Tautulli failures
+/// are reported in the text so command statistics are still delivered.
+async fn build_viewing_report(
+    ctx: &Context<'_>,
+    preferences: &UserPreferences,
+    time_period: TimePeriod,
+    use_dm: bool,
+) -> ViewingReport {
+    let text = |content: &str| ViewingReport {
+        content: content.to_string(),
+        graphs: Vec::new(),
+    };
+
+    let link = match (preferences.approved_link(), &preferences.tautulli_link) {
+        (Some(link), _) => link,
+        (None, Some(_)) => return text("⏳ Your Plex account link is waiting for administrator approval."),
+        (None, None) => return text("💡 Link your Plex account with `/link` to see your viewing history here."),
+    };
+
+    let client = match ctx.data().tautulli.get(&link.server) {
+        Some(client) => client,
+        None => {
+            warn!("Linked Tautulli server '{}' is no longer configured", link.server);
+            return text("⚠️ The Tautulli server your account is linked to is no longer configured. Please contact an administrator.");
+        }
+    };
+
+    // Tautulli filters by its own local date, so ask for a day either side
+    // and trim to the exact period afterwards
+    let since = time_period.duration().map(|duration| Utc::now() - duration);
+    let cancellation = CancellationContext::with_timeout(VIEWING_HISTORY_TIMEOUT);
+    let fetched = match since {
+        Some(since) => {
+            let after = since.date_naive() - chrono::Duration::days(1);
+            let before = Utc::now().date_naive() + chrono::Duration::days(1);
+            client.fetch_user_history_between(&cancellation, link.tautulli_user_id, after, before, 1000).await
+        }
+        None => client.fetch_all_history(&cancellation, Some(link.tautulli_user_id), 1000).await,
+    };
+    let history = match fetched {
+        Ok(history) => history,
+        Err(e) => {
+            warn!("Failed to fetch viewing history for user {}: {}", preferences.user_id, e);
+            return text(if e.is_service_unavailable() {
+                "📡 Tautulli is currently unavailable, so your viewing history could not be loaded."
+            } else {
+                "⚠️ Your viewing history could not be loaded right now. Please try again later."
+            });
+        }
+    };
+
+    let history = history_since(history, since);
+    let stats = PersonalViewingStats::from_history(&history, 5);
+
+    // A DM only reaches the user, so their own name is shown there unchanged
+    let display_name = if use_dm {
+        link.tautulli_username.clone()
+    } else {
+        ctx.data().privacy_policy().display_name(&link.tautulli_username, None)
+    };
+
+    let graphs = match render_personal_graphs(history, &display_name).await {
+        Ok(graphs) => graphs,
+        Err(e) => {
+            warn!("Failed to render personal graphs for user {}: {}", preferences.user_id, e);
+            Vec::new()
+        }
+    };
+
+    ViewingReport {
+        content: format_viewing_stats(&stats, time_period, &display_name),
+        graphs,
+    }
+}
+
+/// Deliver a viewing report via DM or in the channel
+async fn deliver_viewing_report(
+    ctx: &Context<'_>,
+    report: ViewingReport,
+    use_dm: bool,
+) -> Result<bool, CommandError> {
+    let attachments: Vec<CreateAttachment> = report
+        .graphs
+        .into_iter()
+        .map(|graph| CreateAttachment::bytes(graph.data, graph.file_name))
+        .collect();
+
+    if use_dm {
+        let dm_channel = match ctx.author().id.create_dm_channel(&ctx.serenity_context().http).await {
+            Ok(channel) => channel,
+            Err(e) => {
+                warn!("Failed to create DM channel with user {}: {}", ctx.author().id, e);
+                return Ok(false);
+            }
+        };
+        let message = CreateMessage::new().content(report.content).add_files(attachments);
+        deliver_direct_message(ctx, ctx.author().id, &dm_channel, message).await
+    } else {
+        let reply = attachments
+            .into_iter()
+            .fold(CreateReply::default().content(report.content), |reply, attachment| {
+                reply.attachment(attachment)
+            });
+        ctx.send(reply).await?;
+        Ok(true)
+    }
+}
+
+/// Format personal Plex viewing statistics
+fn format_viewing_stats(stats: &PersonalViewingStats, time_period: TimePeriod, username: &str) -> String {
+    if stats.total_plays == 0 {
+        return format!(
+            "🎬 **Plex Viewing ({})** - {}\n🔍 No plays found for this time period.",
+            time_period.name(),
+            username
+        );
+    }
+
+    let (hours, minutes) = stats.watch_time_hm();
+    let top_shows = if stats.top_shows.is_empty() {
+        "None".to_string()
+    } else {
+        stats
+            .top_shows
+            .iter()
+            .enumerate()
+            .map(|(i, show)| format!("{}. {} ({} plays)", i + 1, show.title, show.plays))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    format!(
+        "🎬 **Plex Viewing ({})** - {}\n\
+        ▶️ Total Plays: {}\n\
+        ⏱️ Watch Time: {}h {}m\n\
+        📱 Favorite Platform: {}\n\
+        🕐 Most Active Hour: {}\n\n\
+        📺 **Top Shows:**\n{}",
+        time_period.name(),
+        username,
+        stats.total_plays,
+        hours,
+        minutes,
+        stats.favorite_platform.as_deref().unwrap_or("Unknown"),
+        stats
+            .most_active_hour
+            .map(|hour| format!("{:02}:00 UTC", hour))
+            .unwrap_or_else(|| "N/A".to_string()),
+        top_shows
+    )
+}
+
 /// Format user statistics for DM delivery with enhanced privacy formatting
 fn format_user_statistics_for_dm(stats: &crate::statistics::UserActivity) -> String {
     let period_name = stats.period.name();
@@ -293,7 +470,7 @@ fn format_user_statistics_for_dm(stats: &crate::statistics::UserActivity) -> Str
     )
 }
 
-/// My stats command - shows personal user statistics
+/// My stats command - shows personal command usage and, for linked accounts, Plex viewing statistics
 #[poise::command(slash_command)]
 pub async fn my_stats(
     ctx: Context<'_>,
@@ -343,7 +520,10 @@ pub async fn my_stats(
                 
                 // Proceed with channel delivery as fallback
                 let user_executions = ctx.data().metrics.get_user_executions(user_id);
-                handle_stats_display(&ctx, user_id, time_period, &user_executions, false).await?;
+                // Without their preferences, keep the user private
+                let mut fallback = UserPreferences::new(user_id);
+                fallback.username_visible = false;
+                handle_stats_display(&ctx, &fallback, time_period, &user_executions, false).await?;
                 return Ok(());
             }
         };
@@ -376,12 +556,17 @@ pub async fn my_stats(
                     *If you don't receive a DM, please check your privacy settings or contact an administrator.*").await?;
 
             // Try to send DM
-            let dm_sent = handle_stats_display(&ctx, user_id, time_period, &user_executions, true).await?;
+            let dm_sent = handle_stats_display(&ctx, &user_preferences, time_period, &user_executions, true).await?;
             
             if dm_sent {
                 // Record DM sent for throttling
                 ctx.data().dm_throttle.record_dm_sent(user_id).await;
                 debug!("Successfully sent statistics DM to user {}", user_id);
+
+                let viewing_report = build_viewing_report(&ctx, &user_preferences, time_period, true).await;
+                if !deliver_viewing_report(&ctx, viewing_report, true).await? {
+                    warn!("Failed to send viewing statistics DM to user {}", user_id);
+                }
             } else {
                 // DM failed, show in channel as fallback
                 ctx.say("❌ **Failed to send DM**\n\
@@ -389,11 +574,15 @@ pub async fn my_stats(
                         \n\
                         📊 **Showing your statistics here instead:**").await?;
                 
-                handle_stats_display(&ctx, user_id, time_period, &user_executions, false).await?;
+                handle_stats_display(&ctx, &user_preferences, time_period, &user_executions, false).await?;
+                let viewing_report = build_viewing_report(&ctx, &user_preferences, time_period, false).await;
+                deliver_viewing_report(&ctx, viewing_report, false).await?;
             }
         } else {
             // User prefers channel delivery, show statistics in channel
-            handle_stats_display(&ctx, user_id, time_period, &user_executions, false).await?;
+            handle_stats_display(&ctx, &user_preferences, time_period, &user_executions, false).await?;
+            let viewing_report = build_viewing_report(&ctx, &user_preferences, time_period, false).await;
+            deliver_viewing_report(&ctx, viewing_report, false).await?;
         }
 
         // Apply cooldown after successful execution
@@ -425,14 +614,15 @@ pub async fn my_stats(
 /// Handle statistics display either in channel or via DM
 async fn handle_stats_display(
     ctx: &Context<'_>, 
-    user_id: u64, 
+    preferences: &UserPreferences,
     time_period: TimePeriod, 
     user_executions: &[crate::metrics::CommandExecution],
     use_dm: bool,
 ) -> Result<bool, CommandError> {
+    let user_id = preferences.user_id;
     // Get user statistics for the specified period
     match ctx.data().user_stats.get_user_statistics(user_id, time_period, user_executions).await {
-        Ok(Some(stats)) => {
+        Ok(Some(mut stats)) => {
             if use_dm {
                 // Format for DM with enhanced privacy formatting
                 let dm_response = format_user_statistics_for_dm(&stats);
@@ -445,6 +635,7 @@ async fn handle_stats_display(
                 }
             } else {
                 // Format for channel response
+                ctx.data().user_stats.apply_privacy_filters(&mut stats, preferences, &ctx.data().privacy_policy());
                 let response = format_user_statistics(&stats);
                 ctx.say(response).await?;
                 Ok(true) // Channel message sent successfully
@@ -951,6 +1142,125 @@ pub async fn delete_my_data(
     result
 }
 
+/// Link command - requests a link between the Discord account and a Plex/Tautulli account
+#[poise::command(slash_command)]
+pub async fn link(
+    ctx: Context<'_>,
+    #[description = "Your Plex username or email"]
+    identifier: String,
+    #[description = "Tautulli server name (defaults to the primary server)"]
+    server: Option<String>,
+) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        let user_id = ctx.author().id.get();
+
+        // Each request hits the Tautulli user list, so keep the cooldown generous
+        let cooldown_config = CooldownConfig {
+            user: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+
+        if let Err(cooldown_err) = ctx.data().cooldown.check_cooldown(
+            "link",
+            ctx.author().id,
+            Some(ctx.channel_id()),
+            &cooldown_config,
+        ) {
+            ctx.say(format!("⏰ {}", cooldown_err)).await?;
+            return Ok(());
+        }
+
+        let link = match crate::linking::resolve_link(&ctx.data().tautulli, server.as_deref(), &identifier).await? {
+            Some(link) => link,
+            None => {
+                ctx.say("❌ No Plex account matches that username or email. Check the spelling and try again.").await?;
+                return Ok(());
+            }
+        };
+
+        match ctx.data().user_db.request_link(user_id, link).await {
+            Ok(link) => {
+                ctx.say(format!(
+                    "🔗 **Link Requested**\n\
+                    Your Discord account will be linked to **{}** on **{}** once an administrator approves it.\n\
+                    Use `/unlink` to cancel the request.",
+                    link.tautulli_username, link.server
+                )).await?;
+            }
+            Err(e) => {
+                warn!("Link request by user {} rejected: {}", user_id, e);
+                ctx.say("❌ That Plex account is already linked to another Discord account. Please contact an administrator.").await?;
+                return Ok(());
+            }
+        }
+
+        ctx.data().cooldown.apply_cooldown(
+            "link",
+            ctx.author().id,
+            Some(ctx.channel_id()),
+            &cooldown_config,
+        );
+
+        ctx.data().audit_logger.log_preferences_modification(user_id, Some(user_id), "tautulli_link_requested").await;
+        info!("Link command executed by user {}", user_id);
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "link", start_time, &result);
+
+    result
+}
+
+/// Unlink command - removes the link to a Plex/Tautulli account
+#[poise::command(slash_command)]
+pub async fn unlink(ctx: Context<'_>) -> Result<(), CommandError> {
+    let start_time = Instant::now();
+
+    let result = async {
+        let user_id = ctx.author().id.get();
+
+        let cooldown_config = CooldownConfig {
+            user: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+
+        if let Err(cooldown_err) = ctx.data().cooldown.check_cooldown(
+            "unlink",
+            ctx.author().id,
+            Some(ctx.channel_id()),
+            &cooldown_config,
+        ) {
+            ctx.say(format!("⏰ {}", cooldown_err)).await?;
+            return Ok(());
+        }
+
+        if ctx.data().user_db.remove_link(user_id).await? {
+            ctx.say("✅ Your Plex account link has been removed.").await?;
+            ctx.data().audit_logger.log_preferences_modification(user_id, Some(user_id), "tautulli_link_removed").await;
+        } else {
+            ctx.say("ℹ️ Your Discord account is not linked to a Plex account.").await?;
+        }
+
+        ctx.data().cooldown.apply_cooldown(
+            "unlink",
+            ctx.author().id,
+            Some(ctx.channel_id()),
+            &cooldown_config,
+        );
+
+        info!("Unlink command executed by user {}", user_id);
+        Ok(())
+    }.await;
+
+    // Record metrics
+    record_command_execution(&ctx, "unlink", start_time, &result);
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1058,4 +1368,26 @@ mod tests {
         assert!(formatted.contains("• 🔢 **Total Commands:** 0"));
         assert!(formatted.contains("📱 This data is private"));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_format_viewing_stats() {
+        let stats = PersonalViewingStats {
+            total_plays: 12,
+            watch_time_seconds: 5 * 3600 + 30 * 60,
+            top_shows: vec![tgraph_graphs::ShowPlays { title: "Severance".to_string(), plays: 9 }],
+            favorite_platform: Some("Roku".to_string()),
+            most_active_hour: Some(21),
+        };
+        let formatted = format_viewing_stats(&stats, TimePeriod::Weekly, "alice");
+
+        assert!(formatted.contains("**Plex Viewing (Weekly)** - alice"));
+        assert!(formatted.contains("Total Plays: 12"));
+        assert!(formatted.contains("Watch Time: 5h 30m"));
+        assert!(formatted.contains("Favorite Platform: Roku"));
+        assert!(formatted.contains("Most Active Hour: 21:00 UTC"));
+        assert!(formatted.contains("1. Severance (9 plays)"));
+
+        let empty = format_viewing_stats(&PersonalViewingStats::default(), TimePeriod::Daily, "alice");
+        assert!(empty.contains("No plays found"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Cooperative cancellation and deadline propagation
+//!
+//! A [`CancellationContext`] couples a cancellation token with an optional
+//! deadline so that long-running work (history fetching, aggregation and
+//! rendering) can stop promptly when a task is cancelled or times out.
+
+use crate::error::{Result, TGraphError};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// Cancellation token paired with an optional deadline
+#[derive(Debug, Clone, Default)]
+pub struct CancellationContext {
+    token: CancellationToken,
+    deadline: Option<Instant>,
+}
+
+impl CancellationContext {
+    /// Create a context with no deadline that is only cancelled explicitly
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a context that expires after the given timeout
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::new().deadline_in(timeout)
+    }
+
+    /// Set the deadline, keeping the earlier one if a deadline is already set
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(match self.deadline {
+            Some(existing) => existing.min(deadline),
+            None => deadline,
+        });
+        self
+    }
+
+    /// Set the deadline relative to now
+    pub fn deadline_in(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Create a child context that is cancelled together with this one
+    ///
+    /// Cancelling the child does not cancel the parent. The child inherits the
+    /// parent's deadline and may only tighten it.
+    pub fn child(&self) -> Self {
+        Self {
+            token: self.token.child_token(),
+            deadline: self.deadline,
+        }
+    }
+
+    /// Request cancellation of all work using this context
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Whether the deadline has passed
+    pub fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// The deadline, if one was set
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Time left before the deadline, if one was set
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// The underlying cancellation token
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Return an error if the context was cancelled or its deadline has passed
+    ///
+    /// Cancellation takes precedence over deadline expiry so that an explicit
+    /// cancel is never reported as a timeout.
+    pub fn check(&self, operation: &str) -> Result<()> {
+        if self.is_cancelled() {
+            Err(TGraphError::cancelled(operation.to_string()))
+        } else if self.is_expired() {
+            Err(TGraphError::deadline_exceeded(operation.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Run a future until it completes, the context is cancelled, or the
+    /// deadline passes, whichever happens first
+    pub async fn run<F, T>(&self, operation: &str, future: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        self.check(operation)?;
+
+        let deadline = async {
+            match self.deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending::<()>().await,
+            }
+        };
+
+        tokio::select! {
+            biased;
+            _ = self.token.cancelled() => Err(TGraphError::cancelled(operation.to_string())),
+            _ = deadline => Err(TGraphError::deadline_exceeded(operation.to_string())),
+            result = future => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_reports_cancellation() {
+        let ctx = CancellationContext::new();
+        assert!(ctx.check("fetch").is_ok());
+
+        ctx.cancel();
+        let err = ctx.check("fetch").unwrap_err();
+        assert!(err.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_deadline() {
+        let ctx = CancellationContext::with_timeout(Duration::ZERO);
+        let err = ctx.check("render").unwrap_err();
+        assert!(err.is_deadline_exceeded());
+
+        // An explicit cancel wins over an expired deadline
+        ctx.cancel();
+        assert!(ctx.check("render").unwrap_err().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_child_follows_parent() {
+        let parent = CancellationContext::with_timeout(Duration::from_secs(60));
+        let child = parent.child().deadline_in(Duration::from_secs(5));
+
+        assert!(child.deadline() < parent.deadline());
+
+        child.cancel();
+        assert!(!parent.is_cancelled());
+
+        let other_child = parent.child();
+        parent.cancel();
+        assert!(other_child.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_at_deadline() {
+        let ctx = CancellationContext::with_timeout(Duration::from_millis(20));
+        let result = ctx
+            .run("slow", async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await;
+
+        assert!(result.unwrap_err().is_deadline_exceeded());
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_on_cancel() {
+        let ctx = CancellationContext::new();
+        let canceller = ctx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            canceller.cancel();
+        });
+
+        let result = ctx
+            .run("slow", async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await;
+
+        assert!(result.unwrap_err().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_run_returns_result() {
+        let ctx = CancellationContext::with_timeout(Duration::from_secs(5));
+        let value = ctx.run("fast", async { Ok(42) }).await.unwrap();
+        assert_eq!(value, 42);
+    }
+}
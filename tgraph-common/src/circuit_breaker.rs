@@ -0,0 +1,397 @@
+//! Circuit breaker for calls to external services
+//!
+//! When a service keeps failing, the breaker opens and rejects calls
+//! immediately instead of letting every caller wait through its full retry
+//! budget. After a cool-down period a limited number of trial calls are let
+//! through (half-open); if they succeed the breaker closes again.
+
+use crate::error::{Result, TGraphError};
+use serde::Serialize;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Thresholds controlling when the breaker opens and closes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the breaker (default: 5)
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before allowing trial calls (default: 30s)
+    pub open_duration: Duration,
+    /// Successful trial calls needed to close the breaker again (default: 1)
+    pub success_threshold: u32,
+    /// Maximum concurrent trial calls while half-open (default: 1)
+    pub half_open_max_calls: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+            success_threshold: 1,
+            half_open_max_calls: 1,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Set the number of consecutive failures that open the breaker
+    pub fn with_failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold;
+        self
+    }
+
+    /// Set how long the breaker stays open
+    pub fn with_open_duration(mut self, open_duration: Duration) -> Self {
+        self.open_duration = open_duration;
+        self
+    }
+
+    /// Set the number of successful trial calls needed to close the breaker
+    pub fn with_success_threshold(mut self, success_threshold: u32) -> Self {
+        self.success_threshold = success_threshold;
+        self
+    }
+
+    /// Set the maximum concurrent trial calls while half-open
+    pub fn with_half_open_max_calls(mut self, half_open_max_calls: u32) -> Self {
+        self.half_open_max_calls = half_open_max_calls;
+        self
+    }
+}
+
+/// Current state of a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls flow normally
+    Closed,
+    /// Calls are rejected without reaching the service
+    Open,
+    /// A limited number of trial calls are allowed through
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half_open"),
+        }
+    }
+}
+
+/// Point-in-time view of a breaker for metrics and health endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitBreakerSnapshot {
+    /// Current state
+    pub state: CircuitState,
+    /// Consecutive failures recorded while closed
+    pub consecutive_failures: u32,
+    /// Calls rejected because the breaker was open
+    pub rejected_calls: u64,
+    /// Number of times the breaker has opened
+    pub times_opened: u64,
+    /// Seconds until trial calls are allowed, while open
+    pub retry_after_secs: Option<u64>,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    half_open_successes: u32,
+    half_open_in_flight: u32,
+    opened_at: Option<Instant>,
+    rejected_calls: u64,
+    times_opened: u64,
+}
+
+/// Circuit breaker guarding calls to a single service
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    service: String,
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker for the named service
+    pub fn new(service: impl Into<String>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            service: service.into(),
+            config,
+            inner: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                half_open_successes: 0,
+                half_open_in_flight: 0,
+                opened_at: None,
+                rejected_calls: 0,
+                times_opened: 0,
+            }),
+        }
+    }
+
+    /// The breaker configuration
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    /// Current state, moving from open to half-open once the cool-down has passed
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.lock();
+        self.refresh(&mut inner);
+        inner.state
+    }
+
+    /// Ask permission to make a call
+    ///
+    /// Returns a service-unavailable error without touching the service when
+    /// the breaker is open or all half-open trial slots are taken. The returned
+    /// permit records the outcome; dropping it unsettled (e.g. because the
+    /// call was cancelled) frees its trial slot without counting a result.
+    pub fn acquire(&self) -> Result<CircuitPermit<'_>> {
+        let mut inner = self.lock();
+        self.refresh(&mut inner);
+
+        match inner.state {
+            CircuitState::Closed => Ok(CircuitPermit::new(self, false)),
+            CircuitState::HalfOpen if inner.half_open_in_flight < self.config.half_open_max_calls => {
+                inner.half_open_in_flight += 1;
+                Ok(CircuitPermit::new(self, true))
+            }
+            _ => {
+                inner.rejected_calls += 1;
+                Err(TGraphError::service_unavailable(
+                    self.service.clone(),
+                    self.retry_after(&inner),
+                ))
+            }
+        }
+    }
+
+    fn record_success(&self, trial: bool) {
+        let mut inner = self.lock();
+        if trial {
+            inner.half_open_in_flight = inner.half_open_in_flight.saturating_sub(1);
+        }
+        match inner.state {
+            CircuitState::HalfOpen => {
+                inner.half_open_successes += 1;
+                if inner.half_open_successes >= self.config.success_threshold {
+                    info!("Circuit breaker for {} closed", self.service);
+                    inner.state = CircuitState::Closed;
+                    inner.consecutive_failures = 0;
+                    inner.opened_at = None;
+                }
+            }
+            _ => inner.consecutive_failures = 0,
+        }
+    }
+
+    fn record_failure(&self, trial: bool) {
+        let mut inner = self.lock();
+        if trial {
+            inner.half_open_in_flight = inner.half_open_in_flight.saturating_sub(1);
+        }
+        match inner.state {
+            CircuitState::HalfOpen => self.open(&mut inner),
+            CircuitState::Closed => {
+                inner.consecutive_failures += 1;
+                if inner.consecutive_failures >= self.config.failure_threshold {
+                    self.open(&mut inner);
+                }
+            }
+            CircuitState::Open => {}
+        }
+    }
+
+    /// Force the breaker back to closed, e.g. after an operator intervenes
+    pub fn reset(&self) {
+        let mut inner = self.lock();
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.half_open_successes = 0;
+        inner.half_open_in_flight = 0;
+        inner.opened_at = None;
+    }
+
+    /// Snapshot of the breaker for metrics and health reporting
+    pub fn snapshot(&self) -> CircuitBreakerSnapshot {
+        let mut inner = self.lock();
+        self.refresh(&mut inner);
+        CircuitBreakerSnapshot {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            rejected_calls: inner.rejected_calls,
+            times_opened: inner.times_opened,
+            retry_after_secs: self.retry_after(&inner).map(|d| d.as_secs()),
+        }
+    }
+
+    fn release(&self) {
+        let mut inner = self.lock();
+        inner.half_open_in_flight = inner.half_open_in_flight.saturating_sub(1);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        // The state is always left consistent, so a poisoned lock is safe to reuse
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn open(&self, inner: &mut BreakerState) {
+        warn!(
+            "Circuit breaker for {} opened after {} consecutive failures",
+            self.service, inner.consecutive_failures
+        );
+        inner.state = CircuitState::Open;
+        inner.opened_at = Some(Instant::now());
+        inner.half_open_successes = 0;
+        inner.times_opened += 1;
+    }
+
+    fn refresh(&self, inner: &mut BreakerState) {
+        if inner.state == CircuitState::Open
+            && inner
+                .opened_at
+                .is_some_and(|opened_at| opened_at.elapsed() >= self.config.open_duration)
+        {
+            info!("Circuit breaker for {} half-open, allowing trial calls", self.service);
+            inner.state = CircuitState::HalfOpen;
+            inner.half_open_successes = 0;
+            inner.half_open_in_flight = 0;
+        }
+    }
+
+    fn retry_after(&self, inner: &BreakerState) -> Option<Duration> {
+        match (inner.state, inner.opened_at) {
+            (CircuitState::Open, Some(opened_at)) => {
+                Some(self.config.open_duration.saturating_sub(opened_at.elapsed()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Permission to make one call through a [`CircuitBreaker`]
+#[derive(Debug)]
+#[must_use = "the permit should be settled with success() or failure()"]
+pub struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    trial: bool,
+    settled: bool,
+}
+
+impl<'a> CircuitPermit<'a> {
+    fn new(breaker: &'a CircuitBreaker, trial: bool) -> Self {
+        Self {
+            breaker,
+            trial,
+            settled: false,
+        }
+    }
+
+    /// Record that the call succeeded
+    pub fn success(mut self) {
+        self.settled = true;
+        self.breaker.record_success(self.trial);
+    }
+
+    /// Record that the call failed
+    pub fn failure(mut self) {
+        self.settled = true;
+        self.breaker.record_failure(self.trial);
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if !self.settled && self.trial {
+            self.breaker.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(open_duration: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(
+            "Tautulli",
+            CircuitBreakerConfig::default()
+                .with_failure_threshold(3)
+                .with_open_duration(open_duration),
+        )
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = breaker(Duration::from_secs(60));
+
+        for _ in 0..2 {
+            breaker.acquire().unwrap().failure();
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        // A success resets the failure streak
+        breaker.acquire().unwrap().success();
+        assert_eq!(breaker.snapshot().consecutive_failures, 0);
+
+        for _ in 0..3 {
+            breaker.acquire().unwrap().failure();
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let err = breaker.acquire().unwrap_err();
+        assert!(err.is_service_unavailable());
+        assert!(err.retry_after().is_some());
+
+        let snapshot = breaker.snapshot();
+        assert_eq!(snapshot.rejected_calls, 1);
+        assert_eq!(snapshot.times_opened, 1);
+    }
+
+    #[test]
+    fn test_half_open_closes_on_success() {
+        let breaker = breaker(Duration::ZERO);
+        for _ in 0..3 {
+            breaker.acquire().unwrap().failure();
+        }
+
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        let permit = breaker.acquire().unwrap();
+        // Only one trial call at a time
+        assert!(breaker.acquire().is_err());
+
+        permit.success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.acquire().is_ok());
+    }
+
+    #[test]
+    fn test_half_open_reopens_on_failure() {
+        let breaker = breaker(Duration::from_millis(20));
+        for _ in 0..3 {
+            breaker.acquire().unwrap().failure();
+        }
+
+        std::thread::sleep(Duration::from_millis(30));
+
+        // A cancelled trial call frees its slot without deciding anything
+        drop(breaker.acquire().unwrap());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        breaker.acquire().unwrap().failure();
+
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(breaker.snapshot().times_opened, 2);
+
+        breaker.reset();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
@@ -90,6 +90,25 @@ pub enum TGraphError {
         field: Option<String>,
     },
 
+    /// The operation was cancelled before it completed
+    #[error("Operation cancelled: {message}")]
+    Cancelled {
+        message: String,
+    },
+
+    /// The operation ran past its deadline
+    #[error("Deadline exceeded: {message}")]
+    DeadlineExceeded {
+        message: String,
+    },
+
+    /// An external service is temporarily unavailable (circuit breaker open)
+    #[error("{service} unavailable")]
+    ServiceUnavailable {
+        service: String,
+        retry_after: Option<std::time::Duration>,
+    },
+
     /// Generic error with custom message
     #[error("{message}")]
     Generic { 
@@ -272,6 +291,54 @@ impl TGraphError {
             field: Some(field.into()),
         }
     }
+
+    /// Create a new cancellation error
+    pub fn cancelled(msg: impl Into<String>) -> Self {
+        Self::Cancelled {
+            message: msg.into(),
+        }
+    }
+
+    /// Create a new deadline exceeded error
+    pub fn deadline_exceeded(msg: impl Into<String>) -> Self {
+        Self::DeadlineExceeded {
+            message: msg.into(),
+        }
+    }
+
+    /// Whether this error was caused by an explicit cancellation
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled { .. })
+    }
+
+    /// Whether this error was caused by a deadline expiring
+    pub fn is_deadline_exceeded(&self) -> bool {
+        matches!(self, Self::DeadlineExceeded { .. })
+    }
+
+    /// Create a new service unavailable error
+    pub fn service_unavailable(
+        service: impl Into<String>,
+        retry_after: Option<std::time::Duration>,
+    ) -> Self {
+        Self::ServiceUnavailable {
+            service: service.into(),
+            retry_after,
+        }
+    }
+
+    /// Whether this error was raised because a service is unavailable
+    pub fn is_service_unavailable(&self) -> bool {
+        matches!(self, Self::ServiceUnavailable { .. })
+    }
+
+    /// Suggested wait before retrying, if the error carries one
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            Self::ServiceUnavailable { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
 }
 
 // Error conversion implementations for external types
@@ -349,6 +416,23 @@ mod tests {
         let localization_error = TGraphError::localization_with_locale("Translation missing", "en-US");
         assert!(localization_error.to_string().contains("Localization error"));
         assert!(localization_error.to_string().contains("Translation missing"));
+
+        let cancelled_error = TGraphError::cancelled("history fetch");
+        assert!(cancelled_error.is_cancelled());
+        assert!(!cancelled_error.is_deadline_exceeded());
+        assert_eq!(cancelled_error.to_string(), "Operation cancelled: history fetch");
+
+        let deadline_error = TGraphError::deadline_exceeded("rendering");
+        assert!(deadline_error.is_deadline_exceeded());
+        assert_eq!(deadline_error.to_string(), "Deadline exceeded: rendering");
+
+        let unavailable_error = TGraphError::service_unavailable(
+            "Tautulli",
+            Some(std::time::Duration::from_secs(30)),
+        );
+        assert!(unavailable_error.is_service_unavailable());
+        assert_eq!(unavailable_error.to_string(), "Tautulli unavailable");
+        assert_eq!(unavailable_error.retry_after(), Some(std::time::Duration::from_secs(30)));
     }
 
     #[test]
@@ -1,21 +1,31 @@
 //! Common utilities and types for TGraph Telegram bot
 
+pub mod cancellation;
+pub mod circuit_breaker;
 pub mod error;
 pub mod logging;
 pub mod macros;
+pub mod request_metrics;
 pub mod tautulli;
+pub mod tautulli_servers;
 pub mod types;
 pub mod utils;
 
 // Re-export commonly used types
+pub use cancellation::CancellationContext;
+pub use circuit_breaker::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerSnapshot, CircuitPermit, CircuitState,
+};
 pub use error::{Result, TGraphError};
 pub use logging::{
     init_default_logging, init_dev_logging, init_dual_logging, init_logging, init_prod_logging,
     LoggingConfig,
 };
+pub use request_metrics::{EndpointMetrics, LatencyHistogram, RequestMetrics};
 pub use tautulli::{
-    ActivityResponse, ClientMetrics, HistoryEntry, HistoryResponse, Library, LibrariesResponse,
-    ServerInfoResponse, Session, TautulliClient, TautulliConfig, TautulliResponse,
-    TautulliResponseData, User, UsersResponse,
+    ActivityResponse, ClientMetrics, GeoIpInfo, GraphSeries, GraphSeriesResponse, HistoryEntry,
+    HistoryResponse, Library, LibrariesResponse, ServerInfoResponse, Session, TautulliClient,
+    TautulliConfig, TautulliResponse, TautulliResponseData, User, UsersResponse,
 };
+pub use tautulli_servers::{ServerHistory, ServerMetrics, ServerSelection, TautulliServers};
 pub use types::*; 
\ No newline at end of file
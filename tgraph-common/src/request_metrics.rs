@@ -0,0 +1,206 @@
+//! Per-endpoint request metrics for API clients
+//!
+//! Records request counts, error classes, retries and latency histograms per
+//! API command so slow or failing calls can be identified. The data is kept in
+//! memory and exported by whoever owns the metrics registry.
+
+use crate::error::TGraphError;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets in seconds
+pub const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Latency histogram with fixed buckets
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyHistogram {
+    /// Bucket upper bounds in seconds
+    pub bounds: Vec<f64>,
+    /// Cumulative observation count for each bound
+    pub cumulative_counts: Vec<u64>,
+    /// Sum of all observations in seconds
+    pub sum_seconds: f64,
+    /// Number of observations
+    pub count: u64,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            bounds: LATENCY_BUCKETS.to_vec(),
+            cumulative_counts: vec![0; LATENCY_BUCKETS.len()],
+            sum_seconds: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, latency: Duration) {
+        let seconds = latency.as_secs_f64();
+        for (bound, count) in self.bounds.iter().zip(self.cumulative_counts.iter_mut()) {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum_seconds += seconds;
+        self.count += 1;
+    }
+
+    /// Average latency, if anything was observed
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_secs_f64(self.sum_seconds / self.count as f64))
+    }
+}
+
+/// Metrics for a single API command
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointMetrics {
+    /// API command name (the `cmd` parameter)
+    pub cmd: String,
+    /// Completed requests, successful or not
+    pub requests: u64,
+    /// Failed requests by error class
+    pub errors: BTreeMap<String, u64>,
+    /// Retry attempts after the first try
+    pub retries: u64,
+    /// Responses that could not be parsed
+    pub parse_failures: u64,
+    /// Request latency including retries and parsing
+    pub latency: LatencyHistogram,
+}
+
+impl EndpointMetrics {
+    fn new(cmd: &str) -> Self {
+        Self {
+            cmd: cmd.to_string(),
+            requests: 0,
+            errors: BTreeMap::new(),
+            retries: 0,
+            parse_failures: 0,
+            latency: LatencyHistogram::new(),
+        }
+    }
+
+    /// Total failed requests across all error classes
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+}
+
+/// Classify an error for metrics labels
+pub fn error_class(error: &TGraphError) -> &'static str {
+    match error {
+        TGraphError::ServiceUnavailable { .. } => "unavailable",
+        TGraphError::Tautulli { status_code: Some(status), .. } if *status < 500 => "client_error",
+        TGraphError::Tautulli { status_code: Some(_), .. } => "server_error",
+        TGraphError::Tautulli { .. } => "api_error",
+        TGraphError::Network { .. } => "network",
+        TGraphError::Serialization(_) => "parse",
+        TGraphError::Cancelled { .. } | TGraphError::DeadlineExceeded { .. } => "cancelled",
+        _ => "other",
+    }
+}
+
+/// Thread-safe recorder of per-command request metrics
+#[derive(Debug, Default)]
+pub struct RequestMetrics {
+    endpoints: Mutex<HashMap<String, EndpointMetrics>>,
+}
+
+impl RequestMetrics {
+    /// Create an empty recorder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a completed request
+    pub fn record(
+        &self,
+        cmd: &str,
+        latency: Duration,
+        retries: u32,
+        result: std::result::Result<(), &TGraphError>,
+    ) {
+        let mut endpoints = self.lock();
+        let endpoint = endpoints
+            .entry(cmd.to_string())
+            .or_insert_with(|| EndpointMetrics::new(cmd));
+
+        endpoint.requests += 1;
+        endpoint.retries += u64::from(retries);
+        endpoint.latency.observe(latency);
+
+        if let Err(error) = result {
+            let class = error_class(error);
+            if class == "parse" {
+                endpoint.parse_failures += 1;
+            }
+            *endpoint.errors.entry(class.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// Metrics for every command seen so far, sorted by command name
+    pub fn snapshot(&self) -> Vec<EndpointMetrics> {
+        let mut endpoints: Vec<EndpointMetrics> = self.lock().values().cloned().collect();
+        endpoints.sort_by(|a, b| a.cmd.cmp(&b.cmd));
+        endpoints
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, EndpointMetrics>> {
+        // Counters stay usable even if a holder panicked
+        self.endpoints.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_histogram_buckets() {
+        let mut histogram = LatencyHistogram::new();
+        histogram.observe(Duration::from_millis(40));
+        histogram.observe(Duration::from_millis(700));
+        histogram.observe(Duration::from_secs(60));
+
+        assert_eq!(histogram.count, 3);
+        // 0.05s bucket holds only the fast request, 1s bucket holds two
+        assert_eq!(histogram.cumulative_counts[0], 1);
+        assert_eq!(histogram.cumulative_counts[4], 2);
+        // The slowest request is above the last bound
+        assert_eq!(*histogram.cumulative_counts.last().unwrap(), 2);
+        assert!(histogram.mean().unwrap() > Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_record_by_command() {
+        let metrics = RequestMetrics::new();
+        let parse_error: TGraphError = serde_json::from_str::<u32>("oops").unwrap_err().into();
+        let server_error = TGraphError::tautulli_with_status("boom", 503);
+
+        metrics.record("get_history", Duration::from_millis(120), 0, Ok(()));
+        metrics.record("get_history", Duration::from_millis(900), 2, Err(&server_error));
+        metrics.record("get_activity", Duration::from_millis(30), 0, Err(&parse_error));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].cmd, "get_activity");
+        assert_eq!(snapshot[0].parse_failures, 1);
+        assert_eq!(snapshot[0].errors.get("parse"), Some(&1));
+
+        let history = &snapshot[1];
+        assert_eq!(history.requests, 2);
+        assert_eq!(history.retries, 2);
+        assert_eq!(history.error_count(), 1);
+        assert_eq!(history.errors.get("server_error"), Some(&1));
+        assert_eq!(history.latency.count, 2);
+    }
+
+    #[test]
+    fn test_error_class() {
+        assert_eq!(error_class(&TGraphError::tautulli_with_status("nope", 404)), "client_error");
+        assert_eq!(error_class(&TGraphError::network("down")), "network");
+        assert_eq!(error_class(&TGraphError::service_unavailable("Tautulli", None)), "unavailable");
+    }
+}
@@ -3,16 +3,25 @@
 //! This module provides a robust HTTP client for interacting with the Tautulli API,
 //! including authentication, rate limiting, retry logic, and comprehensive error handling.
 
+use crate::cancellation::CancellationContext;
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerSnapshot};
 use crate::error::{Result, TGraphError};
+use crate::request_metrics::{EndpointMetrics, RequestMetrics};
 use governor::{DefaultDirectRateLimiter, Quota};
 use reqwest::{Client, Response};
 use serde::{Deserialize, Serialize};
-use std::{num::NonZeroU32, sync::Arc, time::Duration};
+use std::{
+    fmt,
+    num::NonZeroU32,
+    sync::atomic::{AtomicU32, Ordering},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio_retry::{strategy::ExponentialBackoff, Retry};
 use tracing::{debug, error, info, instrument, warn};
 
 /// Configuration for the Tautulli API client
-#[derive(Debug, Clone)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct TautulliConfig {
     /// Base URL of the Tautulli server (e.g., "http://localhost:8181")
     pub base_url: String,
@@ -26,6 +35,8 @@ pub struct TautulliConfig {
     pub rate_limit_per_sec: u32,
     /// Maximum number of retry attempts (default: 3)
     pub max_retries: usize,
+    /// Circuit breaker thresholds
+    pub circuit_breaker: CircuitBreakerConfig,
 }
 
 impl Default for TautulliConfig {
@@ -37,10 +48,26 @@ impl Default for TautulliConfig {
             max_idle_per_host: 10,
             rate_limit_per_sec: 10,
             max_retries: 3,
+            circuit_breaker: CircuitBreakerConfig::default(),
         }
     }
 }
 
+impl fmt::Debug for TautulliConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The API key is never printed
+        f.debug_struct("TautulliConfig")
+            .field("base_url", &self.base_url)
+            .field("api_key", &if self.api_key.is_empty() { "" } else { "********" })
+            .field("timeout_secs", &self.timeout_secs)
+            .field("max_idle_per_host", &self.max_idle_per_host)
+            .field("rate_limit_per_sec", &self.rate_limit_per_sec)
+            .field("max_retries", &self.max_retries)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .finish()
+    }
+}
+
 impl TautulliConfig {
     /// Create a new configuration with the minimum required parameters
     pub fn new(base_url: impl Into<String>, api_key: impl Into<String>) -> Self {
@@ -74,6 +101,12 @@ impl TautulliConfig {
         self.max_retries = max_retries;
         self
     }
+
+    /// Set the circuit breaker thresholds
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
 }
 
 /// Tautulli API client with connection pooling and rate limiting
@@ -82,6 +115,8 @@ pub struct TautulliClient {
     client: Client,
     config: TautulliConfig,
     rate_limiter: Arc<DefaultDirectRateLimiter>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    request_metrics: Arc<RequestMetrics>,
 }
 
 impl TautulliClient {
@@ -101,10 +136,22 @@ impl TautulliClient {
         );
         let rate_limiter = Arc::new(DefaultDirectRateLimiter::direct(quota));
 
+        if config.circuit_breaker.failure_threshold == 0 {
+            return Err(TGraphError::config(
+                "Circuit breaker failure threshold must be greater than 0",
+            ));
+        }
+        let circuit_breaker = Arc::new(CircuitBreaker::new(
+            "Tautulli",
+            config.circuit_breaker.clone(),
+        ));
+
         Ok(Self {
             client,
             config,
             rate_limiter,
+            circuit_breaker,
+            request_metrics: Arc::new(RequestMetrics::new()),
         })
     }
 
@@ -120,8 +167,18 @@ impl TautulliClient {
     }
 
     /// Make an authenticated request to the Tautulli API with retry logic
-    #[instrument(skip(self), fields(endpoint = %endpoint))]
-    async fn make_request(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Response> {
+    ///
+    /// Fails fast with a service-unavailable error while the circuit breaker
+    /// is open instead of spending the retry budget on a server that is down.
+    #[instrument(skip(self, params, attempts), fields(endpoint = %endpoint))]
+    async fn make_request(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        attempts: &AtomicU32,
+    ) -> Result<Response> {
+        let permit = self.circuit_breaker.acquire()?;
+
         // Wait for rate limiter
         self.rate_limiter.until_ready().await;
 
@@ -141,11 +198,13 @@ impl TautulliClient {
             .take(self.config.max_retries);
 
         let response = Retry::spawn(retry_strategy, || async {
+            attempts.fetch_add(1, Ordering::Relaxed);
             let request = self.client.get(&url).query(&query_params);
 
             debug!("Sending request with {} parameters", query_params.len());
 
-            match request.send().await {
+            // The request URL carries the API key, so it is kept out of errors and logs
+            match request.send().await.map_err(reqwest::Error::without_url) {
                 Ok(response) => {
                     if response.status().is_success() {
                         debug!("Request successful: {}", response.status());
@@ -180,10 +239,35 @@ impl TautulliClient {
                 }
             }
         })
-        .await?;
+        .await;
+
+        // Client errors mean Tautulli answered, so only outages count against the breaker
+        match response {
+            Ok(response) => {
+                permit.success();
+                info!("Successfully completed request to {}", endpoint);
+                Ok(response)
+            }
+            Err(e) if Self::is_outage(&e) => {
+                permit.failure();
+                Err(e)
+            }
+            Err(e) => {
+                permit.success();
+                Err(e)
+            }
+        }
+    }
 
-        info!("Successfully completed request to {}", endpoint);
-        Ok(response)
+    /// Whether an error indicates Tautulli itself is unreachable or failing
+    fn is_outage(error: &TGraphError) -> bool {
+        match error {
+            TGraphError::Network { .. } => true,
+            TGraphError::Tautulli { status_code, .. } => {
+                status_code.is_some_and(|status| status >= 500)
+            }
+            _ => false,
+        }
     }
 
     /// Parse a JSON response into the specified type
@@ -203,13 +287,44 @@ impl TautulliClient {
     }
 
     /// Make a request and parse the JSON response
-    #[instrument(skip(self), fields(endpoint = %endpoint))]
+    #[instrument(skip(self, params), fields(endpoint = %endpoint))]
     async fn request_json<T>(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<T>
     where
         T: for<'de> Deserialize<'de>,
     {
-        let response = self.make_request(endpoint, params).await?;
-        self.parse_response(response).await
+        let started = Instant::now();
+        let attempts = AtomicU32::new(0);
+
+        let result = match self.make_request(endpoint, params, &attempts).await {
+            Ok(response) => self.parse_response(response).await,
+            Err(e) => Err(e),
+        };
+
+        let retries = attempts.load(Ordering::Relaxed).saturating_sub(1);
+        self.request_metrics
+            .record(endpoint, started.elapsed(), retries, result.as_ref().map(|_| ()));
+        result
+    }
+
+    /// Make a request and return the raw response body
+    #[instrument(skip(self, params), fields(endpoint = %endpoint))]
+    async fn request_bytes(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Vec<u8>> {
+        let started = Instant::now();
+        let attempts = AtomicU32::new(0);
+
+        let result = match self.make_request(endpoint, params, &attempts).await {
+            Ok(response) => response
+                .bytes()
+                .await
+                .map(|bytes| bytes.to_vec())
+                .map_err(|e| TGraphError::network_with_source("Failed to read response body", e)),
+            Err(e) => Err(e),
+        };
+
+        let retries = attempts.load(Ordering::Relaxed).saturating_sub(1);
+        self.request_metrics
+            .record(endpoint, started.elapsed(), retries, result.as_ref().map(|_| ()));
+        result
     }
 
     // ============================================================================
@@ -252,13 +367,22 @@ impl TautulliClient {
         length: Option<i32>, 
         start: Option<i32>
     ) -> Result<HistoryResponse> {
-        info!("Fetching playback history");
-        
         let mut params = Vec::new();
-        
         if let Some(uid) = user_id {
             params.push(("user_id", uid.to_string()));
         }
+        self.get_history_filtered(params, length, start).await
+    }
+
+    /// Get one page of playback history with arbitrary filter parameters
+    async fn get_history_filtered(
+        &self,
+        mut params: Vec<(&'static str, String)>,
+        length: Option<i32>,
+        start: Option<i32>,
+    ) -> Result<HistoryResponse> {
+        info!("Fetching playback history");
+
         if let Some(len) = length {
             params.push(("length", len.to_string()));
         }
@@ -285,6 +409,132 @@ impl TautulliClient {
         }
     }
 
+    /// Get playback history, giving up as soon as the context is cancelled or expires
+    ///
+    /// The context covers the rate limiter wait, every retry attempt and the
+    /// backoff delays between them.
+    #[instrument(skip(self, ctx), fields(user_id = ?user_id, length = ?length, start = ?start))]
+    pub async fn get_history_with_context(
+        &self,
+        ctx: &CancellationContext,
+        user_id: Option<i32>,
+        length: Option<i32>,
+        start: Option<i32>,
+    ) -> Result<HistoryResponse> {
+        ctx.run("get_history", self.get_history(user_id, length, start)).await
+    }
+
+    /// Fetch the complete playback history page by page
+    ///
+    /// The context is checked before each page so a cancelled or timed-out
+    /// generation stops fetching instead of paging through the whole history.
+    #[instrument(skip(self, ctx), fields(user_id = ?user_id, page_size = page_size))]
+    pub async fn fetch_all_history(
+        &self,
+        ctx: &CancellationContext,
+        user_id: Option<i32>,
+        page_size: i32,
+    ) -> Result<Vec<HistoryEntry>> {
+        let mut params = Vec::new();
+        if let Some(uid) = user_id {
+            params.push(("user_id", uid.to_string()));
+        }
+        self.fetch_history_pages(ctx, params, page_size).await
+    }
+
+    /// Fetch the complete playback history of one library section
+    #[instrument(skip(self, ctx), fields(section_id = section_id, page_size = page_size))]
+    pub async fn fetch_library_history(
+        &self,
+        ctx: &CancellationContext,
+        section_id: i32,
+        page_size: i32,
+    ) -> Result<Vec<HistoryEntry>> {
+        self.fetch_history_pages(ctx, vec![("section_id", section_id.to_string())], page_size)
+            .await
+    }
+
+    /// Fetch history played between two dates, both inclusive
+    pub async fn fetch_history_between(
+        &self,
+        ctx: &CancellationContext,
+        after: chrono::NaiveDate,
+        before: chrono::NaiveDate,
+        page_size: i32,
+    ) -> Result<Vec<HistoryEntry>> {
+        let params = vec![
+            ("after", after.format("%Y-%m-%d").to_string()),
+            ("before", before.format("%Y-%m-%d").to_string()),
+        ];
+        self.fetch_history_pages(ctx, params, page_size).await
+    }
+
+    /// Fetch one user's history played between two dates, both inclusive
+    pub async fn fetch_user_history_between(
+        &self,
+        ctx: &CancellationContext,
+        user_id: i32,
+        after: chrono::NaiveDate,
+        before: chrono::NaiveDate,
+        page_size: i32,
+    ) -> Result<Vec<HistoryEntry>> {
+        let params = vec![
+            ("user_id", user_id.to_string()),
+            ("after", after.format("%Y-%m-%d").to_string()),
+            ("before", before.format("%Y-%m-%d").to_string()),
+        ];
+        self.fetch_history_pages(ctx, params, page_size).await
+    }
+
+    /// Timestamp of the oldest play in the history, `None` if it is empty
+    pub async fn get_earliest_history_timestamp(&self) -> Result<Option<i64>> {
+        let params = vec![
+            ("order_column", "date".to_string()),
+            ("order_dir", "asc".to_string()),
+        ];
+        let page = self.get_history_filtered(params, Some(1), Some(0)).await?;
+        Ok(page.data.first().and_then(|entry| entry.date))
+    }
+
+    /// Page through history matching the given filter parameters
+    async fn fetch_history_pages(
+        &self,
+        ctx: &CancellationContext,
+        params: Vec<(&'static str, String)>,
+        page_size: i32,
+    ) -> Result<Vec<HistoryEntry>> {
+        if page_size <= 0 {
+            return Err(TGraphError::validation_field(
+                "Page size must be greater than 0",
+                "page_size",
+            ));
+        }
+
+        let mut entries = Vec::new();
+        let mut start = 0;
+
+        loop {
+            ctx.check("get_history")?;
+
+            let page = ctx
+                .run(
+                    "get_history",
+                    self.get_history_filtered(params.clone(), Some(page_size), Some(start)),
+                )
+                .await?;
+            let page_len = page.data.len() as i32;
+            entries.extend(page.data);
+
+            if page_len < page_size || entries.len() as i32 >= page.records_filtered {
+                break;
+            }
+            start += page_len;
+        }
+
+        debug!("Fetched {} history entries", entries.len());
+        Ok(entries)
+    }
+
     /// Get all users
     /// 
     /// Returns a list of all users known to Tautulli with their statistics and settings.
@@ -305,6 +555,92 @@ impl TautulliClient {
         }
     }
 
+    /// Fetch a poster or thumbnail through Tautulli's Plex image proxy
+    ///
+    /// Returns the raw image bytes (usually JPEG or PNG) for the given rating key,
+    /// scaled by Plex to the requested size.
+    #[instrument(skip(self))]
+    pub async fn pms_image_proxy(&self, rating_key: &str, width: u32, height: u32) -> Result<Vec<u8>> {
+        debug!("Fetching image for rating key {}", rating_key);
+        let width = width.to_string();
+        let height = height.to_string();
+        let bytes = self
+            .request_bytes(
+                "pms_image_proxy",
+                &[
+                    ("rating_key", rating_key),
+                    ("width", width.as_str()),
+                    ("height", height.as_str()),
+                    ("fallback", "poster"),
+                ],
+            )
+            .await?;
+
+        if bytes.is_empty() {
+            return Err(TGraphError::tautulli("Image proxy returned an empty response"));
+        }
+        Ok(bytes)
+    }
+
+    /// Get stream types (direct play, direct stream, transcode) of the top 10 platforms
+    ///
+    /// `time_range` is the number of days to look back.
+    #[instrument(skip(self))]
+    pub async fn get_stream_type_by_top_10_platforms(&self, time_range: u32) -> Result<GraphSeriesResponse> {
+        self.get_graph_data("get_stream_type_by_top_10_platforms", time_range).await
+    }
+
+    /// Get stream types of plays grouped by source resolution
+    #[instrument(skip(self))]
+    pub async fn get_plays_by_source_resolution(&self, time_range: u32) -> Result<GraphSeriesResponse> {
+        self.get_graph_data("get_plays_by_source_resolution", time_range).await
+    }
+
+    /// Get stream types of plays grouped by stream resolution
+    #[instrument(skip(self))]
+    pub async fn get_plays_by_stream_resolution(&self, time_range: u32) -> Result<GraphSeriesResponse> {
+        self.get_graph_data("get_plays_by_stream_resolution", time_range).await
+    }
+
+    /// Fetch one of Tautulli's pre-aggregated graph endpoints, counting plays
+    async fn get_graph_data(&self, endpoint: &str, time_range: u32) -> Result<GraphSeriesResponse> {
+        info!("Fetching graph data from {}", endpoint);
+        let time_range = time_range.to_string();
+        let response: TautulliResponse<GraphSeriesResponse> = self
+            .request_json(endpoint, &[("time_range", time_range.as_str()), ("y_axis", "plays")])
+            .await?;
+
+        if response.is_success() {
+            response.data().ok_or_else(|| {
+                TGraphError::tautulli(format!("{} response contained no data", endpoint))
+            })
+        } else {
+            Err(TGraphError::tautulli(
+                response.error_message().unwrap_or("Unknown error getting graph data")
+            ))
+        }
+    }
+
+    /// Look up the location of an IP address through Tautulli's GeoIP database
+    #[instrument(skip(self, ip_address))]
+    pub async fn get_geoip_lookup(&self, ip_address: &str) -> Result<GeoIpInfo> {
+        // The address itself is deliberately kept out of logs and spans
+        debug!("Fetching GeoIP lookup");
+        let response: TautulliResponse<GeoIpInfo> = self
+            .request_json("get_geoip_lookup", &[("ip_address", ip_address)])
+            .await?;
+
+        if response.is_success() {
+            response.data().ok_or_else(|| {
+                TGraphError::tautulli("GeoIP lookup response contained no data")
+            })
+        } else {
+            Err(TGraphError::tautulli(
+                response.error_message().unwrap_or("Unknown error looking up GeoIP")
+            ))
+        }
+    }
+
     /// Get all libraries
     /// 
     /// Returns a list of all library sections configured in Plex with their statistics.
@@ -368,6 +704,7 @@ impl TautulliClient {
     /// 
     /// Returns information useful for monitoring and debugging.
     pub fn get_client_metrics(&self) -> ClientMetrics {
+        let endpoints = self.request_metrics.snapshot();
         ClientMetrics {
             base_url: self.config.base_url.clone(),
             timeout_secs: self.config.timeout_secs,
@@ -376,8 +713,22 @@ impl TautulliClient {
             max_retries: self.config.max_retries,
             // Check if we have capacity for immediate request
             has_rate_limit_capacity: self.rate_limiter.check().is_ok(),
+            circuit_breaker: self.circuit_breaker.snapshot(),
+            total_retries: endpoints.iter().map(|e| e.retries).sum(),
+            total_parse_failures: endpoints.iter().map(|e| e.parse_failures).sum(),
+            endpoints,
         }
     }
+
+    /// Get the circuit breaker guarding requests to this server
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit_breaker
+    }
+
+    /// Get the configuration this client was built with
+    pub fn config(&self) -> &TautulliConfig {
+        &self.config
+    }
 }
 
 /// Client metrics for monitoring and debugging
@@ -395,6 +746,14 @@ pub struct ClientMetrics {
     pub max_retries: usize,
     /// Whether we currently have rate limit capacity
     pub has_rate_limit_capacity: bool,
+    /// Circuit breaker state
+    pub circuit_breaker: CircuitBreakerSnapshot,
+    /// Retry attempts across all commands
+    pub total_retries: u64,
+    /// Unparseable responses across all commands
+    pub total_parse_failures: u64,
+    /// Per-command request counts, errors and latency
+    pub endpoints: Vec<EndpointMetrics>,
 }
 
 // ============================================================================
@@ -529,7 +888,8 @@ pub struct HistoryEntry {
     pub grandparent_title: Option<String>,
     pub year: Option<i32>,
     /// Playback information
-    pub watched_status: Option<i32>,
+    /// 0 unwatched, 0.5 partially watched, 1 watched
+    pub watched_status: Option<f64>,
     pub percent_complete: Option<i32>,
     pub duration: Option<i64>,
     /// Quality information
@@ -632,6 +992,48 @@ pub struct ServerInfoResponse {
     pub update_available: Option<i32>,
 }
 
+// ============================================================================
+// GeoIP Endpoint Models
+// ============================================================================
+
+/// Location returned by the get_geoip_lookup endpoint
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GeoIpInfo {
+    pub city: Option<String>,
+    pub region: Option<String>,
+    pub country: Option<String>,
+    /// ISO country code
+    pub code: Option<String>,
+    pub continent: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+// ============================================================================
+// Graph Endpoint Models
+// ============================================================================
+
+/// Response model for Tautulli's graph endpoints
+///
+/// Each series has one value per category, e.g. a "Transcode" series with
+/// one play count per platform.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GraphSeriesResponse {
+    /// Category labels (platforms, resolutions, dates)
+    pub categories: Vec<String>,
+    /// Named value series
+    pub series: Vec<GraphSeries>,
+}
+
+/// A single named series of graph values
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GraphSeries {
+    /// Series name, e.g. "Direct Play"
+    pub name: String,
+    /// Values, one per category
+    pub data: Vec<i64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -697,6 +1099,42 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_circuit_breaker_fails_fast() {
+        // Nothing listens on port 1, so every request is a connection error
+        let config = TautulliConfig::new("http://127.0.0.1:1", "test-key")
+            .with_max_retries(0)
+            .with_circuit_breaker(CircuitBreakerConfig::default().with_failure_threshold(2));
+        let client = TautulliClient::new(config).unwrap();
+
+        for _ in 0..2 {
+            let err = client.get_activity().await.unwrap_err();
+            assert!(!err.is_service_unavailable());
+        }
+
+        let err = client.get_activity().await.unwrap_err();
+        assert!(err.is_service_unavailable());
+
+        let metrics = client.get_client_metrics();
+        assert_eq!(metrics.circuit_breaker.state, crate::circuit_breaker::CircuitState::Open);
+        assert_eq!(metrics.circuit_breaker.rejected_calls, 1);
+
+        // Every call is recorded against its command, including the rejected one
+        assert_eq!(metrics.endpoints.len(), 1);
+        assert_eq!(metrics.endpoints[0].cmd, "get_activity");
+        assert_eq!(metrics.endpoints[0].requests, 3);
+        assert_eq!(metrics.endpoints[0].errors.get("network"), Some(&2));
+        assert_eq!(metrics.endpoints[0].errors.get("unavailable"), Some(&1));
+        assert_eq!(metrics.total_retries, 0);
+    }
+
+    #[test]
+    fn test_circuit_breaker_threshold_validation() {
+        let config = TautulliConfig::new("http://example.com", "test-key")
+            .with_circuit_breaker(CircuitBreakerConfig::default().with_failure_threshold(0));
+        assert!(TautulliClient::new(config).is_err());
+    }
+
     #[tokio::test]
     async fn test_rate_limiter_integration() {
         let config = TautulliConfig::new("http://example.com", "test-key")
@@ -848,6 +1286,13 @@ mod tests {
         assert_eq!(entry.percent_complete, Some(95));
     }
 
+    #[test]
+    fn test_partial_watched_status_deserialization() {
+        let json = r#"{"date": 1640995200, "watched_status": 0.5, "percent_complete": 60}"#;
+        let entry: HistoryEntry = serde_json::from_str(json).unwrap();
+        assert_eq!(entry.watched_status, Some(0.5));
+    }
+
     #[test]
     fn test_history_response_deserialization() {
         let json = r#"{
@@ -919,6 +1364,32 @@ mod tests {
     // Error Handling and Metrics Tests
     // ============================================================================
 
+    #[tokio::test]
+    async fn test_fetch_all_history_respects_cancellation() {
+        let client = TautulliClient::with_defaults("http://localhost:8181", "test_key").unwrap();
+        let ctx = CancellationContext::new();
+        ctx.cancel();
+
+        // A cancelled context must fail before any request is sent
+        let err = client.fetch_all_history(&ctx, None, 100).await.unwrap_err();
+        assert!(err.is_cancelled());
+
+        let err = client
+            .get_history_with_context(&ctx, None, Some(25), None)
+            .await
+            .unwrap_err();
+        assert!(err.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_all_history_respects_deadline() {
+        let client = TautulliClient::with_defaults("http://localhost:8181", "test_key").unwrap();
+        let ctx = CancellationContext::with_timeout(Duration::ZERO);
+
+        let err = client.fetch_all_history(&ctx, None, 100).await.unwrap_err();
+        assert!(err.is_deadline_exceeded());
+    }
+
     #[test]
     fn test_client_metrics() {
         let config = TautulliConfig::new("http://example.com", "test-key")
@@ -980,6 +1451,54 @@ mod tests {
         assert!(response.data().is_none());
     }
 
+    #[test]
+    fn test_graph_series_response_parsing() {
+        let graph_json = r#"{
+            "response": {
+                "result": "success",
+                "message": null,
+                "data": {
+                    "categories": ["Roku", "Chrome"],
+                    "series": [
+                        {"name": "Direct Play", "data": [12, 3]},
+                        {"name": "Transcode", "data": [1, 7]}
+                    ]
+                }
+            }
+        }"#;
+
+        let response: TautulliResponse<GraphSeriesResponse> = serde_json::from_str(graph_json).unwrap();
+        let data = response.data().unwrap();
+        assert_eq!(data.categories, vec!["Roku", "Chrome"]);
+        assert_eq!(data.series[1].name, "Transcode");
+        assert_eq!(data.series[1].data, vec![1, 7]);
+    }
+
+    #[test]
+    fn test_geoip_response_parsing() {
+        let geoip_json = r#"{
+            "response": {
+                "result": "success",
+                "message": null,
+                "data": {
+                    "city": "Denver",
+                    "region": "Colorado",
+                    "country": "United States",
+                    "code": "US",
+                    "continent": "North America",
+                    "latitude": 39.7392,
+                    "longitude": -104.9903,
+                    "timezone": "America/Denver"
+                }
+            }
+        }"#;
+
+        let response: TautulliResponse<GeoIpInfo> = serde_json::from_str(geoip_json).unwrap();
+        let data = response.data().unwrap();
+        assert_eq!(data.country.as_deref(), Some("United States"));
+        assert_eq!(data.code.as_deref(), Some("US"));
+    }
+
     #[test]
     fn test_client_metrics_serialization() {
         let config = TautulliConfig::new("http://localhost:8181", "api-key-123");
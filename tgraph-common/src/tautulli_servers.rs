@@ -0,0 +1,394 @@
+//! Named Tautulli servers for multi-server deployments
+//!
+//! A [`TautulliServers`] registry holds one [`TautulliClient`] per configured
+//! Tautulli instance so graphs can target a single server or merge history
+//! from all of them while keeping track of which server each entry came from.
+
+use crate::cancellation::CancellationContext;
+use crate::error::{Result, TGraphError};
+use crate::circuit_breaker::CircuitState;
+use crate::tautulli::{ClientMetrics, HistoryEntry, TautulliClient, TautulliConfig};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard};
+use tracing::{debug, info};
+
+/// Which Tautulli server(s) a request should use
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ServerSelection {
+    /// A single server, by name
+    Server(String),
+    /// Every configured server
+    All,
+}
+
+impl ServerSelection {
+    /// Name used in cache keys and titles, `None` when every server is selected
+    pub fn server_name(&self) -> Option<&str> {
+        match self {
+            ServerSelection::Server(name) => Some(name),
+            ServerSelection::All => None,
+        }
+    }
+}
+
+impl fmt::Display for ServerSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerSelection::Server(name) => write!(f, "{}", name),
+            ServerSelection::All => write!(f, "All servers"),
+        }
+    }
+}
+
+/// History fetched from one named server
+#[derive(Debug, Clone)]
+pub struct ServerHistory {
+    /// Name of the server the entries came from
+    pub server: String,
+    /// History entries from that server
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// Client metrics for one named server
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerMetrics {
+    /// Name of the server
+    pub server: String,
+    /// Metrics reported by that server's client
+    pub metrics: ClientMetrics,
+}
+
+/// Registry of named Tautulli clients
+///
+/// The registry can be reconfigured while shared, so lookups return cheap
+/// clones of the clients rather than references.
+#[derive(Debug, Default)]
+pub struct TautulliServers {
+    servers: RwLock<Vec<(String, TautulliClient)>>,
+}
+
+impl Clone for TautulliServers {
+    fn clone(&self) -> Self {
+        Self {
+            servers: RwLock::new(self.read().clone()),
+        }
+    }
+}
+
+impl TautulliServers {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a registry from named client configurations
+    pub fn from_configs(
+        configs: impl IntoIterator<Item = (String, TautulliConfig)>,
+    ) -> Result<Self> {
+        let mut servers = Self::new();
+        for (name, config) in configs {
+            servers.add(name, TautulliClient::new(config)?)?;
+        }
+        Ok(servers)
+    }
+
+    /// Register a client under a unique name
+    pub fn add(&mut self, name: impl Into<String>, client: TautulliClient) -> Result<()> {
+        let name = name.into();
+        if name.trim().is_empty() {
+            return Err(TGraphError::validation_field("Server name cannot be empty", "name"));
+        }
+        if self.get(&name).is_some() {
+            return Err(TGraphError::validation_field(
+                format!("Duplicate Tautulli server name: {}", name),
+                "name",
+            ));
+        }
+
+        info!("Registered Tautulli server: {}", name);
+        self.servers
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((name, client));
+        Ok(())
+    }
+
+    /// Replace the servers with a new set of named configurations
+    ///
+    /// Clients whose name and configuration are unchanged are kept, so their
+    /// metrics and circuit breaker state survive. On error the current
+    /// servers stay in place.
+    pub fn reconfigure(
+        &self,
+        configs: impl IntoIterator<Item = (String, TautulliConfig)>,
+    ) -> Result<()> {
+        let current = self.read().clone();
+        let mut updated = Self::new();
+        for (name, config) in configs {
+            let existing = current
+                .iter()
+                .find(|(server, client)| *server == name && *client.config() == config);
+            let client = match existing {
+                Some((_, client)) => client.clone(),
+                None => TautulliClient::new(config)?,
+            };
+            updated.add(name, client)?;
+        }
+
+        let updated = updated.servers.into_inner().unwrap_or_else(PoisonError::into_inner);
+        info!("Reconfigured Tautulli servers: {} configured", updated.len());
+        *self.servers.write().unwrap_or_else(PoisonError::into_inner) = updated;
+        Ok(())
+    }
+
+    /// Get the client for a named server, ignoring case like the config validation
+    pub fn get(&self, name: &str) -> Option<TautulliClient> {
+        self.find(name).map(|(_, client)| client)
+    }
+
+    /// Get a named server's client along with the name it is configured under
+    pub fn find(&self, name: &str) -> Option<(String, TautulliClient)> {
+        self.read()
+            .iter()
+            .find(|(server, _)| same_name(server, name))
+            .cloned()
+    }
+
+    /// The first configured server
+    pub fn primary(&self) -> Option<(String, TautulliClient)> {
+        self.read().first().cloned()
+    }
+
+    /// Names of all servers in configuration order
+    pub fn names(&self) -> Vec<String> {
+        self.read().iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Number of configured servers
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Whether no servers are configured
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Client metrics for every server in configuration order
+    pub fn client_metrics(&self) -> Vec<ServerMetrics> {
+        self.read()
+            .iter()
+            .map(|(name, client)| ServerMetrics {
+                server: name.clone(),
+                metrics: client.get_client_metrics(),
+            })
+            .collect()
+    }
+
+    /// Names of servers whose circuit breaker is not closed
+    pub fn unavailable_servers(&self) -> Vec<String> {
+        self.read()
+            .iter()
+            .filter(|(_, client)| client.circuit_breaker().state() != CircuitState::Closed)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Resolve a selection into the named clients it refers to
+    pub fn select(&self, selection: &ServerSelection) -> Result<Vec<(String, TautulliClient)>> {
+        let servers = self.read();
+        match selection {
+            ServerSelection::Server(name) => servers
+                .iter()
+                .find(|(server, _)| same_name(server, name))
+                .map(|server| vec![server.clone()])
+                .ok_or_else(|| {
+                    TGraphError::validation_field(
+                        format!("Unknown Tautulli server: {}", name),
+                        "server",
+                    )
+                }),
+            ServerSelection::All => {
+                if servers.is_empty() {
+                    Err(TGraphError::config("No Tautulli servers are configured"))
+                } else {
+                    Ok(servers.clone())
+                }
+            }
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Vec<(String, TautulliClient)>> {
+        self.servers.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Fetch the complete history from the selected servers
+    ///
+    /// Servers are queried concurrently. Results keep the configuration order,
+    /// and the first error (including cancellation) aborts the whole fetch.
+    /// Cancellation and open circuits are returned as they are, so callers
+    /// can tell them apart from failed requests.
+    pub async fn fetch_history(
+        &self,
+        ctx: &CancellationContext,
+        selection: &ServerSelection,
+        user_id: Option<i32>,
+        page_size: i32,
+    ) -> Result<Vec<ServerHistory>> {
+        let selected = self.select(selection)?;
+        debug!("Fetching history from {} Tautulli server(s)", selected.len());
+
+        let mut join_set = tokio::task::JoinSet::new();
+        for (index, (name, client)) in selected.into_iter().enumerate() {
+            let ctx = ctx.clone();
+            join_set.spawn(async move {
+                let entries = client.fetch_all_history(&ctx, user_id, page_size).await;
+                (index, name, entries)
+            });
+        }
+
+        let mut results = Vec::with_capacity(join_set.len());
+        while let Some(joined) = join_set.join_next().await {
+            let (index, server, entries) = joined
+                .map_err(|e| TGraphError::new(format!("History fetch task failed: {}", e)))?;
+            let entries = entries.map_err(|e| match e {
+                TGraphError::Cancelled { .. }
+                | TGraphError::DeadlineExceeded { .. }
+                | TGraphError::ServiceUnavailable { .. } => e,
+                other => TGraphError::with_source(
+                    format!("Failed to fetch history from server '{}'", server),
+                    other,
+                ),
+            })?;
+            results.push((index, ServerHistory { server, entries }));
+        }
+
+        results.sort_by_key(|(index, _)| *index);
+        Ok(results.into_iter().map(|(_, history)| history).collect())
+    }
+}
+
+/// Server names are unique and looked up without regard to case
+fn same_name(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_breaker::CircuitBreakerConfig;
+
+    fn test_servers() -> TautulliServers {
+        TautulliServers::from_configs(vec![
+            ("Home".to_string(), TautulliConfig::new("http://home:8181", "key1")),
+            ("Cabin".to_string(), TautulliConfig::new("http://cabin:8181", "key2")),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let servers = test_servers();
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers.names(), vec!["Home", "Cabin"]);
+        assert_eq!(servers.primary().unwrap().0, "Home");
+        assert!(servers.get("Cabin").is_some());
+        assert!(servers.get("Office").is_none());
+        assert!(servers.get("cabin").is_some());
+        assert_eq!(servers.find("cabin").unwrap().0, "Cabin");
+
+        let metrics = servers.client_metrics();
+        assert_eq!(metrics[1].server, "Cabin");
+        assert_eq!(metrics[1].metrics.circuit_breaker.state, CircuitState::Closed);
+        assert!(servers.unavailable_servers().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_names_rejected() {
+        let mut servers = test_servers();
+        let client = TautulliClient::with_defaults("http://other:8181", "key3").unwrap();
+        assert!(servers.add("Home", client.clone()).is_err());
+        assert!(servers.add("HOME", client.clone()).is_err());
+        assert!(servers.add("  ", client).is_err());
+    }
+
+    #[test]
+    fn test_reconfigure_keeps_unchanged_clients() {
+        let servers = test_servers();
+        let home = servers.get("Home").unwrap();
+        home.circuit_breaker().acquire().unwrap().failure();
+
+        servers
+            .reconfigure(vec![
+                ("Home".to_string(), TautulliConfig::new("http://home:8181", "key1")),
+                ("Office".to_string(), TautulliConfig::new("http://office:8181", "key3")),
+            ])
+            .unwrap();
+        assert_eq!(servers.names(), vec!["Home", "Office"]);
+        assert_eq!(servers.get("Home").unwrap().circuit_breaker().snapshot().consecutive_failures, 1);
+
+        // A changed key replaces the client; invalid sets leave the servers alone
+        servers
+            .reconfigure(vec![("Home".to_string(), TautulliConfig::new("http://home:8181", "new"))])
+            .unwrap();
+        assert_eq!(servers.get("Home").unwrap().config().api_key, "new");
+        assert_eq!(servers.get("Home").unwrap().circuit_breaker().snapshot().consecutive_failures, 0);
+
+        let duplicate = ("Home".to_string(), TautulliConfig::new("http://x:8181", "k"));
+        assert!(servers.reconfigure(vec![duplicate.clone(), duplicate]).is_err());
+        assert_eq!(servers.names(), vec!["Home"]);
+    }
+
+    #[test]
+    fn test_selection() {
+        let servers = test_servers();
+
+        let single = servers.select(&ServerSelection::Server("cabin".to_string())).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].0, "Cabin");
+
+        let all = servers.select(&ServerSelection::All).unwrap();
+        assert_eq!(all.len(), 2);
+
+        assert!(servers.select(&ServerSelection::Server("Office".to_string())).is_err());
+        assert!(TautulliServers::new().select(&ServerSelection::All).is_err());
+    }
+
+    #[test]
+    fn test_selection_display() {
+        assert_eq!(ServerSelection::Server("Home".to_string()).to_string(), "Home");
+        assert_eq!(ServerSelection::All.to_string(), "All servers");
+        assert_eq!(ServerSelection::All.server_name(), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_history_honours_cancellation() {
+        let servers = test_servers();
+        let ctx = CancellationContext::new();
+        ctx.cancel();
+
+        let err = servers
+            .fetch_history(&ctx, &ServerSelection::All, None, 100)
+            .await
+            .unwrap_err();
+        assert!(err.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_history_reports_open_circuit() {
+        let breaker = CircuitBreakerConfig::default().with_failure_threshold(1);
+        let servers = TautulliServers::from_configs(vec![(
+            "Home".to_string(),
+            TautulliConfig::new("http://home:8181", "key1").with_circuit_breaker(breaker),
+        )])
+        .unwrap();
+        servers.get("Home").unwrap().circuit_breaker().acquire().unwrap().failure();
+
+        let err = servers
+            .fetch_history(&CancellationContext::new(), &ServerSelection::All, None, 100)
+            .await
+            .unwrap_err();
+        assert!(err.is_service_unavailable());
+    }
+}
@@ -0,0 +1,148 @@
+//! Configuration change notifications
+//!
+//! [`ConfigManager`](crate::ConfigManager) publishes every accepted change as
+//! a [`ConfigChange`] listing the sections that differ. Running subsystems
+//! implement [`ConfigSubscriber`] to apply the sections they care about.
+
+use crate::Config;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+
+/// Top-level sections of the configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigSection {
+    Discord,
+    Tautulli,
+    Scheduling,
+    Graph,
+    Database,
+    Logging,
+    Privacy,
+}
+
+impl ConfigSection {
+    /// Every section, in configuration file order
+    pub const ALL: [ConfigSection; 7] = [
+        Self::Discord,
+        Self::Tautulli,
+        Self::Scheduling,
+        Self::Graph,
+        Self::Database,
+        Self::Logging,
+        Self::Privacy,
+    ];
+
+    /// Key of the section in configuration files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Discord => "discord",
+            Self::Tautulli => "tautulli",
+            Self::Scheduling => "scheduling",
+            Self::Graph => "graph",
+            Self::Database => "database",
+            Self::Logging => "logging",
+            Self::Privacy => "privacy",
+        }
+    }
+
+    fn value(&self, config: &Config) -> serde_json::Value {
+        fn to_value(section: &impl Serialize) -> serde_json::Value {
+            serde_json::to_value(section).unwrap_or(serde_json::Value::Null)
+        }
+
+        match self {
+            Self::Discord => to_value(&config.discord),
+            Self::Tautulli => to_value(&config.tautulli),
+            Self::Scheduling => to_value(&config.scheduling),
+            Self::Graph => to_value(&config.graph),
+            Self::Database => to_value(&config.database),
+            Self::Logging => to_value(&config.logging),
+            Self::Privacy => to_value(&config.privacy),
+        }
+    }
+}
+
+impl fmt::Display for ConfigSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Sections whose values differ between two configurations
+pub fn diff_sections(old: &Config, new: &Config) -> Vec<ConfigSection> {
+    ConfigSection::ALL
+        .into_iter()
+        .filter(|section| section.value(old) != section.value(new))
+        .collect()
+}
+
+/// An accepted configuration change
+#[derive(Debug, Clone)]
+pub struct ConfigChange {
+    /// Configuration before the change
+    pub previous: Arc<Config>,
+    /// Configuration now in effect
+    pub current: Arc<Config>,
+    /// Sections that differ between the two
+    pub changed: Vec<ConfigSection>,
+}
+
+impl ConfigChange {
+    /// Initial state published before any change, with nothing changed
+    pub fn initial(config: Arc<Config>) -> Self {
+        Self {
+            previous: Arc::clone(&config),
+            current: config,
+            changed: Vec::new(),
+        }
+    }
+
+    /// Whether the change touches a section
+    pub fn touches(&self, section: ConfigSection) -> bool {
+        self.changed.contains(&section)
+    }
+
+    /// Whether the change touches any of the given sections
+    pub fn touches_any(&self, sections: &[ConfigSection]) -> bool {
+        sections.iter().any(|section| self.touches(*section))
+    }
+}
+
+/// A running subsystem that applies configuration changes live
+#[async_trait]
+pub trait ConfigSubscriber: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> &str;
+
+    /// Sections the subscriber depends on
+    fn sections(&self) -> &[ConfigSection];
+
+    /// Apply the new configuration
+    async fn apply(&self, config: &Config) -> Result<(), String>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_sections() {
+        let old = Config::default();
+        assert!(diff_sections(&old, &old.clone()).is_empty());
+
+        let mut new = old.clone();
+        new.graph.width = 1280;
+        new.scheduling.enabled = !old.scheduling.enabled;
+        assert_eq!(diff_sections(&old, &new), vec![ConfigSection::Scheduling, ConfigSection::Graph]);
+
+        let change = ConfigChange {
+            previous: Arc::new(old),
+            current: Arc::new(new),
+            changed: vec![ConfigSection::Graph],
+        };
+        assert!(change.touches(ConfigSection::Graph));
+        assert!(!change.touches_any(&[ConfigSection::Discord, ConfigSection::Tautulli]));
+    }
+}
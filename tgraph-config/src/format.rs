@@ -0,0 +1,187 @@
+//! Configuration file formats
+
+use crate::loader::ConfigError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// File names searched, in order, when no configuration path is given
+pub const DEFAULT_CONFIG_FILES: &[&str] = &["config.toml", "config.yaml", "config.yml", "config.json"];
+
+/// Supported configuration file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Format implied by a file extension, if it is a known one
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from file content
+    ///
+    /// Looks at the first line that is not blank or a comment: `{` means
+    /// JSON, a `[table]` header or `key = value` means TOML, anything else is
+    /// treated as YAML.
+    pub fn sniff(content: &str) -> Self {
+        let first_line = content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"));
+
+        let Some(line) = first_line else {
+            return Self::Yaml;
+        };
+        if line.starts_with('{') {
+            return Self::Json;
+        }
+        if line.starts_with('[') && line.ends_with(']') && !line.contains(',') {
+            return Self::Toml;
+        }
+        match line.split_once('=') {
+            Some((key, _)) if !key.contains(':') && !key.trim().is_empty() => Self::Toml,
+            _ => Self::Yaml,
+        }
+    }
+
+    /// Format for a file: its extension if known, otherwise sniffed from content
+    pub fn detect(path: &Path, content: &str) -> Self {
+        Self::from_extension(path).unwrap_or_else(|| Self::sniff(content))
+    }
+
+    /// Parse `content` read from `path` in this format
+    pub fn parse<T: DeserializeOwned>(self, path: &Path, content: &str) -> Result<T, ConfigError> {
+        let (location, message) = match self {
+            Self::Toml => match toml::from_str(content) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let location = e.span().map(|span| line_column(content, span.start));
+                    (location, e.message().trim().to_string())
+                }
+            },
+            Self::Yaml => match serde_yaml::from_str(content) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let location = e.location().map(|l| (l.line(), l.column()));
+                    (location, strip_location(e.to_string(), location))
+                }
+            },
+            Self::Json => match serde_json::from_str(content) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let location = Some((e.line(), e.column())).filter(|(line, _)| *line > 0);
+                    (location, strip_location(e.to_string(), location))
+                }
+            },
+        };
+
+        Err(ConfigError::ParseError {
+            path: path.to_path_buf(),
+            format: self,
+            line: location.map(|(line, _)| line),
+            column: location.map(|(_, column)| column),
+            message,
+        })
+    }
+
+    /// Serialize a value in this format
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String, ConfigError> {
+        let serialized = match self {
+            Self::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string()),
+            Self::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+            Self::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+        };
+        serialized.map_err(|message| ConfigError::SerializeError { format: self, message })
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Toml => "TOML",
+            Self::Yaml => "YAML",
+            Self::Json => "JSON",
+        })
+    }
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Drop the " at line X column Y" serde_yaml and serde_json add to messages
+fn strip_location(message: String, location: Option<(usize, usize)>) -> String {
+    match location {
+        Some((line, column)) => message.replacen(&format!(" at line {} column {}", line, column), "", 1),
+        None => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(ConfigFormat::from_extension(Path::new("config.TOML")), Some(ConfigFormat::Toml));
+        assert_eq!(ConfigFormat::from_extension(Path::new("config.yml")), Some(ConfigFormat::Yaml));
+        assert_eq!(ConfigFormat::from_extension(Path::new("config.json")), Some(ConfigFormat::Json));
+        assert_eq!(ConfigFormat::from_extension(Path::new("config")), None);
+
+        assert_eq!(ConfigFormat::sniff("# comment\n\n[discord]\ntoken = \"x\""), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::sniff("title = \"x\"\n"), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::sniff("# comment\ndiscord:\n  token: x"), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::sniff("url: \"http://a?b=c\""), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::sniff("  {\"discord\": {}}"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::sniff(""), ConfigFormat::Yaml);
+
+        // The extension wins over the content
+        assert_eq!(ConfigFormat::detect(Path::new("c.yaml"), "[discord]"), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::detect(Path::new("c.conf"), "[discord]"), ConfigFormat::Toml);
+    }
+
+    #[test]
+    fn test_parse_error_locations() {
+        let path = PathBuf::from("bad.toml");
+        let err = ConfigFormat::Toml
+            .parse::<toml::Value>(&path, "[discord]\ntoken = \"x\"\nwidth = = 3\n")
+            .unwrap_err();
+        match &err {
+            ConfigError::ParseError { line, column, format, .. } => {
+                assert_eq!(*format, ConfigFormat::Toml);
+                assert_eq!((*line, *column), (Some(3), Some(9)));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        assert!(err.to_string().starts_with("Failed to parse TOML configuration at bad.toml:3:9: "));
+
+        let err = ConfigFormat::Yaml
+            .parse::<serde_yaml::Value>(Path::new("bad.yaml"), "discord:\n  token: x\n  width: y: z\n")
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::ParseError { line: Some(3), .. }), "{}", err);
+        assert!(err.to_string().contains("bad.yaml:3:"), "{}", err);
+        assert_eq!(err.to_string().matches(" at line ").count(), 0, "{}", err);
+
+        let err = ConfigFormat::Json
+            .parse::<serde_json::Value>(Path::new("bad.json"), "{\n  \"discord\": {\n    \"token\": ,\n  }\n}")
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::ParseError { line: Some(3), column: Some(14), .. }), "{}", err);
+        assert!(err.to_string().contains("bad.json:3:14: expected value"));
+    }
+}
@@ -0,0 +1,331 @@
+//! Per-guild configuration overrides
+//!
+//! One bot instance can serve several Discord guilds. A guild may override
+//! its channels, locale, graph theme, enabled graph types, schedule and role
+//! permissions; anything it leaves unset falls back to the global
+//! configuration. [`GuildOverrides::apply`] layers the overrides over a
+//! [`Config`].
+
+use crate::loader::ConfigError;
+use crate::validation::cron_min_interval;
+use crate::Config;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use validator::{ValidationError, ValidationErrors};
+
+/// Graph types a guild can enable or disable
+pub const GRAPH_TYPES: &[&str] = &[
+    "daily_play_count",
+    "day_of_week",
+    "hourly_distribution",
+    "monthly_trends",
+    "top_platforms",
+    "top_users",
+    "top_movies",
+    "top_shows",
+    "top_artists",
+];
+
+/// Keys a guild can override, as used by `/config edit`
+pub const GUILD_KEYS: &[&str] = &[
+    "channels",
+    "locale",
+    "theme.background_color",
+    "theme.primary_color",
+    "theme.secondary_color",
+    "theme.font_family",
+    "graph_types",
+    "auto_graph_cron",
+    "timezone",
+    "admin_role_ids",
+    "moderator_role_ids",
+];
+
+/// Shortest time a guild's own `auto_graph_cron` may leave between two posts
+pub const MIN_GUILD_CRON_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Settings of one guild that replace the global ones
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct GuildOverrides {
+    /// Channels the bot posts in, replacing `discord.channels`
+    pub channels: Option<Vec<String>>,
+    /// Locale for messages and week starts, e.g. `de-DE`
+    pub locale: Option<String>,
+    /// Graph colours and font, replacing those in `[graph]`
+    pub theme: GuildTheme,
+    /// Graph types that can be used in the guild; all of them when unset
+    pub graph_types: Option<Vec<String>>,
+    /// Cron expression for automatic graphs, replacing `scheduling.auto_graph_cron`
+    pub auto_graph_cron: Option<String>,
+    /// Timezone for schedules and date ranges, replacing `scheduling.timezone`
+    pub timezone: Option<String>,
+    /// Roles with administrator permissions, replacing `discord.admin_role_ids`
+    pub admin_role_ids: Option<Vec<u64>>,
+    /// Roles with moderator permissions, replacing `discord.moderator_role_ids`
+    pub moderator_role_ids: Option<Vec<u64>>,
+}
+
+/// Graph style of a guild
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct GuildTheme {
+    pub background_color: Option<String>,
+    pub primary_color: Option<String>,
+    pub secondary_color: Option<String>,
+    pub font_family: Option<String>,
+}
+
+impl GuildOverrides {
+    /// Whether nothing is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The global configuration with this guild's overrides applied
+    pub fn apply(&self, global: &Config) -> Config {
+        let mut config = global.clone();
+        if let Some(channels) = &self.channels {
+            config.discord.channels = channels.clone();
+        }
+        if let Some(roles) = &self.admin_role_ids {
+            config.discord.admin_role_ids = roles.clone();
+        }
+        if let Some(roles) = &self.moderator_role_ids {
+            config.discord.moderator_role_ids = roles.clone();
+        }
+        if let Some(cron) = &self.auto_graph_cron {
+            config.scheduling.auto_graph_cron = Some(cron.clone());
+        }
+        if let Some(timezone) = &self.timezone {
+            config.scheduling.timezone = Some(timezone.clone());
+        }
+
+        let theme = &self.theme;
+        let graph = &mut config.graph;
+        for (value, target) in [
+            (&theme.background_color, &mut graph.background_color),
+            (&theme.primary_color, &mut graph.primary_color),
+            (&theme.secondary_color, &mut graph.secondary_color),
+            (&theme.font_family, &mut graph.font_family),
+        ] {
+            if let Some(value) = value {
+                *target = value.clone();
+            }
+        }
+        config
+    }
+
+    /// Validate the configuration the guild ends up with
+    ///
+    /// A guild's own schedule also needs its own channels, so that it never
+    /// posts to the global ones, and may fire at most once per
+    /// [`MIN_GUILD_CRON_INTERVAL`].
+    pub fn validate(&self, global: &Config) -> Result<(), ValidationErrors> {
+        let mut errors = self.apply(global).validate_all().err().unwrap_or_default();
+        if let Some(cron) = &self.auto_graph_cron {
+            let message = if self.channels.is_none() {
+                Some("set this server's channels before giving it its own schedule".to_string())
+            } else {
+                cron_min_interval(cron)
+                    .filter(|interval| *interval < MIN_GUILD_CRON_INTERVAL)
+                    .map(|_| format!("must leave at least {} minutes between posts", MIN_GUILD_CRON_INTERVAL.as_secs() / 60))
+            };
+            if let Some(message) = message {
+                let mut err = ValidationError::new("invalid_guild_schedule");
+                err.message = Some(message.into());
+                errors.add("auto_graph_cron", err);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Whether the guild posts automatic graphs on its own schedule
+    pub fn has_own_schedule(&self) -> bool {
+        self.auto_graph_cron.is_some() && self.channels.is_some()
+    }
+
+    /// Whether a graph type from [`GRAPH_TYPES`] can be used in the guild
+    pub fn graph_enabled(&self, graph_type: &str) -> bool {
+        self.graph_types
+            .as_ref()
+            .map_or(true, |enabled| enabled.iter().any(|name| name == graph_type))
+    }
+
+    /// Override `key` with a value typed into `/config edit`
+    ///
+    /// Lists are separated by commas or spaces and accept channel and role
+    /// mentions as well as plain IDs. Channels must be among `guild_channels`,
+    /// the IDs of the guild's own channels.
+    pub fn set(&mut self, key: &str, raw: &str, guild_channels: &[u64]) -> Result<(), ConfigError> {
+        let invalid = |message: String| ConfigError::InvalidOverride {
+            key: key.to_string(),
+            message,
+        };
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Err(invalid("a value is required; use reset to remove the override".to_string()));
+        }
+
+        match key {
+            "channels" => {
+                let ids = parse_ids(raw).map_err(invalid)?;
+                if let Some(foreign) = ids.iter().find(|id| !guild_channels.contains(id)) {
+                    return Err(invalid(format!("channel {} is not in this server", foreign)));
+                }
+                self.channels = Some(ids.iter().map(u64::to_string).collect());
+            }
+            "locale" => self.locale = Some(raw.to_string()),
+            "theme.background_color" => self.theme.background_color = Some(raw.to_string()),
+            "theme.primary_color" => self.theme.primary_color = Some(raw.to_string()),
+            "theme.secondary_color" => self.theme.secondary_color = Some(raw.to_string()),
+            "theme.font_family" => self.theme.font_family = Some(raw.to_string()),
+            "graph_types" => {
+                let names: Vec<String> = split_list(raw).map(str::to_string).collect();
+                if let Some(unknown) = names.iter().find(|name| !GRAPH_TYPES.contains(&name.as_str())) {
+                    return Err(invalid(format!("unknown graph type '{}', expected one of {}", unknown, GRAPH_TYPES.join(", "))));
+                }
+                self.graph_types = Some(names);
+            }
+            "auto_graph_cron" => self.auto_graph_cron = Some(raw.to_string()),
+            "timezone" => self.timezone = Some(raw.to_string()),
+            "admin_role_ids" => self.admin_role_ids = Some(parse_ids(raw).map_err(invalid)?),
+            "moderator_role_ids" => self.moderator_role_ids = Some(parse_ids(raw).map_err(invalid)?),
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// Remove the override for `key`, falling back to the global value
+    pub fn reset(&mut self, key: &str) -> Result<(), ConfigError> {
+        match key {
+            "channels" => self.channels = None,
+            "locale" => self.locale = None,
+            "theme.background_color" => self.theme.background_color = None,
+            "theme.primary_color" => self.theme.primary_color = None,
+            "theme.secondary_color" => self.theme.secondary_color = None,
+            "theme.font_family" => self.theme.font_family = None,
+            "graph_types" => self.graph_types = None,
+            "auto_graph_cron" => self.auto_graph_cron = None,
+            "timezone" => self.timezone = None,
+            "admin_role_ids" => self.admin_role_ids = None,
+            "moderator_role_ids" => self.moderator_role_ids = None,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// Every key with its effective value and whether the guild overrides it
+    pub fn describe(&self, global: &Config) -> Vec<(&'static str, String, bool)> {
+        let config = self.apply(global);
+        let list = |values: &[String]| if values.is_empty() { "none".to_string() } else { values.join(", ") };
+        let ids = |values: &[u64]| list(&values.iter().map(u64::to_string).collect::<Vec<_>>());
+        let optional = |value: &Option<String>| value.clone().unwrap_or_else(|| "not set".to_string());
+
+        GUILD_KEYS
+            .iter()
+            .map(|&key| {
+                let (value, overridden) = match key {
+                    "channels" => (list(&config.discord.channels), self.channels.is_some()),
+                    "locale" => (optional(&self.locale), self.locale.is_some()),
+                    "theme.background_color" => (config.graph.background_color.clone(), self.theme.background_color.is_some()),
+                    "theme.primary_color" => (config.graph.primary_color.clone(), self.theme.primary_color.is_some()),
+                    "theme.secondary_color" => (config.graph.secondary_color.clone(), self.theme.secondary_color.is_some()),
+                    "theme.font_family" => (config.graph.font_family.clone(), self.theme.font_family.is_some()),
+                    "graph_types" => (
+                        self.graph_types.as_deref().map_or_else(|| "all".to_string(), list),
+                        self.graph_types.is_some(),
+                    ),
+                    "auto_graph_cron" => (optional(&config.scheduling.auto_graph_cron), self.auto_graph_cron.is_some()),
+                    "timezone" => (optional(&config.scheduling.timezone), self.timezone.is_some()),
+                    "admin_role_ids" => (ids(&config.discord.admin_role_ids), self.admin_role_ids.is_some()),
+                    _ => (ids(&config.discord.moderator_role_ids), self.moderator_role_ids.is_some()),
+                };
+                (key, value, overridden)
+            })
+            .collect()
+    }
+}
+
+fn unknown_key(key: &str) -> ConfigError {
+    ConfigError::InvalidOverride {
+        key: key.to_string(),
+        message: format!("not a guild setting, expected one of {}", GUILD_KEYS.join(", ")),
+    }
+}
+
+fn split_list(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split(|c: char| c == ',' || c.is_whitespace()).filter(|part| !part.is_empty())
+}
+
+/// IDs from a list of plain IDs or `<#channel>` / `<@&role>` mentions
+fn parse_ids(raw: &str) -> Result<Vec<u64>, String> {
+    split_list(raw)
+        .map(|part| {
+            let id = part.trim_start_matches("<#").trim_start_matches("<@&").trim_end_matches('>');
+            id.parse().map_err(|_| format!("'{}' is not a Discord ID", part))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_layer_over_global() {
+        let mut global = Config::default();
+        global.discord.channels = vec!["1".to_string()];
+        global.discord.admin_role_ids = vec![10];
+
+        let mut guild = GuildOverrides::default();
+        assert!(guild.is_empty());
+        guild.set("channels", "<#2>, 3", &[2, 3]).unwrap();
+        assert!(guild.set("channels", "2, 4", &[2, 3]).is_err());
+        guild.set("theme.primary_color", "#112233", &[]).unwrap();
+        guild.set("graph_types", "daily_play_count top_users", &[]).unwrap();
+        assert!(guild.set("graph_types", "pie_of_the_day", &[]).is_err());
+        assert!(guild.set("prefix", "!", &[]).is_err());
+
+        let config = guild.apply(&global);
+        assert_eq!(config.discord.channels, vec!["2", "3"]);
+        assert_eq!(config.discord.admin_role_ids, vec![10]);
+        assert_eq!(config.graph.primary_color, "#112233");
+        assert_eq!(config.graph.background_color, global.graph.background_color);
+        assert!(guild.graph_enabled("top_users"));
+        assert!(!guild.graph_enabled("monthly_trends"));
+
+        let described = guild.describe(&global);
+        assert!(described.contains(&("channels", "2, 3".to_string(), true)));
+        assert!(described.contains(&("admin_role_ids", "10".to_string(), false)));
+
+        guild.set("theme.primary_color", "teal", &[]).unwrap();
+        assert!(guild.validate(&global).is_err());
+        guild.reset("theme.primary_color").unwrap();
+        assert!(guild.validate(&global).is_ok());
+    }
+
+    #[test]
+    fn test_own_schedule_needs_channels_and_an_interval() {
+        let global = Config::default();
+        let mut guild = GuildOverrides::default();
+        guild.set("auto_graph_cron", "0 0 18 * * *", &[]).unwrap();
+        assert!(!guild.has_own_schedule());
+        assert!(guild.validate(&global).is_err());
+
+        guild.set("channels", "2", &[2]).unwrap();
+        assert!(guild.has_own_schedule());
+        assert!(guild.validate(&global).is_ok());
+
+        for too_often in ["* * * * * *", "0 */5 * * * *", "0 0,30 18 * * *"] {
+            guild.set("auto_graph_cron", too_often, &[]).unwrap();
+            assert!(guild.validate(&global).is_err(), "{}", too_often);
+        }
+    }
+}
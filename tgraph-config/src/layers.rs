@@ -0,0 +1,511 @@
+//! Layered configuration
+//!
+//! Values are merged in order: built-in defaults, the main configuration
+//! file, `conf.d/*.toml` fragments, environment variables and finally
+//! `--set key=value` overrides from the command line. Every value remembers
+//! the layer that supplied it.
+
+use crate::format::ConfigFormat;
+use crate::loader::ConfigError;
+use crate::migrate::{dropped_settings, migrate_as, DocumentKind};
+use crate::secrets::{SecretResolver, REDACTED, SECRET_KEYS};
+use crate::Config;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Prefix of the generated environment variables, e.g. `TGRAPH__GRAPH__WIDTH`
+pub const ENV_PREFIX: &str = "TGRAPH__";
+
+/// Separator between key segments in generated environment variables
+pub const ENV_SEPARATOR: &str = "__";
+
+/// Name of the fragment directory next to the main configuration file
+pub const FRAGMENT_DIR: &str = "conf.d";
+
+/// Older environment variables that map onto a single key
+const LEGACY_ENV_VARS: &[(&str, &str)] = &[
+    ("DISCORD_TOKEN", "discord.token"),
+    ("DISCORD_TOKEN_FILE", "discord.token_file"),
+    ("DISCORD_CHANNELS", "discord.channels"),
+    ("DISCORD_TIMEOUT", "discord.request_timeout_seconds"),
+    ("DISCORD_MAX_REQUESTS", "discord.max_concurrent_requests"),
+    ("TAUTULLI_URL", "tautulli.url"),
+    ("TAUTULLI_API_KEY", "tautulli.api_key"),
+    ("TAUTULLI_API_KEY_FILE", "tautulli.api_key_file"),
+    ("TAUTULLI_TIMEOUT", "tautulli.timeout_seconds"),
+    ("TAUTULLI_MAX_RETRIES", "tautulli.max_retries"),
+    ("AUTO_GRAPH_CRON", "scheduling.auto_graph_cron"),
+    ("CLEANUP_CRON", "scheduling.cleanup_cron"),
+    ("TIMEZONE", "scheduling.timezone"),
+    ("SCHEDULING_ENABLED", "scheduling.enabled"),
+    ("GRAPH_WIDTH", "graph.width"),
+    ("GRAPH_HEIGHT", "graph.height"),
+    ("GRAPH_BACKGROUND_COLOR", "graph.background_color"),
+    ("GRAPH_PRIMARY_COLOR", "graph.primary_color"),
+    ("GRAPH_SECONDARY_COLOR", "graph.secondary_color"),
+    ("GRAPH_FONT_FAMILY", "graph.font_family"),
+    ("GRAPH_FONT_SIZE", "graph.font_size"),
+    ("GRAPH_SHOW_GRID", "graph.show_grid"),
+    ("GRAPH_SHOW_LEGEND", "graph.show_legend"),
+    ("GRAPH_MAX_DATA_POINTS", "graph.max_data_points"),
+    ("DATABASE_URL", "database.url"),
+    ("DATABASE_MAX_CONNECTIONS", "database.max_connections"),
+    ("DATABASE_CONNECTION_TIMEOUT", "database.connection_timeout_seconds"),
+    ("DATABASE_QUERY_TIMEOUT", "database.query_timeout_seconds"),
+    ("LOG_LEVEL", "logging.level"),
+    ("LOG_FILE", "logging.file"),
+    ("LOG_COLORED", "logging.colored"),
+    ("LOG_INCLUDE_TIMESTAMPS", "logging.include_timestamps"),
+    ("LOG_INCLUDE_LOCATION", "logging.include_location"),
+    ("LOG_MAX_FILE_SIZE_MB", "logging.max_file_size_mb"),
+    ("LOG_MAX_FILES", "logging.max_files"),
+];
+
+/// The layer a configuration value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// The main configuration file
+    File(PathBuf),
+    /// A fragment in the `conf.d` directory
+    Fragment(PathBuf),
+    /// An environment variable
+    Env(String),
+    /// A `--set` command line override
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Fragment(path) => write!(f, "fragment {}", path.display()),
+            Self::Env(var) => write!(f, "env {}", var),
+            Self::Cli => write!(f, "--set"),
+        }
+    }
+}
+
+/// Configuration merged from several layers, with the source of each value
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    value: Value,
+    sources: BTreeMap<String, ConfigSource>,
+    main_file: Option<PathBuf>,
+}
+
+impl LayeredConfig {
+    /// Start from the built-in defaults
+    pub fn defaults() -> Self {
+        let value = serde_json::to_value(Config::default()).expect("default configuration serializes");
+        let mut layered = Self {
+            value: Value::Object(Map::new()),
+            sources: BTreeMap::new(),
+            main_file: None,
+        };
+        layered.merge(value, &ConfigSource::Default);
+        layered
+    }
+
+    /// Merge the main configuration file, in any supported format
+    pub fn merge_file(&mut self, path: &Path) -> Result<(), ConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        let value = ConfigFormat::detect(path, &content).parse(path, &content)?;
+        let report = migrate_as(DocumentKind::Config, value)?;
+        if !report.is_noop() {
+            tracing::info!(
+                "Read {} as configuration version {}; run `tgraph-bot config migrate` to upgrade it to version {}",
+                path.display(),
+                report.from,
+                report.to
+            );
+        }
+        for note in &report.notes {
+            tracing::warn!("{}: {}", path.display(), note);
+        }
+        self.merge(report.migrated, &ConfigSource::File(path.to_path_buf()));
+        self.main_file = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// The main configuration file merged, if any
+    pub fn main_file(&self) -> Option<&Path> {
+        self.main_file.as_deref()
+    }
+
+    /// Merge an already parsed document as a layer
+    pub fn merge_value(&mut self, value: Value, source: ConfigSource) {
+        self.merge(value, &source);
+    }
+
+    /// Merge every `*.toml` fragment in `dir`, in file name order
+    ///
+    /// A missing directory is not an error.
+    pub fn merge_fragments(&mut self, dir: &Path) -> Result<(), ConfigError> {
+        if !dir.is_dir() {
+            return Ok(());
+        }
+        let mut fragments: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && ConfigFormat::from_extension(path) == Some(ConfigFormat::Toml))
+            .collect();
+        fragments.sort();
+
+        for path in fragments {
+            let content = std::fs::read_to_string(&path)?;
+            let value = ConfigFormat::Toml.parse(&path, &content)?;
+            self.merge(value, &ConfigSource::Fragment(path));
+        }
+        Ok(())
+    }
+
+    /// Apply environment variables
+    ///
+    /// The older fixed names such as `DISCORD_TOKEN` are applied first, then
+    /// `TGRAPH__SECTION__KEY` variables, which exist for every key.
+    pub fn apply_env<I>(&mut self, vars: I) -> Result<(), ConfigError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let vars: BTreeMap<String, String> = vars.into_iter().collect();
+
+        for (var, key) in LEGACY_ENV_VARS {
+            if let Some(raw) = vars.get(*var) {
+                self.set(key, raw, ConfigSource::Env(var.to_string()))
+                    .map_err(|source| ConfigError::EnvParseError {
+                        var: var.to_string(),
+                        source,
+                    })?;
+            }
+        }
+
+        for (var, raw) in vars.iter().filter(|(var, _)| var.starts_with(ENV_PREFIX)) {
+            let key = var[ENV_PREFIX.len()..].to_lowercase().replace(ENV_SEPARATOR, ".");
+            if self.get(&key).is_none() {
+                return Err(ConfigError::UnknownKey {
+                    key,
+                    layer: ConfigSource::Env(var.clone()),
+                });
+            }
+            self.set(&key, raw, ConfigSource::Env(var.clone()))
+                .map_err(|source| ConfigError::EnvParseError {
+                    var: var.clone(),
+                    source,
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Apply `section.key=value` overrides from the command line
+    pub fn apply_overrides<S: AsRef<str>>(&mut self, overrides: &[S]) -> Result<(), ConfigError> {
+        for assignment in overrides {
+            let assignment = assignment.as_ref();
+            let Some((key, raw)) = assignment.split_once('=') else {
+                return Err(ConfigError::InvalidOverride {
+                    key: assignment.to_string(),
+                    message: "expected section.key=value".to_string(),
+                });
+            };
+            let key = key.trim();
+            if self.get(key).is_none() {
+                return Err(ConfigError::UnknownKey {
+                    key: key.to_string(),
+                    layer: ConfigSource::Cli,
+                });
+            }
+            self.set(key, raw, ConfigSource::Cli)
+                .map_err(|e| ConfigError::InvalidOverride {
+                    key: key.to_string(),
+                    message: e.to_string(),
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Value at a dotted key such as `graph.width`
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        key.split('.').try_fold(&self.value, |value, segment| value.get(segment))
+    }
+
+    /// Layer that supplied the value at a dotted key
+    ///
+    /// Values inside a section that was replaced as a whole report the layer
+    /// that replaced it.
+    pub fn source_of(&self, key: &str) -> Option<&ConfigSource> {
+        let mut key = key;
+        loop {
+            if let Some(source) = self.sources.get(key) {
+                return Some(source);
+            }
+            key = &key[..key.rfind('.')?];
+        }
+    }
+
+    /// Keys that no configuration setting reads, which would be ignored
+    ///
+    /// Empty when the merged values do not deserialize; [`build`](Self::build)
+    /// reports that error.
+    pub fn unknown_keys(&self) -> Vec<String> {
+        dropped_settings::<Config>(&self.value).unwrap_or_default()
+    }
+
+    /// Deserialize, resolve secrets and validate the merged configuration
+    ///
+    /// Strings are converted to numbers where a number is expected, so list
+    /// items from the environment work for both string and numeric lists.
+    pub fn build(&self) -> Result<Config, ConfigError> {
+        self.build_with(&SecretResolver::default())
+    }
+
+    /// Like [`build`](Self::build), resolving secrets through `secrets`
+    pub fn build_with(&self, secrets: &SecretResolver) -> Result<Config, ConfigError> {
+        let mut config: Config = config::Config::builder()
+            .add_source(config::File::from_str(&self.value.to_string(), config::FileFormat::Json))
+            .build()
+            .and_then(|merged| merged.try_deserialize())
+            .map_err(|e| ConfigError::InvalidValue(e.to_string()))?;
+        secrets.resolve_config(&mut config)?;
+        config.validate_all().map_err(ConfigError::ValidationError)?;
+        Ok(config)
+    }
+
+    /// Every value with the layer that supplied it, secrets redacted
+    pub fn describe(&self) -> String {
+        let mut lines = Vec::new();
+        self.describe_value("", &self.value, &mut lines);
+        let mut output = lines.join("\n");
+        output.push('\n');
+        output
+    }
+
+    fn describe_value(&self, key: &str, value: &Value, lines: &mut Vec<String>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (name, child) in map {
+                    let child_key = if key.is_empty() { name.clone() } else { format!("{}.{}", key, name) };
+                    self.describe_value(&child_key, child, lines);
+                }
+            }
+            _ => {
+                let source = self.source_of(key).unwrap_or(&ConfigSource::Default);
+                lines.push(format!("{} = {}  # {}", key, redact(key, value), source));
+            }
+        }
+    }
+
+    /// Deep-merge `value` into the tree, recording `source` for every leaf it sets
+    fn merge(&mut self, value: Value, source: &ConfigSource) {
+        let mut leaves = Vec::new();
+        merge_into(&mut self.value, value, "", &mut leaves);
+        for key in leaves {
+            self.record(key, source.clone());
+        }
+    }
+
+    /// Replace the value at an existing key with `raw`, typed like the value it replaces
+    fn set(
+        &mut self,
+        key: &str,
+        raw: &str,
+        source: ConfigSource,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut segments = key.split('.');
+        let last = segments.next_back().unwrap_or_default();
+        let mut parent = &mut self.value;
+        for segment in segments {
+            parent = parent
+                .as_object_mut()
+                .map(|map| map.entry(segment).or_insert_with(|| Value::Object(Map::new())))
+                .ok_or_else(|| format!("'{}' is not a section", segment))?;
+        }
+        let map = parent.as_object_mut().ok_or_else(|| format!("cannot set '{}'", key))?;
+        let typed = parse_typed(map.get(last).unwrap_or(&Value::Null), raw)?;
+        map.insert(last.to_string(), typed);
+        self.record(key.to_string(), source);
+        Ok(())
+    }
+
+    fn record(&mut self, key: String, source: ConfigSource) {
+        let prefix = format!("{}.", key);
+        self.sources.retain(|existing, _| !existing.starts_with(&prefix));
+        self.sources.insert(key, source);
+    }
+}
+
+impl Default for LayeredConfig {
+    fn default() -> Self {
+        Self::defaults()
+    }
+}
+
+/// Merge objects key by key; anything else replaces the target
+fn merge_into(target: &mut Value, value: Value, key: &str, leaves: &mut Vec<String>) {
+    match (target, value) {
+        (Value::Object(target_map), Value::Object(map)) => {
+            for (name, child) in map {
+                let child_key = if key.is_empty() { name.clone() } else { format!("{}.{}", key, name) };
+                let slot = target_map.entry(name).or_insert(Value::Null);
+                if !slot.is_object() && !child.is_object() {
+                    leaves.push(child_key);
+                    *slot = child;
+                } else {
+                    merge_into(slot, child, &child_key, leaves);
+                }
+            }
+        }
+        (target, value) => {
+            leaves.push(key.to_string());
+            *target = value;
+        }
+    }
+}
+
+/// Parse a string from the environment or command line like the value it replaces
+///
+/// JSON arrays and objects are accepted anywhere. Lists also accept a
+/// comma-separated form.
+fn parse_typed(existing: &Value, raw: &str) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+    let trimmed = raw.trim();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        return Ok(serde_json::from_str(trimmed)?);
+    }
+    Ok(match existing {
+        Value::Bool(_) => Value::Bool(trimmed.parse()?),
+        Value::Number(number) if number.is_f64() => serde_json::Number::from_f64(trimmed.parse()?)
+            .map(Value::Number)
+            .ok_or("not a finite number")?,
+        Value::Number(_) => match trimmed.parse::<u64>() {
+            Ok(value) => Value::from(value),
+            Err(_) => Value::from(trimmed.parse::<i64>()?),
+        },
+        Value::Array(_) => Value::Array(
+            trimmed
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        ),
+        _ => Value::String(raw.to_string()),
+    })
+}
+
+/// Value rendered for display, with secret keys replaced
+fn redact(key: &str, value: &Value) -> String {
+    let mut value = value.clone();
+    let name = key.rsplit('.').next().unwrap_or(key);
+    if SECRET_KEYS.contains(&name) {
+        if value.as_str().is_some_and(|secret| !secret.is_empty()) {
+            value = Value::String(REDACTED.to_string());
+        }
+    } else {
+        redact_nested(&mut value);
+    }
+    value.to_string()
+}
+
+fn redact_nested(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map.iter_mut() {
+                if SECRET_KEYS.contains(&name.as_str()) && child.is_string() {
+                    *child = Value::String(REDACTED.to_string());
+                } else {
+                    redact_nested(child);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_nested),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_layer_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("config.yaml");
+        fs::write(&file, "graph:\n  width: 1000\n  height: 500\ntautulli:\n  api_key: from_file\n").unwrap();
+        let fragments = dir.path().join(FRAGMENT_DIR);
+        fs::create_dir(&fragments).unwrap();
+        fs::write(fragments.join("20-late.toml"), "[graph]\nheight = 700\n").unwrap();
+        fs::write(fragments.join("10-early.toml"), "[graph]\nheight = 600\nfont_size = 14\n").unwrap();
+        fs::write(fragments.join("ignored.yaml"), "graph:\n  font_size: 99\n").unwrap();
+
+        let mut layered = LayeredConfig::defaults();
+        layered.merge_file(&file).unwrap();
+        layered.merge_fragments(&fragments).unwrap();
+        layered
+            .apply_env(vars(&[
+                ("GRAPH_WIDTH", "1100"),
+                ("TGRAPH__GRAPH__WIDTH", "1200"),
+                ("TGRAPH__DISCORD__OWNER_IDS", "1, 2"),
+                ("TGRAPH__SCHEDULING__TIMEZONE", "Europe/Oslo"),
+                ("HOME", "/root"),
+            ]))
+            .unwrap();
+        layered.apply_overrides(&["graph.show_grid=false"]).unwrap();
+
+        let config = layered.build().unwrap();
+        assert_eq!(config.graph.width, 1200);
+        assert_eq!(config.graph.height, 700);
+        assert_eq!(config.graph.font_size, 14);
+        assert!(!config.graph.show_grid);
+        assert_eq!(config.discord.owner_ids, vec![1, 2]);
+        assert_eq!(config.scheduling.timezone.as_deref(), Some("Europe/Oslo"));
+        assert_eq!(config.tautulli.api_key, "from_file");
+
+        assert_eq!(layered.source_of("graph.max_data_points"), Some(&ConfigSource::Default));
+        assert_eq!(layered.source_of("tautulli.api_key"), Some(&ConfigSource::File(file.clone())));
+        assert_eq!(layered.source_of("graph.height"), Some(&ConfigSource::Fragment(fragments.join("20-late.toml"))));
+        assert_eq!(layered.source_of("graph.width"), Some(&ConfigSource::Env("TGRAPH__GRAPH__WIDTH".to_string())));
+        assert_eq!(layered.source_of("graph.show_grid"), Some(&ConfigSource::Cli));
+    }
+
+    #[test]
+    fn test_invalid_layers() {
+        let mut layered = LayeredConfig::defaults();
+        let err = layered.apply_env(vars(&[("TGRAPH__GRAPH__DEPTH", "3")])).unwrap_err();
+        assert!(matches!(err, ConfigError::UnknownKey { ref key, .. } if key == "graph.depth"));
+
+        let err = layered.apply_env(vars(&[("TGRAPH__GRAPH__WIDTH", "wide")])).unwrap_err();
+        assert!(matches!(err, ConfigError::EnvParseError { ref var, .. } if var == "TGRAPH__GRAPH__WIDTH"));
+
+        assert!(matches!(layered.apply_overrides(&["graph.width"]), Err(ConfigError::InvalidOverride { .. })));
+        assert!(matches!(layered.apply_overrides(&["graph.show_grid=maybe"]), Err(ConfigError::InvalidOverride { .. })));
+
+        // Values that parse but fail validation are reported on build
+        layered.apply_overrides(&["graph.background_color=blue"]).unwrap();
+        assert!(matches!(layered.build(), Err(ConfigError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_describe_redacts_secrets() {
+        let mut layered = LayeredConfig::defaults();
+        layered
+            .apply_overrides(&[
+                "tautulli.api_key=super-secret",
+                r#"tautulli.servers=[{"name":"cabin","url":"http://cabin:8181","api_key":"cabin-secret"}]"#,
+            ])
+            .unwrap();
+        let described = layered.describe();
+
+        assert!(!described.contains("super-secret"));
+        assert!(!described.contains("cabin-secret"));
+        assert!(described.contains("tautulli.api_key = \"********\"  # --set"));
+        assert!(described.contains("graph.width = 1920  # default"));
+        assert!(described.contains("\"name\":\"cabin\""));
+    }
+}
@@ -1,10 +1,25 @@
 //! Configuration management for TGraph Telegram bot
 
+pub mod changes;
+pub mod format;
+pub mod guild;
+pub mod layers;
 pub mod loader;
 pub mod manager;
+pub mod migrate;
+pub mod schema;
+pub mod secrets;
 pub mod settings;
 pub mod validation;
 
+pub use changes::{ConfigChange, ConfigSection, ConfigSubscriber};
+pub use format::{ConfigFormat, DEFAULT_CONFIG_FILES};
+pub use guild::{GuildOverrides, GuildTheme};
+pub use layers::{ConfigSource, LayeredConfig};
 pub use loader::{ConfigLoader, ConfigError};
+pub use secrets::{SecretProvider, SecretResolver};
 pub use manager::{ConfigManager, ConfigManagerError};
+pub use migrate::{migrate, migrate_as, DocumentKind, MigrationReport};
+pub use schema::{config_schema, config_schema_json};
+pub use validation::FieldError;
 pub use settings::{AppConfig, Config}; 
\ No newline at end of file
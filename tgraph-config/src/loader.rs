@@ -1,9 +1,10 @@
 //! Configuration loading utilities
 
+use crate::format::{ConfigFormat, DEFAULT_CONFIG_FILES};
+use crate::layers::{ConfigSource, LayeredConfig, FRAGMENT_DIR};
 use crate::Config;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::env;
-use serde_yaml;
 use thiserror::Error;
 use tgraph_common::Result as TGraphResult;
 
@@ -14,9 +15,22 @@ pub enum ConfigError {
     #[error("Failed to read configuration file: {0}")]
     IoError(#[from] std::io::Error),
     
-    /// YAML parsing error
-    #[error("Failed to parse YAML configuration: {0}")]
-    ParseError(#[from] serde_yaml::Error),
+    /// Parsing error, with the position in the file when the parser reports one
+    #[error("Failed to parse {format} configuration at {}: {message}", describe_location(.path, *.line, *.column))]
+    ParseError {
+        path: PathBuf,
+        format: ConfigFormat,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+
+    /// Serialization error
+    #[error("Failed to serialize configuration as {format}: {message}")]
+    SerializeError {
+        format: ConfigFormat,
+        message: String,
+    },
     
     /// Configuration validation error
     #[error("Configuration validation failed: {0}")]
@@ -30,11 +44,53 @@ pub enum ConfigError {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
     
+    /// A key set from the environment or command line that does not exist
+    #[error("Unknown configuration key '{key}' (set by {layer})")]
+    UnknownKey {
+        key: String,
+        layer: ConfigSource,
+    },
+
+    /// Malformed `--set` override
+    #[error("Invalid override for '{key}': {message}")]
+    InvalidOverride {
+        key: String,
+        message: String,
+    },
+
+    /// A secret that could not be read or resolved
+    #[error("Failed to resolve secret for '{field}': {message}")]
+    SecretError {
+        field: String,
+        message: String,
+    },
+
+    /// A document written in a format version this release cannot read
+    #[error("Unsupported {kind} format version {version} (this release reads up to {current})")]
+    UnsupportedVersion {
+        kind: String,
+        version: String,
+        current: String,
+    },
+
+    /// Merged values that do not fit the configuration structure
+    #[error("Invalid configuration value: {0}")]
+    InvalidValue(String),
+
     /// Missing required configuration
     #[error("Missing required configuration: {0}")]
     MissingConfig(String),
 }
 
+/// `path:line:column`, or as much of it as is known
+fn describe_location(path: &Path, line: Option<usize>, column: Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", path.display(), line, column),
+        (Some(line), None) => format!("{}:{}", path.display(), line),
+        _ => path.display().to_string(),
+    }
+}
+
 impl From<ConfigError> for tgraph_common::TGraphError {
     fn from(err: ConfigError) -> Self {
         tgraph_common::TGraphError::config(err.to_string())
@@ -45,268 +101,67 @@ impl From<ConfigError> for tgraph_common::TGraphError {
 pub struct ConfigLoader;
 
 impl ConfigLoader {
-    /// Load configuration from a YAML file with environment variable overrides
+    /// Load configuration from a TOML, YAML or JSON file with environment variable overrides
+    ///
+    /// The format comes from the file extension, or from the content when
+    /// the extension is not one of `.toml`, `.yaml`, `.yml` or `.json`.
     pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Config, ConfigError> {
-        // Read and parse the YAML file
-        let content = std::fs::read_to_string(path.as_ref())?;
-        let mut config: Config = serde_yaml::from_str(&content)?;
-        
-        // Apply environment variable overrides
-        Self::apply_env_overrides(&mut config)?;
-        
-        // Validate the final configuration
-        config.validate_all().map_err(ConfigError::ValidationError)?;
-        
-        Ok(config)
+        Self::load_layered(Some(path.as_ref()), &[] as &[&str])?.build()
     }
     
     /// Load configuration from environment variables and files
     pub fn load() -> TGraphResult<Config> {
-        // Try to load from default config file first, fall back to defaults
-        let config = if let Ok(config_path) = env::var("TGRAPH_CONFIG_PATH") {
-            Self::load_config(&config_path)?
-        } else if Path::new("config.yaml").exists() {
-            Self::load_config("config.yaml")?
-        } else if Path::new("config.yml").exists() {
-            Self::load_config("config.yml")?
-        } else {
-            // No config file found, use defaults with env overrides
-            let mut config = Config::default();
-            Self::apply_env_overrides(&mut config)?;
-            config.validate_all().map_err(ConfigError::ValidationError)?;
-            config
+        Ok(Self::load_layered(None, &[] as &[&str])?.build()?)
+    }
+
+    /// Merge every configuration layer without building the final configuration
+    ///
+    /// Layers, lowest precedence first: built-in defaults, the main file,
+    /// `conf.d/*.toml` fragments next to it, environment variables and the
+    /// `overrides` given as `section.key=value`. Without an explicit path the
+    /// main file is `TGRAPH_CONFIG_PATH` or the first of the default files
+    /// present, and is skipped when there is none.
+    pub fn load_layered<S: AsRef<str>>(path: Option<&Path>, overrides: &[S]) -> Result<LayeredConfig, ConfigError> {
+        let path = match path {
+            Some(path) => Some(path.to_path_buf()),
+            None => env::var_os("TGRAPH_CONFIG_PATH")
+                .map(PathBuf::from)
+                .or_else(|| Self::find_default_config().map(Path::to_path_buf)),
         };
-        
-        Ok(config)
+
+        let mut layered = LayeredConfig::defaults();
+        if let Some(path) = &path {
+            layered.merge_file(path)?;
+        }
+        layered.merge_fragments(&Self::fragment_dir(path.as_deref()))?;
+        layered.apply_env(env::vars())?;
+        layered.apply_overrides(overrides)?;
+        for key in layered.unknown_keys() {
+            let source = layered.source_of(&key).map(ToString::to_string).unwrap_or_default();
+            tracing::warn!("Ignoring unknown configuration key '{}' from {}", key, source);
+        }
+        Ok(layered)
+    }
+
+    /// `conf.d` directory next to the main file, or in the working directory
+    pub fn fragment_dir(path: Option<&Path>) -> PathBuf {
+        path.and_then(Path::parent)
+            .map(|parent| parent.join(FRAGMENT_DIR))
+            .unwrap_or_else(|| PathBuf::from(FRAGMENT_DIR))
+    }
+
+    /// First of the default configuration files present in the working directory
+    pub fn find_default_config() -> Option<&'static Path> {
+        DEFAULT_CONFIG_FILES
+            .iter()
+            .map(Path::new)
+            .find(|path| path.exists())
     }
 
     /// Load configuration from a specific file
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> TGraphResult<Config> {
         Ok(Self::load_config(path)?)
     }
-    
-    /// Apply environment variable overrides to configuration
-    fn apply_env_overrides(config: &mut Config) -> Result<(), ConfigError> {
-        // Discord configuration overrides
-        if let Ok(token) = env::var("DISCORD_TOKEN") {
-            config.discord.token = token;
-        }
-        
-        if let Ok(channels) = env::var("DISCORD_CHANNELS") {
-            config.discord.channels = channels
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-        }
-        
-        if let Ok(timeout) = env::var("DISCORD_TIMEOUT") {
-            config.discord.request_timeout_seconds = timeout.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "DISCORD_TIMEOUT".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(max_requests) = env::var("DISCORD_MAX_REQUESTS") {
-            config.discord.max_concurrent_requests = max_requests.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "DISCORD_MAX_REQUESTS".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        // Tautulli configuration overrides
-        if let Ok(url) = env::var("TAUTULLI_URL") {
-            config.tautulli.url = url;
-        }
-        
-        if let Ok(api_key) = env::var("TAUTULLI_API_KEY") {
-            config.tautulli.api_key = api_key;
-        }
-        
-        if let Ok(timeout) = env::var("TAUTULLI_TIMEOUT") {
-            config.tautulli.timeout_seconds = timeout.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "TAUTULLI_TIMEOUT".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(retries) = env::var("TAUTULLI_MAX_RETRIES") {
-            config.tautulli.max_retries = retries.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "TAUTULLI_MAX_RETRIES".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        // Scheduling configuration overrides
-        if let Ok(cron) = env::var("AUTO_GRAPH_CRON") {
-            config.scheduling.auto_graph_cron = Some(cron);
-        }
-        
-        if let Ok(cron) = env::var("CLEANUP_CRON") {
-            config.scheduling.cleanup_cron = Some(cron);
-        }
-        
-        if let Ok(timezone) = env::var("TIMEZONE") {
-            config.scheduling.timezone = Some(timezone);
-        }
-        
-        if let Ok(enabled) = env::var("SCHEDULING_ENABLED") {
-            config.scheduling.enabled = enabled.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "SCHEDULING_ENABLED".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        // Graph configuration overrides
-        if let Ok(width) = env::var("GRAPH_WIDTH") {
-            config.graph.width = width.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "GRAPH_WIDTH".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(height) = env::var("GRAPH_HEIGHT") {
-            config.graph.height = height.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "GRAPH_HEIGHT".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(bg_color) = env::var("GRAPH_BACKGROUND_COLOR") {
-            config.graph.background_color = bg_color;
-        }
-        
-        if let Ok(primary_color) = env::var("GRAPH_PRIMARY_COLOR") {
-            config.graph.primary_color = primary_color;
-        }
-        
-        if let Ok(secondary_color) = env::var("GRAPH_SECONDARY_COLOR") {
-            config.graph.secondary_color = secondary_color;
-        }
-        
-        if let Ok(font_family) = env::var("GRAPH_FONT_FAMILY") {
-            config.graph.font_family = font_family;
-        }
-        
-        if let Ok(font_size) = env::var("GRAPH_FONT_SIZE") {
-            config.graph.font_size = font_size.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "GRAPH_FONT_SIZE".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(show_grid) = env::var("GRAPH_SHOW_GRID") {
-            config.graph.show_grid = show_grid.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "GRAPH_SHOW_GRID".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(show_legend) = env::var("GRAPH_SHOW_LEGEND") {
-            config.graph.show_legend = show_legend.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "GRAPH_SHOW_LEGEND".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(max_points) = env::var("GRAPH_MAX_DATA_POINTS") {
-            config.graph.max_data_points = max_points.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "GRAPH_MAX_DATA_POINTS".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        // Database configuration overrides
-        if let Ok(url) = env::var("DATABASE_URL") {
-            config.database.url = url;
-        }
-        
-        if let Ok(max_connections) = env::var("DATABASE_MAX_CONNECTIONS") {
-            config.database.max_connections = max_connections.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "DATABASE_MAX_CONNECTIONS".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(connection_timeout) = env::var("DATABASE_CONNECTION_TIMEOUT") {
-            config.database.connection_timeout_seconds = connection_timeout.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "DATABASE_CONNECTION_TIMEOUT".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(query_timeout) = env::var("DATABASE_QUERY_TIMEOUT") {
-            config.database.query_timeout_seconds = query_timeout.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "DATABASE_QUERY_TIMEOUT".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        // Logging configuration overrides
-        if let Ok(level) = env::var("LOG_LEVEL") {
-            config.logging.level = level;
-        }
-        
-        if let Ok(file) = env::var("LOG_FILE") {
-            config.logging.file = Some(file);
-        }
-        
-        if let Ok(colored) = env::var("LOG_COLORED") {
-            config.logging.colored = colored.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "LOG_COLORED".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(timestamps) = env::var("LOG_INCLUDE_TIMESTAMPS") {
-            config.logging.include_timestamps = timestamps.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "LOG_INCLUDE_TIMESTAMPS".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(location) = env::var("LOG_INCLUDE_LOCATION") {
-            config.logging.include_location = location.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "LOG_INCLUDE_LOCATION".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(max_size) = env::var("LOG_MAX_FILE_SIZE_MB") {
-            config.logging.max_file_size_mb = max_size.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "LOG_MAX_FILE_SIZE_MB".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        if let Ok(max_files) = env::var("LOG_MAX_FILES") {
-            config.logging.max_files = max_files.parse()
-                .map_err(|e| ConfigError::EnvParseError {
-                    var: "LOG_MAX_FILES".to_string(),
-                    source: Box::new(e),
-                })?;
-        }
-        
-        Ok(())
-    }
 }
 
 #[cfg(test)]
@@ -363,9 +218,53 @@ mod tests {
         let result = ConfigLoader::load_config(temp_file.path());
         
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ConfigError::ParseError(_)));
+        match result.unwrap_err() {
+            ConfigError::ParseError { format, line, .. } => {
+                assert_eq!(format, ConfigFormat::Yaml);
+                assert!(line.is_some());
+            }
+            other => panic!("Expected a parse error, got: {}", other),
+        }
     }
     
+    #[test]
+    fn test_format_round_trip() {
+        let mut config = Config::default();
+        config.discord.channels = vec!["123456789".to_string()];
+        config.graph.width = 1280;
+        config.scheduling.timezone = Some("Europe/Oslo".to_string());
+        let expected = serde_json::to_value(&config).unwrap();
+
+        for (format, suffix) in [(ConfigFormat::Toml, ".toml"), (ConfigFormat::Yaml, ".yaml"), (ConfigFormat::Json, ".json")] {
+            let content = format.serialize(&config).expect("Failed to serialize config");
+
+            // Once by extension and once sniffed from a file without one
+            for suffix in [suffix, ".conf"] {
+                let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+                file.write_all(content.as_bytes()).unwrap();
+                let read = fs::read_to_string(file.path()).unwrap();
+                assert_eq!(ConfigFormat::detect(file.path(), &read), format);
+
+                let parsed: Config = format.parse(file.path(), &read).expect("Failed to parse config");
+                assert_eq!(serde_json::to_value(&parsed).unwrap(), expected, "{} round trip", format);
+            }
+        }
+    }
+
+    #[test]
+    fn test_example_config_parses() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../config.example.toml");
+        let content = fs::read_to_string(&path).expect("Failed to read config.example.toml");
+        let mut config: Config = ConfigFormat::detect(&path, &content)
+            .parse(&path, &content)
+            .expect("config.example.toml should parse");
+        assert_eq!(config.tautulli.url, "http://localhost:8181");
+
+        // Apart from the placeholder token, the example is valid as it stands
+        config.discord.token = "123456789.abcdef.ghijklmnop".to_string();
+        config.validate_all().expect("config.example.toml should pass validation");
+    }
+
     #[test]
     fn test_validation_error() {
         // Clean up environment variables that might override our invalid values
@@ -439,8 +338,9 @@ mod tests {
     #[test]
     fn test_load_defaults_with_fallback() {
         // Remove any potential config files from current directory for this test
-        let _ = fs::remove_file("config.yaml");
-        let _ = fs::remove_file("config.yml");
+        for file in DEFAULT_CONFIG_FILES {
+            let _ = fs::remove_file(file);
+        }
         env::remove_var("TGRAPH_CONFIG_PATH");
         
         // Clean up any environment variables that might affect the test
@@ -1,5 +1,6 @@
 //! Thread-safe configuration manager
 
+use crate::changes::{diff_sections, ConfigChange, ConfigSubscriber};
 use crate::{Config, ConfigError, ConfigLoader};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::path::{Path, PathBuf};
@@ -11,6 +12,7 @@ use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 use thiserror::Error;
 use tgraph_common::Result as TGraphResult;
+use tokio::sync::watch;
 
 /// Enhanced configuration error for manager operations
 #[derive(Debug, Error)]
@@ -66,12 +68,17 @@ struct WatcherState {
 /// Provides safe access to configuration across multiple threads using Arc<RwLock<Config>>.
 /// Supports both read-only and mutable access patterns while preventing deadlocks.
 /// Can optionally watch configuration files for changes and automatically reload.
+/// Every accepted change is published to subscribers with the sections it touched.
 #[derive(Debug)]
 pub struct ConfigManager {
     /// The configuration wrapped in a thread-safe container
     config: Arc<RwLock<Config>>,
     /// Optional file watcher state
     watcher_state: Arc<Mutex<Option<WatcherState>>>,
+    /// Latest accepted change, for subscribers
+    changes: Arc<watch::Sender<Arc<ConfigChange>>>,
+    /// `--set` overrides re-applied on every reload
+    overrides: Arc<Vec<String>>,
 }
 
 impl Clone for ConfigManager {
@@ -79,6 +86,8 @@ impl Clone for ConfigManager {
         Self {
             config: Arc::clone(&self.config),
             watcher_state: Arc::clone(&self.watcher_state),
+            changes: Arc::clone(&self.changes),
+            overrides: Arc::clone(&self.overrides),
         }
     }
 }
@@ -86,11 +95,20 @@ impl Clone for ConfigManager {
 impl ConfigManager {
     /// Create a new ConfigManager with the provided configuration
     pub fn new(config: Config) -> Self {
+        let (changes, _) = watch::channel(Arc::new(ConfigChange::initial(Arc::new(config.clone()))));
         Self {
             config: Arc::new(RwLock::new(config)),
             watcher_state: Arc::new(Mutex::new(None)),
+            changes: Arc::new(changes),
+            overrides: Arc::new(Vec::new()),
         }
     }
+
+    /// Keep applying `section.key=value` overrides on top of reloaded files
+    pub fn with_overrides<S: AsRef<str>>(mut self, overrides: &[S]) -> Self {
+        self.overrides = Arc::new(overrides.iter().map(|o| o.as_ref().to_string()).collect());
+        self
+    }
     
     /// Load configuration from default sources and create a ConfigManager
     pub fn load() -> TGraphResult<Self> {
@@ -171,11 +189,7 @@ impl ConfigManager {
             .map_err(|e| ConfigManagerError::Config(ConfigError::ValidationError(e)))?;
         
         // If validation succeeds, replace the original configuration
-        let mut guard = self.config.write()
-            .map_err(|_| ConfigManagerError::LockPoisoned)?;
-        *guard = new_config;
-        
-        Ok(())
+        self.store(new_config)
     }
     
     /// Replace the entire configuration with a new one
@@ -187,12 +201,59 @@ impl ConfigManager {
         new_config.validate_all()
             .map_err(|e| ConfigManagerError::Config(ConfigError::ValidationError(e)))?;
         
+        self.store(new_config)
+    }
+
+    /// Swap in a validated configuration and notify subscribers of the sections that changed
+    fn store(&self, new_config: Config) -> Result<(), ConfigManagerError> {
         let mut guard = self.config.write()
             .map_err(|_| ConfigManagerError::LockPoisoned)?;
-        
-        *guard = new_config;
+        let changed = diff_sections(&guard, &new_config);
+        let previous = std::mem::replace(&mut *guard, new_config);
+
+        if !changed.is_empty() {
+            tracing::info!(
+                "Configuration changed: {}",
+                changed.iter().map(|section| section.as_str()).collect::<Vec<_>>().join(", ")
+            );
+            self.changes.send_replace(Arc::new(ConfigChange {
+                previous: Arc::new(previous),
+                current: Arc::new(guard.clone()),
+                changed,
+            }));
+        }
         Ok(())
     }
+
+    /// Receive every accepted configuration change
+    pub fn subscribe(&self) -> watch::Receiver<Arc<ConfigChange>> {
+        self.changes.subscribe()
+    }
+
+    /// Apply changes to `subscriber` as they happen
+    ///
+    /// The subscriber only sees changes touching its sections. A failed
+    /// apply is logged and the subscriber keeps running with its old settings.
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_subscriber(&self, subscriber: Arc<dyn ConfigSubscriber>) -> tokio::task::JoinHandle<()> {
+        let mut changes = self.subscribe();
+        tokio::spawn(async move {
+            while changes.changed().await.is_ok() {
+                let change = Arc::clone(&changes.borrow_and_update());
+                if !change.touches_any(subscriber.sections()) {
+                    continue;
+                }
+                match subscriber.apply(&change.current).await {
+                    Ok(()) => tracing::info!("Applied configuration change to {}", subscriber.name()),
+                    Err(e) => tracing::warn!(
+                        "Failed to apply configuration change to {}: {}",
+                        subscriber.name(),
+                        e
+                    ),
+                }
+            }
+        })
+    }
     
     /// Reload configuration from the same source
     /// 
@@ -200,18 +261,7 @@ impl ConfigManager {
     /// loading mechanism. If the reload fails, the current configuration
     /// remains unchanged.
     pub fn reload(&self) -> Result<(), ConfigManagerError> {
-        let new_config = ConfigLoader::load()
-            .map_err(|e| match e {
-                tgraph_common::TGraphError::Config { message, source } => {
-                    if let Some(config_err) = source.and_then(|s| s.downcast::<ConfigError>().ok()) {
-                        ConfigManagerError::Config(*config_err)
-                    } else {
-                        ConfigManagerError::Config(ConfigError::MissingConfig(message))
-                    }
-                }
-                _ => ConfigManagerError::Config(ConfigError::MissingConfig(e.to_string()))
-            })?;
-        
+        let new_config = ConfigLoader::load_layered(None, &self.overrides)?.build()?;
         self.replace_config(new_config)
     }
     
@@ -220,25 +270,14 @@ impl ConfigManager {
     /// This method attempts to reload the configuration from the specified file.
     /// If the reload fails, the current configuration remains unchanged.
     pub fn reload_from_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigManagerError> {
-        let new_config = ConfigLoader::load_from_file(path)
-            .map_err(|e| match e {
-                tgraph_common::TGraphError::Config { message, source } => {
-                    if let Some(config_err) = source.and_then(|s| s.downcast::<ConfigError>().ok()) {
-                        ConfigManagerError::Config(*config_err)
-                    } else {
-                        ConfigManagerError::Config(ConfigError::MissingConfig(message))
-                    }
-                }
-                _ => ConfigManagerError::Config(ConfigError::MissingConfig(e.to_string()))
-            })?;
-        
+        let new_config = ConfigLoader::load_layered(Some(path.as_ref()), &self.overrides)?.build()?;
         self.replace_config(new_config)
     }
     
     /// Start watching a configuration file for changes and automatically reload
     /// 
     /// This method starts a background thread that monitors the specified file
-    /// for changes. When changes are detected, the configuration is automatically
+    /// and its `conf.d` fragment directory for changes. When changes are detected, the configuration is automatically
     /// reloaded and validated. If the new configuration is invalid, the old
     /// configuration is preserved and an error is logged.
     /// 
@@ -267,8 +306,12 @@ impl ConfigManager {
         // Create the file system watcher
         let mut watcher = RecommendedWatcher::new(tx, notify::Config::default())?;
         
-        // Watch the configuration file
+        // Watch the configuration file and its fragments
         watcher.watch(&config_path, RecursiveMode::NonRecursive)?;
+        let fragment_dir = ConfigLoader::fragment_dir(Some(&config_path));
+        if fragment_dir.is_dir() {
+            watcher.watch(&fragment_dir, RecursiveMode::NonRecursive)?;
+        }
         
         // Create stop signal
         let stop_signal = Arc::new(AtomicBool::new(false));
@@ -280,7 +323,7 @@ impl ConfigManager {
         
         // Spawn the watcher thread
         let thread_handle = thread::spawn(move || {
-            Self::watcher_thread(config_manager, config_path_clone, rx, stop_signal_clone, watcher);
+            Self::watcher_thread(config_manager, config_path_clone, fragment_dir, rx, stop_signal_clone, watcher);
         });
         
         // Store the watcher state
@@ -341,6 +384,7 @@ impl ConfigManager {
     fn watcher_thread(
         config_manager: ConfigManager,
         config_path: PathBuf,
+        fragment_dir: PathBuf,
         rx: mpsc::Receiver<notify::Result<Event>>,
         stop_signal: Arc<AtomicBool>,
         _watcher: RecommendedWatcher, // Keep watcher alive
@@ -352,7 +396,9 @@ impl ConfigManager {
             match rx.recv_timeout(Duration::from_millis(100)) {
                 Ok(Ok(event)) => {
                     // Check if this event is relevant
-                    if Self::should_reload_for_event(&event, &config_path) {
+                    if Self::should_reload_for_event(&event, &config_path)
+                        || Self::should_reload_for_fragment(&event, &fragment_dir)
+                    {
                         // Debounce: only reload if enough time has passed since last reload
                         let now = Instant::now();
                         if now.duration_since(last_reload_time) >= DEBOUNCE_DURATION {
@@ -361,7 +407,7 @@ impl ConfigManager {
                             // Attempt to reload the configuration
                             if let Err(e) = config_manager.reload_from_file(&config_path) {
                                 tracing::warn!(
-                                    "Failed to reload configuration from {}: {}",
+                                    "Rejected configuration reload from {}, keeping the last good configuration: {}",
                                     config_path.display(),
                                     e
                                 );
@@ -410,6 +456,15 @@ impl ConfigManager {
         }
     }
     
+    /// Determine if a file system event touches a `conf.d` fragment
+    fn should_reload_for_fragment(event: &Event, fragment_dir: &Path) -> bool {
+        matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_))
+            && event.paths.iter().any(|path| {
+                path.parent() == Some(fragment_dir)
+                    && path.extension().is_some_and(|extension| extension == "toml")
+            })
+    }
+    
     /// Get a read guard for low-level access
     /// 
     /// This method provides direct access to the RwLockReadGuard for advanced
@@ -700,6 +755,32 @@ mod tests {
         assert_eq!(token, "987654321.abcdef.testtoken");
     }
 
+    #[test]
+    fn test_change_notifications() {
+        let manager = ConfigManager::new(create_test_config());
+        let mut changes = manager.subscribe();
+        assert!(!changes.has_changed().unwrap());
+
+        // Unchanged configurations are not published
+        manager.replace_config(create_test_config()).unwrap();
+        assert!(!changes.has_changed().unwrap());
+
+        let width = create_test_config().graph.width;
+        manager.update_config(|config| config.graph.width = 1280).unwrap();
+        assert!(changes.has_changed().unwrap());
+        let change = Arc::clone(&changes.borrow_and_update());
+        assert_eq!(change.changed, vec![crate::ConfigSection::Graph]);
+        assert_eq!(change.previous.graph.width, width);
+        assert_eq!(change.current.graph.width, 1280);
+
+        // A rejected reload keeps the last good configuration and notifies nobody
+        let mut temp_file = NamedTempFile::with_suffix(".toml").unwrap();
+        writeln!(temp_file, "[graph]\nwidth = 5").unwrap();
+        assert!(manager.reload_from_file(temp_file.path()).is_err());
+        assert!(!changes.has_changed().unwrap());
+        assert_eq!(manager.with_config(|config| config.graph.width).unwrap(), 1280);
+    }
+
     #[test]
     fn test_get_shared() {
         let config = create_test_config();
@@ -0,0 +1,432 @@
+//! Configuration format migrations
+//!
+//! Every document the bot reads from disk carries a format version: the main
+//! configuration, schedule files and saved graph configurations. Older
+//! documents are upgraded step by step through the registered [`Migration`]s,
+//! on the generic [`Value`] tree so that no setting is lost to a struct that
+//! no longer knows about it. [`MigrationReport::changes`] gives the diff for
+//! a dry run.
+
+use crate::loader::ConfigError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+/// Current version of the main configuration format
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Current version of schedule configuration files
+pub const SCHEDULES_VERSION: &str = "1.0";
+
+/// Current version of saved graph configurations
+pub const GRAPH_CONFIG_VERSION: &str = "1.1.0";
+
+/// Kind of versioned document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    /// The main bot configuration
+    Config,
+    /// A schedule configuration file
+    Schedules,
+    /// A saved graph configuration
+    GraphConfig,
+}
+
+impl DocumentKind {
+    /// Guess the kind of a parsed document from its top-level keys
+    pub fn detect(value: &Value) -> Self {
+        if value.get("schedules").is_some() {
+            Self::Schedules
+        } else if value.get("metadata").is_some() && value.get("base").is_some() {
+            Self::GraphConfig
+        } else {
+            Self::Config
+        }
+    }
+
+    /// Name used on the command line and in messages
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Schedules => "schedules",
+            Self::GraphConfig => "graph",
+        }
+    }
+
+    /// Parse a name given on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Config, Self::Schedules, Self::GraphConfig]
+            .into_iter()
+            .find(|kind| kind.as_str() == name)
+    }
+
+    /// Version written by this release
+    pub fn current_version(&self) -> String {
+        match self {
+            Self::Config => CONFIG_VERSION.to_string(),
+            Self::Schedules => SCHEDULES_VERSION.to_string(),
+            Self::GraphConfig => GRAPH_CONFIG_VERSION.to_string(),
+        }
+    }
+
+    /// Every version this release can read, oldest first
+    fn versions(&self) -> Vec<String> {
+        let mut versions: Vec<String> = self.migrations().iter().map(|m| m.from.to_string()).collect();
+        versions.push(self.current_version());
+        versions
+    }
+
+    /// Version of a parsed document
+    ///
+    /// Main configurations written before the `version` key existed are
+    /// recognised by their shape. Schedule files have always defaulted to
+    /// `1.0` and graph configurations have always recorded their version.
+    pub fn detect_version(&self, value: &Value) -> String {
+        match self {
+            Self::Config => match value.get("version") {
+                Some(Value::Number(n)) => n.to_string(),
+                Some(Value::String(s)) => s.clone(),
+                _ if value.get("discord").is_some_and(|d| d.get("owner_ids").is_none()) => "1".to_string(),
+                _ => "2".to_string(),
+            },
+            Self::Schedules => value
+                .get("version")
+                .and_then(Value::as_str)
+                .unwrap_or(SCHEDULES_VERSION)
+                .to_string(),
+            Self::GraphConfig => value
+                .pointer("/metadata/version")
+                .and_then(Value::as_str)
+                .unwrap_or("1.0.0")
+                .to_string(),
+        }
+    }
+
+    /// Registered migrations, in order
+    pub fn migrations(&self) -> &'static [Migration] {
+        match self {
+            Self::Config => CONFIG_MIGRATIONS,
+            Self::Schedules => &[],
+            Self::GraphConfig => GRAPH_CONFIG_MIGRATIONS,
+        }
+    }
+
+    fn set_version(&self, value: &mut Value, version: &str) {
+        match self {
+            Self::Config => {
+                if let Some(map) = value.as_object_mut() {
+                    let version = version.parse::<u64>().map_or_else(|_| Value::from(version), Value::from);
+                    map.insert("version".to_string(), version);
+                }
+            }
+            Self::Schedules => {
+                if let Some(map) = value.as_object_mut() {
+                    map.insert("version".to_string(), Value::from(version));
+                }
+            }
+            Self::GraphConfig => {
+                if let Some(metadata) = value.get_mut("metadata").and_then(Value::as_object_mut) {
+                    metadata.insert("version".to_string(), Value::from(version));
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DocumentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One step from a format version to the next
+pub struct Migration {
+    /// Version the step reads
+    pub from: &'static str,
+    /// Version the step writes
+    pub to: &'static str,
+    /// What the step changes
+    pub description: &'static str,
+    /// Rewrite the document, adding a note for anything that needs attention
+    apply: fn(&mut Value, &mut Vec<String>),
+}
+
+const CONFIG_MIGRATIONS: &[Migration] = &[Migration {
+    from: "1",
+    to: "2",
+    description: "Add the Discord owner, admin and moderator lists",
+    apply: add_permission_lists,
+}];
+
+const GRAPH_CONFIG_MIGRATIONS: &[Migration] = &[Migration {
+    from: "1.0.0",
+    to: "1.1.0",
+    description: "Keep rolling time range presets now that Last Week, Month and Quarter are calendar periods",
+    apply: keep_rolling_presets,
+}];
+
+fn add_permission_lists(value: &mut Value, notes: &mut Vec<String>) {
+    let Some(discord) = value.get_mut("discord").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for key in ["owner_ids", "admin_ids", "admin_role_ids", "moderator_role_ids"] {
+        discord.entry(key).or_insert_with(|| Value::Array(Vec::new()));
+    }
+    notes.push("discord.owner_ids is empty; owner-only commands are unavailable until it is set".to_string());
+}
+
+fn keep_rolling_presets(value: &mut Value, notes: &mut Vec<String>) {
+    let Some(preset) = value.pointer_mut("/filters/time_range_preset") else {
+        return;
+    };
+    let replacement = match preset.as_str() {
+        Some("LastWeek") => "LastSevenDays",
+        Some("LastMonth") => "LastThirtyDays",
+        Some("LastQuarter") => "LastNinetyDays",
+        Some("LastYear") => {
+            notes.push("filters.time_range_preset LastYear now means the previous calendar year instead of the last 365 days".to_string());
+            return;
+        }
+        Some("AllTime") => {
+            notes.push("filters.time_range_preset AllTime now covers the whole history instead of the last 730 days".to_string());
+            return;
+        }
+        _ => return,
+    };
+    *preset = Value::from(replacement);
+}
+
+/// Result of migrating one document
+#[derive(Debug, Clone)]
+pub struct MigrationReport {
+    /// Kind of document
+    pub kind: DocumentKind,
+    /// Version the document had
+    pub from: String,
+    /// Version the document has now
+    pub to: String,
+    /// Descriptions of the steps applied, in order
+    pub applied: Vec<&'static str>,
+    /// Behaviour changes the user should review
+    pub notes: Vec<String>,
+    /// The document as read
+    pub original: Value,
+    /// The upgraded document
+    pub migrated: Value,
+}
+
+impl MigrationReport {
+    /// Whether the document was already current
+    pub fn is_noop(&self) -> bool {
+        self.applied.is_empty()
+    }
+
+    /// Lines describing every difference: `+` added, `-` removed, `~` changed
+    pub fn changes(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        diff_values(&self.original, &self.migrated, "", &mut lines);
+        lines
+    }
+}
+
+/// Migrate a document of the kind detected from its content
+pub fn migrate(value: Value) -> Result<MigrationReport, ConfigError> {
+    migrate_as(DocumentKind::detect(&value), value)
+}
+
+/// Apply every migration from the document's version to the current one
+pub fn migrate_as(kind: DocumentKind, value: Value) -> Result<MigrationReport, ConfigError> {
+    let from = kind.detect_version(&value);
+    let versions = kind.versions();
+    let start = versions
+        .iter()
+        .position(|version| *version == from)
+        .ok_or_else(|| ConfigError::UnsupportedVersion {
+            kind: kind.as_str().to_string(),
+            version: from.clone(),
+            current: kind.current_version(),
+        })?;
+
+    let mut migrated = value.clone();
+    let mut applied = Vec::new();
+    let mut notes = Vec::new();
+    for migration in &kind.migrations()[start..] {
+        (migration.apply)(&mut migrated, &mut notes);
+        kind.set_version(&mut migrated, migration.to);
+        applied.push(migration.description);
+    }
+
+    Ok(MigrationReport {
+        kind,
+        to: kind.detect_version(&migrated),
+        from,
+        applied,
+        notes,
+        original: value,
+        migrated,
+    })
+}
+
+/// Settings in `value` that would be lost when read as a `T`
+///
+/// The document is deserialized and serialized again; every non-null value
+/// missing from the result is reported by its dotted path.
+pub fn dropped_settings<T: DeserializeOwned + Serialize>(value: &Value) -> Result<Vec<String>, ConfigError> {
+    let typed: T = serde_json::from_value(value.clone()).map_err(|e| ConfigError::InvalidValue(e.to_string()))?;
+    let kept = serde_json::to_value(typed).map_err(|e| ConfigError::InvalidValue(e.to_string()))?;
+    let mut dropped = Vec::new();
+    find_dropped(value, &kept, "", &mut dropped);
+    Ok(dropped)
+}
+
+fn find_dropped(original: &Value, kept: &Value, path: &str, dropped: &mut Vec<String>) {
+    match (original, kept) {
+        (Value::Object(original), Value::Object(kept)) => {
+            for (key, child) in original {
+                let child_path = join_path(path, key);
+                match kept.get(key) {
+                    Some(kept_child) => find_dropped(child, kept_child, &child_path, dropped),
+                    None if !child.is_null() => dropped.push(child_path),
+                    None => {}
+                }
+            }
+        }
+        (Value::Array(original), Value::Array(kept)) => {
+            for (i, child) in original.iter().enumerate() {
+                if let Some(kept_child) = kept.get(i) {
+                    find_dropped(child, kept_child, &format!("{}[{}]", path, i), dropped);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn diff_values(old: &Value, new: &Value, path: &str, lines: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_child) in old {
+                let child_path = join_path(path, key);
+                match new.get(key) {
+                    Some(new_child) => diff_values(old_child, new_child, &child_path, lines),
+                    None => lines.push(format!("- {} = {}", child_path, old_child)),
+                }
+            }
+            for (key, new_child) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                lines.push(format!("+ {} = {}", join_path(path, key), new_child));
+            }
+        }
+        (old, new) if old != new => lines.push(format!("~ {}: {} -> {}", path, old, new)),
+        _ => {}
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Remove null values, which TOML cannot represent
+pub fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, child| !child.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, ConfigSource, LayeredConfig};
+    use serde_json::json;
+
+    /// Main configuration as written before the permission lists existed
+    fn config_v1() -> Value {
+        json!({
+            "discord": { "token": "abc.def.ghi", "channels": ["123"], "max_concurrent_requests": 10, "request_timeout_seconds": 30 },
+            "tautulli": { "url": "http://localhost:8181", "api_key": "key", "timeout_seconds": 30, "max_retries": 3 }
+        })
+    }
+
+    #[test]
+    fn test_config_v1_upgrades_to_current() {
+        let report = migrate(config_v1()).unwrap();
+        assert_eq!(report.kind, DocumentKind::Config);
+        assert_eq!((report.from.as_str(), report.to.as_str()), ("1", "2"));
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.migrated["discord"]["owner_ids"], json!([]));
+        assert_eq!(report.migrated["discord"]["channels"], json!(["123"]));
+        assert_eq!(report.migrated["version"], json!(CONFIG_VERSION));
+        assert!(report.changes().contains(&"+ discord.admin_ids = []".to_string()));
+        assert!(report.changes().contains(&"+ version = 2".to_string()));
+    }
+
+    #[test]
+    fn test_config_v2_is_current() {
+        // Files from before the version key are recognised by their shape
+        let mut v2 = config_v1();
+        v2["discord"]["owner_ids"] = json!([42]);
+        let report = migrate(v2.clone()).unwrap();
+        assert_eq!(report.from, "2");
+        assert!(report.is_noop());
+        assert!(report.changes().is_empty());
+
+        v2["version"] = json!(2);
+        assert!(migrate(v2).unwrap().is_noop());
+
+        let err = migrate(json!({ "version": 9 })).unwrap_err();
+        assert!(matches!(err, ConfigError::UnsupportedVersion { .. }), "{}", err);
+    }
+
+    #[test]
+    fn test_graph_config_keeps_rolling_presets() {
+        let saved = |preset: &str| json!({
+            "base": {}, "filters": { "time_range_preset": preset }, "metadata": { "name": "Weekly", "version": "1.0.0" }
+        });
+
+        let report = migrate(saved("LastMonth")).unwrap();
+        assert_eq!(report.kind, DocumentKind::GraphConfig);
+        assert_eq!(report.to, GRAPH_CONFIG_VERSION);
+        assert_eq!(report.migrated["filters"]["time_range_preset"], json!("LastThirtyDays"));
+        assert_eq!(report.changes(), vec![
+            "~ filters.time_range_preset: \"LastMonth\" -> \"LastThirtyDays\"".to_string(),
+            "~ metadata.version: \"1.0.0\" -> \"1.1.0\"".to_string(),
+        ]);
+
+        let report = migrate(saved("LastYear")).unwrap();
+        assert_eq!(report.migrated["filters"]["time_range_preset"], json!("LastYear"));
+        assert_eq!(report.notes.len(), 1);
+
+        assert!(migrate(json!({ "base": {}, "metadata": { "version": "1.1.0" } })).unwrap().is_noop());
+    }
+
+    #[test]
+    fn test_schedules_without_version() {
+        let report = migrate(json!({ "schedules": [] })).unwrap();
+        assert_eq!(report.kind, DocumentKind::Schedules);
+        assert_eq!(report.from, SCHEDULES_VERSION);
+        assert!(report.is_noop());
+        assert!(migrate(json!({ "version": "2.0", "schedules": [] })).is_err());
+    }
+
+    #[test]
+    fn test_dropped_settings() {
+        let mut layered = LayeredConfig::defaults();
+        let mut file = migrate(config_v1()).unwrap().migrated;
+        file["discord"]["prefix"] = json!("!");
+        file["graph"] = json!({ "dpi": 300, "width": 1280 });
+        layered.merge_value(file, ConfigSource::Cli);
+        assert_eq!(layered.unknown_keys(), vec!["discord.prefix", "graph.dpi"]);
+
+        let defaults = serde_json::to_value(Config::default()).unwrap();
+        assert!(dropped_settings::<Config>(&defaults).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,44 @@
+//! JSON Schema for configuration files
+//!
+//! The schema is generated from [`Config`] and its `validator` attributes, so
+//! editors can complete keys and flag out-of-range values. Checks that only
+//! run in code, such as cron expressions and token format, are not included.
+
+use crate::Config;
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+/// JSON Schema describing a configuration file
+pub fn config_schema() -> RootSchema {
+    let mut schema = schema_for!(Config);
+    schema.schema.metadata().title = Some("TGraph Bot configuration".to_string());
+    schema
+}
+
+/// The configuration schema as pretty-printed JSON
+pub fn config_schema_json() -> String {
+    serde_json::to_string_pretty(&config_schema()).expect("schema serializes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_schema_describes_config() {
+        let schema: Value = serde_json::from_str(&config_schema_json()).unwrap();
+        assert_eq!(schema["title"], "TGraph Bot configuration");
+        for section in crate::ConfigSection::ALL {
+            assert!(schema["properties"].get(section.as_str()).is_some(), "{}", section);
+        }
+
+        let width = &schema["definitions"]["GraphConfig"]["properties"]["width"];
+        assert_eq!(width["minimum"], 100.0);
+        assert_eq!(width["maximum"], 4000.0);
+        assert_eq!(
+            schema["definitions"]["GraphConfig"]["properties"]["background_color"]["pattern"],
+            "^#[0-9A-Fa-f]{6}$"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Secret resolution and redaction
+//!
+//! Secret fields (`discord.token` and the Tautulli `api_key`s) can be given
+//! inline, read from a file through their `*_file` variant, or point at a
+//! secret manager with a `secret://<provider>/<name>` reference.
+
+use crate::loader::ConfigError;
+use crate::Config;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Prefix of references resolved through a [`SecretProvider`]
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// Placeholder shown instead of a secret
+pub const REDACTED: &str = "********";
+
+/// Configuration keys whose values are secrets
+pub const SECRET_KEYS: &[&str] = &["token", "api_key", "pseudonym_salt"];
+
+/// Default directory for file secrets, as mounted by Docker and Kubernetes
+pub const DEFAULT_SECRETS_DIR: &str = "/run/secrets";
+
+/// A source of secrets addressed by name
+pub trait SecretProvider: Send + Sync {
+    /// Look up the secret called `name`
+    fn resolve(&self, name: &str) -> Result<String, String>;
+}
+
+/// Secrets from environment variables: `secret://env/DISCORD_TOKEN`
+#[derive(Debug, Default)]
+pub struct EnvSecretProvider;
+
+impl SecretProvider for EnvSecretProvider {
+    fn resolve(&self, name: &str) -> Result<String, String> {
+        std::env::var(name).map_err(|_| format!("environment variable {} is not set", name))
+    }
+}
+
+/// Secrets from files in a directory: `secret://file/discord_token`
+#[derive(Debug)]
+pub struct FileSecretProvider {
+    root: PathBuf,
+}
+
+impl FileSecretProvider {
+    /// Provider reading files below `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Default for FileSecretProvider {
+    fn default() -> Self {
+        Self::new(DEFAULT_SECRETS_DIR)
+    }
+}
+
+impl SecretProvider for FileSecretProvider {
+    fn resolve(&self, name: &str) -> Result<String, String> {
+        // Absolute names would replace the root when joined
+        if !Path::new(name).components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(format!("invalid secret name {}", name));
+        }
+        read_secret_file(&self.root.join(name))
+    }
+}
+
+/// Resolves `*_file` fields and `secret://` references in a configuration
+pub struct SecretResolver {
+    providers: HashMap<String, Box<dyn SecretProvider>>,
+}
+
+impl SecretResolver {
+    /// Resolver without any providers
+    pub fn new() -> Self {
+        Self {
+            providers: HashMap::new(),
+        }
+    }
+
+    /// Register a provider under the name used in `secret://<name>/...`
+    pub fn with_provider(mut self, name: impl Into<String>, provider: impl SecretProvider + 'static) -> Self {
+        self.providers.insert(name.into(), Box::new(provider));
+        self
+    }
+
+    /// Resolve a `secret://` reference; other values are returned unchanged
+    pub fn resolve(&self, value: &str) -> Result<String, String> {
+        let Some(reference) = value.strip_prefix(SECRET_SCHEME) else {
+            return Ok(value.to_string());
+        };
+        let (provider, name) = reference
+            .split_once('/')
+            .filter(|(_, name)| !name.is_empty())
+            .ok_or_else(|| format!("expected {}<provider>/<name>", SECRET_SCHEME))?;
+        self.providers
+            .get(provider)
+            .ok_or_else(|| format!("unknown secret provider {}", provider))?
+            .resolve(name)
+    }
+
+    /// Replace every secret field with its resolved value
+    ///
+    /// A `*_file` field takes precedence over the inline value.
+    pub fn resolve_config(&self, config: &mut Config) -> Result<(), ConfigError> {
+        self.resolve_field("discord.token", &mut config.discord.token, config.discord.token_file.as_deref())?;
+        self.resolve_field(
+            "tautulli.api_key",
+            &mut config.tautulli.api_key,
+            config.tautulli.api_key_file.as_deref(),
+        )?;
+        for server in &mut config.tautulli.servers {
+            let field = format!("tautulli.servers.{}.api_key", server.name);
+            self.resolve_field(&field, &mut server.api_key, server.api_key_file.as_deref())?;
+        }
+        Ok(())
+    }
+
+    fn resolve_field(&self, field: &str, value: &mut String, file: Option<&str>) -> Result<(), ConfigError> {
+        let resolved = match file {
+            Some(path) => read_secret_file(Path::new(path)),
+            None => self.resolve(value),
+        };
+        *value = resolved.map_err(|message| ConfigError::SecretError {
+            field: field.to_string(),
+            message,
+        })?;
+        Ok(())
+    }
+}
+
+impl Default for SecretResolver {
+    /// Resolver with the `env` and `file` providers
+    fn default() -> Self {
+        Self::new()
+            .with_provider("env", EnvSecretProvider)
+            .with_provider("file", FileSecretProvider::default())
+    }
+}
+
+/// Read a secret file, without the trailing newline editors and `echo` add
+pub fn read_secret_file(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path)
+        .map(|secret| secret.trim_end_matches(['\r', '\n']).to_string())
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))
+}
+
+/// Secret as shown in `Debug` output and exports; empty values stay empty
+pub fn redact(secret: &str) -> &str {
+    if secret.is_empty() {
+        secret
+    } else {
+        REDACTED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    struct StaticProvider;
+
+    impl SecretProvider for StaticProvider {
+        fn resolve(&self, name: &str) -> Result<String, String> {
+            Ok(format!("vault-{}", name))
+        }
+    }
+
+    #[test]
+    fn test_resolve_secret_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("discord_token");
+        fs::write(&token_file, "file-token\n").unwrap();
+        fs::write(dir.path().join("cabin_key"), "cabin-secret").unwrap();
+
+        let mut config = Config::default();
+        config.discord.token_file = Some(token_file.display().to_string());
+        config.tautulli.api_key = "secret://vault/tautulli".to_string();
+        let mut cabin = config.tautulli.all_servers().remove(0);
+        cabin.name = "cabin".to_string();
+        cabin.api_key = "secret://file/cabin_key".to_string();
+        config.tautulli.servers.push(cabin);
+
+        let resolver = SecretResolver::default()
+            .with_provider("vault", StaticProvider)
+            .with_provider("file", FileSecretProvider::new(dir.path()));
+        resolver.resolve_config(&mut config).unwrap();
+
+        assert_eq!(config.discord.token, "file-token");
+        assert_eq!(config.tautulli.api_key, "vault-tautulli");
+        assert_eq!(config.tautulli.servers[0].api_key, "cabin-secret");
+        assert_eq!(resolver.resolve("plain").unwrap(), "plain");
+    }
+
+    #[test]
+    fn test_unresolvable_secrets() {
+        let resolver = SecretResolver::default();
+        assert!(resolver.resolve("secret://vault/x").unwrap_err().contains("unknown secret provider vault"));
+        assert!(resolver.resolve("secret://env").is_err());
+        assert!(resolver.resolve("secret://file/../etc/passwd").is_err());
+        assert!(resolver.resolve("secret://file//etc/passwd").is_err());
+        assert!(FileSecretProvider::new("/run/secrets").resolve("./token").is_err());
+
+        let mut config = Config::default();
+        config.tautulli.api_key_file = Some("/nonexistent/tautulli_key".to_string());
+        let err = resolver.resolve_config(&mut config).unwrap_err();
+        assert!(matches!(err, ConfigError::SecretError { ref field, .. } if field == "tautulli.api_key"));
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let mut config = Config::default();
+        config.discord.token = "very-secret-token".to_string();
+        config.tautulli.api_key = "very-secret-key".to_string();
+        config.privacy.pseudonym_salt = Some("very-secret-salt".to_string());
+        let mut cabin = config.tautulli.all_servers().remove(0);
+        cabin.name = "cabin".to_string();
+        config.tautulli.servers.push(cabin);
+
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("very-secret"));
+        assert!(debug.contains(REDACTED));
+        assert!(!format!("{:?}", config.tautulli.client_configs()).contains("very-secret"));
+        assert_eq!(redact(""), "");
+    }
+}
@@ -1,12 +1,18 @@
 //! Application configuration structures
 
+use crate::secrets::redact;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use validator::Validate;
 
 /// Main application configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
-#[derive(Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct Config {
+    /// Format version of the file, see [`crate::migrate`]
+    #[serde(default = "default_config_version")]
+    pub version: u32,
+
     /// Discord-related configuration
     #[validate]
     pub discord: DiscordConfig,
@@ -30,15 +36,24 @@ pub struct Config {
     /// Logging configuration
     #[validate]
     pub logging: LoggingConfig,
+
+    /// Username censoring for graphs and messages
+    #[serde(default)]
+    #[validate]
+    pub privacy: PrivacySettings,
 }
 
 /// Discord bot configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct DiscordConfig {
-    /// Discord bot token
+    /// Discord bot token, or a `secret://<provider>/<name>` reference
     #[validate(length(min = 1, message = "Discord token cannot be empty"))]
     #[validate(custom(function = "crate::validation::validate_discord_token", message = "Invalid Discord token format"))]
     pub token: String,
+
+    /// File containing the bot token, e.g. a Docker secret; takes precedence over `token`
+    #[serde(default)]
+    pub token_file: Option<String>,
     
     /// List of allowed channel IDs where the bot can operate
     pub channels: Vec<String>,
@@ -65,15 +80,27 @@ pub struct DiscordConfig {
 }
 
 /// Tautulli API configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+///
+/// The top-level fields describe the primary server. Additional servers are
+/// listed under `servers` and are addressed by their unique `name`.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct TautulliConfig {
+    /// Display name of the primary server, shown in graph titles
+    #[serde(default = "default_tautulli_server_name")]
+    #[validate(length(min = 1, max = 64, message = "Tautulli server name must be between 1 and 64 characters"))]
+    pub name: String,
+
     /// Tautulli server base URL
     #[validate(url(message = "Tautulli URL must be a valid URL"))]
     pub url: String,
     
-    /// Tautulli API key
+    /// Tautulli API key, or a `secret://<provider>/<name>` reference
     #[validate(length(min = 1, message = "Tautulli API key cannot be empty"))]
     pub api_key: String,
+
+    /// File containing the API key; takes precedence over `api_key`
+    #[serde(default)]
+    pub api_key_file: Option<String>,
     
     /// Request timeout in seconds
     #[validate(range(min = 1, max = 300, message = "Timeout must be between 1 and 300 seconds"))]
@@ -82,10 +109,67 @@ pub struct TautulliConfig {
     /// Maximum number of retries for failed requests
     #[validate(range(max = 10, message = "Max retries cannot exceed 10"))]
     pub max_retries: u32,
+
+    /// Circuit breaker thresholds, applied to every server
+    #[serde(default)]
+    #[validate]
+    pub circuit_breaker: CircuitBreakerSettings,
+
+    /// Additional named Tautulli servers
+    #[serde(default)]
+    #[validate]
+    pub servers: Vec<TautulliServerConfig>,
+}
+
+/// Circuit breaker thresholds for Tautulli requests
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+#[serde(default)]
+pub struct CircuitBreakerSettings {
+    /// Consecutive failed requests before the breaker opens
+    #[validate(range(min = 1, max = 100, message = "Failure threshold must be between 1 and 100"))]
+    pub failure_threshold: u32,
+
+    /// Seconds the breaker stays open before trial requests are allowed
+    #[validate(range(min = 1, max = 3600, message = "Open duration must be between 1 and 3600 seconds"))]
+    pub open_seconds: u64,
+
+    /// Successful trial requests needed to close the breaker again
+    #[validate(range(min = 1, max = 10, message = "Success threshold must be between 1 and 10"))]
+    pub success_threshold: u32,
+}
+
+/// An additional named Tautulli server
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct TautulliServerConfig {
+    /// Unique display name, shown in graph titles and used to select the server
+    #[validate(length(min = 1, max = 64, message = "Tautulli server name must be between 1 and 64 characters"))]
+    pub name: String,
+
+    /// Tautulli server base URL
+    #[validate(url(message = "Tautulli URL must be a valid URL"))]
+    pub url: String,
+
+    /// Tautulli API key, or a `secret://<provider>/<name>` reference
+    #[validate(length(min = 1, message = "Tautulli API key cannot be empty"))]
+    pub api_key: String,
+
+    /// File containing the API key; takes precedence over `api_key`
+    #[serde(default)]
+    pub api_key_file: Option<String>,
+
+    /// Request timeout in seconds
+    #[serde(default = "default_tautulli_timeout_seconds")]
+    #[validate(range(min = 1, max = 300, message = "Timeout must be between 1 and 300 seconds"))]
+    pub timeout_seconds: u64,
+
+    /// Maximum number of retries for failed requests
+    #[serde(default = "default_tautulli_max_retries")]
+    #[validate(range(max = 10, message = "Max retries cannot exceed 10"))]
+    pub max_retries: u32,
 }
 
 /// Scheduling configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct SchedulingConfig {
     /// Cron expression for automatic graph generation
     /// Example: "0 0 * * *" for daily at midnight
@@ -105,7 +189,7 @@ pub struct SchedulingConfig {
 }
 
 /// Graph rendering configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct GraphConfig {
     /// Graph width in pixels
     #[validate(range(min = 100, max = 4000, message = "Width must be between 100 and 4000 pixels"))]
@@ -149,7 +233,7 @@ pub struct GraphConfig {
 }
 
 /// Database configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct DatabaseConfig {
     /// Database connection URL
     #[validate(length(min = 1, message = "Database URL cannot be empty"))]
@@ -169,7 +253,7 @@ pub struct DatabaseConfig {
 }
 
 /// Logging configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct LoggingConfig {
     /// Log level (trace, debug, info, warn, error)
     #[validate(custom(function = "validate_log_level", message = "Log level must be one of: trace, debug, info, warn, error"))]
@@ -196,24 +280,97 @@ pub struct LoggingConfig {
     pub max_files: u32,
 }
 
+/// Username censoring settings
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Validate)]
+#[serde(default)]
+pub struct PrivacySettings {
+    /// How usernames are shown by default (visible, masked, pseudonym, hidden)
+    #[validate(custom(function = "validate_username_display", message = "Username display must be one of: visible, masked, pseudonym, hidden"))]
+    pub username_display: String,
+
+    /// Characters kept when masking, e.g. 2 shows "Jo***"
+    #[validate(range(min = 1, max = 8, message = "Masked characters must be between 1 and 8"))]
+    pub masked_visible_chars: u32,
+
+    /// Secret mixed into pseudonyms so they cannot be matched across servers
+    pub pseudonym_salt: Option<String>,
+}
 
 impl Config {
     /// Comprehensive validation of the entire configuration
+    ///
+    /// Every check runs, so the error lists all problems at once with the
+    /// custom checks nested under their section.
     pub fn validate_all(&self) -> Result<(), validator::ValidationErrors> {
         // First run the standard validator validation
-        self.validate()?;
+        let mut errors = self.validate().err().unwrap_or_default();
         
         // Then run custom validation for scheduling
-        self.scheduling.validate_scheduling()?;
+        add_section_errors(&mut errors, "scheduling", self.scheduling.validate_scheduling());
+
+        // Server names must be unique across all Tautulli servers
+        add_section_errors(&mut errors, "tautulli", self.tautulli.validate_servers());
         
-        Ok(())
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Nest a section's custom check errors under it, next to its attribute errors
+fn add_section_errors(
+    errors: &mut validator::ValidationErrors,
+    section: &'static str,
+    result: Result<(), validator::ValidationErrors>,
+) {
+    let Err(section_errors) = result else {
+        return;
+    };
+    let nested = errors
+        .errors_mut()
+        .entry(section)
+        .or_insert_with(|| validator::ValidationErrorsKind::Struct(Box::default()));
+    if let validator::ValidationErrorsKind::Struct(nested) = nested {
+        for (field, kind) in section_errors.into_errors() {
+            match (nested.errors_mut().get_mut(field), kind) {
+                (
+                    Some(validator::ValidationErrorsKind::Field(existing)),
+                    validator::ValidationErrorsKind::Field(added),
+                ) => existing.extend(added),
+                (_, kind) => {
+                    nested.errors_mut().insert(field, kind);
+                }
+            }
+        }
     }
 }
 
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: default_config_version(),
+            discord: DiscordConfig::default(),
+            tautulli: TautulliConfig::default(),
+            scheduling: SchedulingConfig::default(),
+            graph: GraphConfig::default(),
+            database: DatabaseConfig::default(),
+            logging: LoggingConfig::default(),
+            privacy: PrivacySettings::default(),
+        }
+    }
+}
+
+fn default_config_version() -> u32 {
+    crate::migrate::CONFIG_VERSION
+}
+
 impl Default for DiscordConfig {
     fn default() -> Self {
         Self {
             token: "000000000000000000.XXXXXX.YYYYYYYYYYYYYYYYYYYYYY".to_string(),
+            token_file: None,
             channels: Vec::new(),
             max_concurrent_requests: 10,
             request_timeout_seconds: 30,
@@ -228,14 +385,170 @@ impl Default for DiscordConfig {
 impl Default for TautulliConfig {
     fn default() -> Self {
         Self {
+            name: default_tautulli_server_name(),
             url: "http://localhost:8181".to_string(),
             api_key: "your_api_key_here".to_string(),
+            api_key_file: None,
             timeout_seconds: 30,
             max_retries: 3,
+            circuit_breaker: CircuitBreakerSettings::default(),
+            servers: Vec::new(),
+        }
+    }
+}
+
+impl Default for CircuitBreakerSettings {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_seconds: 30,
+            success_threshold: 1,
         }
     }
 }
 
+impl CircuitBreakerSettings {
+    /// Convert into the API client breaker configuration
+    pub fn to_breaker_config(&self) -> tgraph_common::CircuitBreakerConfig {
+        tgraph_common::CircuitBreakerConfig::default()
+            .with_failure_threshold(self.failure_threshold)
+            .with_open_duration(std::time::Duration::from_secs(self.open_seconds))
+            .with_success_threshold(self.success_threshold)
+    }
+}
+
+impl TautulliConfig {
+    /// The primary server followed by every additional server
+    pub fn all_servers(&self) -> Vec<TautulliServerConfig> {
+        let primary = TautulliServerConfig {
+            name: self.name.clone(),
+            url: self.url.clone(),
+            api_key: self.api_key.clone(),
+            api_key_file: self.api_key_file.clone(),
+            timeout_seconds: self.timeout_seconds,
+            max_retries: self.max_retries,
+        };
+
+        std::iter::once(primary)
+            .chain(self.servers.iter().cloned())
+            .collect()
+    }
+
+    /// Look up a server by name, ignoring case, including the primary server
+    pub fn server(&self, name: &str) -> Option<TautulliServerConfig> {
+        let name = name.to_lowercase();
+        self.all_servers().into_iter().find(|server| server.name.to_lowercase() == name)
+    }
+
+    /// Custom validation ensuring server names are unique
+    pub fn validate_servers(&self) -> Result<(), validator::ValidationErrors> {
+        let mut errors = validator::ValidationErrors::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for server in self.all_servers() {
+            if !seen.insert(server.name.to_lowercase()) {
+                let mut err = validator::ValidationError::new("duplicate_server_name");
+                err.message = Some(format!("Duplicate Tautulli server name: {}", server.name).into());
+                errors.add("servers", err);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Named client configurations for every server
+    pub fn client_configs(&self) -> Vec<(String, tgraph_common::TautulliConfig)> {
+        self.all_servers()
+            .into_iter()
+            .map(|server| {
+                let client_config = server
+                    .to_client_config()
+                    .with_circuit_breaker(self.circuit_breaker.to_breaker_config());
+                (server.name, client_config)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for DiscordConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiscordConfig")
+            .field("token", &redact(&self.token))
+            .field("token_file", &self.token_file)
+            .field("channels", &self.channels)
+            .field("max_concurrent_requests", &self.max_concurrent_requests)
+            .field("request_timeout_seconds", &self.request_timeout_seconds)
+            .field("owner_ids", &self.owner_ids)
+            .field("admin_ids", &self.admin_ids)
+            .field("admin_role_ids", &self.admin_role_ids)
+            .field("moderator_role_ids", &self.moderator_role_ids)
+            .finish()
+    }
+}
+
+impl fmt::Debug for TautulliConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TautulliConfig")
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .field("api_key", &redact(&self.api_key))
+            .field("api_key_file", &self.api_key_file)
+            .field("timeout_seconds", &self.timeout_seconds)
+            .field("max_retries", &self.max_retries)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("servers", &self.servers)
+            .finish()
+    }
+}
+
+impl fmt::Debug for TautulliServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TautulliServerConfig")
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .field("api_key", &redact(&self.api_key))
+            .field("api_key_file", &self.api_key_file)
+            .field("timeout_seconds", &self.timeout_seconds)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+impl fmt::Debug for PrivacySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivacySettings")
+            .field("username_display", &self.username_display)
+            .field("masked_visible_chars", &self.masked_visible_chars)
+            .field("pseudonym_salt", &self.pseudonym_salt.as_deref().map(redact))
+            .finish()
+    }
+}
+
+impl TautulliServerConfig {
+    /// Convert into the API client configuration
+    pub fn to_client_config(&self) -> tgraph_common::TautulliConfig {
+        tgraph_common::TautulliConfig::new(self.url.clone(), self.api_key.clone())
+            .with_timeout(self.timeout_seconds)
+            .with_max_retries(self.max_retries as usize)
+    }
+}
+
+fn default_tautulli_server_name() -> String {
+    "default".to_string()
+}
+
+fn default_tautulli_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_tautulli_max_retries() -> u32 {
+    3
+}
+
 impl Default for SchedulingConfig {
     fn default() -> Self {
         Self {
@@ -323,6 +636,16 @@ impl Default for LoggingConfig {
     }
 }
 
+impl Default for PrivacySettings {
+    fn default() -> Self {
+        Self {
+            username_display: "visible".to_string(),
+            masked_visible_chars: 2,
+            pseudonym_salt: None,
+        }
+    }
+}
+
 // Custom validation functions
 fn validate_log_level(level: &str) -> Result<(), validator::ValidationError> {
     match level {
@@ -331,6 +654,12 @@ fn validate_log_level(level: &str) -> Result<(), validator::ValidationError> {
     }
 }
 
+fn validate_username_display(display: &str) -> Result<(), validator::ValidationError> {
+    match display {
+        "visible" | "masked" | "pseudonym" | "hidden" => Ok(()),
+        _ => Err(validator::ValidationError::new("invalid_username_display")),
+    }
+}
 
 
 // Re-export for backward compatibility  
@@ -410,6 +739,80 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_tautulli_multi_server_config() {
+        let yaml = r"
+name: 'Home'
+url: 'https://home.example.com'
+api_key: 'home_key'
+timeout_seconds: 30
+max_retries: 3
+servers:
+  - name: 'Cabin'
+    url: 'https://cabin.example.com'
+    api_key: 'cabin_key'
+";
+
+        let config: TautulliConfig = serde_yaml::from_str(yaml).expect("Failed to parse servers");
+        assert!(config.validate().is_ok());
+        assert!(config.validate_servers().is_ok());
+
+        let servers = config.all_servers();
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].name, "Home");
+        assert_eq!(servers[1].name, "Cabin");
+        assert_eq!(servers[1].timeout_seconds, 30);
+        assert_eq!(config.server("Cabin").unwrap().api_key, "cabin_key");
+
+        let client_configs = config.client_configs();
+        assert_eq!(client_configs[1].0, "Cabin");
+        assert_eq!(client_configs[1].1.base_url, "https://cabin.example.com");
+    }
+
+    #[test]
+    fn test_circuit_breaker_settings() {
+        let yaml = r"
+url: 'https://home.example.com'
+api_key: 'home_key'
+timeout_seconds: 30
+max_retries: 3
+circuit_breaker:
+  failure_threshold: 3
+";
+
+        let mut config: TautulliConfig = serde_yaml::from_str(yaml).expect("Failed to parse breaker");
+        assert_eq!(config.circuit_breaker.failure_threshold, 3);
+        assert_eq!(config.circuit_breaker.open_seconds, 30);
+        assert!(config.validate().is_ok());
+
+        let client_configs = config.client_configs();
+        assert_eq!(client_configs[0].1.circuit_breaker.failure_threshold, 3);
+
+        config.circuit_breaker.failure_threshold = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_tautulli_duplicate_server_names() {
+        let mut config = TautulliConfig::default();
+        config.servers.push(TautulliServerConfig {
+            name: "Default".to_string(),
+            url: "https://other.example.com".to_string(),
+            api_key: "other_key".to_string(),
+            api_key_file: None,
+            timeout_seconds: 30,
+            max_retries: 3,
+        });
+        assert!(config.validate_servers().is_err());
+
+        config.servers[0].name = "Other".to_string();
+        assert!(config.validate_servers().is_ok());
+
+        // Nested servers are validated too
+        config.servers[0].url = "not_a_url".to_string();
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_graph_config_validation() {
         let mut config = GraphConfig::default();
@@ -452,6 +855,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_privacy_settings_validation() {
+        let yaml = r"
+username_display: 'masked'
+";
+        let mut config: PrivacySettings = serde_yaml::from_str(yaml).expect("Failed to parse privacy");
+        assert_eq!(config.masked_visible_chars, 2);
+        assert!(config.validate().is_ok());
+
+        config.username_display = "blurred".to_string();
+        assert!(config.validate().is_err());
+
+        config.username_display = "hidden".to_string();
+        config.masked_visible_chars = 0;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_minimal_valid_config() {
         let yaml = r"
@@ -568,6 +988,7 @@ logging:
         // Test individual components first
         let discord_config = DiscordConfig {
             token: "792715454196088842.X-hvzA.Ovy4MCQywSkoMRRclStW4xAYK7I".to_string(),
+            token_file: None,
             channels: vec![],
             max_concurrent_requests: 10,
             request_timeout_seconds: 30,
@@ -579,10 +1000,14 @@ logging:
         assert!(discord_config.validate().is_ok());
 
         let tautulli_config = TautulliConfig {
+            name: "default".to_string(),
             url: "https://tautulli.example.com".to_string(),
             api_key: "test_key".to_string(),
+            api_key_file: None,
             timeout_seconds: 30,
             max_retries: 3,
+            circuit_breaker: CircuitBreakerSettings::default(),
+            servers: vec![],
         };
         assert!(tautulli_config.validate().is_ok());
 
@@ -1,27 +1,82 @@
 //! Validation utilities and regex patterns
 
 use regex::Regex;
+use std::fmt;
 use std::str::FromStr;
 use std::sync::LazyLock;
-use validator::ValidationError;
+use std::time::Duration;
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
 
 /// Regex pattern for validating hex color codes (e.g., #FFFFFF, #FF0000)
 pub static HEX_COLOR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"^#[0-9A-Fa-f]{6}$").expect("Invalid hex color regex pattern")
 });
 
-/// Validate a cron expression
+/// Validate a cron expression, in five-field or six-field form
 pub fn validate_cron_expression(cron_expr: &str) -> Result<(), ValidationError> {
     if cron_expr.is_empty() {
         return Err(ValidationError::new("empty_cron_expression"));
     }
 
-    match cron::Schedule::from_str(cron_expr) {
+    match cron::Schedule::from_str(&expand_cron_expression(cron_expr)) {
         Ok(_) => Ok(()),
         Err(_) => Err(ValidationError::new("invalid_cron_expression")),
     }
 }
 
+/// Expand a five-field cron expression, as used in the configuration, to the
+/// six-field form with seconds that the scheduler expects
+///
+/// Five-field expressions number weekdays from Sunday = 0, while the
+/// scheduler starts at Sunday = 1, so numeric weekdays are spelled out.
+/// Other expressions are returned as they are.
+pub fn expand_cron_expression(cron_expression: &str) -> String {
+    let fields: Vec<&str> = cron_expression.split_whitespace().collect();
+    if fields.len() == 5 {
+        format!("0 {} {}", fields[..4].join(" "), weekday_names(fields[4]))
+    } else {
+        cron_expression.trim().to_string()
+    }
+}
+
+/// Replace the weekday numbers of a five-field expression with names
+fn weekday_names(field: &str) -> String {
+    const NAMES: [&str; 8] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let name = |day: &str| match day.parse::<usize>() {
+        Ok(day) if day < NAMES.len() => NAMES[day].to_string(),
+        _ => day.to_string(),
+    };
+
+    field
+        .split(',')
+        .map(|part| {
+            // Steps such as "*/2" count days, not weekdays
+            let (days, step) = match part.split_once('/') {
+                Some((days, step)) => (days, Some(step)),
+                None => (part, None),
+            };
+            let days: Vec<String> = days.split('-').map(name).collect();
+            match step {
+                Some(step) => format!("{}/{}", days.join("-"), step),
+                None => days.join("-"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Shortest gap between two runs of a cron expression, `None` when it is invalid
+///
+/// Only the next few hundred runs are looked at, which covers at least a
+/// year for expressions that run daily or less often.
+pub fn cron_min_interval(cron_expr: &str) -> Option<Duration> {
+    let schedule = cron::Schedule::from_str(&expand_cron_expression(cron_expr)).ok()?;
+    let runs: Vec<_> = schedule.upcoming(chrono::Utc).take(500).collect();
+    runs.windows(2)
+        .filter_map(|pair| (pair[1] - pair[0]).to_std().ok())
+        .min()
+}
+
 /// Validate timezone string (basic check for common IANA timezone format)
 pub fn validate_timezone(timezone: &str) -> Result<(), ValidationError> {
     if timezone.is_empty() {
@@ -69,6 +124,57 @@ pub fn validate_file_path(path: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// A validation error located by its field path, e.g. `tautulli.servers[0].url`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// Dotted path of the field
+    pub path: String,
+    /// What is wrong with the value
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Every error in `errors` with the path of its field, sorted by path
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut found = Vec::new();
+    collect_field_errors("", errors, &mut found);
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found
+}
+
+fn collect_field_errors(prefix: &str, errors: &ValidationErrors, found: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                found.extend(errors.iter().map(|error| FieldError {
+                    path: path.clone(),
+                    message: error
+                        .message
+                        .as_ref()
+                        .map(|message| message.to_string())
+                        .unwrap_or_else(|| error.code.replace('_', " ")),
+                }));
+            }
+            ValidationErrorsKind::Struct(nested) => collect_field_errors(&path, nested, found),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_field_errors(&format!("{}[{}]", path, index), nested, found);
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,6 +207,8 @@ mod tests {
         assert!(validate_cron_expression("0 0 9-18 * * MON-FRI").is_ok()); // Weekdays 9-18
         assert!(validate_cron_expression("0 0 0 1 * *").is_ok());      // Monthly
         assert!(validate_cron_expression("0 0 2 * * 7").is_ok());      // Sunday at 2 AM
+        assert!(validate_cron_expression("0 0 * * *").is_ok());        // Five fields: daily at midnight
+        assert!(validate_cron_expression("0 2 * * 0").is_ok());        // Five fields: Sunday at 2 AM
 
         // Invalid cron expressions
         assert!(validate_cron_expression("").is_err());              // Empty
@@ -110,6 +218,23 @@ mod tests {
         assert!(validate_cron_expression("0 0 2 * * 0").is_err());   // Invalid weekday (0 not supported)
     }
 
+    #[test]
+    fn test_expand_cron_expression() {
+        assert_eq!(expand_cron_expression("0 0 * * *"), "0 0 0 * * *");
+        assert_eq!(expand_cron_expression("0 2 * * 0"), "0 0 2 * * Sun");
+        assert_eq!(expand_cron_expression("30 8 * * 1-5,7"), "0 30 8 * * Mon-Fri,Sun");
+        assert_eq!(expand_cron_expression("0 8 * * */2"), "0 0 8 * * */2");
+        assert_eq!(expand_cron_expression("0 0 2 * * 1"), "0 0 2 * * 1");
+    }
+
+    #[test]
+    fn test_cron_min_interval() {
+        assert_eq!(cron_min_interval("0 */5 * * * *"), Some(Duration::from_secs(300)));
+        assert_eq!(cron_min_interval("0 0 9,10 * * *"), Some(Duration::from_secs(3600)));
+        assert_eq!(cron_min_interval("0 0 * * *"), Some(Duration::from_secs(86400)));
+        assert_eq!(cron_min_interval("invalid"), None);
+    }
+
     #[test]
     fn test_validate_timezone() {
         // Valid timezones
@@ -156,4 +281,25 @@ mod tests {
         assert!(validate_file_path("file?name.txt").is_err());       // Invalid character ?
         assert!(validate_file_path("file*name.txt").is_err());       // Invalid character *
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_field_errors_have_paths() {
+        let mut config = crate::Config::default();
+        config.graph.width = 5;
+        config.scheduling.timezone = Some("Mars".to_string());
+        config.tautulli.servers.push(crate::settings::TautulliServerConfig {
+            name: "Cabin".to_string(),
+            url: "not a url".to_string(),
+            api_key: "key".to_string(),
+            api_key_file: None,
+            timeout_seconds: 30,
+            max_retries: 3,
+        });
+
+        let errors = field_errors(&config.validate_all().unwrap_err());
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["graph.width", "scheduling.timezone", "tautulli.servers[0].url"]);
+        assert_eq!(errors[0].to_string(), "graph.width: Width must be between 100 and 4000 pixels");
+        assert_eq!(errors[1].message, "invalid timezone format");
+    }
+}
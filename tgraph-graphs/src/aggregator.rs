@@ -1,11 +1,13 @@
 //! Data aggregation pipeline for processing Tautulli history into graph data
 
 use crate::{
-    DayOfWeekDataPoint, HourlyDataPoint, MonthlyDataPoint, PlayCountDataPoint,
+    local_time, CompletionAggregator, CompletionDataPoint, DayOfWeekDataPoint, DecisionInterval, HourlyDataPoint, MonthlyDataPoint, PlayCountDataPoint, PrivacyPolicy,
+    TranscodeDecisionAggregator, TranscodeDecisionDataPoint,
 };
 use chrono::{Datelike, NaiveDate, Timelike, Weekday};
+use chrono_tz::Tz;
 use std::collections::HashMap;
-use tgraph_common::{HistoryEntry, Result};
+use tgraph_common::{CancellationContext, HistoryEntry, Result};
 use tokio::sync::mpsc;
 use tracing::{debug, info, instrument, warn};
 
@@ -37,6 +39,12 @@ pub struct AggregationConfig {
     pub enable_progress: bool,
     /// Maximum memory usage in MB before using disk buffering
     pub max_memory_mb: usize,
+    /// Cancellation and deadline checked between chunks
+    pub cancellation: CancellationContext,
+    /// Username censoring applied to user-facing results
+    pub privacy: PrivacyPolicy,
+    /// Timezone that decides which day, hour and month a play falls in
+    pub timezone: Tz,
 }
 
 impl Default for AggregationConfig {
@@ -45,6 +53,9 @@ impl Default for AggregationConfig {
             chunk_size: 1000,
             enable_progress: true,
             max_memory_mb: 256,
+            cancellation: CancellationContext::new(),
+            privacy: PrivacyPolicy::default(),
+            timezone: Tz::UTC,
         }
     }
 }
@@ -66,6 +77,7 @@ pub trait DataAggregator<T> {
         progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
     ) -> Result<Vec<T>> {
         let total = entries.len();
+        config.cancellation.check("aggregation")?;
         self.send_progress(&progress_tx, AggregationStage::Initializing, 0, total, "Starting aggregation".to_string());
 
         let result = if total > config.chunk_size {
@@ -134,9 +146,8 @@ impl DailyPlayCountAggregator {
     }
 
     /// Extract date from timestamp
-    fn extract_date(&self, timestamp: i64) -> Option<NaiveDate> {
-        chrono::DateTime::from_timestamp(timestamp, 0)
-            .map(|dt| dt.naive_utc().date())
+    fn extract_date(&self, timestamp: i64, timezone: Tz) -> Option<NaiveDate> {
+        local_time(timestamp, timezone).map(|dt| dt.date_naive())
     }
 
     /// Check if date is within range
@@ -160,13 +171,13 @@ impl DataAggregator<PlayCountDataPoint> for DailyPlayCountAggregator {
     fn aggregate(
         &self,
         entries: Vec<HistoryEntry>,
-        _config: &AggregationConfig,
+        config: &AggregationConfig,
     ) -> Result<Vec<PlayCountDataPoint>> {
         let mut daily_counts: HashMap<NaiveDate, u32> = HashMap::new();
 
         for entry in entries {
             if let Some(timestamp) = entry.date {
-                if let Some(date) = self.extract_date(timestamp) {
+                if let Some(date) = self.extract_date(timestamp, config.timezone) {
                     if self.is_in_range(date) {
                         *daily_counts.entry(date).or_insert(0) += 1;
                     }
@@ -201,6 +212,8 @@ impl DataAggregator<PlayCountDataPoint> for DailyPlayCountAggregator {
         let mut processed = 0;
 
         for chunk in entries.chunks(config.chunk_size) {
+            config.cancellation.check("aggregation")?;
+
             self.send_progress(
                 &progress_tx,
                 AggregationStage::Processing,
@@ -211,7 +224,7 @@ impl DataAggregator<PlayCountDataPoint> for DailyPlayCountAggregator {
 
             for entry in chunk {
                 if let Some(timestamp) = entry.date {
-                    if let Some(date) = self.extract_date(timestamp) {
+                    if let Some(date) = self.extract_date(timestamp, config.timezone) {
                         if self.is_in_range(date) {
                             *daily_counts.entry(date).or_insert(0) += 1;
                         }
@@ -254,9 +267,8 @@ impl DayOfWeekAggregator {
     }
 
     /// Extract weekday from timestamp
-    fn extract_weekday(&self, timestamp: i64) -> Option<Weekday> {
-        chrono::DateTime::from_timestamp(timestamp, 0)
-            .map(|dt| dt.weekday())
+    fn extract_weekday(&self, timestamp: i64, timezone: Tz) -> Option<Weekday> {
+        local_time(timestamp, timezone).map(|dt| dt.weekday())
     }
 }
 
@@ -265,13 +277,13 @@ impl DataAggregator<DayOfWeekDataPoint> for DayOfWeekAggregator {
     fn aggregate(
         &self,
         entries: Vec<HistoryEntry>,
-        _config: &AggregationConfig,
+        config: &AggregationConfig,
     ) -> Result<Vec<DayOfWeekDataPoint>> {
         let mut weekday_counts: HashMap<Weekday, u32> = HashMap::new();
 
         for entry in entries {
             if let Some(timestamp) = entry.date {
-                if let Some(weekday) = self.extract_weekday(timestamp) {
+                if let Some(weekday) = self.extract_weekday(timestamp, config.timezone) {
                     *weekday_counts.entry(weekday).or_insert(0) += 1;
                 }
             }
@@ -312,6 +324,8 @@ impl DataAggregator<DayOfWeekDataPoint> for DayOfWeekAggregator {
         let mut processed = 0;
 
         for chunk in entries.chunks(config.chunk_size) {
+            config.cancellation.check("aggregation")?;
+
             self.send_progress(
                 &progress_tx,
                 AggregationStage::Processing,
@@ -322,7 +336,7 @@ impl DataAggregator<DayOfWeekDataPoint> for DayOfWeekAggregator {
 
             for entry in chunk {
                 if let Some(timestamp) = entry.date {
-                    if let Some(weekday) = self.extract_weekday(timestamp) {
+                    if let Some(weekday) = self.extract_weekday(timestamp, config.timezone) {
                         *weekday_counts.entry(weekday).or_insert(0) += 1;
                     }
                 }
@@ -370,9 +384,8 @@ impl HourlyDistributionAggregator {
     }
 
     /// Extract hour from timestamp
-    fn extract_hour(&self, timestamp: i64) -> Option<u8> {
-        chrono::DateTime::from_timestamp(timestamp, 0)
-            .map(|dt| dt.hour() as u8)
+    fn extract_hour(&self, timestamp: i64, timezone: Tz) -> Option<u8> {
+        local_time(timestamp, timezone).map(|dt| dt.hour() as u8)
     }
 }
 
@@ -381,13 +394,13 @@ impl DataAggregator<HourlyDataPoint> for HourlyDistributionAggregator {
     fn aggregate(
         &self,
         entries: Vec<HistoryEntry>,
-        _config: &AggregationConfig,
+        config: &AggregationConfig,
     ) -> Result<Vec<HourlyDataPoint>> {
         let mut hourly_counts: HashMap<u8, u32> = HashMap::new();
 
         for entry in entries {
             if let Some(timestamp) = entry.date {
-                if let Some(hour) = self.extract_hour(timestamp) {
+                if let Some(hour) = self.extract_hour(timestamp, config.timezone) {
                     *hourly_counts.entry(hour).or_insert(0) += 1;
                 }
             }
@@ -420,6 +433,8 @@ impl DataAggregator<HourlyDataPoint> for HourlyDistributionAggregator {
         let mut processed = 0;
 
         for chunk in entries.chunks(config.chunk_size) {
+            config.cancellation.check("aggregation")?;
+
             self.send_progress(
                 &progress_tx,
                 AggregationStage::Processing,
@@ -430,7 +445,7 @@ impl DataAggregator<HourlyDataPoint> for HourlyDistributionAggregator {
 
             for entry in chunk {
                 if let Some(timestamp) = entry.date {
-                    if let Some(hour) = self.extract_hour(timestamp) {
+                    if let Some(hour) = self.extract_hour(timestamp, config.timezone) {
                         *hourly_counts.entry(hour).or_insert(0) += 1;
                     }
                 }
@@ -484,9 +499,8 @@ impl MonthlyTrendsAggregator {
     }
 
     /// Extract year and month from timestamp
-    fn extract_year_month(&self, timestamp: i64) -> Option<(i32, u32)> {
-        chrono::DateTime::from_timestamp(timestamp, 0)
-            .map(|dt| (dt.year(), dt.month()))
+    fn extract_year_month(&self, timestamp: i64, timezone: Tz) -> Option<(i32, u32)> {
+        local_time(timestamp, timezone).map(|dt| (dt.year(), dt.month()))
     }
 
     /// Check if year is within range
@@ -510,13 +524,13 @@ impl DataAggregator<MonthlyDataPoint> for MonthlyTrendsAggregator {
     fn aggregate(
         &self,
         entries: Vec<HistoryEntry>,
-        _config: &AggregationConfig,
+        config: &AggregationConfig,
     ) -> Result<Vec<MonthlyDataPoint>> {
         let mut monthly_counts: HashMap<(i32, u32), u32> = HashMap::new();
 
         for entry in entries {
             if let Some(timestamp) = entry.date {
-                if let Some((year, month)) = self.extract_year_month(timestamp) {
+                if let Some((year, month)) = self.extract_year_month(timestamp, config.timezone) {
                     if self.is_year_in_range(year) {
                         *monthly_counts.entry((year, month)).or_insert(0) += 1;
                     }
@@ -552,6 +566,8 @@ impl DataAggregator<MonthlyDataPoint> for MonthlyTrendsAggregator {
         let mut processed = 0;
 
         for chunk in entries.chunks(config.chunk_size) {
+            config.cancellation.check("aggregation")?;
+
             self.send_progress(
                 &progress_tx,
                 AggregationStage::Processing,
@@ -562,7 +578,7 @@ impl DataAggregator<MonthlyDataPoint> for MonthlyTrendsAggregator {
 
             for entry in chunk {
                 if let Some(timestamp) = entry.date {
-                    if let Some((year, month)) = self.extract_year_month(timestamp) {
+                    if let Some((year, month)) = self.extract_year_month(timestamp, config.timezone) {
                         if self.is_year_in_range(year) {
                             *monthly_counts.entry((year, month)).or_insert(0) += 1;
                         }
@@ -681,6 +697,8 @@ impl DataAggregator<TopPlatformDataPoint> for TopPlatformsAggregator {
         let mut processed = 0;
 
         for chunk in entries.chunks(config.chunk_size) {
+            config.cancellation.check("aggregation")?;
+
             self.send_progress(
                 &progress_tx,
                 AggregationStage::Processing,
@@ -746,7 +764,7 @@ impl DataAggregator<TopUserDataPoint> for TopUsersAggregator {
     fn aggregate(
         &self,
         entries: Vec<HistoryEntry>,
-        _config: &AggregationConfig,
+        config: &AggregationConfig,
     ) -> Result<Vec<TopUserDataPoint>> {
         let mut user_counts: HashMap<(i32, String, Option<String>), u32> = HashMap::new();
 
@@ -772,6 +790,7 @@ impl DataAggregator<TopUserDataPoint> for TopUsersAggregator {
         // Sort by count descending, take top N
         result.sort_by(|a, b| b.count.cmp(&a.count));
         result.truncate(self.limit);
+        config.privacy.apply_to_users(&mut result);
 
         debug!("Aggregated {} top user data points", result.len());
         Ok(result)
@@ -788,6 +807,8 @@ impl DataAggregator<TopUserDataPoint> for TopUsersAggregator {
         let mut processed = 0;
 
         for chunk in entries.chunks(config.chunk_size) {
+            config.cancellation.check("aggregation")?;
+
             self.send_progress(
                 &progress_tx,
                 AggregationStage::Processing,
@@ -823,6 +844,7 @@ impl DataAggregator<TopUserDataPoint> for TopUsersAggregator {
 
         result.sort_by(|a, b| b.count.cmp(&a.count));
         result.truncate(self.limit);
+        config.privacy.apply_to_users(&mut result);
 
         self.send_progress(&progress_tx, AggregationStage::Complete, processed, total, "Top users aggregation complete".to_string());
         
@@ -831,6 +853,267 @@ impl DataAggregator<TopUserDataPoint> for TopUsersAggregator {
     }
 }
 
+/// Kind of content ranked by a top content aggregator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum ContentKind {
+    /// Movies, keyed by `rating_key`
+    Movie,
+    /// TV shows, episodes grouped by `grandparent_rating_key`
+    Show,
+    /// Music artists, tracks grouped by `grandparent_rating_key`
+    Artist,
+}
+
+impl ContentKind {
+    /// Human-readable plural name
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContentKind::Movie => "Movies",
+            ContentKind::Show => "Shows",
+            ContentKind::Artist => "Artists",
+        }
+    }
+
+    /// Rating key, title and year identifying the content an entry belongs to
+    pub(crate) fn identify<'a>(&self, entry: &'a HistoryEntry) -> Option<(&'a str, &'a str, Option<i32>)> {
+        let (media_type, key, title, year) = match self {
+            ContentKind::Movie => ("movie", &entry.rating_key, &entry.title, entry.year),
+            ContentKind::Show => ("episode", &entry.grandparent_rating_key, &entry.grandparent_title, None),
+            ContentKind::Artist => ("track", &entry.grandparent_rating_key, &entry.grandparent_title, None),
+        };
+
+        if entry.media_type.as_deref() != Some(media_type) {
+            return None;
+        }
+        match (key.as_deref(), title.as_deref()) {
+            (Some(key), Some(title)) if !key.is_empty() => Some((key, title, year)),
+            _ => None,
+        }
+    }
+}
+
+/// Data point for top movies, shows or artists
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TopContentDataPoint {
+    /// Plex rating key, usable with Tautulli's image proxy
+    pub rating_key: String,
+    pub title: String,
+    pub year: Option<i32>,
+    pub count: u32,
+    /// Share of plays of this content kind
+    pub percentage: f64,
+    pub label: Option<String>,
+}
+
+/// Play counts per content item, keyed by rating key
+#[derive(Debug, Default)]
+struct ContentCounts {
+    items: HashMap<String, (String, Option<i32>, u32)>,
+    total: u32,
+}
+
+impl ContentCounts {
+    fn add(&mut self, kind: ContentKind, entry: &HistoryEntry) {
+        if let Some((key, title, year)) = kind.identify(entry) {
+            let item = self
+                .items
+                .entry(key.to_string())
+                .or_insert_with(|| (title.to_string(), year, 0));
+            item.2 += 1;
+            self.total += 1;
+        }
+    }
+
+    fn rank(self, limit: usize) -> Vec<TopContentDataPoint> {
+        let total = self.total as f64;
+        let mut result: Vec<TopContentDataPoint> = self
+            .items
+            .into_iter()
+            .map(|(rating_key, (title, year, count))| {
+                let percentage = (count as f64 / total) * 100.0;
+                TopContentDataPoint {
+                    label: Some(format!("{} - {} plays ({:.1}%)", title, count, percentage)),
+                    rating_key,
+                    title,
+                    year,
+                    count,
+                    percentage,
+                }
+            })
+            .collect();
+
+        // Sort by count descending, ties by title so rankings are stable
+        result.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.title.cmp(&b.title)));
+        result.truncate(limit);
+        result
+    }
+}
+
+/// Shared implementation of the top content aggregators
+fn aggregate_top_content(kind: ContentKind, entries: &[HistoryEntry], limit: usize) -> Vec<TopContentDataPoint> {
+    let mut counts = ContentCounts::default();
+    for entry in entries {
+        counts.add(kind, entry);
+    }
+    let result = counts.rank(limit);
+    debug!("Aggregated {} top {} data points", result.len(), kind.name().to_lowercase());
+    result
+}
+
+async fn aggregate_top_content_chunked<A>(
+    aggregator: &A,
+    kind: ContentKind,
+    entries: Vec<HistoryEntry>,
+    limit: usize,
+    config: &AggregationConfig,
+    progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+) -> Result<Vec<TopContentDataPoint>>
+where
+    A: DataAggregator<TopContentDataPoint> + ?Sized,
+{
+    let total = entries.len();
+    let name = kind.name().to_lowercase();
+    let mut counts = ContentCounts::default();
+    let mut processed = 0;
+
+    for chunk in entries.chunks(config.chunk_size) {
+        config.cancellation.check("aggregation")?;
+
+        aggregator.send_progress(
+            &progress_tx,
+            AggregationStage::Processing,
+            processed,
+            total,
+            format!("Processing {} chunk {}/{}", name, processed / config.chunk_size + 1, (total + config.chunk_size - 1) / config.chunk_size),
+        );
+
+        for entry in chunk {
+            counts.add(kind, entry);
+            processed += 1;
+        }
+
+        tokio::task::yield_now().await;
+    }
+
+    aggregator.send_progress(&progress_tx, AggregationStage::Finalizing, processed, total, format!("Finalizing {} results", name));
+    let result = counts.rank(limit);
+    aggregator.send_progress(&progress_tx, AggregationStage::Complete, processed, total, format!("Top {} aggregation complete", name));
+
+    info!("Chunked aggregation completed: {} top {} data points", result.len(), name);
+    Ok(result)
+}
+
+/// Aggregator for most-watched movies
+#[derive(Debug)]
+pub struct TopMoviesAggregator {
+    /// Maximum number of items to return
+    pub limit: usize,
+}
+
+impl TopMoviesAggregator {
+    pub fn new() -> Self {
+        Self { limit: 10 }
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        Self { limit }
+    }
+}
+
+impl DataAggregator<TopContentDataPoint> for TopMoviesAggregator {
+    #[instrument(skip(self, entries))]
+    fn aggregate(
+        &self,
+        entries: Vec<HistoryEntry>,
+        _config: &AggregationConfig,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        Ok(aggregate_top_content(ContentKind::Movie, &entries, self.limit))
+    }
+
+    async fn aggregate_chunked(
+        &self,
+        entries: Vec<HistoryEntry>,
+        config: &AggregationConfig,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        aggregate_top_content_chunked(self, ContentKind::Movie, entries, self.limit, config, progress_tx).await
+    }
+}
+
+/// Aggregator for most-watched shows, grouping episodes by show
+#[derive(Debug)]
+pub struct TopShowsAggregator {
+    /// Maximum number of items to return
+    pub limit: usize,
+}
+
+impl TopShowsAggregator {
+    pub fn new() -> Self {
+        Self { limit: 10 }
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        Self { limit }
+    }
+}
+
+impl DataAggregator<TopContentDataPoint> for TopShowsAggregator {
+    #[instrument(skip(self, entries))]
+    fn aggregate(
+        &self,
+        entries: Vec<HistoryEntry>,
+        _config: &AggregationConfig,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        Ok(aggregate_top_content(ContentKind::Show, &entries, self.limit))
+    }
+
+    async fn aggregate_chunked(
+        &self,
+        entries: Vec<HistoryEntry>,
+        config: &AggregationConfig,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        aggregate_top_content_chunked(self, ContentKind::Show, entries, self.limit, config, progress_tx).await
+    }
+}
+
+/// Aggregator for most-played music artists
+#[derive(Debug)]
+pub struct TopArtistsAggregator {
+    /// Maximum number of items to return
+    pub limit: usize,
+}
+
+impl TopArtistsAggregator {
+    pub fn new() -> Self {
+        Self { limit: 10 }
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        Self { limit }
+    }
+}
+
+impl DataAggregator<TopContentDataPoint> for TopArtistsAggregator {
+    #[instrument(skip(self, entries))]
+    fn aggregate(
+        &self,
+        entries: Vec<HistoryEntry>,
+        _config: &AggregationConfig,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        Ok(aggregate_top_content(ContentKind::Artist, &entries, self.limit))
+    }
+
+    async fn aggregate_chunked(
+        &self,
+        entries: Vec<HistoryEntry>,
+        config: &AggregationConfig,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        aggregate_top_content_chunked(self, ContentKind::Artist, entries, self.limit, config, progress_tx).await
+    }
+}
+
 /// Convenience aggregation manager for all graph types
 #[derive(Debug)]
 pub struct AggregationManager {
@@ -931,6 +1214,90 @@ impl AggregationManager {
 
         aggregator.aggregate_streaming(entries, &self.config, progress_tx).await
     }
+
+    /// Aggregate direct play, direct stream and transcode counts per day or month
+    pub async fn aggregate_transcode_decisions(
+        &self,
+        entries: Vec<HistoryEntry>,
+        interval: DecisionInterval,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TranscodeDecisionDataPoint>> {
+        let aggregator = TranscodeDecisionAggregator::with_interval(interval);
+        aggregator.aggregate_streaming(entries, &self.config, progress_tx).await
+    }
+
+    /// Aggregate completion distribution overall or per show, movie or artist
+    pub async fn aggregate_completion(
+        &self,
+        entries: Vec<HistoryEntry>,
+        aggregator: CompletionAggregator,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<CompletionDataPoint>> {
+        aggregator.aggregate_streaming(entries, &self.config, progress_tx).await
+    }
+
+    /// Aggregate data for top movies, shows or artists
+    pub async fn aggregate_top_content(
+        &self,
+        kind: ContentKind,
+        entries: Vec<HistoryEntry>,
+        limit: Option<usize>,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        match kind {
+            ContentKind::Movie => self.aggregate_top_movies(entries, limit, progress_tx).await,
+            ContentKind::Show => self.aggregate_top_shows(entries, limit, progress_tx).await,
+            ContentKind::Artist => self.aggregate_top_artists(entries, limit, progress_tx).await,
+        }
+    }
+
+    /// Aggregate data for top movies
+    pub async fn aggregate_top_movies(
+        &self,
+        entries: Vec<HistoryEntry>,
+        limit: Option<usize>,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        let aggregator = if let Some(limit) = limit {
+            TopMoviesAggregator::with_limit(limit)
+        } else {
+            TopMoviesAggregator::new()
+        };
+
+        aggregator.aggregate_streaming(entries, &self.config, progress_tx).await
+    }
+
+    /// Aggregate data for top shows
+    pub async fn aggregate_top_shows(
+        &self,
+        entries: Vec<HistoryEntry>,
+        limit: Option<usize>,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        let aggregator = if let Some(limit) = limit {
+            TopShowsAggregator::with_limit(limit)
+        } else {
+            TopShowsAggregator::new()
+        };
+
+        aggregator.aggregate_streaming(entries, &self.config, progress_tx).await
+    }
+
+    /// Aggregate data for top artists
+    pub async fn aggregate_top_artists(
+        &self,
+        entries: Vec<HistoryEntry>,
+        limit: Option<usize>,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        let aggregator = if let Some(limit) = limit {
+            TopArtistsAggregator::with_limit(limit)
+        } else {
+            TopArtistsAggregator::new()
+        };
+
+        aggregator.aggregate_streaming(entries, &self.config, progress_tx).await
+    }
 }
 
 impl Default for DailyPlayCountAggregator {
@@ -969,34 +1336,33 @@ impl Default for TopUsersAggregator {
     }
 }
 
+impl Default for TopMoviesAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for TopShowsAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for TopArtistsAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::history_entry;
     use chrono::{NaiveDate, Weekday};
     use tgraph_common::HistoryEntry;
 
     fn create_test_history_entry(date: i64, user_id: i32, username: &str, platform: &str) -> HistoryEntry {
-        HistoryEntry {
-            date: Some(date),
-            user_id: Some(user_id),
-            username: Some(username.to_string()),
-            platform: Some(platform.to_string()),
-            friendly_name: Some(format!("Friendly {}", username)),
-            media_type: None,
-            rating_key: None,
-            parent_rating_key: None,
-            grandparent_rating_key: None,
-            title: None,
-            parent_title: None,
-            grandparent_title: None,
-            year: None,
-            watched_status: None,
-            percent_complete: None,
-            duration: None,
-            transcode_decision: None,
-            player: None,
-            ip_address: None,
-        }
+        history_entry().date(date).user(user_id, username).platform(platform).build()
     }
 
     #[tokio::test]
@@ -1071,6 +1437,81 @@ mod tests {
         assert_eq!(result[1].percentage, 25.0);
     }
 
+    #[tokio::test]
+    async fn test_top_users_apply_privacy_policy() {
+        let user = |user_id, username: &str| {
+            history_entry().user(user_id, username).friendly_name(&format!("Friendly {}", username)).build()
+        };
+        let entries = vec![user(1, "user1"), user(1, "user1"), user(2, "user2")];
+
+        let mut privacy = PrivacyPolicy::new(crate::UsernameDisplay::Masked);
+        privacy.opt_out("user2", crate::UsernameDisplay::Hidden);
+        let config = AggregationConfig { privacy, ..AggregationConfig::default() };
+
+        let direct = TopUsersAggregator::new().aggregate(entries.clone(), &config).unwrap();
+        let chunked = TopUsersAggregator::new().aggregate_chunked(entries, &config, None).await.unwrap();
+        for result in [direct, chunked] {
+            assert_eq!(result[0].username, "Fr***");
+            assert_eq!(result[0].label.as_deref(), Some("Fr*** - 2 plays"));
+            assert_eq!(result[1].username, crate::HIDDEN_USER);
+        }
+    }
+
+    fn create_content_entry(media_type: &str, key: &str, title: &str) -> HistoryEntry {
+        let mut entry = create_test_history_entry(1640995200, 1, "user1", "Web");
+        entry.media_type = Some(media_type.to_string());
+        if media_type == "movie" {
+            entry.rating_key = Some(key.to_string());
+            entry.title = Some(title.to_string());
+            entry.year = Some(2022);
+        } else {
+            entry.rating_key = Some(format!("{}-item", key));
+            entry.grandparent_rating_key = Some(key.to_string());
+            entry.grandparent_title = Some(title.to_string());
+        }
+        entry
+    }
+
+    #[tokio::test]
+    async fn test_top_content_aggregation() {
+        let entries = vec![
+            create_content_entry("movie", "10", "Dune"),
+            create_content_entry("movie", "10", "Dune"),
+            create_content_entry("movie", "11", "Arrival"),
+            create_content_entry("episode", "20", "Severance"),
+            create_content_entry("episode", "20", "Severance"),
+            create_content_entry("episode", "21", "Andor"),
+            create_content_entry("track", "30", "Daft Punk"),
+        ];
+        let config = AggregationConfig::default();
+
+        let movies = TopMoviesAggregator::new().aggregate(entries.clone(), &config).unwrap();
+        assert_eq!(movies.len(), 2);
+        assert_eq!(movies[0].rating_key, "10");
+        assert_eq!(movies[0].year, Some(2022));
+        assert_eq!(movies[0].count, 2);
+
+        // Episodes are grouped by show, and shares are relative to episode plays
+        let shows = TopShowsAggregator::with_limit(1).aggregate(entries.clone(), &config).unwrap();
+        assert_eq!(shows.len(), 1);
+        assert_eq!(shows[0].title, "Severance");
+        assert_eq!(shows[0].rating_key, "20");
+        assert!((shows[0].percentage - 66.666).abs() < 0.01);
+
+        let artists = TopArtistsAggregator::new().aggregate(entries.clone(), &config).unwrap();
+        assert_eq!(artists.len(), 1);
+        assert_eq!(artists[0].title, "Daft Punk");
+
+        // Chunked processing gives the same ranking
+        let chunked_config = AggregationConfig { chunk_size: 2, ..AggregationConfig::default() };
+        let chunked = TopShowsAggregator::new()
+            .aggregate_streaming(entries, &chunked_config, None)
+            .await
+            .unwrap();
+        assert_eq!(chunked[0].title, "Severance");
+        assert_eq!(chunked[1].title, "Andor");
+    }
+
     #[tokio::test]
     async fn test_aggregation_manager() {
         let manager = AggregationManager::default();
@@ -1094,6 +1535,24 @@ mod tests {
         assert_eq!(platforms_result.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_chunked_processing_stops_when_cancelled() {
+        let entries: Vec<HistoryEntry> = (0..2500)
+            .map(|i| create_test_history_entry(1640995200 + i, 1, "user1", "Web"))
+            .collect();
+
+        let config = AggregationConfig {
+            chunk_size: 1000,
+            ..AggregationConfig::default()
+        };
+        config.cancellation.cancel();
+
+        let result = DailyPlayCountAggregator::new()
+            .aggregate_chunked(entries, &config, None)
+            .await;
+        assert!(result.unwrap_err().is_cancelled());
+    }
+
     #[tokio::test]
     async fn test_chunked_processing() {
         let mut entries = Vec::new();
@@ -10,12 +10,12 @@ use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn, instrument};
-use tgraph_common::Result;
+use tgraph_common::{Result, ServerSelection};
 use chrono::{NaiveDate, NaiveDateTime};
 
 use crate::{
-    DayOfWeekDataPoint, HourlyDataPoint, MonthlyDataPoint, PlayCountDataPoint,
-    TopPlatformDataPoint, TopUserDataPoint,
+    ContentKind, DayOfWeekDataPoint, HourlyDataPoint, MonthlyDataPoint, PlayCountDataPoint,
+    TopContentDataPoint, TopPlatformDataPoint, TopUserDataPoint,
 };
 
 /// Configuration for the cache system
@@ -57,6 +57,7 @@ pub enum GraphTypeKey {
     MonthlyTrends,
     TopPlatforms,
     TopUsers,
+    TopContent(ContentKind),
 }
 
 impl fmt::Display for GraphTypeKey {
@@ -68,6 +69,7 @@ impl fmt::Display for GraphTypeKey {
             GraphTypeKey::MonthlyTrends => write!(f, "monthly_trends"),
             GraphTypeKey::TopPlatforms => write!(f, "top_platforms"),
             GraphTypeKey::TopUsers => write!(f, "top_users"),
+            GraphTypeKey::TopContent(kind) => write!(f, "top_{}", kind.name().to_lowercase()),
         }
     }
 }
@@ -85,6 +87,9 @@ pub struct CacheKey {
     pub end_year: Option<i32>,
     /// Result limit (for top platforms/users)
     pub limit: Option<usize>,
+    /// Tautulli server the data came from (`None` for single-server setups)
+    #[serde(default)]
+    pub server: Option<ServerSelection>,
     /// Additional parameters hash for complex filters
     pub params_hash: u64,
 }
@@ -99,6 +104,7 @@ impl CacheKey {
             start_year: None,
             end_year: None,
             limit: None,
+            server: None,
             params_hash: 0,
         }
     }
@@ -112,6 +118,7 @@ impl CacheKey {
             start_year: None,
             end_year: None,
             limit: None,
+            server: None,
             params_hash: 0,
         }
     }
@@ -125,6 +132,7 @@ impl CacheKey {
             start_year: None,
             end_year: None,
             limit: None,
+            server: None,
             params_hash: 0,
         }
     }
@@ -138,6 +146,7 @@ impl CacheKey {
             start_year,
             end_year,
             limit: None,
+            server: None,
             params_hash: 0,
         }
     }
@@ -151,6 +160,7 @@ impl CacheKey {
             start_year: None,
             end_year: None,
             limit,
+            server: None,
             params_hash: 0,
         }
     }
@@ -164,6 +174,21 @@ impl CacheKey {
             start_year: None,
             end_year: None,
             limit,
+            server: None,
+            params_hash: 0,
+        }
+    }
+
+    /// Create a cache key for top movies, shows or artists data
+    pub fn top_content(kind: ContentKind, limit: Option<usize>) -> Self {
+        Self {
+            graph_type: GraphTypeKey::TopContent(kind),
+            start_date: None,
+            end_date: None,
+            start_year: None,
+            end_year: None,
+            limit,
+            server: None,
             params_hash: 0,
         }
     }
@@ -175,12 +200,24 @@ impl CacheKey {
         self.params_hash = hasher.finish();
         self
     }
+
+    /// Scope the key to a Tautulli server selection
+    pub fn for_server(mut self, selection: ServerSelection) -> Self {
+        self.server = Some(selection);
+        self
+    }
 }
 
 impl fmt::Display for CacheKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}:", self.graph_type)?;
-        
+
+        match &self.server {
+            Some(ServerSelection::Server(name)) => write!(f, "server_{}:", name)?,
+            Some(ServerSelection::All) => write!(f, "server_all:")?,
+            None => {}
+        }
+
         if let (Some(start), Some(end)) = (self.start_date, self.end_date) {
             write!(f, "{}_{}_", start.format("%Y%m%d"), end.format("%Y%m%d"))?;
         } else if let Some(start) = self.start_date {
@@ -218,6 +255,7 @@ pub enum CachedData {
     MonthlyTrends(Vec<MonthlyDataPoint>),
     TopPlatforms(Vec<TopPlatformDataPoint>),
     TopUsers(Vec<TopUserDataPoint>),
+    TopContent(Vec<TopContentDataPoint>),
 }
 
 /// Cache entry with metadata
@@ -489,6 +527,27 @@ mod tests {
         assert_ne!(key.params_hash, 0);
     }
 
+    #[test]
+    fn test_cache_key_server_scope() {
+        let home = CacheKey::day_of_week().for_server(ServerSelection::Server("Home".to_string()));
+        let all = CacheKey::day_of_week().for_server(ServerSelection::All);
+
+        assert_ne!(home, CacheKey::day_of_week());
+        assert_ne!(home, all);
+        assert!(home.to_string().contains("server_Home"));
+        assert!(all.to_string().contains("server_all"));
+    }
+
+    #[test]
+    fn test_top_content_cache_keys() {
+        let movies = CacheKey::top_content(ContentKind::Movie, Some(10));
+        let shows = CacheKey::top_content(ContentKind::Show, Some(10));
+
+        assert_ne!(movies, shows);
+        assert!(movies.to_string().starts_with("top_movies:"));
+        assert!(shows.to_string().starts_with("top_shows:"));
+    }
+
     #[tokio::test]
     async fn test_cache_basic_operations() {
         let cache = GraphDataCache::new(CacheConfig::default());
@@ -2,9 +2,9 @@
 
 use crate::{
     AggregationConfig, AggregationManager, AggregationProgress,
-    CacheConfig, CacheKey, CachedData, GraphDataCache,
+    CacheConfig, CacheKey, CachedData, ContentKind, GraphDataCache,
     DayOfWeekDataPoint, HourlyDataPoint, MonthlyDataPoint, PlayCountDataPoint,
-    TopPlatformDataPoint, TopUserDataPoint,
+    TopContentDataPoint, TopPlatformDataPoint, TopUserDataPoint,
 };
 use chrono::NaiveDate;
 use std::sync::Arc;
@@ -306,6 +306,41 @@ impl CachedAggregationManager {
         Ok(data)
     }
 
+    /// Aggregate top movies, shows or artists with caching
+    #[instrument(skip(self, entries, progress_tx))]
+    pub async fn aggregate_top_content(
+        &self,
+        kind: ContentKind,
+        entries: Vec<HistoryEntry>,
+        limit: Option<usize>,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TopContentDataPoint>> {
+        let cache_key = CacheKey::top_content(kind, limit);
+
+        // Try cache first if enabled
+        if self.cache_enabled {
+            if let Some(CachedData::TopContent(data)) = self.cache.get(&cache_key).await {
+                debug!("Cache hit for top {} data", kind.name().to_lowercase());
+                return Ok(data);
+            }
+        }
+
+        // Cache miss or disabled - compute data
+        debug!("Cache miss for top {} data - computing data", kind.name().to_lowercase());
+        let data = self.aggregator
+            .aggregate_top_content(kind, entries, limit, progress_tx)
+            .await?;
+
+        // Store in cache if enabled
+        if self.cache_enabled {
+            self.cache
+                .put(cache_key, CachedData::TopContent(data.clone()))
+                .await;
+        }
+
+        Ok(data)
+    }
+
     /// Aggregate with custom parameters and caching
     #[instrument(skip(self, _entries, _progress_tx, params, aggregation_fn))]
     pub async fn aggregate_with_params<T, F>(
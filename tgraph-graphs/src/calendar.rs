@@ -0,0 +1,329 @@
+//! Calendar-aware date arithmetic for time range presets
+//!
+//! Presets such as "Last Month" mean the previous calendar month, not the
+//! last 30 days, and "today" depends on the configured timezone rather than
+//! UTC. [`TimeRangeContext`] carries everything needed to resolve a preset:
+//! the timezone, the first day of the week and, for "All Time", the date of
+//! the earliest play.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
+use crate::DateRange;
+use tgraph_common::{HistoryEntry, Result, TGraphError, TautulliClient};
+
+/// Range used for "All Time" when the earliest play is unknown
+pub const ALL_TIME_FALLBACK_DAYS: i64 = 730;
+
+/// First day of the week for week presets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum WeekStart {
+    /// ISO 8601 weeks
+    #[default]
+    Monday,
+    Sunday,
+    Saturday,
+}
+
+/// Regions whose weeks start on Sunday
+const SUNDAY_REGIONS: &[&str] = &[
+    "US", "CA", "MX", "BR", "JP", "KR", "TW", "HK", "PH", "IN", "IL", "ZA", "SA",
+];
+
+/// Regions whose weeks start on Saturday
+const SATURDAY_REGIONS: &[&str] = &[
+    "AE", "AF", "BH", "DZ", "EG", "IQ", "IR", "JO", "KW", "LY", "OM", "QA", "SD", "SY",
+];
+
+impl WeekStart {
+    /// Week start for a locale tag such as `en-US`, `pt_BR` or `ja`
+    ///
+    /// Tags without a region use the usual region for the language.
+    pub fn for_locale(locale: &str) -> Self {
+        let mut parts = locale.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region = match parts.next() {
+            Some(region) => region.to_ascii_uppercase(),
+            None => match language.as_str() {
+                "en" => "US",
+                "ja" => "JP",
+                "ko" => "KR",
+                "he" => "IL",
+                "hi" => "IN",
+                "ar" => "EG",
+                "fa" => "IR",
+                _ => "",
+            }
+            .to_string(),
+        };
+
+        if SUNDAY_REGIONS.contains(&region.as_str()) {
+            WeekStart::Sunday
+        } else if SATURDAY_REGIONS.contains(&region.as_str()) {
+            WeekStart::Saturday
+        } else {
+            WeekStart::Monday
+        }
+    }
+
+    fn weekday(self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+            WeekStart::Saturday => Weekday::Sat,
+        }
+    }
+}
+
+/// Source of the earliest play, used to resolve "All Time"
+#[async_trait]
+pub trait HistoryOrigin: Send + Sync {
+    /// Unix timestamp of the oldest play, `None` if there is no history
+    async fn earliest_timestamp(&self) -> Result<Option<i64>>;
+}
+
+#[async_trait]
+impl HistoryOrigin for TautulliClient {
+    async fn earliest_timestamp(&self) -> Result<Option<i64>> {
+        self.get_earliest_history_timestamp().await
+    }
+}
+
+/// History already loaded locally, e.g. for a graph being generated
+#[async_trait]
+impl HistoryOrigin for [HistoryEntry] {
+    async fn earliest_timestamp(&self) -> Result<Option<i64>> {
+        Ok(self.iter().filter_map(|entry| entry.date).min())
+    }
+}
+
+/// Time of a Unix timestamp in a timezone, `None` when it is out of range
+pub fn local_time(timestamp: i64, timezone: Tz) -> Option<DateTime<Tz>> {
+    DateTime::from_timestamp(timestamp, 0).map(|time| time.with_timezone(&timezone))
+}
+
+/// Everything needed to turn a preset into concrete dates
+#[derive(Debug, Clone)]
+pub struct TimeRangeContext {
+    /// Timezone that decides where days begin
+    pub timezone: Tz,
+    /// Current date in `timezone`
+    pub today: NaiveDate,
+    pub week_start: WeekStart,
+    /// Date of the earliest play, resolved for "All Time"
+    pub earliest_date: Option<NaiveDate>,
+}
+
+impl TimeRangeContext {
+    /// Context for the current date in a timezone
+    pub fn new(timezone: Tz) -> Self {
+        Self {
+            timezone,
+            today: Utc::now().with_timezone(&timezone).date_naive(),
+            week_start: WeekStart::default(),
+            earliest_date: None,
+        }
+    }
+
+    /// Context for an IANA timezone name such as `Europe/Berlin`
+    pub fn for_timezone_name(name: &str) -> Result<Self> {
+        let timezone: Tz = name
+            .parse()
+            .map_err(|_| TGraphError::config(format!("Unknown timezone: {}", name)))?;
+        Ok(Self::new(timezone))
+    }
+
+    /// Pin the current date, mostly for tests and reports for a past day
+    pub fn with_today(mut self, today: NaiveDate) -> Self {
+        self.today = today;
+        self
+    }
+
+    pub fn with_week_start(mut self, week_start: WeekStart) -> Self {
+        self.week_start = week_start;
+        self
+    }
+
+    pub fn with_earliest_date(mut self, earliest_date: NaiveDate) -> Self {
+        self.earliest_date = Some(earliest_date);
+        self
+    }
+
+    /// Resolve the earliest play from a history source
+    pub async fn with_history_origin<O: HistoryOrigin + ?Sized>(mut self, origin: &O) -> Result<Self> {
+        if let Some(timestamp) = origin.earliest_timestamp().await? {
+            self.earliest_date = Some(self.local_date(timestamp)?);
+        }
+        Ok(self)
+    }
+
+    /// Calendar date of a Unix timestamp in this context's timezone
+    pub fn local_date(&self, timestamp: i64) -> Result<NaiveDate> {
+        local_time(timestamp, self.timezone)
+            .map(|time| time.date_naive())
+            .ok_or_else(|| TGraphError::validation(format!("Invalid timestamp: {}", timestamp)))
+    }
+
+    /// The last `days` days including today
+    pub fn last_days(&self, days: u32) -> DateRange {
+        let days = days.max(1) as i64;
+        DateRange::new(self.today - Duration::days(days - 1), self.today)
+    }
+
+    /// First day of the week containing `date`
+    pub fn week_start_of(&self, date: NaiveDate) -> NaiveDate {
+        date.week(self.week_start.weekday()).first_day()
+    }
+
+    /// The current week up to today
+    pub fn this_week(&self) -> DateRange {
+        DateRange::new(self.week_start_of(self.today), self.today)
+    }
+
+    /// The previous full calendar week
+    pub fn previous_week(&self) -> DateRange {
+        let start = self.week_start_of(self.today) - Duration::days(7);
+        DateRange::new(start, start + Duration::days(6))
+    }
+
+    /// The current month up to today
+    pub fn this_month(&self) -> DateRange {
+        DateRange::new(first_of_month(self.today), self.today)
+    }
+
+    /// The previous full calendar month
+    pub fn previous_month(&self) -> DateRange {
+        let end = first_of_month(self.today) - Duration::days(1);
+        DateRange::new(first_of_month(end), end)
+    }
+
+    /// The current quarter up to today
+    pub fn this_quarter(&self) -> DateRange {
+        DateRange::new(first_of_quarter(self.today), self.today)
+    }
+
+    /// The previous full calendar quarter
+    pub fn previous_quarter(&self) -> DateRange {
+        let end = first_of_quarter(self.today) - Duration::days(1);
+        DateRange::new(first_of_quarter(end), end)
+    }
+
+    /// The current year up to today
+    pub fn this_year(&self) -> DateRange {
+        DateRange::new(first_of_year(self.today.year()), self.today)
+    }
+
+    /// The previous full calendar year
+    pub fn previous_year(&self) -> DateRange {
+        let year = self.today.year() - 1;
+        DateRange::new(first_of_year(year), first_of_year(year + 1) - Duration::days(1))
+    }
+
+    /// The same day `months` calendar months ago up to today
+    pub fn last_months(&self, months: u32) -> DateRange {
+        let start = self
+            .today
+            .checked_sub_months(Months::new(months))
+            .unwrap_or(NaiveDate::MIN);
+        DateRange::new(start, self.today)
+    }
+
+    /// From the earliest play up to today
+    pub fn all_time(&self) -> DateRange {
+        let start = self
+            .earliest_date
+            .unwrap_or(self.today - Duration::days(ALL_TIME_FALLBACK_DAYS));
+        DateRange::new(start.min(self.today), self.today)
+    }
+}
+
+impl Default for TimeRangeContext {
+    fn default() -> Self {
+        Self::new(Tz::UTC)
+    }
+}
+
+/// Monday to Sunday of an ISO 8601 week
+///
+/// Weeks past the last week of the year are clamped to it.
+pub fn iso_week_range(year: i32, week: u32) -> Option<DateRange> {
+    let last_week = NaiveDate::from_ymd_opt(year, 12, 28)?.iso_week().week();
+    let start = NaiveDate::from_isoywd_opt(year, week.clamp(1, last_week), Weekday::Mon)?;
+    Some(DateRange::new(start, start + Duration::days(6)))
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn first_of_quarter(date: NaiveDate) -> NaiveDate {
+    let month = (date.month0() / 3) * 3 + 1;
+    NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap_or(date)
+}
+
+fn first_of_year(year: i32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, 1, 1).unwrap_or(NaiveDate::MIN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_calendar_periods() {
+        // Wednesday 2024-03-13
+        let ctx = TimeRangeContext::default().with_today(date(2024, 3, 13));
+
+        assert_eq!(ctx.previous_month(), DateRange::new(date(2024, 2, 1), date(2024, 2, 29)));
+        assert_eq!(ctx.previous_quarter(), DateRange::new(date(2023, 10, 1), date(2023, 12, 31)));
+        assert_eq!(ctx.this_quarter(), DateRange::new(date(2024, 1, 1), date(2024, 3, 13)));
+        assert_eq!(ctx.previous_year(), DateRange::new(date(2023, 1, 1), date(2023, 12, 31)));
+        assert_eq!(ctx.last_days(7), DateRange::new(date(2024, 3, 7), date(2024, 3, 13)));
+        assert_eq!(ctx.last_months(1), DateRange::new(date(2024, 2, 13), date(2024, 3, 13)));
+
+        assert_eq!(ctx.previous_week(), DateRange::new(date(2024, 3, 4), date(2024, 3, 10)));
+        let sunday = ctx.clone().with_week_start(WeekStart::Sunday);
+        assert_eq!(sunday.this_week(), DateRange::new(date(2024, 3, 10), date(2024, 3, 13)));
+
+        assert_eq!(ctx.all_time().start, date(2022, 3, 14));
+        let ctx = ctx.with_earliest_date(date(2019, 6, 1));
+        assert_eq!(ctx.all_time(), DateRange::new(date(2019, 6, 1), date(2024, 3, 13)));
+    }
+
+    #[test]
+    fn test_week_start_for_locale() {
+        assert_eq!(WeekStart::for_locale("en-US"), WeekStart::Sunday);
+        assert_eq!(WeekStart::for_locale("en-GB"), WeekStart::Monday);
+        assert_eq!(WeekStart::for_locale("pt_BR"), WeekStart::Sunday);
+        assert_eq!(WeekStart::for_locale("ja"), WeekStart::Sunday);
+        assert_eq!(WeekStart::for_locale("ar-EG"), WeekStart::Saturday);
+        assert_eq!(WeekStart::for_locale("de"), WeekStart::Monday);
+    }
+
+    #[test]
+    fn test_iso_weeks() {
+        assert_eq!(iso_week_range(2024, 1), Some(DateRange::new(date(2024, 1, 1), date(2024, 1, 7))));
+        // 2020 has 53 ISO weeks, 2021 only 52
+        assert_eq!(iso_week_range(2020, 53), Some(DateRange::new(date(2020, 12, 28), date(2021, 1, 3))));
+        assert_eq!(iso_week_range(2021, 53), iso_week_range(2021, 52));
+    }
+
+    #[tokio::test]
+    async fn test_all_time_from_history_in_timezone() {
+        let entry = |date: i64| -> HistoryEntry { serde_json::from_value(serde_json::json!({ "date": date })).unwrap() };
+        // 2024-01-01 02:00 UTC is still New Year's Eve in New York
+        let entries = vec![entry(1706745600), entry(1704074400)];
+
+        let ctx = TimeRangeContext::for_timezone_name("America/New_York")
+            .unwrap()
+            .with_history_origin(entries.as_slice())
+            .await
+            .unwrap();
+        assert_eq!(ctx.earliest_date, Some(date(2023, 12, 31)));
+        assert!(TimeRangeContext::for_timezone_name("Mars/Olympus").is_err());
+    }
+}
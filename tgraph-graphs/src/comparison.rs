@@ -1,12 +1,14 @@
 //! Graph comparison functionality for overlaying multiple time periods
 
 use crate::{
-    ComparisonConfig, ComparisonDisplayMode, ComparisonPeriod, DateRange, PlayCountDataPoint,
-    DayOfWeekDataPoint,
+    entries_in_range, AggregationConfig, ComparisonConfig, ComparisonDisplayMode, ComparisonPeriod,
+    DailyPlayCountAggregator, DataAggregator, DateRange, DayOfWeekAggregator, DayOfWeekDataPoint,
+    HourlyDataPoint, HourlyDistributionAggregator, MonthlyDataPoint, MonthlyTrendsAggregator,
+    PlayCountDataPoint, TopPlatformDataPoint, TopPlatformsAggregator, TopUserDataPoint,
+    TopUsersAggregator,
 };
-use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tgraph_common::{HistoryEntry, Result};
 
 /// Comparison result containing data for multiple periods
@@ -71,28 +73,220 @@ pub enum GrowthDirection {
     Neutral,
 }
 
+/// Data points that can be compared across periods
+pub trait ComparableDataPoint {
+    /// Identifies the same slot or item in every period
+    fn comparison_key(&self) -> String;
+
+    /// Value summarised and compared between periods
+    fn comparison_value(&self) -> f64;
+
+    /// Name shown for rank changes
+    fn comparison_label(&self) -> String {
+        self.comparison_key()
+    }
+}
+
+impl ComparableDataPoint for PlayCountDataPoint {
+    fn comparison_key(&self) -> String {
+        self.date.to_string()
+    }
+
+    fn comparison_value(&self) -> f64 {
+        self.count as f64
+    }
+}
+
+impl ComparableDataPoint for DayOfWeekDataPoint {
+    fn comparison_key(&self) -> String {
+        self.weekday.to_string()
+    }
+
+    fn comparison_value(&self) -> f64 {
+        self.count as f64
+    }
+}
+
+impl ComparableDataPoint for HourlyDataPoint {
+    fn comparison_key(&self) -> String {
+        format!("{:02}:00", self.hour)
+    }
+
+    fn comparison_value(&self) -> f64 {
+        self.count as f64
+    }
+}
+
+impl ComparableDataPoint for MonthlyDataPoint {
+    /// Keyed by month alone so year-over-year periods line up
+    fn comparison_key(&self) -> String {
+        format!("{:02}", self.month)
+    }
+
+    fn comparison_value(&self) -> f64 {
+        self.count as f64
+    }
+}
+
+impl ComparableDataPoint for TopPlatformDataPoint {
+    fn comparison_key(&self) -> String {
+        self.platform.clone()
+    }
+
+    fn comparison_value(&self) -> f64 {
+        self.count as f64
+    }
+}
+
+impl ComparableDataPoint for TopUserDataPoint {
+    fn comparison_key(&self) -> String {
+        self.user_id.to_string()
+    }
+
+    fn comparison_value(&self) -> f64 {
+        self.count as f64
+    }
+
+    /// Names are already censored by the aggregation privacy policy
+    fn comparison_label(&self) -> String {
+        self.friendly_name.clone().unwrap_or_else(|| self.username.clone())
+    }
+}
+
+/// How an item's rank moved since the previous period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RankMovement {
+    Up(usize),
+    Down(usize),
+    Unchanged,
+    /// Not ranked in the previous period
+    New,
+}
+
+/// Rank of a top-N item compared with the previous period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankChange {
+    pub key: String,
+    pub label: String,
+    /// 1-based rank in the primary period
+    pub rank: usize,
+    pub previous_rank: Option<usize>,
+    pub movement: RankMovement,
+}
+
+impl std::fmt::Display for RankChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.movement {
+            RankMovement::Up(places) => write!(f, "{} ↑{}", self.label, places),
+            RankMovement::Down(places) => write!(f, "{} ↓{}", self.label, places),
+            RankMovement::Unchanged => write!(f, "{} =", self.label),
+            RankMovement::New => write!(f, "{} (new)", self.label),
+        }
+    }
+}
+
+impl<T: ComparableDataPoint> ComparisonResult<T> {
+    /// Rank movement of each primary item against the first comparison period
+    ///
+    /// Data points are expected in rank order, as the top-N aggregators
+    /// return them.
+    pub fn rank_changes(&self) -> Vec<RankChange> {
+        let previous: HashMap<String, usize> = self
+            .secondary
+            .first()
+            .map(|period| {
+                period
+                    .data
+                    .iter()
+                    .enumerate()
+                    .map(|(i, point)| (point.comparison_key(), i + 1))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.primary
+            .data
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let rank = i + 1;
+                let key = point.comparison_key();
+                let previous_rank = previous.get(&key).copied();
+                let movement = match previous_rank {
+                    None => RankMovement::New,
+                    Some(before) if before > rank => RankMovement::Up(before - rank),
+                    Some(before) if before < rank => RankMovement::Down(rank - before),
+                    Some(_) => RankMovement::Unchanged,
+                };
+                RankChange {
+                    label: point.comparison_label(),
+                    key,
+                    rank,
+                    previous_rank,
+                    movement,
+                }
+            })
+            .collect()
+    }
+
+    /// Items ranked in the first comparison period that dropped out
+    pub fn dropped_entries(&self) -> Vec<String> {
+        let current: HashSet<String> = self.primary.data.iter().map(|point| point.comparison_key()).collect();
+        self.secondary
+            .first()
+            .map(|period| {
+                period
+                    .data
+                    .iter()
+                    .filter(|point| !current.contains(&point.comparison_key()))
+                    .map(|point| point.comparison_label())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
 /// Manager for handling graph comparisons
 pub struct ComparisonManager {
     config: ComparisonConfig,
+    /// Passed to the aggregators, including the privacy policy
+    aggregation: AggregationConfig,
 }
 
 impl ComparisonManager {
     pub fn new(config: ComparisonConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            aggregation: AggregationConfig::default(),
+        }
     }
 
-    /// Compare daily play count data across multiple periods
-    pub async fn compare_daily_play_counts(
+    /// Use a custom aggregation config, e.g. to censor usernames
+    pub fn with_aggregation_config(mut self, aggregation: AggregationConfig) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// Compare any aggregated data across the configured periods
+    ///
+    /// Entries are filtered to each period's date range before aggregation,
+    /// so the same history can be passed for every period.
+    pub async fn compare<T, A>(
         &self,
+        aggregator: &A,
         primary_data: Vec<HistoryEntry>,
         comparison_data: Vec<Vec<HistoryEntry>>,
-    ) -> Result<ComparisonResult<PlayCountDataPoint>> {
-        let primary_aggregated = self.aggregate_daily_play_counts(&primary_data, &self.config.primary_range)?;
+    ) -> Result<ComparisonResult<T>>
+    where
+        T: ComparableDataPoint,
+        A: DataAggregator<T>,
+    {
+        let primary_aggregated = self.aggregate_period(aggregator, &primary_data, &self.config.primary_range)?;
         let primary_period = ComparisonPeriodData {
             label: "Primary Period".to_string(),
             date_range: self.config.primary_range.clone(),
             color: None,
-            summary: self.calculate_summary_play_counts(&primary_aggregated),
+            summary: self.calculate_summary(&primary_aggregated),
             data: primary_aggregated,
         };
 
@@ -100,12 +294,12 @@ impl ComparisonManager {
         for (i, data) in comparison_data.iter().enumerate() {
             if let Some(period_config) = self.config.comparison_ranges.get(i) {
                 if period_config.enabled {
-                    let aggregated = self.aggregate_daily_play_counts(data, &period_config.date_range)?;
+                    let aggregated = self.aggregate_period(aggregator, data, &period_config.date_range)?;
                     secondary_periods.push(ComparisonPeriodData {
                         label: period_config.label.clone(),
                         date_range: period_config.date_range.clone(),
                         color: period_config.color.clone(),
-                        summary: self.calculate_summary_play_counts(&aggregated),
+                        summary: self.calculate_summary(&aggregated),
                         data: aggregated,
                     });
                 }
@@ -113,7 +307,7 @@ impl ComparisonManager {
         }
 
         let differences = if self.config.show_differences {
-            Some(self.calculate_play_count_differences(&primary_period, &secondary_periods)?)
+            Some(self.calculate_differences(&primary_period, &secondary_periods))
         } else {
             None
         };
@@ -126,139 +320,74 @@ impl ComparisonManager {
         })
     }
 
+    /// Compare daily play count data across multiple periods
+    pub async fn compare_daily_play_counts(
+        &self,
+        primary_data: Vec<HistoryEntry>,
+        comparison_data: Vec<Vec<HistoryEntry>>,
+    ) -> Result<ComparisonResult<PlayCountDataPoint>> {
+        self.compare(&DailyPlayCountAggregator::new(), primary_data, comparison_data).await
+    }
+
     /// Compare day of week data across multiple periods
     pub async fn compare_day_of_week(
         &self,
         primary_data: Vec<HistoryEntry>,
         comparison_data: Vec<Vec<HistoryEntry>>,
     ) -> Result<ComparisonResult<DayOfWeekDataPoint>> {
-        let primary_aggregated = self.aggregate_day_of_week(&primary_data)?;
-        let primary_period = ComparisonPeriodData {
-            label: "Primary Period".to_string(),
-            date_range: self.config.primary_range.clone(),
-            color: None,
-            summary: self.calculate_summary_day_of_week(&primary_aggregated),
-            data: primary_aggregated,
-        };
-
-        let mut secondary_periods = Vec::new();
-        for (i, data) in comparison_data.iter().enumerate() {
-            if let Some(period_config) = self.config.comparison_ranges.get(i) {
-                if period_config.enabled {
-                    let aggregated = self.aggregate_day_of_week(data)?;
-                    secondary_periods.push(ComparisonPeriodData {
-                        label: period_config.label.clone(),
-                        date_range: period_config.date_range.clone(),
-                        color: period_config.color.clone(),
-                        summary: self.calculate_summary_day_of_week(&aggregated),
-                        data: aggregated,
-                    });
-                }
-            }
-        }
-
-        let differences = if self.config.show_differences {
-            Some(self.calculate_day_of_week_differences(&primary_period, &secondary_periods)?)
-        } else {
-            None
-        };
-
-        Ok(ComparisonResult {
-            primary: primary_period,
-            secondary: secondary_periods,
-            config: self.config.clone(),
-            differences,
-        })
+        self.compare(&DayOfWeekAggregator::new(), primary_data, comparison_data).await
     }
 
-    /// Aggregate daily play count data for a specific date range
-    fn aggregate_daily_play_counts(&self, entries: &[HistoryEntry], date_range: &DateRange) -> Result<Vec<PlayCountDataPoint>> {
-        let mut daily_counts: HashMap<NaiveDate, u32> = HashMap::new();
-
-        for entry in entries {
-            if let Some(timestamp) = entry.date {
-                if let Some(date) = chrono::DateTime::from_timestamp(timestamp, 0)
-                    .map(|dt| dt.naive_utc().date()) {
-                    if date >= date_range.start && date <= date_range.end {
-                        *daily_counts.entry(date).or_insert(0) += 1;
-                    }
-                }
-            }
-        }
-
-        let mut result: Vec<PlayCountDataPoint> = daily_counts
-            .into_iter()
-            .map(|(date, count)| PlayCountDataPoint {
-                date,
-                count,
-                label: Some(format!("{} plays", count)),
-            })
-            .collect();
-
-        result.sort_by_key(|point| point.date);
-        Ok(result)
+    /// Compare hour of day distributions across multiple periods
+    pub async fn compare_hourly(
+        &self,
+        primary_data: Vec<HistoryEntry>,
+        comparison_data: Vec<Vec<HistoryEntry>>,
+    ) -> Result<ComparisonResult<HourlyDataPoint>> {
+        self.compare(&HourlyDistributionAggregator::new(), primary_data, comparison_data).await
     }
 
-    /// Aggregate day of week data
-    fn aggregate_day_of_week(&self, entries: &[HistoryEntry]) -> Result<Vec<DayOfWeekDataPoint>> {
-        let mut weekday_counts: HashMap<chrono::Weekday, u32> = HashMap::new();
-
-        for entry in entries {
-            if let Some(timestamp) = entry.date {
-                if let Some(date) = chrono::DateTime::from_timestamp(timestamp, 0)
-                    .map(|dt| dt.naive_utc().date()) {
-                    let weekday = date.weekday();
-                    *weekday_counts.entry(weekday).or_insert(0) += 1;
-                }
-            }
-        }
-
-        let total_count: u32 = weekday_counts.values().sum();
-        let mut result: Vec<DayOfWeekDataPoint> = weekday_counts
-            .into_iter()
-            .map(|(weekday, count)| {
-                let percentage = if total_count > 0 {
-                    (count as f64 / total_count as f64) * 100.0
-                } else {
-                    0.0
-                };
-                DayOfWeekDataPoint {
-                    weekday,
-                    count,
-                    label: Some(format!("{}: {} plays ({:.1}%)", weekday, count, percentage)),
-                }
-            })
-            .collect();
-
-        result.sort_by_key(|point| point.weekday.num_days_from_monday());
-        Ok(result)
+    /// Compare monthly play counts across multiple periods
+    pub async fn compare_monthly(
+        &self,
+        primary_data: Vec<HistoryEntry>,
+        comparison_data: Vec<Vec<HistoryEntry>>,
+    ) -> Result<ComparisonResult<MonthlyDataPoint>> {
+        self.compare(&MonthlyTrendsAggregator::new(), primary_data, comparison_data).await
     }
 
-    /// Calculate summary statistics for play count data
-    fn calculate_summary_play_counts(&self, data: &[PlayCountDataPoint]) -> ComparisonSummary {
-        if data.is_empty() {
-            return ComparisonSummary {
-                total: 0.0,
-                average: 0.0,
-                peak: 0.0,
-                data_points: 0,
-            };
-        }
+    /// Compare the top platforms, see [`ComparisonResult::rank_changes`]
+    pub async fn compare_top_platforms(
+        &self,
+        primary_data: Vec<HistoryEntry>,
+        comparison_data: Vec<Vec<HistoryEntry>>,
+        limit: usize,
+    ) -> Result<ComparisonResult<TopPlatformDataPoint>> {
+        self.compare(&TopPlatformsAggregator::with_limit(limit), primary_data, comparison_data).await
+    }
 
-        let total = data.iter().map(|p| p.count as f64).sum();
-        let peak = data.iter().map(|p| p.count as f64).fold(0.0, f64::max);
-        let average = total / data.len() as f64;
+    /// Compare the top users, see [`ComparisonResult::rank_changes`]
+    pub async fn compare_top_users(
+        &self,
+        primary_data: Vec<HistoryEntry>,
+        comparison_data: Vec<Vec<HistoryEntry>>,
+        limit: usize,
+    ) -> Result<ComparisonResult<TopUserDataPoint>> {
+        self.compare(&TopUsersAggregator::with_limit(limit), primary_data, comparison_data).await
+    }
 
-        ComparisonSummary {
-            total,
-            average,
-            peak,
-            data_points: data.len(),
-        }
+    /// Aggregate the entries that fall within a date range
+    fn aggregate_period<T, A: DataAggregator<T>>(
+        &self,
+        aggregator: &A,
+        entries: &[HistoryEntry],
+        date_range: &DateRange,
+    ) -> Result<Vec<T>> {
+        aggregator.aggregate(entries_in_range(entries, date_range, self.aggregation.timezone), &self.aggregation)
     }
 
-    /// Calculate summary statistics for day of week data
-    fn calculate_summary_day_of_week(&self, data: &[DayOfWeekDataPoint]) -> ComparisonSummary {
+    /// Calculate summary statistics for a period
+    fn calculate_summary<T: ComparableDataPoint>(&self, data: &[T]) -> ComparisonSummary {
         if data.is_empty() {
             return ComparisonSummary {
                 total: 0.0,
@@ -268,8 +397,8 @@ impl ComparisonManager {
             };
         }
 
-        let total = data.iter().map(|p| p.count as f64).sum();
-        let peak = data.iter().map(|p| p.count as f64).fold(0.0, f64::max);
+        let total = data.iter().map(|p| p.comparison_value()).sum();
+        let peak = data.iter().map(|p| p.comparison_value()).fold(0.0, f64::max);
         let average = total / data.len() as f64;
 
         ComparisonSummary {
@@ -280,18 +409,21 @@ impl ComparisonManager {
         }
     }
 
-    /// Calculate differences between play count periods
-    fn calculate_play_count_differences(
+    /// Calculate the change of the primary period against each comparison period
+    ///
+    /// Differences are the primary total minus the other period's, relative
+    /// to the other period, so a busier current week shows as growth.
+    fn calculate_differences<T>(
         &self,
-        primary: &ComparisonPeriodData<PlayCountDataPoint>,
-        secondary: &[ComparisonPeriodData<PlayCountDataPoint>],
-    ) -> Result<Vec<ComparisonDifference>> {
+        primary: &ComparisonPeriodData<T>,
+        secondary: &[ComparisonPeriodData<T>],
+    ) -> Vec<ComparisonDifference> {
         let mut differences = Vec::new();
 
         for period in secondary {
-            let absolute_diff = period.summary.total - primary.summary.total;
-            let percentage_diff = if primary.summary.total != 0.0 {
-                (absolute_diff / primary.summary.total) * 100.0
+            let absolute_diff = primary.summary.total - period.summary.total;
+            let percentage_diff = if period.summary.total != 0.0 {
+                (absolute_diff / period.summary.total) * 100.0
             } else {
                 0.0
             };
@@ -305,49 +437,14 @@ impl ComparisonManager {
             };
 
             differences.push(ComparisonDifference {
-                label: format!("{} vs Primary", period.label),
+                label: format!("{} vs {}", primary.label, period.label),
                 absolute_difference: absolute_diff,
                 percentage_difference: percentage_diff,
                 growth_direction,
             });
         }
 
-        Ok(differences)
-    }
-
-    /// Calculate differences between day of week periods
-    fn calculate_day_of_week_differences(
-        &self,
-        primary: &ComparisonPeriodData<DayOfWeekDataPoint>,
-        secondary: &[ComparisonPeriodData<DayOfWeekDataPoint>],
-    ) -> Result<Vec<ComparisonDifference>> {
-        let mut differences = Vec::new();
-
-        for period in secondary {
-            let absolute_diff = period.summary.total - primary.summary.total;
-            let percentage_diff = if primary.summary.total != 0.0 {
-                (absolute_diff / primary.summary.total) * 100.0
-            } else {
-                0.0
-            };
-
-            let growth_direction = if absolute_diff > 0.0 {
-                GrowthDirection::Positive
-            } else if absolute_diff < 0.0 {
-                GrowthDirection::Negative
-            } else {
-                GrowthDirection::Neutral
-            };
-
-            differences.push(ComparisonDifference {
-                label: format!("{} vs Primary", period.label),
-                absolute_difference: absolute_diff,
-                percentage_difference: percentage_diff,
-                growth_direction,
-            });
-        }
-
-        Ok(differences)
+        differences
     }
 
     /// Create a comparison configuration with common time periods
@@ -408,6 +505,33 @@ impl ComparisonManager {
         }
     }
 
+    /// Create a comparison with the period of the same length just before
+    pub fn create_previous_period_comparison(base_date_range: DateRange) -> ComparisonConfig {
+        let days_in_range = (base_date_range.end - base_date_range.start).num_days();
+        let previous_end = base_date_range.start - chrono::Duration::days(1);
+        let previous_start = previous_end - chrono::Duration::days(days_in_range);
+
+        ComparisonConfig {
+            enabled: true,
+            primary_range: base_date_range,
+            comparison_ranges: vec![
+                ComparisonPeriod {
+                    label: "Previous Period".to_string(),
+                    date_range: DateRange::new(previous_start, previous_end),
+                    color: Some("#FF6B6B".to_string()),
+                    enabled: true,
+                },
+            ],
+            display_mode: ComparisonDisplayMode::Overlay,
+            show_differences: true,
+            show_growth_percentages: true,
+            comparison_colors: vec![
+                "#FF6B6B".to_string(),
+                "#4ECDC4".to_string(),
+            ],
+        }
+    }
+
     /// Create a comparison configuration for week-over-week
     pub fn create_week_over_week_comparison(base_date_range: DateRange) -> ComparisonConfig {
         let days_in_range = (base_date_range.end - base_date_range.start).num_days();
@@ -497,7 +621,9 @@ mod tests {
             create_test_history_entry(1704153600, 1, "user1"), // 2024-01-02
         ];
 
-        let result = manager.aggregate_daily_play_counts(&entries, &date_range).unwrap();
+        let result = manager
+            .aggregate_period(&DailyPlayCountAggregator::new(), &entries, &date_range)
+            .unwrap();
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].count, 2); // Two plays on 2024-01-01
         assert_eq!(result[1].count, 1); // One play on 2024-01-02
@@ -513,11 +639,73 @@ mod tests {
 
         let config = ComparisonConfig::default();
         let manager = ComparisonManager::new(config);
-        let summary = manager.calculate_summary_play_counts(&data);
+        let summary = manager.calculate_summary(&data);
 
         assert_eq!(summary.total, 45.0);
         assert_eq!(summary.average, 15.0);
         assert_eq!(summary.peak, 20.0);
         assert_eq!(summary.data_points, 3);
     }
-} 
\ No newline at end of file
+
+    fn week_over_week_manager() -> ComparisonManager {
+        let primary = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 14).unwrap(),
+        );
+        ComparisonManager::new(ComparisonManager::create_week_over_week_comparison(primary))
+    }
+
+    fn platform_entry(date: i64, platform: &str) -> HistoryEntry {
+        let mut entry = create_test_history_entry(date, 1, "user1");
+        entry.platform = Some(platform.to_string());
+        entry
+    }
+
+    #[tokio::test]
+    async fn test_compare_hourly_and_monthly() {
+        let manager = week_over_week_manager();
+        let entries = vec![
+            create_test_history_entry(1704711600, 1, "user1"), // 2024-01-08 11:00
+            create_test_history_entry(1704711600, 2, "user2"), // 2024-01-08 11:00
+            create_test_history_entry(1704106800, 1, "user1"), // 2024-01-01 11:00
+            create_test_history_entry(1703502000, 1, "user1"), // 2023-12-25, outside both
+        ];
+
+        let hourly = manager.compare_hourly(entries.clone(), vec![entries.clone()]).await.unwrap();
+        assert_eq!(hourly.primary.summary.total, 2.0);
+        assert_eq!(hourly.secondary[0].summary.total, 1.0);
+        let difference = &hourly.differences.unwrap()[0];
+        assert_eq!(difference.absolute_difference, 1.0);
+        assert_eq!(difference.percentage_difference, 100.0);
+
+        let monthly = manager.compare_monthly(entries.clone(), vec![entries]).await.unwrap();
+        assert_eq!(monthly.primary.data[0].comparison_key(), "01");
+        assert_eq!(monthly.secondary[0].summary.total, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_top_platform_rank_changes() {
+        let manager = week_over_week_manager();
+        let current = 1704711600; // 2024-01-08
+        let previous = 1704106800; // 2024-01-01
+        let mut entries = Vec::new();
+        for (date, platform, plays) in [
+            (current, "Roku", 3),
+            (current, "Web", 2),
+            (current, "Android", 1),
+            (previous, "Web", 3),
+            (previous, "iOS", 2),
+            (previous, "Roku", 1),
+        ] {
+            entries.extend((0..plays).map(|_| platform_entry(date, platform)));
+        }
+
+        let result = manager
+            .compare_top_platforms(entries.clone(), vec![entries], 5)
+            .await
+            .unwrap();
+        let changes: Vec<String> = result.rank_changes().iter().map(ToString::to_string).collect();
+        assert_eq!(changes, vec!["Roku ↑2", "Web ↓1", "Android (new)"]);
+        assert_eq!(result.dropped_entries(), vec!["iOS".to_string()]);
+    }
+}
@@ -0,0 +1,546 @@
+//! Rendering of period-over-period comparisons
+//!
+//! Each [`ComparisonDisplayMode`] draws every period on one chart with a
+//! legend, followed by the totals from [`ComparisonSummary`] and the deltas
+//! from [`ComparisonDifference`].
+
+use crate::{
+    local_time, ComparisonDifference, ComparisonDisplayMode, ComparisonPeriodData, ComparisonResult, ComparisonSummary, DataSet, DateRange,
+    DayOfWeekDataPoint, GraphConfig, GraphRenderer, PlayCountDataPoint,
+};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use plotters::prelude::*;
+use std::path::Path;
+use tgraph_common::{HistoryEntry, Result, TGraphError};
+
+/// Height of one line of summary text in pixels
+const SUMMARY_LINE_HEIGHT: u32 = 24;
+
+/// One period's values, aligned to the shared x axis
+#[derive(Debug, Clone)]
+pub struct ComparisonSeries {
+    pub label: String,
+    /// Hex color, falling back to the comparison palette
+    pub color: Option<String>,
+    pub values: Vec<f64>,
+    pub summary: ComparisonSummary,
+}
+
+/// Renderer for comparison results in any display mode
+#[derive(Debug)]
+pub struct ComparisonGraph {
+    pub mode: ComparisonDisplayMode,
+    /// Labels for each slot on the x axis
+    pub x_labels: Vec<String>,
+    pub primary: Option<ComparisonSeries>,
+    pub secondary: Vec<ComparisonSeries>,
+    /// Change of the primary period against each secondary period
+    pub differences: Vec<ComparisonDifference>,
+    /// Colors for secondary periods without their own
+    pub palette: Vec<String>,
+}
+
+impl ComparisonGraph {
+    pub fn new(mode: ComparisonDisplayMode) -> Self {
+        Self {
+            mode,
+            x_labels: Vec::new(),
+            primary: None,
+            secondary: Vec::new(),
+            differences: Vec::new(),
+            palette: Vec::new(),
+        }
+    }
+
+    /// Create a new graph with custom title
+    pub fn with_config(title: &str, mode: ComparisonDisplayMode) -> (Self, GraphConfig) {
+        let graph_type = match mode {
+            ComparisonDisplayMode::Overlay => crate::GraphType::Line,
+            _ => crate::GraphType::Bar,
+        };
+        let config = GraphConfig {
+            title: title.to_string(),
+            y_label: Some(match mode {
+                ComparisonDisplayMode::Difference => "Difference in Plays".to_string(),
+                _ => "Plays".to_string(),
+            }),
+            graph_type,
+            width: 1200,
+            height: 700,
+            ..Default::default()
+        };
+        (Self::new(mode), config)
+    }
+
+    /// Set daily play counts, aligning each period by day offset from its start
+    pub fn set_daily_data(&mut self, result: &ComparisonResult<PlayCountDataPoint>) {
+        let primary_range = &result.primary.date_range;
+        let slots = std::iter::once(&result.primary.date_range)
+            .chain(result.secondary.iter().map(|period| &period.date_range))
+            .map(|range| (range.end - range.start).num_days().max(0) as usize + 1)
+            .max()
+            .unwrap_or(1);
+
+        let series = |period: &ComparisonPeriodData<PlayCountDataPoint>| {
+            let mut values = vec![0.0; slots];
+            for point in &period.data {
+                let offset = (point.date - period.date_range.start).num_days();
+                if let Some(value) = usize::try_from(offset).ok().and_then(|i| values.get_mut(i)) {
+                    *value += point.count as f64;
+                }
+            }
+            Self::series(period, values)
+        };
+
+        self.x_labels = (0..slots)
+            .map(|i| (primary_range.start + chrono::Duration::days(i as i64)).format("%m-%d").to_string())
+            .collect();
+        self.primary = Some(series(&result.primary));
+        self.secondary = result.secondary.iter().map(series).collect();
+        self.differences = result.differences.clone().unwrap_or_default();
+        self.palette = result.config.comparison_colors.clone();
+        self.mode = result.config.display_mode.clone();
+    }
+
+    /// Set day of week counts, Monday first
+    pub fn set_day_of_week_data(&mut self, result: &ComparisonResult<DayOfWeekDataPoint>) {
+        let series = |period: &ComparisonPeriodData<DayOfWeekDataPoint>| {
+            let mut values = vec![0.0; 7];
+            for point in &period.data {
+                values[point.weekday.num_days_from_monday() as usize] += point.count as f64;
+            }
+            Self::series(period, values)
+        };
+
+        self.x_labels = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"].iter().map(|s| s.to_string()).collect();
+        self.primary = Some(series(&result.primary));
+        self.secondary = result.secondary.iter().map(series).collect();
+        self.differences = result.differences.clone().unwrap_or_default();
+        self.palette = result.config.comparison_colors.clone();
+        self.mode = result.config.display_mode.clone();
+    }
+
+    fn series<T>(period: &ComparisonPeriodData<T>, values: Vec<f64>) -> ComparisonSeries {
+        ComparisonSeries {
+            label: period.label.clone(),
+            color: period.color.clone(),
+            values,
+            summary: period.summary.clone(),
+        }
+    }
+
+    /// Totals per period and the primary period's change against each other period
+    ///
+    /// Deltas are read from the comparison's [`ComparisonDifference`]s, the
+    /// primary period minus the other, relative to the other. Without
+    /// differences only the totals are listed.
+    pub fn summary_lines(&self) -> Vec<String> {
+        let Some(primary) = &self.primary else {
+            return Vec::new();
+        };
+
+        let mut lines = vec![format!(
+            "{}: {} plays (avg {:.1}, peak {})",
+            primary.label, primary.summary.total, primary.summary.average, primary.summary.peak
+        )];
+        for (i, period) in self.secondary.iter().enumerate() {
+            let mut line = format!("{}: {} plays", period.label, period.summary.total);
+            if let Some(difference) = self.differences.get(i) {
+                let percentage = if period.summary.total != 0.0 {
+                    format!("{:+.1}%", difference.percentage_difference)
+                } else {
+                    "n/a".to_string()
+                };
+                line.push_str(&format!(
+                    ", {}: {:+} ({})",
+                    difference.label, difference.absolute_difference, percentage
+                ));
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Every period with values, primary first
+    fn periods(&self) -> impl Iterator<Item = &ComparisonSeries> {
+        self.primary.iter().chain(self.secondary.iter())
+    }
+
+    fn colors(&self, config: &GraphConfig) -> Vec<RGBColor> {
+        let scheme = self.get_colors(&config.style.color_scheme);
+        let primary = self
+            .primary
+            .as_ref()
+            .and_then(|p| p.color.as_deref())
+            .map(|c| self.parse_color(c))
+            .unwrap_or_else(|| scheme.first().copied().unwrap_or(RGBColor(31, 119, 180)));
+
+        let mut colors = vec![primary];
+        for (i, period) in self.secondary.iter().enumerate() {
+            let color = period
+                .color
+                .as_deref()
+                .or_else(|| self.palette.get(i % self.palette.len().max(1)).map(String::as_str))
+                .map(|c| self.parse_color(c))
+                .unwrap_or_else(|| scheme.get((i + 1) % scheme.len().max(1)).copied().unwrap_or(RED));
+            colors.push(color);
+        }
+        colors
+    }
+
+    /// The y range for the current display mode
+    fn y_range(&self) -> (f64, f64) {
+        let slots = self.x_labels.len();
+        let (min, max) = match self.mode {
+            ComparisonDisplayMode::Stacked => {
+                let max = (0..slots)
+                    .map(|i| self.periods().map(|p| p.values.get(i).copied().unwrap_or(0.0)).sum::<f64>())
+                    .fold(0.0, f64::max);
+                (0.0, max)
+            }
+            ComparisonDisplayMode::Difference => self.slot_differences().flat_map(|(_, d)| d).fold((0.0, 0.0), |(lo, hi), v| (f64::min(lo, v), f64::max(hi, v))),
+            _ => (0.0, self.periods().flat_map(|p| p.values.iter().copied()).fold(0.0, f64::max)),
+        };
+        let padding = ((max - min) * 0.1).max(1.0);
+        (if min < 0.0 { min - padding } else { 0.0 }, max + padding)
+    }
+
+    /// The primary's values minus each secondary period's, per slot
+    fn slot_differences(&self) -> impl Iterator<Item = (usize, Vec<f64>)> + '_ {
+        let primary = self.primary.as_ref().map(|p| p.values.as_slice()).unwrap_or(&[]);
+        self.secondary.iter().enumerate().map(move |(i, period)| {
+            let values = period
+                .values
+                .iter()
+                .enumerate()
+                .map(|(slot, v)| primary.get(slot).copied().unwrap_or(0.0) - v)
+                .collect();
+            (i + 1, values)
+        })
+    }
+
+    fn x_label(&self, x: f64) -> String {
+        let step = (self.x_labels.len() / 12).max(1);
+        let index = x.round();
+        if (x - index).abs() > 0.01 || index < 0.0 {
+            return String::new();
+        }
+        match self.x_labels.get(index as usize) {
+            Some(label) if index as usize % step == 0 => label.clone(),
+            _ => String::new(),
+        }
+    }
+
+    /// Bars for the series, grouped side by side within each slot
+    fn grouped_bars(series: &[(usize, Vec<f64>)], colors: &[RGBColor]) -> Vec<Rectangle<(f64, f64)>> {
+        let width = 0.8 / series.len().max(1) as f64;
+        let mut bars = Vec::new();
+        for (group, (color_index, values)) in series.iter().enumerate() {
+            let color = colors[*color_index % colors.len()];
+            for (slot, value) in values.iter().enumerate() {
+                let left = slot as f64 - 0.4 + group as f64 * width;
+                bars.push(Rectangle::new([(left, 0.0), (left + width, *value)], color.filled()));
+            }
+        }
+        bars
+    }
+}
+
+#[async_trait]
+impl GraphRenderer for ComparisonGraph {
+    async fn render_to_file(
+        &self,
+        config: &GraphConfig,
+        _datasets: &[DataSet],
+        path: &Path,
+    ) -> Result<()> {
+        let slots = self.x_labels.len();
+        if self.primary.is_none() || slots == 0 {
+            return Err(TGraphError::graph("No comparison data to render"));
+        }
+        if matches!(self.mode, ComparisonDisplayMode::Difference) && self.secondary.is_empty() {
+            return Err(TGraphError::graph("A difference comparison needs at least two periods"));
+        }
+
+        let root = BitMapBackend::new(path, (config.width, config.height)).into_drawing_area();
+        self.apply_styling(&root, config)?;
+
+        let root = root.titled(&config.title, ("sans-serif", config.style.title_font.size))?;
+        let summary = self.summary_lines();
+        let summary_height = SUMMARY_LINE_HEIGHT * summary.len() as u32 + 10;
+        let (chart_area, summary_area) = root.split_vertically(root.dim_in_pixel().1.saturating_sub(summary_height));
+
+        let colors = self.colors(config);
+        let (y_min, y_max) = self.y_range();
+        let x_range = match self.mode {
+            ComparisonDisplayMode::Overlay => 0f64..(slots.max(2) - 1) as f64,
+            _ => -0.5f64..slots as f64 - 0.5,
+        };
+
+        let mut chart = ChartBuilder::on(&chart_area)
+            .margin(config.style.margins.top)
+            .x_label_area_size(config.style.margins.bottom)
+            .y_label_area_size(config.style.margins.left)
+            .build_cartesian_2d(x_range, y_min..y_max)?;
+
+        let x_formatter = |x: &f64| self.x_label(*x);
+        let mut mesh = chart.configure_mesh();
+        mesh.x_labels(slots.min(50)).x_label_formatter(&x_formatter);
+        if let Some(y_label) = &config.y_label {
+            mesh.y_desc(y_label);
+        }
+        if let Some(x_label) = &config.x_label {
+            mesh.x_desc(x_label);
+        }
+        mesh.draw()?;
+
+        match self.mode {
+            ComparisonDisplayMode::Overlay => {
+                for (i, period) in self.periods().enumerate() {
+                    let color = colors[i];
+                    chart
+                        .draw_series(LineSeries::new(
+                            period.values.iter().enumerate().map(|(x, y)| (x as f64, *y)),
+                            color.stroke_width(2),
+                        ))?
+                        .label(&period.label)
+                        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], color));
+                }
+            }
+            ComparisonDisplayMode::SideBySide => {
+                let series: Vec<(usize, Vec<f64>)> = self.periods().map(|p| p.values.clone()).enumerate().collect();
+                chart.draw_series(Self::grouped_bars(&series, &colors))?;
+                for (i, period) in self.periods().enumerate() {
+                    let color = colors[i];
+                    chart
+                        .draw_series(std::iter::empty::<Rectangle<(f64, f64)>>())?
+                        .label(&period.label)
+                        .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled()));
+                }
+            }
+            ComparisonDisplayMode::Stacked => {
+                let mut base = vec![0.0; slots];
+                for (i, period) in self.periods().enumerate() {
+                    let color = colors[i];
+                    let bars: Vec<Rectangle<(f64, f64)>> = period
+                        .values
+                        .iter()
+                        .enumerate()
+                        .map(|(slot, value)| {
+                            let bottom = base[slot];
+                            base[slot] += value;
+                            Rectangle::new([(slot as f64 - 0.4, bottom), (slot as f64 + 0.4, base[slot])], color.filled())
+                        })
+                        .collect();
+                    chart
+                        .draw_series(bars)?
+                        .label(&period.label)
+                        .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled()));
+                }
+            }
+            ComparisonDisplayMode::Difference => {
+                let primary_label = self.primary.as_ref().map(|p| p.label.as_str()).unwrap_or_default();
+                let series: Vec<(usize, Vec<f64>)> = self.slot_differences().collect();
+                chart.draw_series(Self::grouped_bars(&series, &colors))?;
+                chart.draw_series(LineSeries::new(vec![(-0.5, 0.0), (slots as f64 - 0.5, 0.0)], BLACK))?;
+                for (color_index, _) in &series {
+                    let color = colors[*color_index];
+                    chart
+                        .draw_series(std::iter::empty::<Rectangle<(f64, f64)>>())?
+                        .label(format!("{} - {}", primary_label, self.secondary[color_index - 1].label))
+                        .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled()));
+                }
+            }
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        let text_style = ("sans-serif", 16).into_font().color(&BLACK);
+        for (i, line) in summary.iter().enumerate() {
+            summary_area.draw(&Text::new(
+                line.clone(),
+                (config.style.margins.left as i32, (i as u32 * SUMMARY_LINE_HEIGHT) as i32),
+                text_style.clone(),
+            ))?;
+        }
+
+        root.present()?;
+        tracing::info!("Successfully rendered comparison graph to {}", path.display());
+        Ok(())
+    }
+
+    async fn render_to_bytes(
+        &self,
+        _config: &GraphConfig,
+        _datasets: &[DataSet],
+    ) -> Result<Vec<u8>> {
+        Err(TGraphError::graph("render_to_bytes not implemented for ComparisonGraph"))
+    }
+
+    fn apply_styling<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, plotters::coord::Shift>,
+        config: &GraphConfig,
+    ) -> Result<()>
+    where
+        DB::ErrorType: std::error::Error + Send + Sync + 'static,
+    {
+        let bg_color = self.get_background_color(config);
+        root.fill(&bg_color)?;
+        Ok(())
+    }
+}
+
+/// History entries played within a date range, with days in `timezone`
+///
+/// Useful when one fetch covers every period of a comparison, since the day
+/// of week comparison does not filter by range itself. Use the timezone the
+/// range was resolved in, see [`TimeRangeContext`](crate::TimeRangeContext).
+pub fn entries_in_range(entries: &[HistoryEntry], range: &DateRange, timezone: Tz) -> Vec<HistoryEntry> {
+    entries
+        .iter()
+        .filter(|entry| {
+            entry
+                .date
+                .and_then(|timestamp| local_time(timestamp, timezone))
+                .map(|time| time.date_naive())
+                .is_some_and(|date| date >= range.start && date <= range.end)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Earliest start and latest end across every period of a comparison
+pub fn comparison_span(config: &crate::ComparisonConfig) -> (NaiveDate, NaiveDate) {
+    config
+        .comparison_ranges
+        .iter()
+        .filter(|period| period.enabled)
+        .map(|period| &period.date_range)
+        .fold((config.primary_range.start, config.primary_range.end), |(start, end), range| {
+            (start.min(range.start), end.max(range.end))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_entry;
+    use crate::{AggregationConfig, ComparisonManager};
+    use tempfile::tempdir;
+
+    fn entry(date: i64) -> HistoryEntry {
+        history_entry().date(date).build()
+    }
+
+    async fn week_over_week(mode: ComparisonDisplayMode) -> ComparisonResult<PlayCountDataPoint> {
+        // 2024-01-08 to 2024-01-14 against the week before
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 14).unwrap(),
+        );
+        let mut config = ComparisonManager::create_week_over_week_comparison(range);
+        config.display_mode = mode;
+
+        let entries = vec![
+            entry(1704715200), // 2024-01-08
+            entry(1704715200),
+            entry(1704801600), // 2024-01-09
+            entry(1704110400), // 2024-01-01
+        ];
+        let (start, end) = comparison_span(&config);
+        assert_eq!(start, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2024, 1, 14).unwrap());
+
+        ComparisonManager::new(config)
+            .compare_daily_play_counts(entries.clone(), vec![entries])
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_days_follow_the_timezone() {
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 14).unwrap(),
+        );
+        // 2024-01-08 03:00 UTC is still the evening of 2024-01-07 in New York
+        let late = entry(1704682800);
+        assert_eq!(entries_in_range(std::slice::from_ref(&late), &range, Tz::UTC).len(), 1);
+        assert!(entries_in_range(std::slice::from_ref(&late), &range, new_york).is_empty());
+
+        let result = ComparisonManager::new(ComparisonManager::create_week_over_week_comparison(range))
+            .with_aggregation_config(AggregationConfig {
+                timezone: new_york,
+                ..Default::default()
+            })
+            .compare_daily_play_counts(vec![late.clone()], vec![vec![late]])
+            .await
+            .unwrap();
+        assert!(result.primary.data.is_empty());
+        assert_eq!(result.secondary[0].data[0].date, NaiveDate::from_ymd_opt(2024, 1, 7).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_daily_alignment_and_summary() {
+        let result = week_over_week(ComparisonDisplayMode::Overlay).await;
+        let mut graph = ComparisonGraph::new(ComparisonDisplayMode::Overlay);
+        graph.set_daily_data(&result);
+
+        assert_eq!(graph.x_labels.len(), 7);
+        assert_eq!(graph.x_labels[0], "01-08");
+        assert_eq!(graph.primary.as_ref().unwrap().values[..2], [2.0, 1.0]);
+        assert_eq!(graph.secondary[0].values[0], 1.0);
+
+        let lines = graph.summary_lines();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], "Previous Week: 1 plays, Primary Period vs Previous Week: +2 (+200.0%)");
+
+        graph.differences.clear();
+        assert_eq!(graph.summary_lines()[1], "Previous Week: 1 plays");
+    }
+
+    #[tokio::test]
+    async fn test_render_every_display_mode() {
+        let dir = tempdir().unwrap();
+        for mode in [
+            ComparisonDisplayMode::Overlay,
+            ComparisonDisplayMode::SideBySide,
+            ComparisonDisplayMode::Stacked,
+            ComparisonDisplayMode::Difference,
+        ] {
+            let result = week_over_week(mode.clone()).await;
+            let (mut graph, config) = ComparisonGraph::with_config("Week over Week", mode);
+            assert!(graph.render_to_file(&config, &[], &dir.path().join("empty.png")).await.is_err());
+
+            graph.set_daily_data(&result);
+            let path = dir.path().join(format!("{:?}.png", graph.mode));
+            graph.render_to_file(&config, &[], &path).await.unwrap();
+            assert!(path.exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_day_of_week_comparison() {
+        let result = week_over_week(ComparisonDisplayMode::SideBySide).await;
+        let manager = ComparisonManager::new(result.config.clone());
+        let entries = vec![entry(1704715200), entry(1704110400)];
+        let primary = entries_in_range(&entries, &result.config.primary_range, Tz::UTC);
+        let previous = entries_in_range(&entries, &result.config.comparison_ranges[0].date_range, Tz::UTC);
+        assert_eq!(primary.len(), 1);
+
+        let result = manager.compare_day_of_week(primary, vec![previous]).await.unwrap();
+        let mut graph = ComparisonGraph::new(ComparisonDisplayMode::SideBySide);
+        graph.set_day_of_week_data(&result);
+        assert_eq!(graph.x_labels[0], "Mon");
+        assert_eq!(graph.primary.as_ref().unwrap().values[0], 1.0);
+        assert_eq!(graph.secondary[0].values[0], 1.0);
+    }
+}
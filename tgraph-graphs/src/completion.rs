@@ -0,0 +1,516 @@
+//! Completion and abandonment analysis from `percent_complete` and `watched_status`
+
+use crate::{
+    AggregationConfig, AggregationProgress, AggregationStage, ContentKind, DataAggregator, DataSet,
+    GraphConfig, GraphRenderer,
+};
+use async_trait::async_trait;
+use plotters::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+use tgraph_common::{HistoryEntry, Result, TGraphError};
+use tokio::sync::mpsc;
+use tracing::{debug, info, instrument};
+
+/// How far a play got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum CompletionStatus {
+    Finished,
+    DroppedMidway,
+    AbandonedEarly,
+}
+
+impl CompletionStatus {
+    /// All statuses, in stacking order
+    pub const ALL: [CompletionStatus; 3] = [
+        CompletionStatus::Finished,
+        CompletionStatus::DroppedMidway,
+        CompletionStatus::AbandonedEarly,
+    ];
+
+    /// Human-readable name
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompletionStatus::Finished => "Finished",
+            CompletionStatus::DroppedMidway => "Dropped Midway",
+            CompletionStatus::AbandonedEarly => "Abandoned Early",
+        }
+    }
+}
+
+/// Percent complete boundaries between the completion statuses
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CompletionThresholds {
+    /// Plays at or above this percentage count as finished
+    pub finished_percent: i32,
+    /// Plays below this percentage count as abandoned early
+    pub abandoned_percent: i32,
+}
+
+impl Default for CompletionThresholds {
+    fn default() -> Self {
+        Self {
+            finished_percent: 90,
+            abandoned_percent: 25,
+        }
+    }
+}
+
+impl CompletionThresholds {
+    /// Classify a history entry, or `None` if it has no completion data
+    ///
+    /// Tautulli's watched status wins over the raw percentage, so plays marked
+    /// watched by its own threshold always count as finished.
+    pub fn classify(&self, entry: &HistoryEntry) -> Option<CompletionStatus> {
+        if entry.watched_status.is_some_and(|status| status >= 1.0) {
+            return Some(CompletionStatus::Finished);
+        }
+
+        let percent = entry.percent_complete?;
+        Some(if percent >= self.finished_percent {
+            CompletionStatus::Finished
+        } else if percent < self.abandoned_percent {
+            CompletionStatus::AbandonedEarly
+        } else {
+            CompletionStatus::DroppedMidway
+        })
+    }
+}
+
+/// How completion results are grouped
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum CompletionGrouping {
+    /// Everything in one group with the given name, e.g. a library name
+    All(String),
+    /// One group per movie, show or artist
+    Content(ContentKind),
+}
+
+/// Completion counts for one group
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CompletionDataPoint {
+    /// Group name: library, show, movie or artist
+    pub group: String,
+    pub finished: u32,
+    pub dropped_midway: u32,
+    pub abandoned_early: u32,
+    pub label: Option<String>,
+}
+
+impl CompletionDataPoint {
+    /// Plays with completion data
+    pub fn total(&self) -> u32 {
+        self.finished + self.dropped_midway + self.abandoned_early
+    }
+
+    /// Plays with the given status
+    pub fn count(&self, status: CompletionStatus) -> u32 {
+        match status {
+            CompletionStatus::Finished => self.finished,
+            CompletionStatus::DroppedMidway => self.dropped_midway,
+            CompletionStatus::AbandonedEarly => self.abandoned_early,
+        }
+    }
+
+    /// Share of plays with the given status, 0-100
+    pub fn percentage(&self, status: CompletionStatus) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.count(status) as f64 / total as f64 * 100.0,
+        }
+    }
+
+    /// Share of plays that were not finished, 0-100
+    pub fn abandonment_rate(&self) -> f64 {
+        100.0 - self.percentage(CompletionStatus::Finished)
+    }
+}
+
+/// Plays per completion status of each group, keyed by rating key, with the group name
+type GroupCounts = HashMap<String, (String, [u32; 3])>;
+
+/// Aggregator for completion distribution overall or per show, movie or artist
+///
+/// Per library results come from running it on a library's history, see
+/// [`TautulliClient::fetch_library_history`](tgraph_common::TautulliClient::fetch_library_history).
+#[derive(Debug)]
+pub struct CompletionAggregator {
+    pub grouping: CompletionGrouping,
+    pub thresholds: CompletionThresholds,
+    /// Groups with fewer plays are left out
+    pub min_plays: u32,
+    /// Maximum number of groups, most abandoned first
+    pub limit: usize,
+}
+
+impl CompletionAggregator {
+    pub fn new() -> Self {
+        Self {
+            grouping: CompletionGrouping::All("All".to_string()),
+            thresholds: CompletionThresholds::default(),
+            min_plays: 1,
+            limit: 10,
+        }
+    }
+
+    /// One group named after the library
+    pub fn for_library(name: &str) -> Self {
+        Self {
+            grouping: CompletionGrouping::All(name.to_string()),
+            ..Self::new()
+        }
+    }
+
+    /// One group per show, movie or artist
+    pub fn per_content(kind: ContentKind, min_plays: u32, limit: usize) -> Self {
+        Self {
+            grouping: CompletionGrouping::Content(kind),
+            min_plays,
+            limit,
+            ..Self::new()
+        }
+    }
+
+    pub fn with_thresholds(mut self, thresholds: CompletionThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    fn add(&self, counts: &mut GroupCounts, entry: &HistoryEntry) {
+        let Some(status) = self.thresholds.classify(entry) else {
+            return;
+        };
+        // Content is told apart by rating key, so remakes sharing a title stay separate
+        let (key, group) = match &self.grouping {
+            CompletionGrouping::All(name) => (name.as_str(), name.as_str()),
+            CompletionGrouping::Content(kind) => match kind.identify(entry) {
+                Some((key, title, _)) => (key, title),
+                None => return,
+            },
+        };
+
+        let index = CompletionStatus::ALL.iter().position(|s| *s == status).unwrap_or(0);
+        counts.entry(key.to_string()).or_insert_with(|| (group.to_string(), [0; 3])).1[index] += 1;
+    }
+
+    fn finish(&self, counts: GroupCounts) -> Vec<CompletionDataPoint> {
+        let mut result: Vec<CompletionDataPoint> = counts
+            .into_values()
+            .map(|(group, [finished, dropped_midway, abandoned_early])| {
+                let mut point = CompletionDataPoint {
+                    group,
+                    finished,
+                    dropped_midway,
+                    abandoned_early,
+                    label: None,
+                };
+                point.label = Some(format!(
+                    "{} - {:.0}% finished of {} plays",
+                    point.group,
+                    point.percentage(CompletionStatus::Finished),
+                    point.total()
+                ));
+                point
+            })
+            .filter(|point| point.total() >= self.min_plays)
+            .collect();
+
+        // Most abandoned first; ties by plays then name so rankings are stable
+        result.sort_by(|a, b| {
+            b.abandonment_rate()
+                .total_cmp(&a.abandonment_rate())
+                .then_with(|| b.total().cmp(&a.total()))
+                .then_with(|| a.group.cmp(&b.group))
+        });
+        result.truncate(self.limit);
+        result
+    }
+}
+
+impl DataAggregator<CompletionDataPoint> for CompletionAggregator {
+    #[instrument(skip(self, entries))]
+    fn aggregate(
+        &self,
+        entries: Vec<HistoryEntry>,
+        _config: &AggregationConfig,
+    ) -> Result<Vec<CompletionDataPoint>> {
+        let mut counts = HashMap::new();
+        for entry in &entries {
+            self.add(&mut counts, entry);
+        }
+
+        let result = self.finish(counts);
+        debug!("Aggregated {} completion data points", result.len());
+        Ok(result)
+    }
+
+    async fn aggregate_chunked(
+        &self,
+        entries: Vec<HistoryEntry>,
+        config: &AggregationConfig,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<CompletionDataPoint>> {
+        let total = entries.len();
+        let mut counts = HashMap::new();
+        let mut processed = 0;
+
+        for chunk in entries.chunks(config.chunk_size) {
+            config.cancellation.check("aggregation")?;
+
+            self.send_progress(
+                &progress_tx,
+                AggregationStage::Processing,
+                processed,
+                total,
+                format!("Processing completion chunk {}/{}", processed / config.chunk_size + 1, (total + config.chunk_size - 1) / config.chunk_size),
+            );
+
+            for entry in chunk {
+                self.add(&mut counts, entry);
+                processed += 1;
+            }
+
+            tokio::task::yield_now().await;
+        }
+
+        self.send_progress(&progress_tx, AggregationStage::Finalizing, processed, total, "Finalizing completion results".to_string());
+        let result = self.finish(counts);
+        self.send_progress(&progress_tx, AggregationStage::Complete, processed, total, "Completion aggregation complete".to_string());
+
+        info!("Chunked aggregation completed: {} completion data points", result.len());
+        Ok(result)
+    }
+}
+
+impl Default for CompletionAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stacked horizontal bars of finished, dropped and abandoned shares per group
+#[derive(Debug, Default)]
+pub struct CompletionGraph {
+    pub data: Vec<CompletionDataPoint>,
+}
+
+impl CompletionGraph {
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Create a new graph with custom title
+    pub fn with_config(title: &str) -> (Self, GraphConfig) {
+        let mut config = GraphConfig {
+            title: title.to_string(),
+            x_label: Some("Share of Plays (%)".to_string()),
+            graph_type: crate::GraphType::Bar,
+            width: 1000,
+            height: 600,
+            ..Default::default()
+        };
+        config.style.margins.left = 220; // Room for show titles
+
+        (Self::new(), config)
+    }
+
+    pub fn set_data(&mut self, data: Vec<CompletionDataPoint>) {
+        self.data = data;
+    }
+
+    fn truncate_name(name: &str, max_len: usize) -> String {
+        if name.chars().count() <= max_len {
+            name.to_string()
+        } else {
+            format!("{}...", name.chars().take(max_len - 3).collect::<String>())
+        }
+    }
+}
+
+#[async_trait]
+impl GraphRenderer for CompletionGraph {
+    async fn render_to_file(
+        &self,
+        config: &GraphConfig,
+        _datasets: &[DataSet],
+        path: &Path,
+    ) -> Result<()> {
+        if self.data.is_empty() {
+            return Err(TGraphError::graph("No data available for completion chart"));
+        }
+
+        let root = BitMapBackend::new(path, (config.width, config.height)).into_drawing_area();
+        self.apply_styling(&root, config)?;
+
+        // Bars are centred on whole numbers, most abandoned at the top. The
+        // x axis runs past 100% to leave room for the legend.
+        let rows = self.data.len();
+        let title_font = (config.style.title_font.family.as_str(), config.style.title_font.size);
+        let mut chart = ChartBuilder::on(&root)
+            .caption(&config.title, title_font)
+            .margin(config.style.margins.top as i32)
+            .x_label_area_size(config.style.margins.bottom)
+            .y_label_area_size(config.style.margins.left)
+            .build_cartesian_2d(0.0..135.0, -0.5..rows as f64 - 0.5)?;
+
+        chart
+            .configure_mesh()
+            .disable_y_mesh()
+            .x_desc(config.x_label.as_deref().unwrap_or("Share of Plays (%)"))
+            .x_label_formatter(&|x| if *x <= 100.0 { format!("{:.0}", x) } else { String::new() })
+            .y_labels(rows)
+            .y_label_formatter(&|y| {
+                let row = y.round();
+                match rows.checked_sub(row as usize + 1).and_then(|i| self.data.get(i)) {
+                    Some(point) if (row - y).abs() < 0.01 && row >= 0.0 => Self::truncate_name(&point.group, 30),
+                    _ => String::new(),
+                }
+            })
+            .draw()?;
+
+        let colors = [RGBColor(46, 204, 113), RGBColor(243, 156, 18), RGBColor(231, 76, 60)];
+        for (status, color) in CompletionStatus::ALL.iter().zip(colors) {
+            chart
+                .draw_series(std::iter::empty::<Rectangle<(f64, f64)>>())?
+                .label(status.name())
+                .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled()));
+        }
+
+        for (i, point) in self.data.iter().enumerate() {
+            let row = (rows - 1 - i) as f64;
+            let mut start = 0.0;
+            for (status, color) in CompletionStatus::ALL.iter().zip(colors) {
+                let end = start + point.percentage(*status);
+                chart.draw_series(std::iter::once(Rectangle::new(
+                    [(start, row - 0.35), (end, row + 0.35)],
+                    color.filled(),
+                )))?;
+                start = end;
+            }
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperRight)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        root.present()?;
+        tracing::info!("Successfully rendered completion chart to {}", path.display());
+        Ok(())
+    }
+
+    async fn render_to_bytes(
+        &self,
+        _config: &GraphConfig,
+        _datasets: &[DataSet],
+    ) -> Result<Vec<u8>> {
+        Err(TGraphError::graph("render_to_bytes not implemented for CompletionGraph"))
+    }
+
+    fn apply_styling<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, plotters::coord::Shift>,
+        config: &GraphConfig,
+    ) -> Result<()>
+    where
+        DB::ErrorType: std::error::Error + Send + Sync + 'static,
+    {
+        let bg_color = self.get_background_color(config);
+        root.fill(&bg_color)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_entry;
+    use tempfile::tempdir;
+
+    fn entry(show: &str, percent: Option<i32>, watched: Option<f64>) -> HistoryEntry {
+        history_entry().show(show).progress(percent, watched).build()
+    }
+
+    fn entries() -> Vec<HistoryEntry> {
+        vec![
+            entry("Severance", Some(98), None),
+            entry("Severance", Some(85), Some(1.0)),
+            entry("Severance", Some(50), Some(0.5)),
+            entry("Andor", Some(10), None),
+            entry("Andor", Some(60), None),
+            entry("Andor", None, None),
+        ]
+    }
+
+    #[test]
+    fn test_classify() {
+        let thresholds = CompletionThresholds::default();
+        assert_eq!(thresholds.classify(&entry("A", Some(95), None)), Some(CompletionStatus::Finished));
+        assert_eq!(thresholds.classify(&entry("A", Some(80), Some(1.0))), Some(CompletionStatus::Finished));
+        assert_eq!(thresholds.classify(&entry("A", Some(40), None)), Some(CompletionStatus::DroppedMidway));
+        assert_eq!(thresholds.classify(&entry("A", Some(5), None)), Some(CompletionStatus::AbandonedEarly));
+        assert_eq!(thresholds.classify(&entry("A", None, Some(0.5))), None);
+    }
+
+    #[test]
+    fn test_completion_per_show() {
+        let config = AggregationConfig::default();
+        let points = CompletionAggregator::per_content(ContentKind::Show, 1, 10)
+            .aggregate(entries(), &config)
+            .unwrap();
+
+        // Andor is abandoned more, so it comes first
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].group, "Andor");
+        assert_eq!((points[0].dropped_midway, points[0].abandoned_early), (1, 1));
+        assert_eq!(points[1].finished, 2);
+        assert!((points[1].abandonment_rate() - 100.0 / 3.0).abs() < 1e-9);
+
+        let popular = CompletionAggregator::per_content(ContentKind::Show, 3, 10)
+            .aggregate(entries(), &config)
+            .unwrap();
+        assert_eq!(popular.len(), 1);
+    }
+
+    #[test]
+    fn test_shows_sharing_a_title_stay_apart() {
+        let mut remake = entry("The Office", Some(10), None);
+        remake.grandparent_rating_key = Some("key-the-office-us".to_string());
+        let entries = vec![entry("The Office", Some(95), None), entry("The Office", Some(99), None), remake];
+
+        let points = CompletionAggregator::per_content(ContentKind::Show, 1, 10)
+            .aggregate(entries, &AggregationConfig::default())
+            .unwrap();
+        assert_eq!(points.len(), 2);
+        assert!(points.iter().all(|point| point.group == "The Office"));
+        assert_eq!((points[0].abandoned_early, points[0].total()), (1, 1));
+        assert_eq!((points[1].finished, points[1].total()), (2, 2));
+    }
+
+    #[test]
+    fn test_completion_for_library() {
+        let points = CompletionAggregator::for_library("TV Shows")
+            .aggregate(entries(), &AggregationConfig::default())
+            .unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].group, "TV Shows");
+        assert_eq!(points[0].total(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_render_completion_graph() {
+        let (mut graph, config) = CompletionGraph::with_config("Completion by Show");
+        graph.set_data(
+            CompletionAggregator::per_content(ContentKind::Show, 1, 10)
+                .aggregate(entries(), &AggregationConfig::default())
+                .unwrap(),
+        );
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("completion.png");
+        graph.render_to_file(&config, &[], &path).await.unwrap();
+        assert!(path.exists());
+    }
+}
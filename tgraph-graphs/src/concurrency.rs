@@ -0,0 +1,323 @@
+//! Peak concurrent streams and bandwidth from sampled Tautulli activity
+
+use crate::{DataSet, GraphConfig, GraphRenderer};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Timelike, Utc};
+use plotters::prelude::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tgraph_common::{ActivityResponse, Result, TGraphError};
+
+/// One poll of a server's current activity
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ActivitySample {
+    /// Name of the Tautulli server that was polled
+    pub server: String,
+    pub sampled_at: DateTime<Utc>,
+    pub stream_count: u32,
+    pub transcode_count: u32,
+    /// Total bandwidth in kbps
+    pub total_bandwidth: u32,
+}
+
+impl ActivitySample {
+    /// Build a sample from a `get_activity` response
+    pub fn from_activity(server: &str, activity: &ActivityResponse, sampled_at: DateTime<Utc>) -> Self {
+        Self {
+            server: server.to_string(),
+            sampled_at,
+            stream_count: activity.stream_count.max(0) as u32,
+            transcode_count: activity.stream_count_transcode.max(0) as u32,
+            total_bandwidth: activity.total_bandwidth.max(0) as u32,
+        }
+    }
+}
+
+/// Length of the periods samples are grouped into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleInterval {
+    Hourly,
+    Daily,
+}
+
+impl SampleInterval {
+    fn period_start(&self, time: DateTime<Utc>) -> NaiveDateTime {
+        let time = time.naive_utc();
+        let hour = match self {
+            SampleInterval::Hourly => time.hour(),
+            SampleInterval::Daily => 0,
+        };
+        time.date().and_hms_opt(hour, 0, 0).unwrap_or(time)
+    }
+
+    fn format(&self, period: NaiveDateTime) -> String {
+        match self {
+            SampleInterval::Hourly => period.format("%m-%d %H:00").to_string(),
+            SampleInterval::Daily => period.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// Peak concurrency and bandwidth for one hour or day
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ConcurrencyDataPoint {
+    /// Start of the period (UTC)
+    pub period: NaiveDateTime,
+    pub peak_streams: u32,
+    pub peak_transcodes: u32,
+    /// Peak total bandwidth in kbps
+    pub peak_bandwidth: u32,
+    /// Number of samples taken in the period
+    pub samples: u32,
+}
+
+impl ConcurrencyDataPoint {
+    /// Peak bandwidth in Mbps
+    pub fn peak_bandwidth_mbps(&self) -> f64 {
+        self.peak_bandwidth as f64 / 1000.0
+    }
+}
+
+/// Group samples into hourly or daily peaks
+///
+/// Samples sharing a timestamp (one poll of every server) are summed first,
+/// so the peaks reflect total load across all servers.
+pub fn aggregate_activity_samples(samples: &[ActivitySample], interval: SampleInterval) -> Vec<ConcurrencyDataPoint> {
+    let mut moments: BTreeMap<DateTime<Utc>, (u32, u32, u32)> = BTreeMap::new();
+    for sample in samples {
+        let totals = moments.entry(sample.sampled_at).or_default();
+        totals.0 += sample.stream_count;
+        totals.1 += sample.transcode_count;
+        totals.2 += sample.total_bandwidth;
+    }
+
+    let mut periods: BTreeMap<NaiveDateTime, ConcurrencyDataPoint> = BTreeMap::new();
+    for (moment, (streams, transcodes, bandwidth)) in moments {
+        let period = interval.period_start(moment);
+        let point = periods.entry(period).or_insert(ConcurrencyDataPoint {
+            period,
+            peak_streams: 0,
+            peak_transcodes: 0,
+            peak_bandwidth: 0,
+            samples: 0,
+        });
+        point.peak_streams = point.peak_streams.max(streams);
+        point.peak_transcodes = point.peak_transcodes.max(transcodes);
+        point.peak_bandwidth = point.peak_bandwidth.max(bandwidth);
+        point.samples += 1;
+    }
+
+    periods.into_values().collect()
+}
+
+/// Renderer for peak concurrent streams and bandwidth
+///
+/// Streams are drawn on top and bandwidth below, sharing the time axis.
+#[derive(Debug)]
+pub struct ConcurrencyGraph {
+    pub data: Vec<ConcurrencyDataPoint>,
+    pub interval: SampleInterval,
+}
+
+impl ConcurrencyGraph {
+    pub fn new(interval: SampleInterval) -> Self {
+        Self {
+            data: Vec::new(),
+            interval,
+        }
+    }
+
+    /// Create a new graph with custom title
+    pub fn with_config(title: &str, interval: SampleInterval) -> (Self, GraphConfig) {
+        let config = GraphConfig {
+            title: title.to_string(),
+            x_label: Some(match interval {
+                SampleInterval::Hourly => "Hour (UTC)".to_string(),
+                SampleInterval::Daily => "Date".to_string(),
+            }),
+            graph_type: crate::GraphType::Line,
+            width: 1000,
+            height: 700,
+            ..Default::default()
+        };
+        (Self::new(interval), config)
+    }
+
+    pub fn set_data(&mut self, data: Vec<ConcurrencyDataPoint>) {
+        self.data = data;
+        self.data.sort_by_key(|point| point.period);
+    }
+
+    fn max_x(&self) -> f64 {
+        (self.data.len().max(2) - 1) as f64
+    }
+
+    fn x_label(&self, x: f64) -> String {
+        let step = (self.data.len() / 8).max(1);
+        let index = x.round() as usize;
+        match self.data.get(index) {
+            Some(point) if index % step == 0 => self.interval.format(point.period),
+            _ => String::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl GraphRenderer for ConcurrencyGraph {
+    async fn render_to_file(
+        &self,
+        config: &GraphConfig,
+        _datasets: &[DataSet],
+        path: &Path,
+    ) -> Result<()> {
+        if self.data.is_empty() {
+            return Err(TGraphError::graph("No activity samples to render"));
+        }
+
+        let root = BitMapBackend::new(path, (config.width, config.height)).into_drawing_area();
+        self.apply_styling(&root, config)?;
+
+        let root = root.titled(&config.title, ("sans-serif", config.style.title_font.size))?;
+        let (upper, lower) = root.split_vertically(root.dim_in_pixel().1 / 2);
+
+        let colors = self.get_colors(&config.style.color_scheme);
+        let stream_color = colors.first().copied().unwrap_or(RGBColor(31, 119, 180));
+        let transcode_color = colors.get(1).copied().unwrap_or(RGBColor(214, 39, 40));
+        let bandwidth_color = colors.get(2).copied().unwrap_or(RGBColor(44, 160, 44));
+
+        let max_streams = self.data.iter().map(|p| p.peak_streams).max().unwrap_or(0).max(1) as f64 * 1.1;
+        let mut streams_chart = ChartBuilder::on(&upper)
+            .margin(config.style.margins.top)
+            .x_label_area_size(config.style.margins.bottom)
+            .y_label_area_size(config.style.margins.left)
+            .build_cartesian_2d(0f64..self.max_x(), 0f64..max_streams)?;
+
+        streams_chart
+            .configure_mesh()
+            .y_desc("Peak Streams")
+            .x_label_formatter(&|x| self.x_label(*x))
+            .draw()?;
+
+        streams_chart
+            .draw_series(LineSeries::new(
+                self.data.iter().enumerate().map(|(i, p)| (i as f64, p.peak_streams as f64)),
+                &stream_color,
+            ))?
+            .label("Streams")
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], stream_color));
+        streams_chart
+            .draw_series(LineSeries::new(
+                self.data.iter().enumerate().map(|(i, p)| (i as f64, p.peak_transcodes as f64)),
+                &transcode_color,
+            ))?
+            .label("Transcodes")
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], transcode_color));
+        streams_chart.configure_series_labels().border_style(BLACK).draw()?;
+
+        let max_mbps = self.data.iter().map(|p| p.peak_bandwidth_mbps()).fold(0.0, f64::max).max(1.0) * 1.1;
+        let mut bandwidth_chart = ChartBuilder::on(&lower)
+            .margin(config.style.margins.top)
+            .x_label_area_size(config.style.margins.bottom)
+            .y_label_area_size(config.style.margins.left)
+            .build_cartesian_2d(0f64..self.max_x(), 0f64..max_mbps)?;
+
+        let x_formatter = |x: &f64| self.x_label(*x);
+        let mut mesh = bandwidth_chart.configure_mesh();
+        mesh.y_desc("Peak Bandwidth (Mbps)").x_label_formatter(&x_formatter);
+        if let Some(x_label) = &config.x_label {
+            mesh.x_desc(x_label);
+        }
+        mesh.draw()?;
+
+        bandwidth_chart.draw_series(AreaSeries::new(
+            self.data.iter().enumerate().map(|(i, p)| (i as f64, p.peak_bandwidth_mbps())),
+            0.0,
+            bandwidth_color.mix(0.3),
+        ).border_style(bandwidth_color))?;
+
+        root.present()?;
+        tracing::info!("Successfully rendered concurrency graph to {}", path.display());
+        Ok(())
+    }
+
+    async fn render_to_bytes(
+        &self,
+        _config: &GraphConfig,
+        _datasets: &[DataSet],
+    ) -> Result<Vec<u8>> {
+        Err(TGraphError::graph("render_to_bytes not implemented for ConcurrencyGraph"))
+    }
+
+    fn apply_styling<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, plotters::coord::Shift>,
+        config: &GraphConfig,
+    ) -> Result<()>
+    where
+        DB::ErrorType: std::error::Error + Send + Sync + 'static,
+    {
+        let bg_color = self.get_background_color(config);
+        root.fill(&bg_color)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sample(server: &str, timestamp: i64, streams: u32, transcodes: u32, bandwidth: u32) -> ActivitySample {
+        ActivitySample {
+            server: server.to_string(),
+            sampled_at: DateTime::from_timestamp(timestamp, 0).unwrap(),
+            stream_count: streams,
+            transcode_count: transcodes,
+            total_bandwidth: bandwidth,
+        }
+    }
+
+    fn samples() -> Vec<ActivitySample> {
+        // 2022-01-01 20:00, 20:30 and 21:00 UTC, plus 2022-01-02 20:00 UTC
+        vec![
+            sample("Main", 1641067200, 2, 1, 8000),
+            sample("Cabin", 1641067200, 1, 0, 4000),
+            sample("Main", 1641069000, 4, 2, 10000),
+            sample("Main", 1641070800, 1, 0, 3000),
+            sample("Main", 1641153600, 3, 3, 20000),
+        ]
+    }
+
+    #[test]
+    fn test_aggregate_hourly_peaks() {
+        let points = aggregate_activity_samples(&samples(), SampleInterval::Hourly);
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].peak_streams, 4);
+        assert_eq!(points[0].peak_transcodes, 2);
+        // Both servers at 20:00 add up to 12 Mbps
+        assert_eq!(points[0].peak_bandwidth, 12000);
+        assert_eq!(points[0].samples, 2);
+        assert_eq!(points[1].peak_streams, 1);
+    }
+
+    #[test]
+    fn test_aggregate_daily_peaks() {
+        let points = aggregate_activity_samples(&samples(), SampleInterval::Daily);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].peak_streams, 4);
+        assert_eq!(points[1].peak_bandwidth_mbps(), 20.0);
+    }
+
+    #[tokio::test]
+    async fn test_render_concurrency_graph() {
+        let (mut graph, config) = ConcurrencyGraph::with_config("Peak Concurrency", SampleInterval::Hourly);
+        assert!(graph.render_to_file(&config, &[], Path::new("unused.png")).await.is_err());
+
+        graph.set_data(aggregate_activity_samples(&samples(), SampleInterval::Hourly));
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("concurrency.png");
+        graph.render_to_file(&config, &[], &path).await.unwrap();
+        assert!(path.exists());
+    }
+}
@@ -1,11 +1,13 @@
 //! Graph configuration and customization system
 
-use crate::{ColorScheme, GraphConfig, StyleConfig};
-use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use crate::{iso_week_range, ColorScheme, GraphConfig, StyleConfig, TimeRangeContext};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
-use tgraph_common::Result;
+use tgraph_common::{Result, TGraphError};
+use tgraph_config::migrate::{migrate_as, DocumentKind, GRAPH_CONFIG_VERSION};
+use tracing::warn;
 
 /// Configuration specific to different graph types
 pub trait GraphSpecificConfig: Clone + Serialize + for<'de> Deserialize<'de> {
@@ -23,10 +25,15 @@ pub trait GraphSpecificConfig: Clone + Serialize + for<'de> Deserialize<'de> {
 }
 
 /// Time range presets for easy selection
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// "Last" presets are the previous full calendar period, while the numbered
+/// presets are rolling windows ending today.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TimeRangePreset {
+    ThisWeek,
     LastWeek,
     LastMonth,
+    ThisQuarter,
     LastQuarter,
     LastYear,
     LastSevenDays,
@@ -35,41 +42,45 @@ pub enum TimeRangePreset {
     ThisMonth,
     ThisYear,
     AllTime,
+    /// ISO 8601 week, Monday to Sunday
+    IsoWeek { year: i32, week: u32 },
     Custom(DateRange),
 }
 
 impl TimeRangePreset {
+    /// Resolve in UTC with ISO weeks, see [`to_date_range_in`](Self::to_date_range_in)
     pub fn to_date_range(&self) -> DateRange {
-        let now = chrono::Utc::now().date_naive();
-        
+        self.to_date_range_in(&TimeRangeContext::default())
+    }
+
+    /// Resolve against the configured timezone, week start and history
+    pub fn to_date_range_in(&self, ctx: &TimeRangeContext) -> DateRange {
         match self {
-            TimeRangePreset::LastWeek => DateRange::last_days(7),
-            TimeRangePreset::LastMonth => DateRange::last_days(30),
-            TimeRangePreset::LastQuarter => DateRange::last_days(90),
-            TimeRangePreset::LastYear => DateRange::last_days(365),
-            TimeRangePreset::LastSevenDays => DateRange::last_days(7),
-            TimeRangePreset::LastThirtyDays => DateRange::last_days(30),
-            TimeRangePreset::LastNinetyDays => DateRange::last_days(90),
-            TimeRangePreset::ThisMonth => {
-                let start = now.with_day(1).unwrap();
-                DateRange::new(start, now)
-            },
-            TimeRangePreset::ThisYear => {
-                let start = now.with_month(1).unwrap().with_day(1).unwrap();
-                DateRange::new(start, now)
-            },
-            TimeRangePreset::AllTime => {
-                // Default to last 2 years for "all time"
-                DateRange::last_days(730)
-            },
+            TimeRangePreset::ThisWeek => ctx.this_week(),
+            TimeRangePreset::LastWeek => ctx.previous_week(),
+            TimeRangePreset::LastMonth => ctx.previous_month(),
+            TimeRangePreset::ThisQuarter => ctx.this_quarter(),
+            TimeRangePreset::LastQuarter => ctx.previous_quarter(),
+            TimeRangePreset::LastYear => ctx.previous_year(),
+            TimeRangePreset::LastSevenDays => ctx.last_days(7),
+            TimeRangePreset::LastThirtyDays => ctx.last_days(30),
+            TimeRangePreset::LastNinetyDays => ctx.last_days(90),
+            TimeRangePreset::ThisMonth => ctx.this_month(),
+            TimeRangePreset::ThisYear => ctx.this_year(),
+            TimeRangePreset::AllTime => ctx.all_time(),
+            TimeRangePreset::IsoWeek { year, week } => {
+                iso_week_range(*year, *week).unwrap_or_else(|| ctx.this_week())
+            }
             TimeRangePreset::Custom(range) => range.clone(),
         }
     }
     
     pub fn display_name(&self) -> &'static str {
         match self {
+            TimeRangePreset::ThisWeek => "This Week",
             TimeRangePreset::LastWeek => "Last Week",
             TimeRangePreset::LastMonth => "Last Month",
+            TimeRangePreset::ThisQuarter => "This Quarter",
             TimeRangePreset::LastQuarter => "Last Quarter",
             TimeRangePreset::LastYear => "Last Year",
             TimeRangePreset::LastSevenDays => "Last 7 Days",
@@ -78,6 +89,7 @@ impl TimeRangePreset {
             TimeRangePreset::ThisMonth => "This Month",
             TimeRangePreset::ThisYear => "This Year",
             TimeRangePreset::AllTime => "All Time",
+            TimeRangePreset::IsoWeek { .. } => "ISO Week",
             TimeRangePreset::Custom(_) => "Custom Range",
         }
     }
@@ -145,6 +157,31 @@ pub struct TrendConfig {
     pub show_statistics: bool,
     /// Confidence interval for trend predictions
     pub confidence_interval: f64,
+    /// Flag outlying days, disabled when `None`
+    #[serde(default)]
+    pub anomaly_detection: Option<AnomalyConfig>,
+}
+
+/// How far a day must stray from its baseline to be an anomaly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnomalyMethod {
+    /// Standard score against the baseline mean and standard deviation
+    ZScore,
+    /// Modified z-score against the baseline median, robust to earlier outliers
+    MedianAbsoluteDeviation,
+}
+
+/// Configuration for anomaly detection on daily play counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    /// Scoring method
+    pub method: AnomalyMethod,
+    /// Days of history before each day used as its baseline
+    pub window_days: u32,
+    /// Score beyond which a day is flagged
+    pub threshold: f64,
+    /// Compare against the same weekday when a weekly pattern is detected
+    pub seasonal: bool,
 }
 
 impl Default for ComparisonConfig {
@@ -177,6 +214,18 @@ impl Default for TrendConfig {
             show_growth_rate: false,
             show_statistics: false,
             confidence_interval: 0.95,
+            anomaly_detection: None,
+        }
+    }
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            method: AnomalyMethod::MedianAbsoluteDeviation,
+            window_days: 28,
+            threshold: 3.5,
+            seasonal: true,
         }
     }
 }
@@ -221,7 +270,7 @@ impl Default for FilterConfig {
 }
 
 /// Date range specification
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DateRange {
     pub start: NaiveDate,
     pub end: NaiveDate,
@@ -238,10 +287,9 @@ impl DateRange {
         Self { start, end }
     }
     
+    /// From the same day `months` calendar months ago (UTC) up to today
     pub fn last_months(months: u32) -> Self {
-        let end = chrono::Utc::now().date_naive();
-        let start = end - chrono::Duration::days((months * 30) as i64);
-        Self { start, end }
+        TimeRangeContext::default().last_months(months)
     }
 }
 
@@ -608,7 +656,7 @@ impl Default for ConfigMetadata {
             description: None,
             created_at: now,
             modified_at: now,
-            version: "1.0.0".to_string(),
+            version: GRAPH_CONFIG_VERSION.to_string(),
             tags: vec![],
             author: None,
         }
@@ -624,6 +672,18 @@ pub struct ConfigPreset {
     pub config: CompleteGraphConfig,
 }
 
+/// Parse a saved configuration, upgrading it from older format versions
+fn parse_saved_config(json: &str) -> Result<CompleteGraphConfig> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let report = migrate_as(DocumentKind::GraphConfig, value)
+        .map_err(|e| TGraphError::config(format!("Cannot load graph configuration: {}", e)))?;
+    let name = report.migrated.pointer("/metadata/name").and_then(serde_json::Value::as_str);
+    for note in &report.notes {
+        warn!("Graph configuration '{}': {}", name.unwrap_or_default(), note);
+    }
+    Ok(serde_json::from_value(report.migrated)?)
+}
+
 /// Configuration manager for persistence and sharing
 #[derive(Debug)]
 pub struct ConfigurationManager {
@@ -648,7 +708,7 @@ impl ConfigurationManager {
     /// Load configuration from file
     pub async fn load_from_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
         let content = tokio::fs::read_to_string(path).await?;
-        let config: CompleteGraphConfig = serde_json::from_str(&content)?;
+        let config = parse_saved_config(&content)?;
         self.saved_configs.insert(config.metadata.name.clone(), config);
         Ok(())
     }
@@ -667,7 +727,7 @@ impl ConfigurationManager {
 
     /// Import configuration from JSON string
     pub fn import_config(&mut self, json: &str) -> Result<CompleteGraphConfig> {
-        let config: CompleteGraphConfig = serde_json::from_str(json)?;
+        let config = parse_saved_config(json)?;
         self.saved_configs.insert(config.metadata.name.clone(), config.clone());
         Ok(config)
     }
@@ -1014,6 +1074,22 @@ mod tests {
         assert_eq!(imported.metadata.name, config.metadata.name);
     }
 
+    #[test]
+    fn test_import_upgrades_old_presets() {
+        let mut config = CompleteGraphConfig::default();
+        config.filters.time_range_preset = Some(TimeRangePreset::LastWeek);
+        let mut value = serde_json::to_value(&config).unwrap();
+        value["metadata"]["version"] = serde_json::json!("1.0.0");
+
+        let mut manager = ConfigurationManager::new();
+        let imported = manager.import_config(&value.to_string()).unwrap();
+        assert_eq!(imported.filters.time_range_preset, Some(TimeRangePreset::LastSevenDays));
+        assert_eq!(imported.metadata.version, GRAPH_CONFIG_VERSION);
+
+        value["metadata"]["version"] = serde_json::json!("9.0.0");
+        assert!(manager.import_config(&value.to_string()).is_err());
+    }
+
     #[tokio::test]
     async fn test_save_and_load_config() {
         let manager = ConfigurationManager::new();
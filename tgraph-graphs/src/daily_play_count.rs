@@ -1,6 +1,6 @@
 //! Daily play count time series graph implementation
 
-use crate::{DataSet, GraphConfig, GraphRenderer};
+use crate::{daily_play_count_datasets, shared_dates, DataSet, GraphConfig, GraphRenderer, ServerSeries};
 use async_trait::async_trait;
 use chrono::{Datelike, NaiveDate};
 use plotters::prelude::*;
@@ -24,6 +24,8 @@ pub struct DailyPlayCountGraph {
     pub start_date: Option<NaiveDate>,
     /// End date for the graph
     pub end_date: Option<NaiveDate>,
+    /// Per-server series drawn alongside the total when merging servers
+    pub server_series: Vec<ServerSeries<PlayCountDataPoint>>,
 }
 
 impl DailyPlayCountGraph {
@@ -33,6 +35,7 @@ impl DailyPlayCountGraph {
             data: Vec::new(),
             start_date: None,
             end_date: None,
+            server_series: Vec::new(),
         }
     }
 
@@ -54,6 +57,7 @@ impl DailyPlayCountGraph {
             data: Vec::new(),
             start_date: Some(start),
             end_date: Some(end),
+            server_series: Vec::new(),
         }
     }
 
@@ -81,6 +85,29 @@ impl DailyPlayCountGraph {
         }
     }
 
+    /// Set per-server data merged from several Tautulli servers
+    ///
+    /// The main line shows the combined total for each day and every server
+    /// gets its own line on the same date axis.
+    pub fn set_server_series(&mut self, series: Vec<ServerSeries<PlayCountDataPoint>>) {
+        let totals = shared_dates(&series)
+            .into_iter()
+            .map(|date| PlayCountDataPoint {
+                date,
+                count: series
+                    .iter()
+                    .flat_map(|s| s.data.iter())
+                    .filter(|point| point.date == date)
+                    .map(|point| point.count)
+                    .sum(),
+                label: None,
+            })
+            .collect();
+
+        self.set_data(totals);
+        self.server_series = series;
+    }
+
     /// Convert data to plotters-compatible format
     fn prepare_plot_data(&self) -> Vec<(f64, f64)> {
         self.data
@@ -300,6 +327,21 @@ impl GraphRenderer for DailyPlayCountGraph {
             .label("Daily Play Count")
             .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], primary_color));
 
+        // Draw one line per server when history was merged from several servers
+        for (index, dataset) in daily_play_count_datasets(&self.server_series).iter().enumerate() {
+            let color = colors
+                .get((index + 1) % colors.len().max(1))
+                .copied()
+                .unwrap_or(RGBColor(128, 128, 128));
+            chart
+                .draw_series(LineSeries::new(
+                    dataset.data.iter().map(|point| (point.x, point.y)),
+                    &color,
+                ))?
+                .label(dataset.name.clone())
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], color));
+        }
+
         // Draw weekend highlights if we have them
         let weekend_points = self.get_weekend_highlights();
         if !weekend_points.is_empty() {
@@ -412,6 +454,26 @@ mod tests {
         assert_eq!(graph.end_date, Some(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()));
     }
 
+    #[test]
+    fn test_set_server_series_totals() {
+        let day1 = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let point = |date, count| PlayCountDataPoint { date, count, label: None };
+
+        let mut graph = DailyPlayCountGraph::new();
+        graph.set_server_series(vec![
+            ServerSeries { server: "Home".to_string(), data: vec![point(day1, 3), point(day2, 1)] },
+            ServerSeries { server: "Cabin".to_string(), data: vec![point(day2, 4)] },
+        ]);
+
+        assert_eq!(graph.server_series.len(), 2);
+        assert_eq!(graph.data.len(), 2);
+        assert_eq!(graph.data[0].count, 3);
+        assert_eq!(graph.data[1].count, 5);
+        assert_eq!(graph.start_date, Some(day1));
+        assert_eq!(graph.end_date, Some(day2));
+    }
+
     #[test]
     fn test_prepare_plot_data() {
         let mut graph = DailyPlayCountGraph::new();
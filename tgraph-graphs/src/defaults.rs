@@ -0,0 +1,128 @@
+//! Graph defaults from the `[graph]` section
+//!
+//! [`GraphConfig::default`] always uses the built-in values. Callers that
+//! render for the bot take a [`GraphDefaults`] from the current configuration
+//! and pass it along, so a configuration reload changes the size, colours and
+//! fonts of every graph rendered afterwards.
+
+use crate::{FontConfig, GraphConfig};
+use serde::{Deserialize, Serialize};
+use tgraph_config::settings::GraphConfig as GraphSettings;
+
+/// Defaults applied to newly created graph configurations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphDefaults {
+    pub width: u32,
+    pub height: u32,
+    pub background_color: String,
+    pub font_family: String,
+    pub font_size: u32,
+    pub show_grid: bool,
+}
+
+impl Default for GraphDefaults {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            background_color: "#FFFFFF".to_string(),
+            font_family: "sans-serif".to_string(),
+            font_size: 12,
+            show_grid: true,
+        }
+    }
+}
+
+impl From<&GraphSettings> for GraphDefaults {
+    fn from(settings: &GraphSettings) -> Self {
+        Self {
+            width: settings.width,
+            height: settings.height,
+            background_color: settings.background_color.clone(),
+            font_family: settings.font_family.clone(),
+            font_size: settings.font_size,
+            show_grid: settings.show_grid,
+        }
+    }
+}
+
+impl GraphDefaults {
+    /// Give a graph's configuration these defaults
+    ///
+    /// Only values still at their built-in defaults are replaced, so a graph
+    /// that picks its own size or fonts in `with_config` keeps them.
+    pub fn apply(&self, config: &mut GraphConfig) {
+        let builtin = Self::default();
+        if config.width == builtin.width {
+            config.width = self.width;
+        }
+        if config.height == builtin.height {
+            config.height = self.height;
+        }
+
+        let style = &mut config.style;
+        if style.background_color.as_deref() == Some(builtin.background_color.as_str()) {
+            style.background_color = Some(self.background_color.clone());
+        }
+        let fonts: [(&mut FontConfig, u32); 3] = [
+            (&mut style.title_font, 4),
+            (&mut style.axis_font, 0),
+            (&mut style.label_font, 0),
+        ];
+        for (font, extra_size) in fonts {
+            if font.family == builtin.font_family {
+                font.family = self.font_family.clone();
+            }
+            if font.size == builtin.font_size + extra_size {
+                font.size = self.font_size + extra_size;
+            }
+        }
+        if style.grid.show_x == builtin.show_grid {
+            style.grid.show_x = self.show_grid;
+        }
+        if style.grid.show_y == builtin.show_grid {
+            style.grid.show_y = self.show_grid;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tgraph_config::Config;
+
+    #[test]
+    fn test_graph_defaults_from_config() {
+        let mut config = Config::default();
+        config.graph.width = 1280;
+        config.graph.show_grid = false;
+        let defaults = GraphDefaults::from(&config.graph);
+        assert_eq!(defaults.width, 1280);
+        assert_eq!(defaults.height, config.graph.height);
+        assert!(!defaults.show_grid);
+    }
+
+    #[test]
+    fn test_apply_keeps_values_a_graph_chose() {
+        let defaults = GraphDefaults {
+            width: 1280,
+            font_family: "DejaVu Sans".to_string(),
+            font_size: 14,
+            show_grid: false,
+            ..GraphDefaults::default()
+        };
+        let mut config = GraphConfig {
+            height: 700,
+            ..GraphConfig::default()
+        };
+        defaults.apply(&mut config);
+
+        assert_eq!((config.width, config.height), (1280, 700));
+        assert_eq!(config.style.title_font.family, "DejaVu Sans");
+        assert_eq!((config.style.title_font.size, config.style.axis_font.size), (18, 14));
+        assert!(!config.style.grid.show_x && !config.style.grid.show_y);
+
+        // Defaults are never read behind the caller's back
+        assert_eq!(GraphConfig::default().width, GraphDefaults::default().width);
+    }
+}
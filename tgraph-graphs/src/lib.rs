@@ -3,34 +3,61 @@
 pub mod aggregator;
 pub mod cache;
 pub mod cached_aggregator;
+pub mod calendar;
 pub mod comparison;
+pub mod comparison_graph;
+pub mod completion;
+pub mod concurrency;
 pub mod config;
 pub mod config_builder;
 pub mod daily_play_count;
+pub mod defaults;
 pub mod day_of_week;
 pub mod generator;
 pub mod hourly_distribution;
+pub mod locations;
 pub mod monthly_trends;
+pub mod multi_server;
+pub mod personal_stats;
 pub mod pipeline;
+pub mod privacy;
+pub mod range_parser;
 pub mod renderer;
+pub mod scheduled;
+pub mod stream_quality;
 pub mod time_range_selector;
 pub mod top_platforms;
 pub mod trend_analysis;
 pub mod types;
 
+#[cfg(test)]
+mod test_support;
+
 pub use aggregator::*;
 pub use cache::*;
 pub use cached_aggregator::*;
+pub use calendar::*;
 pub use comparison::*;
+pub use comparison_graph::*;
+pub use completion::*;
+pub use concurrency::*;
 pub use config::*;
 pub use config_builder::*;
 pub use daily_play_count::*;
+pub use defaults::*;
 pub use day_of_week::*;
 pub use generator::GraphGenerator;
 pub use hourly_distribution::*;
+pub use locations::*;
 pub use monthly_trends::*;
+pub use multi_server::*;
+pub use personal_stats::*;
 pub use pipeline::*;
+pub use privacy::*;
+pub use range_parser::*;
 pub use renderer::*;
+pub use scheduled::*;
+pub use stream_quality::*;
 pub use time_range_selector::*;
 pub use top_platforms::*;
 pub use trend_analysis::*;
@@ -0,0 +1,483 @@
+//! Top locations from stream IP addresses, anonymized before lookup
+//!
+//! Location data is sensitive when posted to public channels, so the
+//! aggregator enforces anonymization itself rather than trusting callers:
+//! addresses are truncated to their network (/24 for IPv4, /48 for IPv6)
+//! before they are looked up or cached, results never carry an address, and
+//! locations seen from fewer than `min_users` distinct users are folded into
+//! "Other" so a single viewer's city is never singled out.
+
+use async_trait::async_trait;
+use moka::future::Cache;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+use tgraph_common::{GeoIpInfo, HistoryEntry, Result, Session, TautulliClient};
+use tracing::{debug, warn};
+
+/// Name used for private, loopback and LAN addresses
+pub const LOCAL_NETWORK: &str = "Local Network";
+/// Name used for addresses whose location is unknown
+pub const UNKNOWN_LOCATION: &str = "Unknown";
+/// Name used for locations folded together to protect privacy
+pub const OTHER_LOCATIONS: &str = "Other";
+
+/// Level of detail for location results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationGranularity {
+    Country,
+    City,
+}
+
+/// The parts of a GeoIP result the location graphs use
+///
+/// Coordinates are dropped on purpose; a city is as precise as we go.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GeoLocation {
+    pub country: Option<String>,
+    pub country_code: Option<String>,
+    pub city: Option<String>,
+}
+
+impl From<GeoIpInfo> for GeoLocation {
+    fn from(info: GeoIpInfo) -> Self {
+        let non_empty = |value: Option<String>| value.filter(|s| !s.trim().is_empty());
+        Self {
+            country: non_empty(info.country),
+            country_code: non_empty(info.code),
+            city: non_empty(info.city),
+        }
+    }
+}
+
+impl GeoLocation {
+    fn name(&self, granularity: LocationGranularity) -> Option<String> {
+        let country = self.country.as_deref()?;
+        Some(match (granularity, self.city.as_deref()) {
+            (LocationGranularity::City, Some(city)) => format!("{}, {}", city, country),
+            _ => country.to_string(),
+        })
+    }
+}
+
+/// Source of GeoIP lookups, implemented by the Tautulli client
+#[async_trait]
+pub trait GeoIpLookup: Send + Sync {
+    /// Look up an (already anonymized) address
+    async fn lookup(&self, ip: IpAddr) -> Result<GeoLocation>;
+}
+
+#[async_trait]
+impl GeoIpLookup for TautulliClient {
+    async fn lookup(&self, ip: IpAddr) -> Result<GeoLocation> {
+        self.get_geoip_lookup(&ip.to_string()).await.map(GeoLocation::from)
+    }
+}
+
+/// Truncate an address to its network: /24 for IPv4 and /48 for IPv6
+pub fn anonymize_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            IpAddr::V4(Ipv4Addr::new(a, b, c, 0))
+        }
+        IpAddr::V6(v6) => {
+            let s = v6.segments();
+            IpAddr::V6(Ipv6Addr::new(s[0], s[1], s[2], 0, 0, 0, 0, 0))
+        }
+    }
+}
+
+/// Whether an address can never have a public location
+fn is_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_unspecified(),
+        // fc00::/7 unique local and fe80::/10 link local
+        IpAddr::V6(v6) => {
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || (v6.segments()[0] & 0xfe00) == 0xfc00
+                || (v6.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Local cache of GeoIP lookups, keyed by anonymized network
+///
+/// Failed lookups are remembered only briefly, so a broken address does not
+/// hit Tautulli on every graph while a Tautulli outage does not leave every
+/// location unknown for the whole TTL.
+#[derive(Clone)]
+pub struct GeoIpCache {
+    cache: Cache<IpAddr, GeoLocation>,
+    failures: Cache<IpAddr, ()>,
+}
+
+/// How long a failed lookup is remembered by default
+const FAILED_LOOKUP_TTL: Duration = Duration::from_secs(10 * 60);
+
+impl GeoIpCache {
+    pub fn new(max_capacity: u64, ttl: Duration) -> Self {
+        Self {
+            cache: Cache::builder().max_capacity(max_capacity).time_to_live(ttl).build(),
+            failures: Cache::builder().max_capacity(max_capacity).time_to_live(FAILED_LOOKUP_TTL).build(),
+        }
+    }
+
+    /// Remember failed lookups for `ttl` instead of the default ten minutes
+    pub fn with_failure_ttl(mut self, ttl: Duration) -> Self {
+        self.failures = Cache::builder()
+            .max_capacity(self.failures.policy().max_capacity().unwrap_or(10_000))
+            .time_to_live(ttl)
+            .build();
+        self
+    }
+
+    /// Resolve an anonymized address, asking `lookup` only on a cache miss
+    async fn resolve<L: GeoIpLookup>(&self, lookup: &L, network: IpAddr) -> Option<GeoLocation> {
+        if let Some(cached) = self.cache.get(&network).await {
+            return Some(cached);
+        }
+        if self.failures.contains_key(&network) {
+            return None;
+        }
+
+        match lookup.lookup(network).await {
+            Ok(location) => {
+                self.cache.insert(network, location.clone()).await;
+                Some(location)
+            }
+            Err(e) => {
+                // Only the anonymized network is ever logged
+                warn!("GeoIP lookup failed for {}: {}", network, e);
+                self.failures.insert(network, ()).await;
+                None
+            }
+        }
+    }
+
+    /// Number of cached networks
+    pub fn len(&self) -> u64 {
+        self.cache.entry_count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for GeoIpCache {
+    fn default() -> Self {
+        // GeoIP data changes rarely
+        Self::new(10_000, Duration::from_secs(7 * 24 * 3600))
+    }
+}
+
+impl std::fmt::Debug for GeoIpCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print cached networks
+        f.debug_struct("GeoIpCache").field("entries", &self.len()).finish()
+    }
+}
+
+/// Data point for top locations
+///
+/// There is deliberately no address field.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LocationDataPoint {
+    /// Country, or "City, Country"
+    pub location: String,
+    pub country_code: Option<String>,
+    pub count: u32,
+    /// Distinct users seen at this location
+    pub users: u32,
+    pub percentage: f64,
+    pub label: Option<String>,
+}
+
+/// A play to place on the map: who played and from where
+struct Play<'a> {
+    user: Option<&'a str>,
+    ip: Option<IpAddr>,
+    /// Tautulli reports LAN sessions as "lan"
+    lan: bool,
+}
+
+/// Aggregator for top countries or cities
+#[derive(Debug)]
+pub struct TopLocationsAggregator {
+    pub granularity: LocationGranularity,
+    /// Locations shown by name, the rest are folded into "Other"
+    pub limit: usize,
+    /// Locations with fewer distinct users are folded into "Other"
+    pub min_users: u32,
+    cache: GeoIpCache,
+}
+
+impl TopLocationsAggregator {
+    pub fn new(granularity: LocationGranularity) -> Self {
+        Self::with_cache(granularity, GeoIpCache::default())
+    }
+
+    /// Share a lookup cache between aggregators
+    pub fn with_cache(granularity: LocationGranularity, cache: GeoIpCache) -> Self {
+        Self {
+            granularity,
+            limit: 10,
+            min_users: 2,
+            cache,
+        }
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Require at least this many distinct users per location, minimum 1
+    pub fn with_min_users(mut self, min_users: u32) -> Self {
+        self.min_users = min_users.max(1);
+        self
+    }
+
+    /// Top locations of history entries
+    pub async fn aggregate<L: GeoIpLookup>(&self, lookup: &L, entries: &[HistoryEntry]) -> Result<Vec<LocationDataPoint>> {
+        let plays = entries.iter().map(|entry| Play {
+            user: entry.username.as_deref(),
+            ip: entry.ip_address.as_deref().and_then(|ip| ip.trim().parse().ok()),
+            lan: false,
+        });
+        self.aggregate_plays(lookup, plays).await
+    }
+
+    /// Top locations of active sessions
+    pub async fn aggregate_sessions<L: GeoIpLookup>(&self, lookup: &L, sessions: &[Session]) -> Result<Vec<LocationDataPoint>> {
+        let plays = sessions.iter().map(|session| Play {
+            user: session.username.as_deref(),
+            ip: session.ip_address.as_deref().and_then(|ip| ip.trim().parse().ok()),
+            lan: session.location.as_deref() == Some("lan"),
+        });
+        self.aggregate_plays(lookup, plays).await
+    }
+
+    async fn aggregate_plays<'a, L: GeoIpLookup>(
+        &self,
+        lookup: &L,
+        plays: impl Iterator<Item = Play<'a>>,
+    ) -> Result<Vec<LocationDataPoint>> {
+        // location -> (country code, plays, users)
+        let mut locations: HashMap<String, (Option<String>, u32, HashSet<&'a str>)> = HashMap::new();
+
+        for play in plays {
+            let (name, code) = match play.ip {
+                _ if play.lan => (LOCAL_NETWORK.to_string(), None),
+                Some(ip) if is_local(ip) => (LOCAL_NETWORK.to_string(), None),
+                Some(ip) => match self.cache.resolve(lookup, anonymize_ip(ip)).await {
+                    Some(location) => match location.name(self.granularity) {
+                        Some(name) => (name, location.country_code),
+                        None => (UNKNOWN_LOCATION.to_string(), None),
+                    },
+                    None => (UNKNOWN_LOCATION.to_string(), None),
+                },
+                None => (UNKNOWN_LOCATION.to_string(), None),
+            };
+
+            let location = locations.entry(name).or_insert_with(|| (code, 0, HashSet::new()));
+            location.1 += 1;
+            if let Some(user) = play.user {
+                location.2.insert(user);
+            }
+        }
+
+        let total: u32 = locations.values().map(|(_, count, _)| *count).sum();
+        let mut other = (0u32, HashSet::new());
+        let mut shown = Vec::new();
+
+        for (location, (country_code, count, users)) in locations {
+            if (users.len() as u32) < self.min_users {
+                other.0 += count;
+                other.1.extend(users);
+                continue;
+            }
+            shown.push((location, country_code, count, users));
+        }
+
+        // Locations past the limit still count towards the other locations
+        shown.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        for (_, _, count, users) in shown.split_off(self.limit.min(shown.len())) {
+            other.0 += count;
+            other.1.extend(users);
+        }
+
+        let mut result: Vec<LocationDataPoint> = shown
+            .into_iter()
+            .map(|(location, country_code, count, users)| {
+                Self::data_point(location, country_code, count, users.len() as u32, total)
+            })
+            .collect();
+        if other.0 > 0 {
+            result.push(Self::data_point(OTHER_LOCATIONS.to_string(), None, other.0, other.1.len() as u32, total));
+        }
+
+        debug!("Aggregated {} top location data points", result.len());
+        Ok(result)
+    }
+
+    fn data_point(location: String, country_code: Option<String>, count: u32, users: u32, total: u32) -> LocationDataPoint {
+        let percentage = (count as f64 / total.max(1) as f64) * 100.0;
+        LocationDataPoint {
+            label: Some(format!("{} - {} plays ({:.1}%)", location, count, percentage)),
+            location,
+            country_code,
+            count,
+            users,
+            percentage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_entry;
+    use std::sync::Mutex;
+
+    /// Records every address it is asked about
+    #[derive(Default)]
+    struct FakeLookup {
+        seen: Mutex<Vec<IpAddr>>,
+    }
+
+    #[async_trait]
+    impl GeoIpLookup for FakeLookup {
+        async fn lookup(&self, ip: IpAddr) -> Result<GeoLocation> {
+            self.seen.lock().unwrap().push(ip);
+            let (country, code, city) = match ip.to_string().as_str() {
+                "203.0.113.0" => ("United States", "US", "Denver"),
+                "198.51.100.0" => ("Canada", "CA", "Toronto"),
+                _ => ("Germany", "DE", "Berlin"),
+            };
+            Ok(GeoLocation {
+                country: Some(country.to_string()),
+                country_code: Some(code.to_string()),
+                city: Some(city.to_string()),
+            })
+        }
+    }
+
+    /// Fails until `available` is set
+    #[derive(Default)]
+    struct FlakyLookup {
+        available: std::sync::atomic::AtomicBool,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl GeoIpLookup for FlakyLookup {
+        async fn lookup(&self, ip: IpAddr) -> Result<GeoLocation> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if !self.available.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(tgraph_common::TGraphError::service_unavailable("Tautulli", None));
+            }
+            FakeLookup::default().lookup(ip).await
+        }
+    }
+
+    fn entry(user: &str, ip: &str) -> HistoryEntry {
+        history_entry().user(1, user).ip_address(ip).build()
+    }
+
+    fn entries() -> Vec<HistoryEntry> {
+        vec![
+            entry("alice", "203.0.113.7"),
+            entry("bob", "203.0.113.99"),
+            entry("bob", "203.0.113.99"),
+            entry("carol", "198.51.100.4"),
+            entry("dave", "192.168.1.20"),
+            entry("erin", "192.168.1.21"),
+            entry("frank", "not an ip"),
+        ]
+    }
+
+    #[test]
+    fn test_anonymize_ip() {
+        assert_eq!(anonymize_ip("203.0.113.77".parse().unwrap()).to_string(), "203.0.113.0");
+        assert_eq!(anonymize_ip("2001:db8:1234:5678::1".parse().unwrap()).to_string(), "2001:db8:1234::");
+        assert!(is_local("10.1.2.3".parse().unwrap()));
+        assert!(is_local("fd00::1".parse().unwrap()));
+        assert!(!is_local("203.0.113.7".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_top_locations_are_anonymized() {
+        let lookup = FakeLookup::default();
+        let aggregator = TopLocationsAggregator::new(LocationGranularity::City);
+        let points = aggregator.aggregate(&lookup, &entries()).await.unwrap();
+
+        // Only truncated networks reach the lookup, each one once
+        let seen = lookup.seen.lock().unwrap().clone();
+        assert_eq!(seen.len(), 2);
+        assert!(seen.iter().all(|ip| ip.to_string().ends_with(".0")));
+
+        // Toronto and the unknown address have a single user each
+        let names: Vec<&str> = points.iter().map(|p| p.location.as_str()).collect();
+        assert_eq!(names, vec!["Denver, United States", LOCAL_NETWORK, OTHER_LOCATIONS]);
+        assert_eq!(points[0].count, 3);
+        assert_eq!(points[0].users, 2);
+        assert_eq!(points[2].count, 2);
+
+        // No address survives into the output
+        let json = serde_json::to_string(&points).unwrap();
+        assert!(!json.contains("203.0.113") && !json.contains("192.168"));
+    }
+
+    #[tokio::test]
+    async fn test_country_granularity_without_threshold() {
+        let lookup = FakeLookup::default();
+        let aggregator = TopLocationsAggregator::new(LocationGranularity::Country).with_min_users(1);
+        let points = aggregator.aggregate(&lookup, &entries()).await.unwrap();
+
+        let canada = points.iter().find(|p| p.location == "Canada").unwrap();
+        assert_eq!(canada.country_code.as_deref(), Some("CA"));
+        assert!(points.iter().any(|p| p.location == UNKNOWN_LOCATION));
+        assert!(!points.iter().any(|p| p.location == OTHER_LOCATIONS));
+    }
+
+    #[tokio::test]
+    async fn test_locations_past_the_limit_go_to_other() {
+        let lookup = FakeLookup::default();
+        let aggregator = TopLocationsAggregator::new(LocationGranularity::Country)
+            .with_min_users(1)
+            .with_limit(2);
+        let points = aggregator.aggregate(&lookup, &entries()).await.unwrap();
+
+        // Canada and the unknown address are past the limit
+        let names: Vec<&str> = points.iter().map(|p| p.location.as_str()).collect();
+        assert_eq!(names, vec!["United States", LOCAL_NETWORK, OTHER_LOCATIONS]);
+        assert_eq!((points[2].count, points[2].users), (2, 2));
+        assert_eq!(points.iter().map(|p| p.count).sum::<u32>(), entries().len() as u32);
+        let shares: f64 = points.iter().map(|p| p.percentage).sum();
+        assert!((shares - 100.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_failed_lookups_expire_quickly() {
+        use std::sync::atomic::Ordering;
+
+        let lookup = FlakyLookup::default();
+        let cache = GeoIpCache::default().with_failure_ttl(Duration::from_millis(50));
+        let network: IpAddr = "203.0.113.0".parse().unwrap();
+
+        assert!(cache.resolve(&lookup, network).await.is_none());
+        assert!(cache.resolve(&lookup, network).await.is_none());
+        assert_eq!(lookup.calls.load(Ordering::SeqCst), 1);
+
+        // Once Tautulli is back the address resolves again
+        lookup.available.store(true, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let location = cache.resolve(&lookup, network).await.unwrap();
+        assert_eq!(location.city.as_deref(), Some("Denver"));
+        assert_eq!(lookup.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,172 @@
+//! Multi-server history merging and per-server graph series
+
+use crate::{AggregationConfig, DataAggregator, DataPoint, DataSet, PlayCountDataPoint};
+use chrono::NaiveDate;
+use std::collections::BTreeSet;
+use tgraph_common::{HistoryEntry, Result, ServerHistory, ServerSelection};
+use tracing::debug;
+
+/// Aggregated data for a single named server
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ServerSeries<T> {
+    /// Name of the server the data came from
+    pub server: String,
+    /// Aggregated data points for that server
+    pub data: Vec<T>,
+}
+
+/// Append the server selection to a graph title
+///
+/// Single-server deployments pass `None` so existing titles stay unchanged.
+pub fn server_title(title: &str, selection: Option<&ServerSelection>) -> String {
+    match selection {
+        Some(selection) => format!("{} ({})", title, selection),
+        None => title.to_string(),
+    }
+}
+
+/// Merge history from every server into one list of entries
+///
+/// User IDs are local to each Tautulli instance, so rankings keyed by user
+/// should be aggregated per server instead of from the merged history.
+pub fn merge_server_histories(histories: Vec<ServerHistory>) -> Vec<HistoryEntry> {
+    let total: usize = histories.iter().map(|history| history.entries.len()).sum();
+    let mut merged = Vec::with_capacity(total);
+    for history in histories {
+        merged.extend(history.entries);
+    }
+    debug!("Merged {} history entries from all servers", merged.len());
+    merged
+}
+
+/// Aggregate each server's history separately with the same aggregator
+pub async fn aggregate_per_server<T, A>(
+    aggregator: &A,
+    histories: Vec<ServerHistory>,
+    config: &AggregationConfig,
+) -> Result<Vec<ServerSeries<T>>>
+where
+    A: DataAggregator<T>,
+{
+    let mut series = Vec::with_capacity(histories.len());
+    for history in histories {
+        let data = aggregator
+            .aggregate_streaming(history.entries, config, None)
+            .await?;
+        series.push(ServerSeries {
+            server: history.server,
+            data,
+        });
+    }
+    Ok(series)
+}
+
+/// Dates covered by any of the series, in ascending order
+pub fn shared_dates(series: &[ServerSeries<PlayCountDataPoint>]) -> Vec<NaiveDate> {
+    series
+        .iter()
+        .flat_map(|s| s.data.iter().map(|point| point.date))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Convert per-server daily play counts into datasets on a shared date axis
+///
+/// Each dataset is named after its server. Days without plays on a server are
+/// filled with zero so the lines line up across servers.
+pub fn daily_play_count_datasets(series: &[ServerSeries<PlayCountDataPoint>]) -> Vec<DataSet> {
+    let dates = shared_dates(series);
+
+    series
+        .iter()
+        .map(|server_series| {
+            let data = dates
+                .iter()
+                .enumerate()
+                .map(|(index, date)| {
+                    let count = server_series
+                        .data
+                        .iter()
+                        .find(|point| point.date == *date)
+                        .map_or(0, |point| point.count);
+                    DataPoint {
+                        x: index as f64,
+                        y: count as f64,
+                        label: Some(date.format("%Y-%m-%d").to_string()),
+                    }
+                })
+                .collect();
+
+            DataSet {
+                name: server_series.server.clone(),
+                data,
+                color: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::entry_at;
+    use crate::DailyPlayCountAggregator;
+
+    fn histories() -> Vec<ServerHistory> {
+        // 2022-01-01 and 2022-01-02 UTC
+        vec![
+            ServerHistory {
+                server: "Home".to_string(),
+                entries: vec![entry_at(1640995200), entry_at(1640995300), entry_at(1641081600)],
+            },
+            ServerHistory {
+                server: "Cabin".to_string(),
+                entries: vec![entry_at(1641081700)],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_server_title() {
+        let selection = ServerSelection::Server("Home".to_string());
+        assert_eq!(server_title("Daily Plays", Some(&selection)), "Daily Plays (Home)");
+        assert_eq!(
+            server_title("Daily Plays", Some(&ServerSelection::All)),
+            "Daily Plays (All servers)"
+        );
+        assert_eq!(server_title("Daily Plays", None), "Daily Plays");
+    }
+
+    #[test]
+    fn test_merge_server_histories() {
+        let merged = merge_server_histories(histories());
+        assert_eq!(merged.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_per_server_series_alignment() {
+        let series = aggregate_per_server(
+            &DailyPlayCountAggregator::new(),
+            histories(),
+            &AggregationConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].server, "Home");
+        assert_eq!(series[1].data.len(), 1);
+
+        let datasets = daily_play_count_datasets(&series);
+        assert_eq!(datasets.len(), 2);
+        assert_eq!(datasets[0].name, "Home");
+        assert_eq!(datasets[1].name, "Cabin");
+
+        // Both datasets cover both days; Cabin had no plays on the first day
+        assert_eq!(datasets[0].data.len(), 2);
+        assert_eq!(datasets[0].data[0].y, 2.0);
+        assert_eq!(datasets[1].data[0].y, 0.0);
+        assert_eq!(datasets[1].data[1].y, 1.0);
+    }
+}
@@ -0,0 +1,237 @@
+//! Personal viewing statistics for a single Tautulli user
+
+use crate::{
+    AggregationConfig, DailyPlayCountAggregator, DailyPlayCountGraph, DataAggregator,
+    GraphRenderer, HourlyDistributionAggregator, HourlyDistributionGraph, PipelineConfig, TempFileGuard,
+    TempFileManager,
+};
+use chrono::{DateTime, Timelike, Utc};
+use std::collections::HashMap;
+use tgraph_common::{HistoryEntry, Result, TGraphError};
+use tracing::debug;
+
+/// Plays of a single show
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ShowPlays {
+    /// Show title
+    pub title: String,
+    /// Number of episodes played
+    pub plays: u32,
+}
+
+/// Summary of one user's viewing history
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PersonalViewingStats {
+    /// Total number of plays
+    pub total_plays: u32,
+    /// Total time watched in seconds
+    pub watch_time_seconds: i64,
+    /// Most played shows, most plays first
+    pub top_shows: Vec<ShowPlays>,
+    /// Platform with the most plays
+    pub favorite_platform: Option<String>,
+    /// Hour of day (UTC) with the most plays
+    pub most_active_hour: Option<u8>,
+}
+
+impl PersonalViewingStats {
+    /// Summarize history entries, keeping the `top_n` most played shows
+    pub fn from_history(entries: &[HistoryEntry], top_n: usize) -> Self {
+        let mut shows: HashMap<&str, u32> = HashMap::new();
+        let mut platforms: HashMap<&str, u32> = HashMap::new();
+        let mut hours: HashMap<u8, u32> = HashMap::new();
+        let mut watch_time_seconds = 0;
+
+        for entry in entries {
+            watch_time_seconds += entry.duration.unwrap_or(0).max(0);
+
+            if entry.media_type.as_deref() == Some("episode") {
+                if let Some(show) = entry.grandparent_title.as_deref().filter(|s| !s.is_empty()) {
+                    *shows.entry(show).or_insert(0) += 1;
+                }
+            }
+            if let Some(platform) = entry.platform.as_deref().filter(|s| !s.is_empty()) {
+                *platforms.entry(platform).or_insert(0) += 1;
+            }
+            if let Some(date) = entry.date.and_then(|ts| DateTime::from_timestamp(ts, 0)) {
+                *hours.entry(date.hour() as u8).or_insert(0) += 1;
+            }
+        }
+
+        let mut top_shows: Vec<ShowPlays> = shows
+            .into_iter()
+            .map(|(title, plays)| ShowPlays {
+                title: title.to_string(),
+                plays,
+            })
+            .collect();
+        // Ties are broken by title so the ranking is stable
+        top_shows.sort_by(|a, b| b.plays.cmp(&a.plays).then_with(|| a.title.cmp(&b.title)));
+        top_shows.truncate(top_n);
+
+        Self {
+            total_plays: entries.len() as u32,
+            watch_time_seconds,
+            top_shows,
+            favorite_platform: most_common(platforms).map(str::to_string),
+            most_active_hour: most_common(hours),
+        }
+    }
+
+    /// Watch time as hours and minutes
+    pub fn watch_time_hm(&self) -> (i64, i64) {
+        (self.watch_time_seconds / 3600, (self.watch_time_seconds % 3600) / 60)
+    }
+}
+
+fn most_common<K: Ord + Copy>(counts: HashMap<K, u32>) -> Option<K> {
+    counts
+        .into_iter()
+        .max_by(|(a_key, a), (b_key, b)| a.cmp(b).then_with(|| b_key.cmp(a_key)))
+        .map(|(key, _)| key)
+}
+
+/// Keep only entries played at or after `since`
+pub fn history_since(entries: Vec<HistoryEntry>, since: Option<DateTime<Utc>>) -> Vec<HistoryEntry> {
+    match since {
+        Some(since) => entries
+            .into_iter()
+            .filter(|entry| entry.date.is_some_and(|ts| ts >= since.timestamp()))
+            .collect(),
+        None => entries,
+    }
+}
+
+/// A rendered graph image
+#[derive(Debug, Clone)]
+pub struct RenderedGraph {
+    /// Suggested file name
+    pub file_name: String,
+    /// PNG image data
+    pub data: Vec<u8>,
+}
+
+/// Render a user's personal daily plays and hourly distribution graphs
+///
+/// Graphs without data are skipped, so the result may be empty.
+pub async fn render_personal_graphs(
+    entries: Vec<HistoryEntry>,
+    display_name: &str,
+) -> Result<Vec<RenderedGraph>> {
+    let config = AggregationConfig::default();
+    let mut graphs = Vec::new();
+
+    let daily = DailyPlayCountAggregator::new().aggregate(entries.clone(), &config)?;
+    if !daily.is_empty() {
+        let (mut graph, graph_config) = DailyPlayCountGraph::with_config(
+            &format!("Daily Plays - {}", display_name),
+            Some("Date"),
+            Some("Plays"),
+        );
+        graph.set_data(daily);
+        graphs.push(render_png(&graph, &graph_config, "daily_plays.png").await?);
+    }
+
+    let hourly = HourlyDistributionAggregator::new().aggregate(entries, &config)?;
+    if !hourly.is_empty() {
+        let (mut graph, graph_config) = HourlyDistributionGraph::with_config(
+            &format!("Plays by Hour (UTC) - {}", display_name),
+            Some("Hour of Day"),
+            Some("Plays"),
+        );
+        graph.set_data(hourly);
+        graphs.push(render_png(&graph, &graph_config, "hourly_plays.png").await?);
+    }
+
+    debug!("Rendered {} personal graphs for {}", graphs.len(), display_name);
+    Ok(graphs)
+}
+
+/// Render a graph to PNG bytes for attaching to a message
+pub async fn render_png<R: GraphRenderer + Sync>(
+    renderer: &R,
+    config: &crate::GraphConfig,
+    file_name: &str,
+) -> Result<RenderedGraph> {
+    // The renderers only draw to files, so go through a temporary file
+    let mut temp_files = TempFileManager::new(PipelineConfig::default());
+    let path = temp_files
+        .create_temp_file(format!("tgraph_{}_{}", uuid::Uuid::new_v4(), file_name))
+        .await?;
+    let guard = TempFileGuard::new(path);
+    renderer.render_to_file(config, &[], guard.path()).await?;
+    let data = tokio::fs::read(guard.path())
+        .await
+        .map_err(|e| TGraphError::graph(format!("Failed to read rendered graph: {}", e)))?;
+
+    Ok(RenderedGraph {
+        file_name: file_name.to_string(),
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_entry;
+
+    fn entry(date: i64, media_type: &str, show: Option<&str>, platform: &str, duration: i64) -> HistoryEntry {
+        let entry = history_entry().user(7, "alice");
+        let entry = match show {
+            Some(show) => entry.show(show),
+            None => entry,
+        };
+        entry.date(date).media_type(media_type).platform(platform).duration(duration).build()
+    }
+
+    #[test]
+    fn test_personal_viewing_stats() {
+        // 2022-01-01 20:00 UTC and 21:00 UTC
+        let entries = vec![
+            entry(1641067200, "episode", Some("Severance"), "Roku", 3000),
+            entry(1641067300, "episode", Some("Severance"), "Roku", 3000),
+            entry(1641070800, "episode", Some("Andor"), "Chrome", 2400),
+            entry(1641067400, "movie", None, "Roku", 7200),
+        ];
+
+        let stats = PersonalViewingStats::from_history(&entries, 5);
+        assert_eq!(stats.total_plays, 4);
+        assert_eq!(stats.watch_time_seconds, 15600);
+        assert_eq!(stats.watch_time_hm(), (4, 20));
+        assert_eq!(stats.top_shows.len(), 2);
+        assert_eq!(stats.top_shows[0], ShowPlays { title: "Severance".to_string(), plays: 2 });
+        assert_eq!(stats.favorite_platform.as_deref(), Some("Roku"));
+        assert_eq!(stats.most_active_hour, Some(20));
+    }
+
+    #[test]
+    fn test_empty_history() {
+        let stats = PersonalViewingStats::from_history(&[], 5);
+        assert_eq!(stats, PersonalViewingStats::default());
+    }
+
+    #[test]
+    fn test_history_since() {
+        let entries = vec![
+            entry(1641067200, "movie", None, "Roku", 0),
+            entry(1641153600, "movie", None, "Roku", 0),
+        ];
+        let since = DateTime::from_timestamp(1641100000, 0);
+        assert_eq!(history_since(entries.clone(), since).len(), 1);
+        assert_eq!(history_since(entries, None).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_render_png_removes_temp_file_on_failure() {
+        let (graph, config) = DailyPlayCountGraph::with_config("Empty", None, None);
+        assert!(render_png(&graph, &config, "personal_stats_failure.png").await.is_err());
+
+        let temp_dir = PipelineConfig::default().temp_dir;
+        let leftover = std::fs::read_dir(&temp_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .any(|file| file.file_name().to_string_lossy().ends_with("personal_stats_failure.png"));
+        assert!(!leftover);
+    }
+}
@@ -6,7 +6,7 @@ use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::{mpsc, oneshot, Mutex};
 use tokio::task::JoinHandle;
-use tgraph_common::Result;
+use tgraph_common::{CancellationContext, Result, TGraphError};
 use uuid::Uuid;
 
 /// Progress information for graph generation
@@ -18,7 +18,7 @@ pub struct GenerationProgress {
 }
 
 /// Stages of graph generation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GenerationStage {
     Initializing,
     ProcessingData,
@@ -26,6 +26,25 @@ pub enum GenerationStage {
     WritingFile,
     Cleanup,
     Complete,
+    /// Generation stopped because it was cancelled
+    Cancelled,
+    /// Generation stopped because its deadline passed
+    TimedOut,
+    /// Generation stopped because of an error
+    Failed,
+}
+
+impl GenerationStage {
+    /// Terminal stage for a generation that ended with the given error
+    pub fn from_error(error: &TGraphError) -> Self {
+        if error.is_cancelled() {
+            GenerationStage::Cancelled
+        } else if error.is_deadline_exceeded() {
+            GenerationStage::TimedOut
+        } else {
+            GenerationStage::Failed
+        }
+    }
 }
 
 /// Configuration for the generation pipeline
@@ -56,6 +75,7 @@ pub struct GraphGenerationTask {
     pub output_path: Option<PathBuf>,
     pub pipeline_config: PipelineConfig,
     pub progress_tx: Option<mpsc::UnboundedSender<GenerationProgress>>,
+    pub cancellation: CancellationContext,
 }
 
 impl GraphGenerationTask {
@@ -72,6 +92,7 @@ impl GraphGenerationTask {
             output_path,
             pipeline_config: PipelineConfig::default(),
             progress_tx: None,
+            cancellation: CancellationContext::new(),
         }
     }
 
@@ -89,9 +110,19 @@ impl GraphGenerationTask {
             output_path,
             pipeline_config,
             progress_tx: None,
+            cancellation: CancellationContext::new(),
         }
     }
 
+    /// Run the task under the given cancellation context and deadline
+    ///
+    /// This is normally the same context used to fetch and aggregate the
+    /// history, so cancelling a queued task stops every stage of its generation.
+    pub fn with_cancellation(mut self, cancellation: CancellationContext) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
     /// Add progress reporting to the task
     pub fn with_progress_reporting(mut self) -> (Self, mpsc::UnboundedReceiver<GenerationProgress>) {
         let (tx, rx) = mpsc::unbounded_channel();
@@ -140,6 +171,8 @@ impl GraphGenerationTask {
             "Starting data processing".to_string(),
         );
 
+        self.cancellation.check("data processing")?;
+
         let total_points: usize = self.datasets.iter()
             .map(|ds| ds.data.len())
             .sum();
@@ -200,6 +233,7 @@ impl GraphPipeline {
     {
         let temp_manager = Arc::clone(&self.temp_manager);
         let renderer = Arc::new(renderer);
+        let cancellation = task.cancellation.clone();
         
         let (result_tx, result_rx) = oneshot::channel();
         
@@ -211,10 +245,11 @@ impl GraphPipeline {
         Ok(GenerationHandle {
             handle,
             result_rx,
+            cancellation,
         })
     }
 
-    /// Execute a graph generation task
+    /// Execute a graph generation task, reporting a terminal stage on failure
     async fn execute_generation_task<R>(
         task: GraphGenerationTask,
         renderer: Arc<R>,
@@ -223,6 +258,32 @@ impl GraphPipeline {
     where
         R: GraphRenderer + Send + Sync + 'static,
     {
+        let result = Self::run_generation_stages(&task, renderer, temp_manager).await;
+
+        if let Err(ref e) = result {
+            let stage = GenerationStage::from_error(e);
+            match stage {
+                GenerationStage::Cancelled => tracing::info!("Graph generation {} cancelled", task.id),
+                GenerationStage::TimedOut => tracing::warn!("Graph generation {} timed out", task.id),
+                _ => tracing::error!("Graph generation {} failed: {}", task.id, e),
+            }
+            task.send_progress(stage, 1.0, e.to_string());
+        }
+
+        result
+    }
+
+    /// Run each generation stage, stopping at the first cancellation or deadline
+    async fn run_generation_stages<R>(
+        task: &GraphGenerationTask,
+        renderer: Arc<R>,
+        temp_manager: Arc<Mutex<TempFileManager>>,
+    ) -> Result<PathBuf>
+    where
+        R: GraphRenderer + Send + Sync + 'static,
+    {
+        let ctx = &task.cancellation;
+
         task.send_progress(
             GenerationStage::Initializing,
             0.0,
@@ -230,23 +291,27 @@ impl GraphPipeline {
         );
 
         // Process data in memory-efficient chunks
-        let processed_datasets = task.process_datasets_chunked().await?;
+        let processed_datasets = ctx.run("data processing", task.process_datasets_chunked()).await?;
 
-        // Generate temporary file path
+        // Generate temporary file path; the guard removes it on any early exit
         let temp_filename = task.generate_temp_filename();
-        let temp_path = {
+        let temp_file = {
             let mut manager = temp_manager.lock().await;
-            manager.create_temp_file(temp_filename).await?
+            TempFileGuard::new(manager.create_temp_file(temp_filename).await?)
         };
 
         task.send_progress(
             GenerationStage::Rendering,
             0.5,
-            format!("Rendering to {}", temp_path.display()),
+            format!("Rendering to {}", temp_file.path().display()),
         );
 
         // Render the graph
-        renderer.render_to_file(&task.config, &processed_datasets, &temp_path).await?;
+        ctx.run(
+            "rendering",
+            renderer.render_to_file(&task.config, &processed_datasets, temp_file.path()),
+        )
+        .await?;
 
         task.send_progress(
             GenerationStage::WritingFile,
@@ -256,19 +321,26 @@ impl GraphPipeline {
 
         // Handle output path
         let final_path = if let Some(output_path) = &task.output_path {
-            // Copy to final destination
-            tokio::fs::copy(&temp_path, output_path).await?;
+            // Copy to final destination, removing a partial copy if interrupted
+            let output_file = TempFileGuard::new(output_path.clone());
+            ctx.run("writing output", async {
+                tokio::fs::copy(temp_file.path(), output_file.path()).await?;
+                Ok(())
+            })
+            .await?;
+            let output_path = output_file.keep();
             
             // Schedule cleanup of temp file
+            let temp_path = temp_file.keep();
             {
                 let mut manager = temp_manager.lock().await;
                 manager.schedule_cleanup(&temp_path, task.pipeline_config.cleanup_timeout_secs);
             }
             
-            output_path.clone()
+            output_path
         } else {
             // Return temp path (caller responsible for cleanup)
-            temp_path
+            temp_file.keep()
         };
 
         task.send_progress(
@@ -287,10 +359,18 @@ impl Default for GraphPipeline {
     }
 }
 
+/// Final result of a graph generation together with the stage it ended in
+#[derive(Debug)]
+pub struct GenerationOutcome {
+    pub stage: GenerationStage,
+    pub result: Result<PathBuf>,
+}
+
 /// Handle for a running graph generation task
 pub struct GenerationHandle {
     handle: JoinHandle<()>,
     result_rx: oneshot::Receiver<Result<PathBuf>>,
+    cancellation: CancellationContext,
 }
 
 impl GenerationHandle {
@@ -298,11 +378,29 @@ impl GenerationHandle {
     pub async fn await_result(self) -> Result<PathBuf> {
         // Wait for the task to complete
         self.handle.await
-            .map_err(|e| tgraph_common::TGraphError::new(format!("Task join error: {}", e)))?;
+            .map_err(|e| TGraphError::new(format!("Task join error: {}", e)))?;
         
         // Get the result
         self.result_rx.await
-            .map_err(|e| tgraph_common::TGraphError::new(format!("Result channel error: {}", e)))?
+            .map_err(|e| TGraphError::new(format!("Result channel error: {}", e)))?
+    }
+
+    /// Wait for the generation to finish and report the stage it ended in
+    ///
+    /// Cancelled and timed-out runs end in [`GenerationStage::Cancelled`] and
+    /// [`GenerationStage::TimedOut`] respectively.
+    pub async fn await_outcome(self) -> GenerationOutcome {
+        let result = self.await_result().await;
+        let stage = match &result {
+            Ok(_) => GenerationStage::Complete,
+            Err(e) => GenerationStage::from_error(e),
+        };
+        GenerationOutcome { stage, result }
+    }
+
+    /// Cancellation context shared with the running task
+    pub fn cancellation(&self) -> &CancellationContext {
+        &self.cancellation
     }
 
     /// Check if the generation is complete (non-blocking)
@@ -311,8 +409,49 @@ impl GenerationHandle {
     }
 
     /// Cancel the generation task
+    ///
+    /// Cancellation is cooperative: the running stage stops at its next
+    /// cancellation point and any temporary files it created are removed.
     pub fn cancel(&mut self) {
-        self.handle.abort();
+        self.cancellation.cancel();
+    }
+}
+
+/// Removes a file when dropped unless it has been kept
+///
+/// Unlike scheduled cleanup this also runs when the generation future is
+/// dropped mid-stage, so partially written files never outlive a cancelled run.
+#[derive(Debug)]
+pub struct TempFileGuard {
+    path: Option<PathBuf>,
+}
+
+impl TempFileGuard {
+    /// Guard the file at `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self { path: Some(path) }
+    }
+
+    /// Path of the guarded file
+    pub fn path(&self) -> &Path {
+        self.path.as_deref().expect("guard path is only taken by keep")
+    }
+
+    /// Disarm the guard and keep the file
+    pub fn keep(mut self) -> PathBuf {
+        self.path.take().expect("guard path is only taken by keep")
+    }
+}
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            match std::fs::remove_file(&path) {
+                Ok(()) => tracing::debug!("Removed unfinished file: {}", path.display()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => tracing::warn!("Failed to remove unfinished file {}: {}", path.display(), e),
+            }
+        }
     }
 }
 
@@ -692,7 +831,11 @@ mod tests {
 
     #[tokio::test]
     async fn test_task_cancellation() {
-        let config = PipelineConfig::default();
+        let temp_dir = TempDir::new().unwrap();
+        let config = PipelineConfig {
+            temp_dir: temp_dir.path().to_path_buf(),
+            ..PipelineConfig::default()
+        };
         let pipeline = GraphPipeline::new(config);
         
         // Use a renderer with delay to test cancellation
@@ -708,5 +851,60 @@ mod tests {
         // Verify the task was cancelled
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         assert!(handle.is_complete());
+
+        let outcome = handle.await_outcome().await;
+        assert_eq!(outcome.stage, GenerationStage::Cancelled);
+        assert!(outcome.result.unwrap_err().is_cancelled());
+
+        // The reserved temp file must not outlive the cancelled run
+        let mut entries = tokio::fs::read_dir(temp_dir.path()).await.unwrap();
+        assert!(entries.next_entry().await.unwrap().is_none());
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_task_deadline_reports_timed_out() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PipelineConfig {
+            temp_dir: temp_dir.path().to_path_buf(),
+            ..PipelineConfig::default()
+        };
+        let pipeline = GraphPipeline::new(config);
+        let renderer = MockPipelineRenderer::with_delay(1000);
+
+        let (task, mut progress_rx) = GraphGenerationTask::new(GraphConfig::default(), vec![], None)
+            .with_cancellation(CancellationContext::with_timeout(
+                std::time::Duration::from_millis(50),
+            ))
+            .with_progress_reporting();
+        let handle = pipeline.spawn_generation(task, renderer).await.unwrap();
+
+        let outcome = handle.await_outcome().await;
+        assert_eq!(outcome.stage, GenerationStage::TimedOut);
+        assert!(outcome.result.unwrap_err().is_deadline_exceeded());
+
+        let mut last_stage = None;
+        while let Ok(progress) = progress_rx.try_recv() {
+            last_stage = Some(progress.stage);
+        }
+        assert_eq!(last_stage, Some(GenerationStage::TimedOut));
+
+        let mut entries = tokio::fs::read_dir(temp_dir.path()).await.unwrap();
+        assert!(entries.next_entry().await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_temp_file_guard() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let dropped = temp_dir.path().join("dropped.png");
+        std::fs::write(&dropped, b"partial").unwrap();
+        drop(TempFileGuard::new(dropped.clone()));
+        assert!(!dropped.exists());
+
+        let kept = temp_dir.path().join("kept.png");
+        std::fs::write(&kept, b"complete").unwrap();
+        let path = TempFileGuard::new(kept.clone()).keep();
+        assert_eq!(path, kept);
+        assert!(kept.exists());
+    }
+}
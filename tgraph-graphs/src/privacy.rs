@@ -0,0 +1,242 @@
+//! Username censoring for graphs and messages
+//!
+//! A [`PrivacyPolicy`] combines a server-wide display mode with per-user
+//! opt-outs. Every name shown in a graph label or embedded in a Discord
+//! message should go through [`PrivacyPolicy::display_name`].
+
+use crate::TopUserDataPoint;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use tgraph_common::TGraphError;
+
+/// Name shown in place of hidden users
+pub const HIDDEN_USER: &str = "Hidden User";
+
+/// How a username is shown, from least to most private
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsernameDisplay {
+    /// Show the name as-is
+    #[default]
+    Visible,
+    /// Keep the first few characters, e.g. "Jo***"
+    Masked,
+    /// Replace with a stable pseudonym, e.g. "User 3F2A91C0"
+    Pseudonym,
+    /// Replace with "Hidden User"
+    Hidden,
+}
+
+impl UsernameDisplay {
+    pub fn name(&self) -> &'static str {
+        match self {
+            UsernameDisplay::Visible => "visible",
+            UsernameDisplay::Masked => "masked",
+            UsernameDisplay::Pseudonym => "pseudonym",
+            UsernameDisplay::Hidden => "hidden",
+        }
+    }
+}
+
+impl FromStr for UsernameDisplay {
+    type Err = TGraphError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "visible" => Ok(UsernameDisplay::Visible),
+            "masked" => Ok(UsernameDisplay::Masked),
+            "pseudonym" => Ok(UsernameDisplay::Pseudonym),
+            "hidden" => Ok(UsernameDisplay::Hidden),
+            other => Err(TGraphError::config(format!("Unknown username display mode: {}", other))),
+        }
+    }
+}
+
+/// Server-wide username display plus per-user opt-outs
+///
+/// The pseudonym salt is a secret: it is left out of `Debug` output and
+/// never serialized.
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PrivacyPolicy {
+    /// Display mode for users without an opt-out
+    pub display: UsernameDisplay,
+    /// Characters kept when masking
+    pub masked_chars: usize,
+    /// Mixed into pseudonyms so they cannot be matched across servers
+    #[serde(skip)]
+    pub pseudonym_salt: String,
+    /// Stricter display modes requested by users, keyed by lowercase Tautulli username
+    opt_outs: HashMap<String, UsernameDisplay>,
+}
+
+impl Default for PrivacyPolicy {
+    fn default() -> Self {
+        Self {
+            display: UsernameDisplay::Visible,
+            masked_chars: 2,
+            pseudonym_salt: String::new(),
+            opt_outs: HashMap::new(),
+        }
+    }
+}
+
+impl fmt::Debug for PrivacyPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let salt = if self.pseudonym_salt.is_empty() { "" } else { "********" };
+        f.debug_struct("PrivacyPolicy")
+            .field("display", &self.display)
+            .field("masked_chars", &self.masked_chars)
+            .field("pseudonym_salt", &salt)
+            .field("opt_outs", &self.opt_outs)
+            .finish()
+    }
+}
+
+impl PrivacyPolicy {
+    pub fn new(display: UsernameDisplay) -> Self {
+        Self {
+            display,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_masked_chars(mut self, masked_chars: usize) -> Self {
+        self.masked_chars = masked_chars.max(1);
+        self
+    }
+
+    pub fn with_pseudonym_salt(mut self, salt: impl Into<String>) -> Self {
+        self.pseudonym_salt = salt.into();
+        self
+    }
+
+    /// Record a user's opt-out
+    ///
+    /// Opt-outs can only make a user more private than the server default.
+    pub fn opt_out(&mut self, username: &str, display: UsernameDisplay) {
+        let entry = self.opt_outs.entry(username.to_lowercase()).or_default();
+        *entry = (*entry).max(display);
+    }
+
+    /// Display mode that applies to a user
+    pub fn display_for(&self, username: &str) -> UsernameDisplay {
+        let opt_out = self.opt_outs.get(&username.to_lowercase()).copied().unwrap_or_default();
+        self.display.max(opt_out)
+    }
+
+    /// Name to show for a user, preferring the friendly name
+    ///
+    /// The policy is looked up by `username` so renaming a friendly name
+    /// cannot escape an opt-out, and pseudonyms stay stable across renames.
+    pub fn display_name(&self, username: &str, friendly_name: Option<&str>) -> String {
+        let shown = friendly_name.filter(|name| !name.trim().is_empty()).unwrap_or(username);
+        match self.display_for(username) {
+            UsernameDisplay::Visible => shown.to_string(),
+            UsernameDisplay::Masked => mask(shown, self.masked_chars),
+            UsernameDisplay::Pseudonym => format!("User {:08X}", self.pseudonym_hash(username)),
+            UsernameDisplay::Hidden => HIDDEN_USER.to_string(),
+        }
+    }
+
+    /// Censor top users in place, rewriting names and labels
+    pub fn apply_to_users(&self, users: &mut [TopUserDataPoint]) {
+        for user in users {
+            if self.display_for(&user.username) == UsernameDisplay::Visible {
+                continue;
+            }
+            let name = self.display_name(&user.username, user.friendly_name.as_deref());
+            user.label = Some(format!("{} - {} plays", name, user.count));
+            user.username = name;
+            user.friendly_name = None;
+        }
+    }
+
+    /// 64-bit FNV-1a folded to 32 bits; unlike `DefaultHasher` it is stable across releases
+    fn pseudonym_hash(&self, username: &str) -> u32 {
+        let hash = self
+            .pseudonym_salt
+            .bytes()
+            .chain(std::iter::once(0))
+            .chain(username.to_lowercase().bytes())
+            .fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+        (hash ^ (hash >> 32)) as u32
+    }
+}
+
+/// Keep the first `keep` characters, always hiding at least one
+///
+/// Names of a single character have nothing left to hide, so only the mask
+/// is shown.
+fn mask(name: &str, keep: usize) -> String {
+    let length = name.chars().count();
+    if length <= 1 {
+        return "***".to_string();
+    }
+    let keep = keep.clamp(1, length - 1);
+    format!("{}***", name.chars().take(keep).collect::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(username: &str, friendly_name: Option<&str>, count: u32) -> TopUserDataPoint {
+        TopUserDataPoint {
+            user_id: 1,
+            username: username.to_string(),
+            friendly_name: friendly_name.map(str::to_string),
+            count,
+            label: Some(format!("{} - {} plays", friendly_name.unwrap_or(username), count)),
+        }
+    }
+
+    #[test]
+    fn test_display_modes() {
+        let visible = PrivacyPolicy::default();
+        assert_eq!(visible.display_name("john", Some("John")), "John");
+
+        let masked = PrivacyPolicy::new(UsernameDisplay::Masked);
+        assert_eq!(masked.display_name("john", Some("John")), "Jo***");
+        assert_eq!(masked.display_name("al", None), "a***");
+        assert_eq!(masked.display_name("j", None), "***");
+        assert_eq!(masked.display_name("x", Some("Ö")), "***");
+
+        let pseudonym = PrivacyPolicy::new(UsernameDisplay::Pseudonym).with_pseudonym_salt("server");
+        let first = pseudonym.display_name("john", Some("John"));
+        assert!(first.starts_with("User "));
+        assert_eq!(first, pseudonym.display_name("JOHN", Some("Johnny")));
+        assert_ne!(first, pseudonym.display_name("jane", None));
+        assert_eq!(first.len(), "User ".len() + 8);
+        assert!(!format!("{:?}", pseudonym).contains("server"));
+        assert!(!serde_json::to_string(&pseudonym).unwrap().contains("server"));
+
+        assert_eq!(PrivacyPolicy::new(UsernameDisplay::Hidden).display_name("john", None), HIDDEN_USER);
+        assert_eq!("Pseudonym".parse::<UsernameDisplay>().unwrap(), UsernameDisplay::Pseudonym);
+        assert!("blurred".parse::<UsernameDisplay>().is_err());
+    }
+
+    #[test]
+    fn test_opt_outs_are_only_stricter() {
+        let mut policy = PrivacyPolicy::new(UsernameDisplay::Masked);
+        policy.opt_out("Jane", UsernameDisplay::Hidden);
+        policy.opt_out("john", UsernameDisplay::Visible);
+
+        assert_eq!(policy.display_for("jane"), UsernameDisplay::Hidden);
+        assert_eq!(policy.display_for("john"), UsernameDisplay::Masked);
+    }
+
+    #[test]
+    fn test_apply_to_users() {
+        let mut policy = PrivacyPolicy::default();
+        policy.opt_out("jane", UsernameDisplay::Hidden);
+
+        let mut users = vec![user("john", Some("John"), 5), user("jane", Some("Jane Doe"), 3)];
+        policy.apply_to_users(&mut users);
+
+        assert_eq!(users[0].label.as_deref(), Some("John - 5 plays"));
+        assert_eq!(users[1].username, HIDDEN_USER);
+        assert_eq!(users[1].friendly_name, None);
+        assert_eq!(users[1].label.as_deref(), Some("Hidden User - 3 plays"));
+    }
+}
@@ -0,0 +1,313 @@
+//! Natural-language date ranges for command arguments
+//!
+//! Understands phrases such as "last 14 days", "since 2025-06-01",
+//! "2025-01-01..2025-03-31", "Q3 2025", "march", "last 6 months" and "ytd".
+//! Every phrase resolves to a [`TimeRangePreset::Custom`] against a
+//! [`TimeRangeContext`], so "today" and week starts follow the configuration.
+
+use crate::{DateRange, TimeRangeContext, TimeRangePreset};
+use chrono::{Datelike, Duration, NaiveDate};
+use tgraph_common::{Result, TGraphError};
+
+/// Longest rolling range accepted, in days
+const MAX_RANGE_DAYS: u32 = 36_500;
+
+/// Shown when nothing matches
+const EXAMPLES: &str = "\"last 14 days\", \"since 2025-06-01\", \"2025-01-01..2025-03-31\", \"Q3 2025\", \"march\" or \"ytd\"";
+
+/// Phrases offered by autocomplete before the user types anything specific
+const SUGGESTIONS: &[&str] = &[
+    "last 7 days",
+    "last 14 days",
+    "last 30 days",
+    "last 90 days",
+    "last 6 months",
+    "this week",
+    "last week",
+    "this month",
+    "last month",
+    "this quarter",
+    "last quarter",
+    "ytd",
+    "last year",
+    "all time",
+];
+
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june",
+    "july", "august", "september", "october", "november", "december",
+];
+
+/// Parse a natural-language date range
+///
+/// Ranges that run past today end today. Whether the result is sensible,
+/// e.g. not in the future, is left to
+/// [`TimeRangeSelector::validate_range`](crate::TimeRangeSelector::validate_range).
+pub fn parse_date_range(input: &str, ctx: &TimeRangeContext) -> Result<TimeRangePreset> {
+    let text = input.trim().to_lowercase();
+    if text.is_empty() {
+        return Err(invalid(format!("Enter a date range such as {}", EXAMPLES)));
+    }
+
+    let range = parse_phrase(&text, ctx)?
+        .ok_or_else(|| invalid(format!("Couldn't understand \"{}\". Try {}", input.trim(), EXAMPLES)))?;
+    let end = if range.start <= ctx.today { range.end.min(ctx.today) } else { range.end };
+    Ok(TimeRangePreset::Custom(DateRange::new(range.start, end)))
+}
+
+/// Autocomplete choices for a partially typed range, as (label, value)
+///
+/// A parseable input comes first with its resolved dates in the label.
+pub fn suggest_date_ranges(partial: &str, ctx: &TimeRangeContext) -> Vec<(String, String)> {
+    let partial = partial.trim().to_lowercase();
+    let mut choices = Vec::new();
+
+    if !partial.is_empty() {
+        if let Ok(TimeRangePreset::Custom(range)) = parse_date_range(&partial, ctx) {
+            choices.push((describe(&partial, &range), partial.clone()));
+        }
+    }
+
+    for phrase in SUGGESTIONS {
+        if *phrase != partial && phrase.contains(partial.as_str()) {
+            if let Ok(TimeRangePreset::Custom(range)) = parse_date_range(phrase, ctx) {
+                choices.push((describe(phrase, &range), phrase.to_string()));
+            }
+        }
+    }
+
+    choices.truncate(25);
+    choices
+}
+
+fn describe(phrase: &str, range: &DateRange) -> String {
+    if range.start == range.end {
+        format!("{} ({})", phrase, range.start)
+    } else {
+        format!("{} ({} to {})", phrase, range.start, range.end)
+    }
+}
+
+fn invalid(message: String) -> TGraphError {
+    TGraphError::validation_field(message, "period")
+}
+
+fn parse_phrase(text: &str, ctx: &TimeRangeContext) -> Result<Option<DateRange>> {
+    let keyword = match text {
+        "today" => Some(DateRange::new(ctx.today, ctx.today)),
+        "yesterday" => {
+            let day = ctx.today - Duration::days(1);
+            Some(DateRange::new(day, day))
+        }
+        "wtd" | "week to date" | "this week" => Some(ctx.this_week()),
+        "mtd" | "month to date" | "this month" => Some(ctx.this_month()),
+        "qtd" | "quarter to date" | "this quarter" => Some(ctx.this_quarter()),
+        "ytd" | "year to date" | "this year" => Some(ctx.this_year()),
+        "last week" | "previous week" => Some(ctx.previous_week()),
+        "last month" | "previous month" => Some(ctx.previous_month()),
+        "last quarter" | "previous quarter" => Some(ctx.previous_quarter()),
+        "last year" | "previous year" => Some(ctx.previous_year()),
+        "all" | "all time" | "everything" => Some(ctx.all_time()),
+        _ => None,
+    };
+    if keyword.is_some() {
+        return Ok(keyword);
+    }
+
+    if let Some((start, end)) = text.split_once("..").or_else(|| text.split_once(" to ")) {
+        let start = parse_date(start)?;
+        let end = parse_date(end)?;
+        if start > end {
+            return Err(invalid(format!("The range starts ({}) after it ends ({})", start, end)));
+        }
+        return Ok(Some(DateRange::new(start, end)));
+    }
+
+    if let Some(date) = text.strip_prefix("since ") {
+        return Ok(Some(DateRange::new(parse_date(date)?, ctx.today)));
+    }
+
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let rolling = match tokens.as_slice() {
+        ["last" | "past", count, unit] | [count, unit] => parse_rolling(count, unit, ctx)?,
+        _ => None,
+    };
+    if rolling.is_some() {
+        return Ok(rolling);
+    }
+
+    match tokens.as_slice() {
+        [single] => parse_single(single, None, ctx),
+        [first, year] if is_year(year) => parse_single(first, Some(parse_year(year)?), ctx),
+        [year, second] if is_year(year) => parse_single(second, Some(parse_year(year)?), ctx),
+        _ => Ok(None),
+    }
+}
+
+/// "14 days", "6 months", "2 weeks"
+fn parse_rolling(count: &str, unit: &str, ctx: &TimeRangeContext) -> Result<Option<DateRange>> {
+    let Ok(count) = count.parse::<u32>() else {
+        return Ok(None);
+    };
+    let unit = unit.trim_end_matches('s');
+    if !matches!(unit, "day" | "week" | "month" | "year") {
+        return Ok(None);
+    }
+    if count == 0 {
+        return Err(invalid(format!("The number of {}s must be at least 1", unit)));
+    }
+
+    let too_long = match unit {
+        "day" => count > MAX_RANGE_DAYS,
+        "week" => count > MAX_RANGE_DAYS / 7,
+        "month" => count > MAX_RANGE_DAYS / 31,
+        _ => count > MAX_RANGE_DAYS / 366,
+    };
+    if too_long {
+        return Err(invalid("That range is too long".to_string()));
+    }
+
+    Ok(Some(match unit {
+        "day" => ctx.last_days(count),
+        "week" => ctx.last_days(count * 7),
+        "month" => ctx.last_months(count),
+        _ => ctx.last_months(count * 12),
+    }))
+}
+
+/// A date, year, quarter or month, optionally with a year
+fn parse_single(token: &str, year: Option<i32>, ctx: &TimeRangeContext) -> Result<Option<DateRange>> {
+    if year.is_none() {
+        if is_year(token) {
+            let year = parse_year(token)?;
+            return Ok(Some(DateRange::new(first_day(year, 1)?, last_day(year, 12)?)));
+        }
+        if token.starts_with(|c: char| c.is_ascii_digit()) && token.contains('-') {
+            let date = parse_date(token)?;
+            return Ok(Some(DateRange::new(date, date)));
+        }
+    }
+
+    let quarter = token.strip_prefix('q').filter(|q| !q.is_empty() && q.chars().all(|c| c.is_ascii_digit()));
+    if let Some(quarter) = quarter {
+        let quarter = match quarter.parse::<u32>() {
+            Ok(quarter @ 1..=4) => quarter,
+            _ => return Err(invalid(format!("Unknown quarter \"{}\", use Q1 to Q4", token.to_uppercase()))),
+        };
+        let first_month = (quarter - 1) * 3 + 1;
+        let year = year.unwrap_or_else(|| latest_year_for(first_month, ctx));
+        return Ok(Some(DateRange::new(first_day(year, first_month)?, last_day(year, first_month + 2)?)));
+    }
+
+    if let Some(month) = parse_month(token) {
+        let year = year.unwrap_or_else(|| latest_year_for(month, ctx));
+        return Ok(Some(DateRange::new(first_day(year, month)?, last_day(year, month)?)));
+    }
+
+    Ok(None)
+}
+
+/// The most recent year in which `month` has already begun
+fn latest_year_for(month: u32, ctx: &TimeRangeContext) -> i32 {
+    if month > ctx.today.month() {
+        ctx.today.year() - 1
+    } else {
+        ctx.today.year()
+    }
+}
+
+/// Full month names and abbreviations of at least three letters
+fn parse_month(token: &str) -> Option<u32> {
+    if token.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| month.starts_with(token))
+        .map(|index| index as u32 + 1)
+}
+
+fn is_year(token: &str) -> bool {
+    token.len() == 4 && token.chars().all(|c| c.is_ascii_digit())
+}
+
+fn parse_year(token: &str) -> Result<i32> {
+    token
+        .parse()
+        .map_err(|_| invalid(format!("Invalid year \"{}\"", token)))
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate> {
+    let text = text.trim();
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .map_err(|_| invalid(format!("Invalid date \"{}\", expected YYYY-MM-DD", text)))
+}
+
+fn first_day(year: i32, month: u32) -> Result<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| invalid(format!("Invalid year {}", year)))
+}
+
+fn last_day(year: i32, month: u32) -> Result<NaiveDate> {
+    let next = if month == 12 { first_day(year + 1, 1)? } else { first_day(year, month + 1)? };
+    Ok(next - Duration::days(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn parse(input: &str) -> Result<DateRange> {
+        // Friday 2025-08-15
+        let ctx = TimeRangeContext::default().with_today(date(2025, 8, 15));
+        match parse_date_range(input, &ctx)? {
+            TimeRangePreset::Custom(range) => Ok(range),
+            other => panic!("expected a custom range, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_date_ranges() {
+        let range = |start, end| DateRange::new(start, end);
+        assert_eq!(parse("last 14 days").unwrap(), range(date(2025, 8, 2), date(2025, 8, 15)));
+        assert_eq!(parse("since 2025-06-01").unwrap(), range(date(2025, 6, 1), date(2025, 8, 15)));
+        assert_eq!(parse("2025-01-01..2025-03-31").unwrap(), range(date(2025, 1, 1), date(2025, 3, 31)));
+        assert_eq!(parse("2025-01-01 to 2025-01-07").unwrap(), range(date(2025, 1, 1), date(2025, 1, 7)));
+        assert_eq!(parse("Q2 2025").unwrap(), range(date(2025, 4, 1), date(2025, 6, 30)));
+        // The current quarter ends today
+        assert_eq!(parse("q3").unwrap(), range(date(2025, 7, 1), date(2025, 8, 15)));
+        // A month that hasn't begun this year means last year's
+        assert_eq!(parse("March").unwrap(), range(date(2025, 3, 1), date(2025, 3, 31)));
+        assert_eq!(parse("dec").unwrap(), range(date(2024, 12, 1), date(2024, 12, 31)));
+        assert_eq!(parse("feb 2024").unwrap(), range(date(2024, 2, 1), date(2024, 2, 29)));
+        assert_eq!(parse("last 6 months").unwrap(), range(date(2025, 2, 15), date(2025, 8, 15)));
+        assert_eq!(parse("ytd").unwrap(), range(date(2025, 1, 1), date(2025, 8, 15)));
+        assert_eq!(parse("2024").unwrap(), range(date(2024, 1, 1), date(2024, 12, 31)));
+        assert_eq!(parse(" last month ").unwrap(), range(date(2025, 7, 1), date(2025, 7, 31)));
+    }
+
+    #[test]
+    fn test_parse_errors_are_clear() {
+        let message = |input| parse(input).unwrap_err().to_string();
+        assert!(message("fortnight-ish").contains("Couldn't understand \"fortnight-ish\""));
+        assert!(message("since 2025-02-30").contains("Invalid date \"2025-02-30\", expected YYYY-MM-DD"));
+        assert!(message("Q5 2025").contains("use Q1 to Q4"));
+        assert!(message("last 0 days").contains("at least 1"));
+        assert!(message("2025-03-01..2025-01-01").contains("starts (2025-03-01) after it ends"));
+        assert!(message("last 99999 days").contains("too long"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let ctx = TimeRangeContext::default().with_today(date(2025, 8, 15));
+        let choices = suggest_date_ranges("Q2", &ctx);
+        assert_eq!(choices[0], ("q2 (2025-04-01 to 2025-06-30)".to_string(), "q2".to_string()));
+
+        let choices = suggest_date_ranges("last 1", &ctx);
+        assert_eq!(choices[0].0, "last 14 days (2025-08-02 to 2025-08-15)");
+        assert!(suggest_date_ranges("", &ctx).len() <= 25);
+    }
+}
@@ -0,0 +1,93 @@
+//! Server graphs posted on a schedule
+
+use crate::{
+    render_png, server_title, AggregationConfig, DailyPlayCountAggregator, DailyPlayCountGraph, DataAggregator,
+    DayOfWeekAggregator, DayOfWeekGraph, GraphConfig, HourlyDistributionAggregator, HourlyDistributionGraph,
+    RenderedGraph,
+};
+use tgraph_common::{HistoryEntry, Result, ServerSelection};
+use tracing::debug;
+
+/// Graph types rendered for scheduled posts, as named in the configuration
+pub const SCHEDULED_GRAPH_TYPES: &[&str] = &["daily_play_count", "day_of_week", "hourly_distribution"];
+
+/// Render the scheduled graphs from recent history
+///
+/// Only graph types for which `enabled` returns true are rendered, and
+/// graphs without data are skipped, so the result may be empty. `style` can
+/// adjust each graph's configuration before it is drawn.
+pub async fn render_scheduled_graphs<E, S>(
+    entries: Vec<HistoryEntry>,
+    selection: Option<&ServerSelection>,
+    aggregation: &AggregationConfig,
+    enabled: E,
+    style: S,
+) -> Result<Vec<RenderedGraph>>
+where
+    E: Fn(&str) -> bool,
+    S: Fn(&mut GraphConfig),
+{
+    let mut graphs = Vec::new();
+
+    if enabled("daily_play_count") {
+        let daily = DailyPlayCountAggregator::new().aggregate(entries.clone(), aggregation)?;
+        if !daily.is_empty() {
+            let (mut graph, mut config) = DailyPlayCountGraph::with_config(
+                &server_title("Daily Plays", selection),
+                Some("Date"),
+                Some("Plays"),
+            );
+            style(&mut config);
+            graph.set_data(daily);
+            graphs.push(render_png(&graph, &config, "daily_plays.png").await?);
+        }
+    }
+
+    if enabled("day_of_week") {
+        let weekdays = DayOfWeekAggregator::new().aggregate(entries.clone(), aggregation)?;
+        if !weekdays.is_empty() {
+            let (mut graph, mut config) = DayOfWeekGraph::with_config(
+                &server_title("Plays by Day of Week", selection),
+                Some("Day"),
+                Some("Plays"),
+            );
+            style(&mut config);
+            graph.set_data(weekdays);
+            graphs.push(render_png(&graph, &config, "day_of_week.png").await?);
+        }
+    }
+
+    if enabled("hourly_distribution") {
+        let hourly = HourlyDistributionAggregator::new().aggregate(entries, aggregation)?;
+        if !hourly.is_empty() {
+            let (mut graph, mut config) = HourlyDistributionGraph::with_config(
+                &server_title(&format!("Plays by Hour ({})", aggregation.timezone), selection),
+                Some("Hour of Day"),
+                Some("Plays"),
+            );
+            style(&mut config);
+            graph.set_data(hourly);
+            graphs.push(render_png(&graph, &config, "hourly_plays.png").await?);
+        }
+    }
+
+    debug!("Rendered {} scheduled graphs", graphs.len());
+    Ok(graphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_entry;
+
+    #[tokio::test]
+    async fn test_disabled_and_empty_graphs_are_skipped() {
+        let aggregation = AggregationConfig::default();
+        let graphs = render_scheduled_graphs(Vec::new(), None, &aggregation, |_| true, |_| {}).await.unwrap();
+        assert!(graphs.is_empty());
+
+        let entries = vec![history_entry().build()];
+        let graphs = render_scheduled_graphs(entries, None, &aggregation, |_| false, |_| {}).await.unwrap();
+        assert!(graphs.is_empty());
+    }
+}
@@ -0,0 +1,488 @@
+//! Stream quality aggregation: direct play, direct stream and transcode breakdowns
+
+use crate::{
+    AggregationConfig, AggregationProgress, AggregationStage, DataAggregator, DataPoint, DataSet,
+};
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+use tgraph_common::{GraphSeriesResponse, HistoryEntry, Result, Session, TautulliClient};
+use tokio::sync::mpsc;
+use tracing::{debug, info, instrument};
+
+/// How a stream was delivered to the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum StreamDecision {
+    DirectPlay,
+    DirectStream,
+    Transcode,
+}
+
+impl StreamDecision {
+    /// All decisions, in stacking order
+    pub const ALL: [StreamDecision; 3] = [
+        StreamDecision::DirectPlay,
+        StreamDecision::DirectStream,
+        StreamDecision::Transcode,
+    ];
+
+    /// Parse a Tautulli decision or series name
+    ///
+    /// Accepts `direct play`, `copy`/`direct stream` and `transcode`, ignoring case.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "direct play" | "directplay" => Some(StreamDecision::DirectPlay),
+            "copy" | "direct stream" | "directstream" => Some(StreamDecision::DirectStream),
+            "transcode" => Some(StreamDecision::Transcode),
+            _ => None,
+        }
+    }
+
+    /// Decision recorded for a history entry
+    pub fn from_history(entry: &HistoryEntry) -> Option<Self> {
+        entry.transcode_decision.as_deref().and_then(Self::parse)
+    }
+
+    /// Decision of an active session
+    ///
+    /// Uses the video decision, falling back to the audio decision for music.
+    pub fn from_session(session: &Session) -> Option<Self> {
+        session
+            .video_decision
+            .as_deref()
+            .and_then(Self::parse)
+            .or_else(|| session.audio_decision.as_deref().and_then(Self::parse))
+    }
+
+    /// Human-readable name, matching Tautulli's series names
+    pub fn name(&self) -> &'static str {
+        match self {
+            StreamDecision::DirectPlay => "Direct Play",
+            StreamDecision::DirectStream => "Direct Stream",
+            StreamDecision::Transcode => "Transcode",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            StreamDecision::DirectPlay => 0,
+            StreamDecision::DirectStream => 1,
+            StreamDecision::Transcode => 2,
+        }
+    }
+}
+
+/// Length of the periods transcode decisions are grouped into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum DecisionInterval {
+    Daily,
+    Monthly,
+}
+
+impl DecisionInterval {
+    /// Start of the period containing a timestamp
+    fn period_start(&self, timestamp: i64) -> Option<NaiveDate> {
+        let date = chrono::DateTime::from_timestamp(timestamp, 0)?.naive_utc().date();
+        match self {
+            DecisionInterval::Daily => Some(date),
+            DecisionInterval::Monthly => date.with_day(1),
+        }
+    }
+
+    fn format(&self, date: NaiveDate) -> String {
+        match self {
+            DecisionInterval::Daily => date.format("%Y-%m-%d").to_string(),
+            DecisionInterval::Monthly => date.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// Direct play, direct stream and transcode counts for one day or month
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TranscodeDecisionDataPoint {
+    /// First day of the period
+    pub period: NaiveDate,
+    pub direct_play: u32,
+    pub direct_stream: u32,
+    pub transcode: u32,
+    pub label: Option<String>,
+}
+
+impl TranscodeDecisionDataPoint {
+    fn from_counts(period: NaiveDate, counts: [u32; 3], interval: DecisionInterval) -> Self {
+        let [direct_play, direct_stream, transcode] = counts;
+        let total = direct_play + direct_stream + transcode;
+        Self {
+            period,
+            direct_play,
+            direct_stream,
+            transcode,
+            label: Some(format!("{} - {} of {} plays transcoded", interval.format(period), transcode, total)),
+        }
+    }
+
+    /// Plays with a known decision
+    pub fn total(&self) -> u32 {
+        self.direct_play + self.direct_stream + self.transcode
+    }
+
+    /// Plays with the given decision
+    pub fn count(&self, decision: StreamDecision) -> u32 {
+        match decision {
+            StreamDecision::DirectPlay => self.direct_play,
+            StreamDecision::DirectStream => self.direct_stream,
+            StreamDecision::Transcode => self.transcode,
+        }
+    }
+
+    /// Share of plays that were transcoded, 0-100
+    pub fn transcode_percentage(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.transcode as f64 / total as f64 * 100.0,
+        }
+    }
+}
+
+/// Aggregator for transcode decisions over time
+#[derive(Debug)]
+pub struct TranscodeDecisionAggregator {
+    pub interval: DecisionInterval,
+}
+
+impl TranscodeDecisionAggregator {
+    pub fn new() -> Self {
+        Self {
+            interval: DecisionInterval::Daily,
+        }
+    }
+
+    pub fn with_interval(interval: DecisionInterval) -> Self {
+        Self { interval }
+    }
+
+    fn add(&self, counts: &mut HashMap<NaiveDate, [u32; 3]>, entry: &HistoryEntry) {
+        let Some(decision) = StreamDecision::from_history(entry) else {
+            return;
+        };
+        if let Some(period) = entry.date.and_then(|ts| self.interval.period_start(ts)) {
+            counts.entry(period).or_insert([0; 3])[decision.index()] += 1;
+        }
+    }
+
+    fn finish(&self, counts: HashMap<NaiveDate, [u32; 3]>) -> Vec<TranscodeDecisionDataPoint> {
+        let mut result: Vec<TranscodeDecisionDataPoint> = counts
+            .into_iter()
+            .map(|(period, counts)| TranscodeDecisionDataPoint::from_counts(period, counts, self.interval))
+            .collect();
+        result.sort_by_key(|point| point.period);
+        result
+    }
+}
+
+impl DataAggregator<TranscodeDecisionDataPoint> for TranscodeDecisionAggregator {
+    #[instrument(skip(self, entries))]
+    fn aggregate(
+        &self,
+        entries: Vec<HistoryEntry>,
+        _config: &AggregationConfig,
+    ) -> Result<Vec<TranscodeDecisionDataPoint>> {
+        let mut counts = HashMap::new();
+        for entry in &entries {
+            self.add(&mut counts, entry);
+        }
+
+        let result = self.finish(counts);
+        debug!("Aggregated {} transcode decision data points", result.len());
+        Ok(result)
+    }
+
+    async fn aggregate_chunked(
+        &self,
+        entries: Vec<HistoryEntry>,
+        config: &AggregationConfig,
+        progress_tx: Option<mpsc::UnboundedSender<AggregationProgress>>,
+    ) -> Result<Vec<TranscodeDecisionDataPoint>> {
+        let total = entries.len();
+        let mut counts = HashMap::new();
+        let mut processed = 0;
+
+        for chunk in entries.chunks(config.chunk_size) {
+            config.cancellation.check("aggregation")?;
+
+            self.send_progress(
+                &progress_tx,
+                AggregationStage::Processing,
+                processed,
+                total,
+                format!("Processing transcode chunk {}/{}", processed / config.chunk_size + 1, (total + config.chunk_size - 1) / config.chunk_size),
+            );
+
+            for entry in chunk {
+                self.add(&mut counts, entry);
+                processed += 1;
+            }
+
+            tokio::task::yield_now().await;
+        }
+
+        self.send_progress(&progress_tx, AggregationStage::Finalizing, processed, total, "Finalizing transcode results".to_string());
+        let result = self.finish(counts);
+        self.send_progress(&progress_tx, AggregationStage::Complete, processed, total, "Transcode decision aggregation complete".to_string());
+
+        info!("Chunked aggregation completed: {} transcode decision data points", result.len());
+        Ok(result)
+    }
+}
+
+impl Default for TranscodeDecisionAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert transcode decisions into stacked datasets, one per decision
+///
+/// Values are cumulative in [`StreamDecision::ALL`] order, so the last dataset
+/// is the total and each area can be drawn on top of the previous one.
+pub fn transcode_decision_datasets(points: &[TranscodeDecisionDataPoint], interval: DecisionInterval) -> Vec<DataSet> {
+    let mut stacked = vec![0u32; points.len()];
+
+    StreamDecision::ALL
+        .iter()
+        .map(|decision| {
+            let data = points
+                .iter()
+                .zip(stacked.iter_mut())
+                .enumerate()
+                .map(|(index, (point, stack))| {
+                    *stack += point.count(*decision);
+                    DataPoint {
+                        x: index as f64,
+                        y: *stack as f64,
+                        label: Some(interval.format(point.period)),
+                    }
+                })
+                .collect();
+
+            DataSet {
+                name: decision.name().to_string(),
+                data,
+                color: None,
+            }
+        })
+        .collect()
+}
+
+/// Direct play, direct stream and transcode counts for one platform or resolution
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StreamTypeDataPoint {
+    /// Platform or resolution name
+    pub category: String,
+    pub direct_play: u32,
+    pub direct_stream: u32,
+    pub transcode: u32,
+}
+
+impl StreamTypeDataPoint {
+    /// Plays in this category
+    pub fn total(&self) -> u32 {
+        self.direct_play + self.direct_stream + self.transcode
+    }
+
+    /// Share of plays that were transcoded, 0-100
+    pub fn transcode_percentage(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.transcode as f64 / total as f64 * 100.0,
+        }
+    }
+}
+
+/// Stream type breakdowns by platform and resolution
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct StreamQualityReport {
+    pub by_platform: Vec<StreamTypeDataPoint>,
+    pub by_source_resolution: Vec<StreamTypeDataPoint>,
+    pub by_stream_resolution: Vec<StreamTypeDataPoint>,
+}
+
+impl StreamQualityReport {
+    /// Platforms with at least one transcode, most transcodes first
+    pub fn transcoding_platforms(&self) -> Vec<&StreamTypeDataPoint> {
+        let mut platforms: Vec<&StreamTypeDataPoint> =
+            self.by_platform.iter().filter(|point| point.transcode > 0).collect();
+        platforms.sort_by(|a, b| {
+            b.transcode
+                .cmp(&a.transcode)
+                .then_with(|| a.category.cmp(&b.category))
+        });
+        platforms
+    }
+}
+
+/// Aggregator for stream types by platform and resolution
+///
+/// Unlike the history aggregators this uses Tautulli's own pre-aggregated
+/// graph endpoints, which cover the top 10 platforms and all resolutions.
+#[derive(Debug)]
+pub struct StreamResolutionAggregator {
+    /// Number of days to look back
+    pub time_range_days: u32,
+}
+
+impl StreamResolutionAggregator {
+    pub fn new() -> Self {
+        Self { time_range_days: 30 }
+    }
+
+    pub fn with_time_range(days: u32) -> Self {
+        Self { time_range_days: days }
+    }
+
+    /// Fetch platform and resolution breakdowns from Tautulli
+    #[instrument(skip(self, client))]
+    pub async fn fetch(&self, client: &TautulliClient) -> Result<StreamQualityReport> {
+        let (platforms, source, stream) = tokio::try_join!(
+            client.get_stream_type_by_top_10_platforms(self.time_range_days),
+            client.get_plays_by_source_resolution(self.time_range_days),
+            client.get_plays_by_stream_resolution(self.time_range_days),
+        )?;
+
+        Ok(StreamQualityReport {
+            by_platform: Self::from_graph_series(&platforms),
+            by_source_resolution: Self::from_graph_series(&source),
+            by_stream_resolution: Self::from_graph_series(&stream),
+        })
+    }
+
+    /// Convert a Tautulli graph response into one data point per category
+    ///
+    /// Series that are not a stream decision are ignored.
+    pub fn from_graph_series(response: &GraphSeriesResponse) -> Vec<StreamTypeDataPoint> {
+        let mut result: Vec<StreamTypeDataPoint> = response
+            .categories
+            .iter()
+            .map(|category| StreamTypeDataPoint {
+                category: category.clone(),
+                direct_play: 0,
+                direct_stream: 0,
+                transcode: 0,
+            })
+            .collect();
+
+        for series in &response.series {
+            let Some(decision) = StreamDecision::parse(&series.name) else {
+                debug!("Ignoring graph series {}", series.name);
+                continue;
+            };
+            for (point, value) in result.iter_mut().zip(&series.data) {
+                let count = (*value).max(0) as u32;
+                match decision {
+                    StreamDecision::DirectPlay => point.direct_play = count,
+                    StreamDecision::DirectStream => point.direct_stream = count,
+                    StreamDecision::Transcode => point.transcode = count,
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl Default for StreamResolutionAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::history_entry;
+    use tgraph_common::GraphSeries;
+
+    fn entry(date: i64, decision: Option<&str>) -> HistoryEntry {
+        history_entry().date(date).transcode_decision(decision).build()
+    }
+
+    #[test]
+    fn test_parse_stream_decision() {
+        assert_eq!(StreamDecision::parse("Direct Play"), Some(StreamDecision::DirectPlay));
+        assert_eq!(StreamDecision::parse("copy"), Some(StreamDecision::DirectStream));
+        assert_eq!(StreamDecision::parse("TRANSCODE"), Some(StreamDecision::Transcode));
+        assert_eq!(StreamDecision::parse("burn"), None);
+    }
+
+    #[test]
+    fn test_transcode_decision_aggregation() {
+        // 2022-01-01, 2022-01-02 and 2022-02-01
+        let entries = vec![
+            entry(1641067200, Some("direct play")),
+            entry(1641067300, Some("transcode")),
+            entry(1641153600, Some("copy")),
+            entry(1643716800, Some("transcode")),
+            entry(1643716900, None),
+        ];
+        let config = AggregationConfig::default();
+
+        let daily = TranscodeDecisionAggregator::new().aggregate(entries.clone(), &config).unwrap();
+        assert_eq!(daily.len(), 3);
+        assert_eq!((daily[0].direct_play, daily[0].transcode), (1, 1));
+        assert_eq!(daily[0].transcode_percentage(), 50.0);
+
+        let monthly = TranscodeDecisionAggregator::with_interval(DecisionInterval::Monthly)
+            .aggregate(entries, &config)
+            .unwrap();
+        assert_eq!(monthly.len(), 2);
+        assert_eq!(monthly[0].period, NaiveDate::from_ymd_opt(2022, 1, 1).unwrap());
+        assert_eq!(monthly[0].total(), 3);
+        assert_eq!(monthly[1].transcode, 1);
+
+        let datasets = transcode_decision_datasets(&monthly, DecisionInterval::Monthly);
+        assert_eq!(datasets.len(), 3);
+        assert_eq!(datasets[2].name, "Transcode");
+        assert_eq!(datasets[2].data[0].y, 3.0);
+        assert_eq!(datasets[0].data[0].label.as_deref(), Some("2022-01"));
+    }
+
+    #[tokio::test]
+    async fn test_transcode_decision_chunked_matches() {
+        let entries: Vec<HistoryEntry> = (0..25)
+            .map(|i| entry(1641067200 + i * 3600, Some(if i % 3 == 0 { "transcode" } else { "direct play" })))
+            .collect();
+        let config = AggregationConfig {
+            chunk_size: 10,
+            ..AggregationConfig::default()
+        };
+
+        let aggregator = TranscodeDecisionAggregator::new();
+        let direct = aggregator.aggregate(entries.clone(), &config).unwrap();
+        let chunked = aggregator.aggregate_streaming(entries, &config, None).await.unwrap();
+        assert_eq!(direct, chunked);
+    }
+
+    #[test]
+    fn test_stream_types_from_graph_series() {
+        let response = GraphSeriesResponse {
+            categories: vec!["Roku".to_string(), "Chrome".to_string()],
+            series: vec![
+                GraphSeries { name: "Direct Play".to_string(), data: vec![12, 3] },
+                GraphSeries { name: "Direct Stream".to_string(), data: vec![2, 0] },
+                GraphSeries { name: "Transcode".to_string(), data: vec![1, 7] },
+            ],
+        };
+
+        let points = StreamResolutionAggregator::from_graph_series(&response);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].total(), 15);
+        assert_eq!(points[1].transcode, 7);
+
+        let report = StreamQualityReport {
+            by_platform: points,
+            ..StreamQualityReport::default()
+        };
+        let platforms = report.transcoding_platforms();
+        assert_eq!(platforms[0].category, "Chrome");
+        assert_eq!(platforms.len(), 2);
+    }
+}
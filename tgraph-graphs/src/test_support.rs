@@ -0,0 +1,97 @@
+//! Shared helpers for unit tests
+
+use tgraph_common::HistoryEntry;
+
+/// A movie played by user 1 on 2022-01-01 20:00 UTC, changed with the setters
+pub(crate) fn history_entry() -> HistoryEntryBuilder {
+    HistoryEntryBuilder(HistoryEntry {
+        date: Some(1641067200),
+        user_id: Some(1),
+        username: Some("user".to_string()),
+        friendly_name: None,
+        media_type: Some("movie".to_string()),
+        rating_key: None,
+        parent_rating_key: None,
+        grandparent_rating_key: None,
+        title: None,
+        parent_title: None,
+        grandparent_title: None,
+        year: None,
+        watched_status: None,
+        percent_complete: None,
+        duration: None,
+        transcode_decision: None,
+        platform: None,
+        player: None,
+        ip_address: None,
+    })
+}
+
+/// A play at `timestamp`, otherwise as [`history_entry`]
+pub(crate) fn entry_at(timestamp: i64) -> HistoryEntry {
+    history_entry().date(timestamp).build()
+}
+
+/// Builder for [`HistoryEntry`] test data
+pub(crate) struct HistoryEntryBuilder(HistoryEntry);
+
+impl HistoryEntryBuilder {
+    pub fn date(mut self, timestamp: i64) -> Self {
+        self.0.date = Some(timestamp);
+        self
+    }
+
+    pub fn user(mut self, user_id: i32, username: &str) -> Self {
+        self.0.user_id = Some(user_id);
+        self.0.username = Some(username.to_string());
+        self
+    }
+
+    pub fn friendly_name(mut self, friendly_name: &str) -> Self {
+        self.0.friendly_name = Some(friendly_name.to_string());
+        self
+    }
+
+    pub fn media_type(mut self, media_type: &str) -> Self {
+        self.0.media_type = Some(media_type.to_string());
+        self
+    }
+
+    /// An episode of `show`, keyed by its name
+    pub fn show(mut self, show: &str) -> Self {
+        self.0.media_type = Some("episode".to_string());
+        self.0.grandparent_rating_key = Some(format!("key-{}", show));
+        self.0.grandparent_title = Some(show.to_string());
+        self
+    }
+
+    pub fn duration(mut self, seconds: i64) -> Self {
+        self.0.duration = Some(seconds);
+        self
+    }
+
+    pub fn progress(mut self, percent_complete: Option<i32>, watched_status: Option<f64>) -> Self {
+        self.0.percent_complete = percent_complete;
+        self.0.watched_status = watched_status;
+        self
+    }
+
+    pub fn transcode_decision(mut self, decision: Option<&str>) -> Self {
+        self.0.transcode_decision = decision.map(str::to_string);
+        self
+    }
+
+    pub fn platform(mut self, platform: &str) -> Self {
+        self.0.platform = Some(platform.to_string());
+        self
+    }
+
+    pub fn ip_address(mut self, ip_address: &str) -> Self {
+        self.0.ip_address = Some(ip_address.to_string());
+        self
+    }
+
+    pub fn build(self) -> HistoryEntry {
+        self.0
+    }
+}
@@ -1,6 +1,6 @@
 //! Time range selection utilities and preset management
 
-use crate::{DateRange, TimeRangePreset, FilterConfig, ComparisonConfig, ComparisonPeriod};
+use crate::{parse_date_range, DateRange, TimeRangePreset, FilterConfig, ComparisonConfig, ComparisonPeriod, TimeRangeContext};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use tgraph_common::Result;
@@ -16,6 +16,9 @@ pub struct TimeRangeSelector {
     pub available_presets: Vec<TimeRangePreset>,
     /// Quick access buttons for common ranges
     pub quick_ranges: Vec<QuickRange>,
+    /// Timezone, week start and history used to resolve presets
+    #[serde(skip)]
+    pub context: TimeRangeContext,
 }
 
 /// Quick range button configuration
@@ -62,8 +65,11 @@ impl TimeRangeSelector {
             TimeRangePreset::LastSevenDays,
             TimeRangePreset::LastThirtyDays,
             TimeRangePreset::LastNinetyDays,
+            TimeRangePreset::ThisWeek,
+            TimeRangePreset::LastWeek,
             TimeRangePreset::ThisMonth,
             TimeRangePreset::LastMonth,
+            TimeRangePreset::ThisQuarter,
             TimeRangePreset::LastQuarter,
             TimeRangePreset::ThisYear,
             TimeRangePreset::LastYear,
@@ -103,9 +109,16 @@ impl TimeRangeSelector {
             custom_range: None,
             available_presets,
             quick_ranges,
+            context: TimeRangeContext::default(),
         }
     }
 
+    /// Resolve presets in the configured timezone and week start
+    pub fn with_context(mut self, context: TimeRangeContext) -> Self {
+        self.context = context;
+        self
+    }
+
     /// Create selector with custom presets
     pub fn with_presets(presets: Vec<TimeRangePreset>) -> Self {
         let mut selector = Self::new();
@@ -122,7 +135,7 @@ impl TimeRangeSelector {
 
         self.selected_preset = Some(preset.clone());
         
-        let date_range = preset.to_date_range();
+        let date_range = preset.to_date_range_in(&self.context);
         let label = preset.display_name().to_string();
         
         // Validate the range
@@ -169,10 +182,18 @@ impl TimeRangeSelector {
         })
     }
 
+    /// Select a range typed in natural language, e.g. "last 14 days"
+    pub fn select_phrase(&mut self, input: &str) -> Result<TimeRangeSelection> {
+        match parse_date_range(input, &self.context)? {
+            TimeRangePreset::Custom(range) => self.set_custom_range(range.start, range.end),
+            preset => self.select_preset(preset),
+        }
+    }
+
     /// Get the currently selected time range
     pub fn get_current_selection(&self) -> Option<TimeRangeSelection> {
         if let Some(ref preset) = self.selected_preset {
-            let date_range = preset.to_date_range();
+            let date_range = preset.to_date_range_in(&self.context);
             let label = preset.display_name().to_string();
             
             Some(TimeRangeSelection {
@@ -195,7 +216,7 @@ impl TimeRangeSelector {
             warnings: vec![],
         };
 
-        let today = chrono::Utc::now().date_naive();
+        let today = self.context.today;
 
         // Check if start date is after end date
         if range.start > range.end {
@@ -205,6 +226,13 @@ impl TimeRangeSelector {
             return Ok(validation);
         }
 
+        if range.start > today {
+            validation.is_valid = false;
+            validation.error_message = Some("The date range starts in the future".to_string());
+            validation.suggestions.push("Please select a range that includes past dates".to_string());
+            return Ok(validation);
+        }
+
         // Check if end date is in the future
         if range.end > today {
             validation.warnings.push("End date is in the future - no data may be available for future dates".to_string());
@@ -325,6 +353,8 @@ impl TimeRangeSelector {
             ("Last 7 days".to_string(), TimeRangePreset::LastSevenDays),
             ("Last 30 days".to_string(), TimeRangePreset::LastThirtyDays),
             ("Last 90 days".to_string(), TimeRangePreset::LastNinetyDays),
+            ("This week".to_string(), TimeRangePreset::ThisWeek),
+            ("Last week".to_string(), TimeRangePreset::LastWeek),
             ("This month".to_string(), TimeRangePreset::ThisMonth),
             ("Last month".to_string(), TimeRangePreset::LastMonth),
             ("Last quarter".to_string(), TimeRangePreset::LastQuarter),
@@ -336,29 +366,17 @@ impl TimeRangeSelector {
     /// Get business-focused preset ranges
     pub fn get_business_ranges() -> Vec<(String, TimeRangePreset)> {
         vec![
-            ("This week".to_string(), TimeRangePreset::LastSevenDays),
+            ("This week".to_string(), TimeRangePreset::ThisWeek),
             ("This month".to_string(), TimeRangePreset::ThisMonth),
-            ("This quarter".to_string(), TimeRangePreset::LastQuarter),
+            ("This quarter".to_string(), TimeRangePreset::ThisQuarter),
             ("This year".to_string(), TimeRangePreset::ThisYear),
             ("Year to date".to_string(), TimeRangePreset::ThisYear),
         ]
     }
 
-    /// Convert relative time description to preset
+    /// Convert relative time description to preset, resolved in UTC
     pub fn parse_relative_time(input: &str) -> Option<TimeRangePreset> {
-        let input_lower = input.to_lowercase();
-        
-        match input_lower.as_str() {
-            "yesterday" => Some(TimeRangePreset::Custom(DateRange::last_days(1))),
-            "last week" | "past week" | "7 days" => Some(TimeRangePreset::LastSevenDays),
-            "last month" | "past month" | "30 days" => Some(TimeRangePreset::LastThirtyDays),
-            "last quarter" | "past quarter" | "90 days" => Some(TimeRangePreset::LastNinetyDays),
-            "last year" | "past year" | "365 days" => Some(TimeRangePreset::LastYear),
-            "this month" => Some(TimeRangePreset::ThisMonth),
-            "this year" => Some(TimeRangePreset::ThisYear),
-            "all time" | "everything" => Some(TimeRangePreset::AllTime),
-            _ => None,
-        }
+        parse_date_range(input, &TimeRangeContext::default()).ok()
     }
 }
 
@@ -450,4 +468,31 @@ mod tests {
         assert!(filter_config.date_range.is_some());
         assert!(filter_config.time_range_preset.is_some());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_calendar_presets_use_context() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        let mut selector = TimeRangeSelector::new().with_context(TimeRangeContext::default().with_today(today));
+
+        let last_month = selector.select_preset(TimeRangePreset::LastMonth).unwrap();
+        assert_eq!(last_month.date_range.start, NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+        assert_eq!(last_month.date_range.end, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+
+        let week = selector.select_preset(TimeRangePreset::IsoWeek { year: 2024, week: 10 }).unwrap();
+        assert_eq!(week.date_range.start, NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+    }
+
+    #[test]
+    fn test_select_phrase() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        let mut selector = TimeRangeSelector::new().with_context(TimeRangeContext::default().with_today(today));
+
+        let selection = selector.select_phrase("last 14 days").unwrap();
+        assert!(selection.is_custom);
+        assert_eq!(selection.label, "2024-02-29 to 2024-03-13");
+
+        let error = selector.select_phrase("2024-06-01..2024-06-30").unwrap_err();
+        assert!(error.to_string().contains("starts in the future"));
+        assert!(selector.select_phrase("soonish").is_err());
+    }
+}
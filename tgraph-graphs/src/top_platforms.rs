@@ -1,10 +1,17 @@
-//! Top platforms/users horizontal bar chart implementation
+//! Top platforms/users/content horizontal bar chart implementation
 
-use crate::{DataSet, GraphConfig, GraphRenderer};
+use crate::{
+    ContentKind, DataSet, GraphConfig, GraphRenderer, LocationDataPoint, LocationGranularity,
+    TopContentDataPoint, TopUserDataPoint,
+};
 use async_trait::async_trait;
 use plotters::prelude::*;
 use std::path::Path;
-use tgraph_common::{Result, TGraphError};
+use tgraph_common::{Result, TGraphError, TautulliClient};
+use tracing::warn;
+
+/// Width in pixels reserved for a poster next to the chart
+const POSTER_WIDTH: u32 = 220;
 
 /// Data point for top platforms or users
 #[derive(Debug, Clone)]
@@ -26,6 +33,45 @@ pub struct TopPlatformsGraph {
     pub show_percentages: bool,
     /// Chart title (e.g., "Top Platforms", "Top Users")
     pub chart_type: String,
+    /// Encoded poster image drawn next to the chart for the top entry
+    pub poster: Option<Vec<u8>>,
+}
+
+impl From<TopContentDataPoint> for TopItemDataPoint {
+    fn from(point: TopContentDataPoint) -> Self {
+        let name = match point.year {
+            Some(year) => format!("{} ({})", point.title, year),
+            None => point.title,
+        };
+        Self {
+            name,
+            count: point.count,
+            percentage: Some(point.percentage),
+            label: point.label,
+        }
+    }
+}
+
+impl From<TopUserDataPoint> for TopItemDataPoint {
+    fn from(point: TopUserDataPoint) -> Self {
+        Self {
+            name: point.friendly_name.unwrap_or(point.username),
+            count: point.count,
+            percentage: None,
+            label: point.label,
+        }
+    }
+}
+
+impl From<LocationDataPoint> for TopItemDataPoint {
+    fn from(point: LocationDataPoint) -> Self {
+        Self {
+            name: point.location,
+            count: point.count,
+            percentage: Some(point.percentage),
+            label: point.label,
+        }
+    }
 }
 
 impl TopPlatformsGraph {
@@ -36,6 +82,7 @@ impl TopPlatformsGraph {
             limit: 10, // Show top 10 by default
             show_percentages: true,
             chart_type: "Top Items".to_string(),
+            poster: None,
         }
     }
 
@@ -75,6 +122,7 @@ impl TopPlatformsGraph {
             limit,
             show_percentages: true,
             chart_type: "Top Platforms".to_string(),
+            poster: None,
         }
     }
 
@@ -85,6 +133,33 @@ impl TopPlatformsGraph {
             limit,
             show_percentages: true,
             chart_type: "Top Users".to_string(),
+            poster: None,
+        }
+    }
+
+    /// Create for top movies, shows or artists
+    pub fn for_content(kind: ContentKind, limit: usize) -> Self {
+        Self {
+            data: Vec::new(),
+            limit,
+            show_percentages: true,
+            chart_type: format!("Top {}", kind.name()),
+            poster: None,
+        }
+    }
+
+    /// Create a graph for top countries or cities
+    pub fn for_locations(granularity: LocationGranularity, limit: usize) -> Self {
+        let chart_type = match granularity {
+            LocationGranularity::Country => "Top Countries",
+            LocationGranularity::City => "Top Cities",
+        };
+        Self {
+            data: Vec::new(),
+            limit,
+            show_percentages: true,
+            chart_type: chart_type.to_string(),
+            poster: None,
         }
     }
 
@@ -95,6 +170,7 @@ impl TopPlatformsGraph {
             limit: 10,
             show_percentages: false,
             chart_type: "Top Items".to_string(),
+            poster: None,
         }
     }
 
@@ -129,6 +205,35 @@ impl TopPlatformsGraph {
         self.data = data;
     }
 
+    /// Set content data, keeping the aggregator's share of plays as percentages
+    pub fn set_content_data(&mut self, data: Vec<TopContentDataPoint>) {
+        let mut items: Vec<TopItemDataPoint> = data.into_iter().map(TopItemDataPoint::from).collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.count));
+        items.truncate(self.limit);
+        self.data = items;
+    }
+
+    /// Set top users data
+    ///
+    /// Names are shown as the aggregator left them, so censor them with the
+    /// [`PrivacyPolicy`](crate::PrivacyPolicy) in `AggregationConfig` first.
+    pub fn set_user_data(&mut self, data: Vec<TopUserDataPoint>) {
+        let mut items: Vec<TopItemDataPoint> = data.into_iter().map(TopItemDataPoint::from).collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.count));
+        items.truncate(self.limit);
+        self.data = items;
+    }
+
+    /// Set location data, keeping the aggregator's order so "Other" stays last
+    pub fn set_location_data(&mut self, data: Vec<LocationDataPoint>) {
+        self.data = data.into_iter().map(TopItemDataPoint::from).take(self.limit + 1).collect();
+    }
+
+    /// Show a poster (JPEG or PNG) next to the chart
+    pub fn set_poster(&mut self, poster: Vec<u8>) {
+        self.poster = Some(poster);
+    }
+
     /// Get the top N items (already sorted and limited)
     pub fn get_top_items(&self, n: usize) -> Vec<&TopItemDataPoint> {
         self.data.iter().take(n).collect()
@@ -239,12 +344,36 @@ impl GraphRenderer for TopPlatformsGraph {
         let bg_color = self.get_background_color(config);
         root.fill(&bg_color)?;
 
+        // A poster that fails to decode is skipped rather than failing the chart
+        let poster = self.poster.as_deref().and_then(|bytes| match image::load_from_memory(bytes) {
+            Ok(poster) => Some(poster),
+            Err(e) => {
+                warn!("Skipping undecodable poster image: {}", e);
+                None
+            }
+        });
+
+        let chart_area = match poster {
+            Some(poster) if config.width > POSTER_WIDTH * 2 => {
+                let (chart_area, poster_area) = root.split_horizontally(config.width - POSTER_WIDTH);
+                let margin = config.style.margins.top;
+                let poster = poster.resize(
+                    POSTER_WIDTH.saturating_sub(2 * margin).max(1),
+                    config.height.saturating_sub(2 * margin).max(1),
+                    image::imageops::FilterType::Triangle,
+                );
+                poster_area.draw(&BitMapElement::from(((margin as i32, margin as i32), poster)))?;
+                chart_area
+            }
+            _ => root.clone(),
+        };
+
         let max_count = self.get_max_count();
         let num_items = self.data.len();
 
         // Create chart with horizontal orientation
         let title_font = (config.style.title_font.family.as_str(), config.style.title_font.size);
-        let mut chart = ChartBuilder::on(&root)
+        let mut chart = ChartBuilder::on(&chart_area)
             .caption(&config.title, title_font)
             .margin(config.style.margins.top as i32)
             .x_label_area_size(config.style.margins.bottom)
@@ -320,6 +449,20 @@ impl GraphRenderer for TopPlatformsGraph {
     }
 }
 
+/// Fetch a poster for the top entry through Tautulli's image proxy
+///
+/// Posters are decoration, so failures are logged and `None` is returned.
+pub async fn load_top_poster(client: &TautulliClient, data: &[TopContentDataPoint]) -> Option<Vec<u8>> {
+    let top = data.first()?;
+    match client.pms_image_proxy(&top.rating_key, 300, 450).await {
+        Ok(poster) => Some(poster),
+        Err(e) => {
+            warn!("Failed to fetch poster for {}: {}", top.title, e);
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -500,6 +643,49 @@ mod tests {
         assert!(file_path.exists());
     }
 
+    #[tokio::test]
+    async fn test_render_content_with_poster() {
+        let mut graph = TopPlatformsGraph::for_content(ContentKind::Movie, 5);
+        assert_eq!(graph.chart_type, "Top Movies");
+
+        graph.set_content_data(vec![
+            TopContentDataPoint {
+                rating_key: "10".to_string(),
+                title: "Dune".to_string(),
+                year: Some(2021),
+                count: 3,
+                percentage: 75.0,
+                label: None,
+            },
+            TopContentDataPoint {
+                rating_key: "11".to_string(),
+                title: "Arrival".to_string(),
+                year: None,
+                count: 1,
+                percentage: 25.0,
+                label: None,
+            },
+        ]);
+        assert_eq!(graph.data[0].name, "Dune (2021)");
+        assert_eq!(graph.data[0].percentage, Some(75.0));
+
+        let mut poster = Vec::new();
+        image::DynamicImage::new_rgb8(30, 45)
+            .write_to(&mut std::io::Cursor::new(&mut poster), image::ImageOutputFormat::Png)
+            .unwrap();
+        graph.set_poster(poster);
+
+        let (_, config) = TopPlatformsGraph::with_config("Top Movies", Some("Plays"), None, 5);
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("top_movies_test.png");
+        graph.render_to_file(&config, &[], &file_path).await.unwrap();
+        assert!(file_path.exists());
+
+        // A broken poster does not stop the chart from rendering
+        graph.set_poster(vec![1, 2, 3]);
+        graph.render_to_file(&config, &[], &file_path).await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_render_empty_data_error() {
         let graph = TopPlatformsGraph::new();
@@ -1,6 +1,6 @@
 //! Trend analysis and statistical calculations for graph data
 
-use crate::{PlayCountDataPoint, TrendConfig};
+use crate::{AnomalyConfig, AnomalyMethod, PlayCountDataPoint, TrendConfig};
 use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -77,6 +77,58 @@ pub struct TrendAnalysisResult {
     pub trend_line: Vec<TrendLinePoint>,
     /// Seasonal patterns (if detected)
     pub seasonal_patterns: Option<SeasonalPattern>,
+    /// Days that stray from their baseline, oldest first
+    #[serde(default)]
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// Kind of outlying day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnomalyKind {
+    /// Far more plays than usual
+    Spike,
+    /// Far fewer plays than usual
+    Drop,
+    /// No plays on a day that always had some, usually a broken server
+    ZeroPlays,
+}
+
+/// A day whose play count stands out from its baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anomaly {
+    pub date: NaiveDate,
+    pub value: f64,
+    /// Baseline mean or median the day was compared to
+    pub expected: f64,
+    /// Signed score, negative for fewer plays than expected
+    pub score: f64,
+    pub kind: AnomalyKind,
+}
+
+/// Baseline days required before a day can be scored
+const MIN_BASELINE_DAYS: usize = 3;
+
+/// Scale that makes the median absolute deviation comparable to a z-score
+const MAD_SCALE: f64 = 0.6745;
+
+/// Fill days without plays between two dates with zero counts
+///
+/// Aggregators only emit days that had plays, but anomaly detection needs
+/// the empty days to notice an outage.
+pub fn fill_missing_days(data: &[PlayCountDataPoint], start: NaiveDate, end: NaiveDate) -> Vec<PlayCountDataPoint> {
+    let counts: HashMap<NaiveDate, u32> = data.iter().map(|point| (point.date, point.count)).collect();
+    start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .map(|date| {
+            let count = counts.get(&date).copied().unwrap_or(0);
+            PlayCountDataPoint {
+                date,
+                count,
+                label: Some(format!("{} plays", count)),
+            }
+        })
+        .collect()
 }
 
 /// Detected seasonal patterns in the data
@@ -137,15 +189,85 @@ impl TrendAnalyzer {
 
         let seasonal_patterns = self.detect_seasonal_patterns(&values, &dates)?;
 
+        let anomalies = match &self.config.anomaly_detection {
+            Some(anomaly_config) => self.detect_anomalies(&values, &dates, anomaly_config, seasonal_patterns.as_ref()),
+            None => vec![],
+        };
+
         Ok(TrendAnalysisResult {
             statistics,
             growth_analysis,
             moving_averages,
             trend_line,
             seasonal_patterns,
+            anomalies,
         })
     }
 
+    /// Score each day against the days before it
+    ///
+    /// With a weekly pattern only the same weekdays form the baseline, so a
+    /// quiet Monday is not compared with a busy weekend. The spread is floored
+    /// at one play so a flat baseline does not flag tiny changes.
+    fn detect_anomalies(
+        &self,
+        values: &[f64],
+        dates: &[NaiveDate],
+        config: &AnomalyConfig,
+        seasonal_pattern: Option<&SeasonalPattern>,
+    ) -> Vec<Anomaly> {
+        let weekly = config.seasonal
+            && matches!(seasonal_pattern, Some(pattern) if matches!(pattern.pattern_type, SeasonalPatternType::Weekly));
+        let window = chrono::Duration::days(config.window_days as i64);
+        let mut anomalies = Vec::new();
+
+        for (i, (&value, &date)) in values.iter().zip(dates).enumerate() {
+            let baseline: Vec<f64> = values[..i]
+                .iter()
+                .zip(&dates[..i])
+                .filter(|(_, &day)| date - day <= window)
+                .filter(|(_, &day)| !weekly || day.weekday() == date.weekday())
+                .map(|(&v, _)| v)
+                .collect();
+            if baseline.len() < MIN_BASELINE_DAYS {
+                continue;
+            }
+
+            let (expected, score) = match config.method {
+                AnomalyMethod::ZScore => {
+                    let mean = baseline.iter().sum::<f64>() / baseline.len() as f64;
+                    let variance = baseline.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / baseline.len() as f64;
+                    (mean, (value - mean) / variance.sqrt().max(1.0))
+                }
+                AnomalyMethod::MedianAbsoluteDeviation => {
+                    let center = median(&baseline);
+                    let deviations: Vec<f64> = baseline.iter().map(|x| (x - center).abs()).collect();
+                    (center, MAD_SCALE * (value - center) / median(&deviations).max(1.0))
+                }
+            };
+
+            let kind = if value == 0.0 && baseline.iter().all(|&v| v > 0.0) {
+                AnomalyKind::ZeroPlays
+            } else if score >= config.threshold {
+                AnomalyKind::Spike
+            } else if score <= -config.threshold {
+                AnomalyKind::Drop
+            } else {
+                continue;
+            };
+
+            anomalies.push(Anomaly {
+                date,
+                value,
+                expected,
+                score,
+                kind,
+            });
+        }
+
+        anomalies
+    }
+
     /// Calculate basic statistical indicators
     fn calculate_statistics(&self, values: &[f64]) -> Result<TrendStatistics> {
         if values.is_empty() {
@@ -451,6 +573,18 @@ impl TrendAnalyzer {
     }
 }
 
+/// Median of unsorted, non-empty values
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -476,6 +610,7 @@ mod tests {
             show_growth_rate: true,
             show_statistics: true,
             confidence_interval: 0.95,
+            anomaly_detection: None,
         };
 
         let analyzer = TrendAnalyzer::new(config);
@@ -530,4 +665,68 @@ mod tests {
         assert_eq!(stats.sum, 75.0);
         assert_eq!(stats.count, 5);
     }
-} 
\ No newline at end of file
+
+    fn daily_series(counts: &[u32]) -> Vec<PlayCountDataPoint> {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        counts
+            .iter()
+            .zip(start.iter_days())
+            .map(|(&count, date)| PlayCountDataPoint { date, count, label: None })
+            .collect()
+    }
+
+    #[test]
+    fn test_anomaly_detection() {
+        let config = TrendConfig {
+            anomaly_detection: Some(AnomalyConfig {
+                window_days: 7,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let analyzer = TrendAnalyzer::new(config);
+        let data = daily_series(&[10, 12, 11, 9, 10, 11, 12, 40, 10, 11, 10, 0]);
+
+        let result = analyzer.analyze_play_counts(&data).unwrap();
+        let found: Vec<(u32, AnomalyKind)> = result.anomalies.iter().map(|a| (a.date.day(), a.kind)).collect();
+        assert_eq!(found, vec![(8, AnomalyKind::Spike), (12, AnomalyKind::ZeroPlays)]);
+        assert_eq!(result.anomalies[1].expected, 11.0);
+        assert!(result.anomalies[1].score < 0.0);
+
+        // Disabled by default
+        let result = TrendAnalyzer::new(TrendConfig::default()).analyze_play_counts(&data).unwrap();
+        assert!(result.anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_anomaly_detection_uses_weekly_seasonality() {
+        // Busy weekends, quiet weekdays: a weekend is not a spike
+        let week = [5, 5, 5, 5, 5, 30, 30];
+        let counts: Vec<u32> = week.iter().cycle().take(28).copied().collect();
+        let config = TrendConfig {
+            anomaly_detection: Some(AnomalyConfig {
+                method: AnomalyMethod::ZScore,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = TrendAnalyzer::new(config.clone()).analyze_play_counts(&daily_series(&counts)).unwrap();
+        assert!(matches!(result.seasonal_patterns, Some(SeasonalPattern { pattern_type: SeasonalPatternType::Weekly, .. })));
+        assert!(result.anomalies.is_empty());
+
+        let mut flat = config;
+        flat.anomaly_detection.as_mut().unwrap().seasonal = false;
+        let result = TrendAnalyzer::new(flat).analyze_play_counts(&daily_series(&counts)).unwrap();
+        assert!(!result.anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_fill_missing_days() {
+        let data = daily_series(&[3]);
+        let end = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        let filled = fill_missing_days(&data, data[0].date, end);
+        let counts: Vec<u32> = filled.iter().map(|p| p.count).collect();
+        assert_eq!(counts, vec![3, 0, 0]);
+    }
+}
@@ -1,5 +1,6 @@
 //! Graph types and data structures
 
+use crate::defaults::GraphDefaults;
 use serde::{Deserialize, Serialize};
 
 /// Supported graph types
@@ -26,14 +27,21 @@ pub struct GraphConfig {
 
 impl Default for GraphConfig {
     fn default() -> Self {
+        Self::from_defaults(&GraphDefaults::default())
+    }
+}
+
+impl GraphConfig {
+    /// A line graph sized and styled by `defaults`
+    pub fn from_defaults(defaults: &GraphDefaults) -> Self {
         Self {
             graph_type: GraphType::Line,
             title: "Graph".to_string(),
-            width: 800,
-            height: 600,
+            width: defaults.width,
+            height: defaults.height,
             x_label: None,
             y_label: None,
-            style: StyleConfig::default(),
+            style: StyleConfig::from_defaults(defaults),
         }
     }
 }
@@ -74,9 +82,10 @@ pub struct FontConfig {
 
 impl Default for FontConfig {
     fn default() -> Self {
+        let defaults = GraphDefaults::default();
         Self {
-            family: "sans-serif".to_string(),
-            size: 12,
+            family: defaults.font_family,
+            size: defaults.font_size,
         }
     }
 }
@@ -112,9 +121,10 @@ pub struct GridConfig {
 
 impl Default for GridConfig {
     fn default() -> Self {
+        let show_grid = GraphDefaults::default().show_grid;
         Self {
-            show_x: true,
-            show_y: true,
+            show_x: show_grid,
+            show_y: show_grid,
             color: None,
             style: GridStyle::Solid,
         }
@@ -143,17 +153,32 @@ pub struct StyleConfig {
 
 impl Default for StyleConfig {
     fn default() -> Self {
+        Self::from_defaults(&GraphDefaults::default())
+    }
+}
+
+impl StyleConfig {
+    /// The default style with the colours, fonts and grid of `defaults`
+    pub fn from_defaults(defaults: &GraphDefaults) -> Self {
+        let font = FontConfig {
+            family: defaults.font_family.clone(),
+            size: defaults.font_size,
+        };
         Self {
             color_scheme: ColorScheme::Default,
-            background_color: Some("#FFFFFF".to_string()),
+            background_color: Some(defaults.background_color.clone()),
             title_font: FontConfig {
-                family: "sans-serif".to_string(),
-                size: 16,
+                size: defaults.font_size + 4,
+                ..font.clone()
             },
-            axis_font: FontConfig::default(),
-            label_font: FontConfig::default(),
+            axis_font: font.clone(),
+            label_font: font,
             margins: MarginConfig::default(),
-            grid: GridConfig::default(),
+            grid: GridConfig {
+                show_x: defaults.show_grid,
+                show_y: defaults.show_grid,
+                ..GridConfig::default()
+            },
         }
     }
 } 
\ No newline at end of file